@@ -0,0 +1,92 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use neaters::bench_support::{speciate, synthetic_inputs, synthetic_network, synthetic_solver};
+use neaters::{Phenotype, Solver};
+
+/// The number of hidden nodes of the networks used by the computation benchmarks.
+const HIDDEN: [usize; 4] = [0, 10, 50, 200];
+
+fn phenotype_construction(c: &mut Criterion) {
+    let mut group = c.benchmark_group("phenotype_construction");
+    for hidden in HIDDEN {
+        let nn = synthetic_network(8, 4, hidden, 1);
+        group.bench_with_input(BenchmarkId::from_parameter(hidden), &nn, |b, nn| {
+            b.iter(|| Phenotype::from_nn(black_box(nn)))
+        });
+    }
+    group.finish();
+}
+
+fn compute(c: &mut Criterion) {
+    let mut group = c.benchmark_group("compute");
+    let input = synthetic_inputs(1, 8, 2).remove(0);
+    for hidden in HIDDEN {
+        let mut nn = synthetic_network(8, 4, hidden, 1);
+        group.bench_function(BenchmarkId::new("network", hidden), |b| {
+            b.iter(|| nn.compute(black_box(&input)))
+        });
+        let compiled = nn.compile();
+        let mut state = compiled.state();
+        let mut output = [0.0; 4];
+        group.bench_function(BenchmarkId::new("compiled", hidden), |b| {
+            b.iter(|| compiled.compute_with(&mut state, black_box(&input), &mut output))
+        });
+    }
+    group.finish();
+}
+
+fn compute_batch(c: &mut Criterion) {
+    let mut group = c.benchmark_group("compute_batch");
+    let inputs = synthetic_inputs(256, 8, 3);
+    for hidden in HIDDEN {
+        let mut nn = synthetic_network(8, 4, hidden, 1);
+        group.bench_function(BenchmarkId::from_parameter(hidden), |b| {
+            b.iter(|| nn.compute_batch(black_box(&inputs)))
+        });
+    }
+    group.finish();
+}
+
+fn speciation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("speciation");
+    for generation_size in [50, 150, 500] {
+        let mut solver = synthetic_solver(4, 2, generation_size, 10);
+        group.bench_function(BenchmarkId::from_parameter(generation_size), |b| {
+            b.iter(|| speciate(&mut solver))
+        });
+    }
+    // a large population should still be grouped within milliseconds
+    group.sample_size(20);
+    let mut solver = synthetic_solver(4, 2, 10_000, 10);
+    group.bench_function(BenchmarkId::from_parameter(10_000), |b| {
+        b.iter(|| speciate(&mut solver))
+    });
+    group.finish();
+}
+
+fn generation_turnover(c: &mut Criterion) {
+    let mut group = c.benchmark_group("generation_turnover");
+    group.sample_size(20);
+    for generation_size in [50, 150, 500] {
+        let bytes = synthetic_solver(4, 2, generation_size, 10)
+            .as_byte_representation()
+            .unwrap();
+        group.bench_function(BenchmarkId::from_parameter(generation_size), |b| {
+            b.iter_batched(
+                || Solver::load_from_reader(&bytes[..]).unwrap(),
+                |mut solver| solver.new_generation(),
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    phenotype_construction,
+    compute,
+    compute_batch,
+    speciation,
+    generation_turnover
+);
+criterion_main!(benches);
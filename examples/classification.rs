@@ -0,0 +1,60 @@
+//! Trains a network classifying points of a csv file as inside or outside of a circle.
+//!
+//! Run it with `cargo run --release --example classification`.
+use neaters::problems::supervised::{CsvOptions, Dataset, SupervisedLoss, SupervisedProblem};
+use neaters::{InputScaling, OutputActivation, Solver, TrainOptions};
+use rand::SeedableRng;
+
+fn main() -> Result<(), neaters::Error> {
+    let options = CsvOptions {
+        one_hot: true,
+        scaling: Some(InputScaling::z_score),
+        shuffle: Some(1),
+        ..CsvOptions::default()
+    };
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/examples/data/circle.csv");
+    let dataset = Dataset::from_csv(path, &options)?;
+    let mut rng = rand_pcg::Pcg64::seed_from_u64(1);
+    let problem =
+        SupervisedProblem::with_validation(dataset, SupervisedLoss::CrossEntropy, 0.25, &mut rng);
+    let training = problem.training();
+    let mut solver = Solver::with_size(training.inputs(), training.targets(), 150);
+    // the softmax turns the outputs into the probabilities of the classes
+    for nn in solver.neural_nets() {
+        *nn = nn.clone().with_output_activation(OutputActivation::Softmax);
+    }
+    let options = TrainOptions {
+        max_generations: Some(200),
+        ..TrainOptions::default()
+    };
+    // the champion is the network which classified the validation points best
+    let mut report = problem.train_with_early_stopping(&mut solver, &options, 30);
+    println!(
+        "the champion has a training loss of {:.3} and a validation loss of {:.3}",
+        problem.loss(&mut report.champion),
+        problem.validation_loss(&mut report.champion).unwrap()
+    );
+    let validation = problem.validation().unwrap();
+    let correct = validation
+        .samples()
+        .iter()
+        .filter(|(inputs, target)| {
+            let outputs = report.champion.compute(inputs);
+            validation.class(&outputs) == validation.class(target)
+        })
+        .count();
+    println!(
+        "{} of {} validation points are classified correctly",
+        correct,
+        validation.len()
+    );
+    // with the scaling of the dataset the champion computes unscaled points
+    let mut champion = report.champion;
+    champion.set_input_scaling(validation.input_scaling().clone());
+    let outputs = champion.compute([0.5, -0.5]);
+    println!(
+        "the point (0.5, -0.5) inside of the circle is classified as {}",
+        validation.class(&outputs).unwrap()
+    );
+    Ok(())
+}
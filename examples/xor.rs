@@ -0,0 +1,37 @@
+//! Trains a network computing the exclusive or of two inputs.
+//!
+//! Run it with `cargo run --release --example xor`.
+use neaters::problems::XorProblem;
+use neaters::{Activation, Solver, StopReason, TrainOptions};
+
+fn main() {
+    let mut solver = Solver::with_size(2, 1, 150);
+    // the steeper tanh reaches outputs close to 0 and 1 with smaller weights than the default
+    for nn in solver.neural_nets() {
+        *nn = nn.clone().with_activation(Activation::Tanh);
+    }
+    let options = TrainOptions {
+        max_generations: Some(300),
+        target_fitness: Some(XorProblem::TARGET_FITNESS),
+        ..TrainOptions::default()
+    };
+    let mut report = solver.train(&XorProblem, &options);
+    println!(
+        "stopped after {} generations ({:?}) with a fitness of {:.3}",
+        report.history.len(),
+        report.stop_reason,
+        report.champion.fitness()
+    );
+    for (input, expected) in XorProblem::CASES {
+        let output = report.champion.compute(input)[0];
+        println!("{:?} -> {:.3} (expected {})", input, output, expected);
+    }
+    println!(
+        "the champion has {} hidden nodes and {} enabled edges",
+        report.champion.hidden_node_count(),
+        report.champion.enabled_edge_count()
+    );
+    if report.stop_reason != StopReason::TargetFitness {
+        println!("the network has not reached the target fitness");
+    }
+}
@@ -0,0 +1,206 @@
+use crate::float::Float;
+use crate::random;
+use alloc::format;
+use alloc::string::{String, ToString};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// Activation function of a node, it is applied to the sum of all incoming values of the node
+/// before its value is passed on to the next nodes.
+#[derive(Debug, Deserialize, PartialEq, Eq, Serialize, Clone, Copy, Default)]
+pub enum Activation {
+    /// Sigmoid function approximated by `x / (1 + |x|)`, the values are in the range (-1, 1).
+    #[default]
+    Sigmoid,
+    /// Hyperbolic tangent, the values are in the range (-1, 1).
+    Tanh,
+    /// Rectified linear unit: `max(0, x)`.
+    ReLU,
+    /// Leaky rectified linear unit: `x` for positive values and `0.01 * x` otherwise.
+    LeakyReLU,
+    /// Gaussian function `exp(-x^2)`, the values are in the range (0, 1].
+    Gaussian,
+    /// Sine function, the values are in the range [-1, 1].
+    Sine,
+    /// Identity function, the value is passed on unchanged.
+    Identity,
+    /// Step function: **1.0** for positive values and **0.0** otherwise.
+    Step,
+    /// Absolute value `|x|`, which is symmetric around **0.0**.
+    Abs,
+    /// Cosine function, the values are in the range [-1, 1].
+    Cosine,
+    /// Square function `x^2`, which is symmetric around **0.0**.
+    Square,
+}
+
+impl Activation {
+    /// All available activation functions.
+    pub const ALL: [Activation; 11] = [
+        Activation::Sigmoid,
+        Activation::Tanh,
+        Activation::ReLU,
+        Activation::LeakyReLU,
+        Activation::Gaussian,
+        Activation::Sine,
+        Activation::Identity,
+        Activation::Step,
+        Activation::Abs,
+        Activation::Cosine,
+        Activation::Square,
+    ];
+
+    /// The activation functions of compositional pattern-producing networks (CPPNs) as used by
+    /// HyperNEAT: symmetric (`Gaussian`, `Abs`, `Square`), periodic (`Sine`, `Cosine`) and
+    /// monotonic functions, so the patterns painted onto a substrate can contain symmetry and
+    /// repetition.
+    pub const CPPN: [Activation; 9] = [
+        Activation::Sigmoid,
+        Activation::Tanh,
+        Activation::Gaussian,
+        Activation::Sine,
+        Activation::Identity,
+        Activation::Step,
+        Activation::Abs,
+        Activation::Cosine,
+        Activation::Square,
+    ];
+
+    /// Applies the activation function to the given value.
+    pub fn apply<F: Float>(&self, x: F) -> F {
+        match self {
+            Activation::Sigmoid => x / (F::ONE + x.abs()),
+            Activation::Tanh => x.tanh(),
+            Activation::ReLU => x.max(F::ZERO),
+            Activation::LeakyReLU => {
+                if x > F::ZERO {
+                    x
+                } else {
+                    F::from_f64(0.01) * x
+                }
+            }
+            Activation::Gaussian => (-x * x).exp(),
+            Activation::Sine => x.sin(),
+            Activation::Identity => x,
+            Activation::Step => {
+                if x > F::ZERO {
+                    F::ONE
+                } else {
+                    F::ZERO
+                }
+            }
+            Activation::Abs => x.abs(),
+            Activation::Cosine => x.cos(),
+            Activation::Square => x * x,
+        }
+    }
+
+    /// Returns the derivative of the activation function at the given value, used by the local
+    /// search to compute the gradients of the weights. The derivative of the `Step` is **0.0**
+    /// everywhere, at **0.0** the `ReLU` and the `LeakyReLU` use the slope of their negative side
+    /// and the `Abs` uses **0.0**.
+    #[cfg(feature = "std")]
+    pub(crate) fn derivative<F: Float>(&self, x: F) -> F {
+        match self {
+            Activation::Sigmoid => {
+                let d = F::ONE + x.abs();
+                F::ONE / (d * d)
+            }
+            Activation::Tanh => {
+                let y = x.tanh();
+                F::ONE - y * y
+            }
+            Activation::ReLU | Activation::LeakyReLU | Activation::Abs if x > F::ZERO => F::ONE,
+            Activation::ReLU | Activation::Step => F::ZERO,
+            Activation::LeakyReLU => F::from_f64(0.01),
+            Activation::Abs if x < F::ZERO => -F::ONE,
+            Activation::Abs => F::ZERO,
+            Activation::Gaussian => F::from_f64(-2.0) * x * (-x * x).exp(),
+            Activation::Sine => x.cos(),
+            Activation::Cosine => -x.sin(),
+            Activation::Identity => F::ONE,
+            Activation::Square => F::from_f64(2.0) * x,
+        }
+    }
+
+    /// Returns the Rust expression applying the activation function to the given expression, used
+    /// by `NeuralNetwork::codegen_rust`. The expression works for `f32` as well as `f64`.
+    pub(crate) fn rust_expression(&self, x: &str) -> String {
+        match self {
+            Activation::Sigmoid => format!("{x} / (1.0 + {x}.abs())"),
+            Activation::Tanh => format!("{x}.tanh()"),
+            Activation::ReLU => format!("{x}.max(0.0)"),
+            Activation::LeakyReLU => format!("if {x} > 0.0 {{ {x} }} else {{ 0.01 * {x} }}"),
+            Activation::Gaussian => format!("(-{x} * {x}).exp()"),
+            Activation::Sine => format!("{x}.sin()"),
+            Activation::Identity => x.to_string(),
+            Activation::Step => format!("if {x} > 0.0 {{ 1.0 }} else {{ 0.0 }}"),
+            Activation::Abs => format!("{x}.abs()"),
+            Activation::Cosine => format!("{x}.cos()"),
+            Activation::Square => format!("{x} * {x}"),
+        }
+    }
+
+    /// Returns a random activation function, every function is equally likely.
+    pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Activation::ALL[random::index(rng, Activation::ALL.len())]
+    }
+}
+
+/// The activation of the output nodes of a network, which can differ from the one of the hidden
+/// nodes, see `NeuralNetwork::with_output_activation`.
+///
+/// Activation functions like the `Sigmoid` bound the outputs, so a regression problem with
+/// targets outside of the range cannot be fitted. Such problems need outputs using the
+/// `Identity`.
+#[derive(Debug, Deserialize, PartialEq, Eq, Serialize, Clone, Copy, Default)]
+pub enum OutputActivation {
+    /// The output nodes are treated like the hidden nodes, their activation functions are changed
+    /// by mutations as well.
+    #[default]
+    Evolved,
+    /// Every output node uses the given activation function, which is never mutated.
+    Fixed(Activation),
+    /// The output nodes use the `Identity` and the outputs are normalized by the softmax function,
+    /// so they are positive and sum up to **1.0**, e.g. for the probabilities of classes.
+    Softmax,
+}
+
+impl OutputActivation {
+    /// Returns the activation function every output node has to use, `None` if the activation
+    /// functions of the output nodes are evolved.
+    pub fn fixed_activation(&self) -> Option<Activation> {
+        match self {
+            OutputActivation::Evolved => None,
+            OutputActivation::Fixed(activation) => Some(*activation),
+            OutputActivation::Softmax => Some(Activation::Identity),
+        }
+    }
+}
+
+/// Normalizes the values with the softmax function `exp(x_i) / sum(exp(x_j))`. The largest value
+/// is subtracted first, so large values do not overflow.
+pub(crate) fn softmax<F: Float>(values: &mut [F]) {
+    let max = values
+        .iter()
+        .fold(F::from_f64(f64::NEG_INFINITY), |max, &x| max.max(x));
+    let mut sum = F::ZERO;
+    for x in values.iter_mut() {
+        *x = (*x - max).exp();
+        sum += *x;
+    }
+    for x in values.iter_mut() {
+        *x = *x / sum;
+    }
+}
+
+/// Returns the position of the first highest value, NaN values are never the highest.
+pub(crate) fn first_highest<F: Float>(values: &[F]) -> usize {
+    let mut best = 0;
+    for (i, &value) in values.iter().enumerate() {
+        if value > values[best] || values[best].is_nan() {
+            best = i;
+        }
+    }
+    best
+}
@@ -0,0 +1,78 @@
+//! Generators of synthetic networks and populations, available with the `bench_support` feature.
+//!
+//! They create workloads of a given size for the benchmarks in `benches/`, so the performance of
+//! the computation and of the solver can be compared between changes. Networks are generated
+//! from a seed, so the same seed always gives the same network.
+use crate::config::{Config, WeightInit};
+use crate::innovation::InnovationTracker;
+use crate::neuralnetwork::NeuralNetwork;
+use crate::solver::Solver;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
+
+/// Creates a feed-forward network with the given number of inputs, outputs and hidden nodes.
+///
+/// The hidden nodes are added by splitting random edges and every new node gets an additional
+/// random connection, so the network has roughly three edges per hidden node on top of the
+/// initial connections.
+///
+/// # Example:
+/// ```
+/// use neaters::bench_support::synthetic_network;
+/// let nn = synthetic_network(4, 2, 20, 7);
+/// assert_eq!(nn.hidden_node_count(), 20);
+/// assert_eq!(nn, synthetic_network(4, 2, 20, 7));
+/// ```
+pub fn synthetic_network(inputs: usize, outputs: usize, hidden: usize, seed: u64) -> NeuralNetwork {
+    let mut rng = Pcg64::seed_from_u64(seed);
+    let mut nn = NeuralNetwork::with_size(inputs, outputs)
+        .with_weight_init(WeightInit::Uniform(1.0), &mut rng);
+    let mut innovations = InnovationTracker::for_network(&nn);
+    while nn.hidden_node_count() < hidden {
+        nn.mutate_add_node(&mut innovations, &mut rng);
+        nn.mutate_add_connection(&mut innovations, &mut rng);
+    }
+    nn
+}
+
+/// Creates `count` random inputs for a network with the given number of inputs, every value is
+/// in the range `[-1, 1]`.
+pub fn synthetic_inputs(count: usize, inputs: usize, seed: u64) -> Vec<Vec<f32>> {
+    let mut rng = Pcg64::seed_from_u64(seed);
+    (0..count)
+        .map(|_| (0..inputs).map(|_| rng.gen_range(-1.0..=1.0)).collect())
+        .collect()
+}
+
+/// Creates a solver whose population has been evolved for the given number of generations with
+/// high structural mutation rates, so the networks have grown and form multiple species.
+///
+/// The fitness of a network is the sum of its outputs for a constant input, which is cheap to
+/// compute. The networks of the returned solver have been evaluated, so `new_generation` can be
+/// called right away.
+pub fn synthetic_solver(
+    inputs: usize,
+    outputs: usize,
+    generation_size: usize,
+    generations: usize,
+) -> Solver {
+    let config = Config::builder()
+        .generation_size(generation_size)
+        .add_node_rate(0.2)
+        .add_connection_rate(0.5)
+        .build();
+    let mut solver = Solver::with_config(inputs, outputs, config);
+    let input = vec![0.5; inputs];
+    let evaluator = |nn: &mut NeuralNetwork| nn.compute(&input).iter().sum::<f32>();
+    solver.evaluate_generation(&evaluator);
+    for _ in 0..generations {
+        solver.new_generation();
+        solver.evaluate_generation(&evaluator);
+    }
+    solver
+}
+
+/// Groups the networks of the solver into species again, without creating a new generation.
+pub fn speciate(solver: &mut Solver) {
+    solver.speciate();
+}
@@ -0,0 +1,263 @@
+//! Command line tool for inspecting saved networks and for training on the built-in problems.
+//!
+//! Build it with `cargo build --release --features cli`, run `neaters-cli help` for the usage.
+use neaters::problems::{CartPoleProblem, XorProblem};
+use neaters::{
+    Activation, Config, Error, FitnessEvaluator, LogFormat, NeuralNetwork, Solver, StatsLogger,
+    TrainOptions, TrainReport,
+};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::ExitCode;
+
+const USAGE: &str = "usage:
+    neaters-cli inspect <file>
+        prints a summary of the topology of a saved network
+    neaters-cli dot <file>
+        prints the network in the dot format of graphviz
+    neaters-cli convert <file> --to <json|binary> [--output <file>]
+        converts a network between the binary format and json, json is printed if there is no
+        output file
+    neaters-cli train --problem <xor|cartpole> [--config <file.toml>] [--generations <n>]
+                      [--output <file>] [--log <file.csv>]
+        trains networks on a built-in problem and saves the champion
+    neaters-cli help
+        prints this message";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Runs the subcommand given by the arguments and returns an error message if it failed.
+fn run(args: &[String]) -> Result<(), String> {
+    let (command, args) = args.split_first().ok_or(USAGE)?;
+    let options = Options::parse(args)?;
+    match command.as_str() {
+        "inspect" => {
+            let path = options.single_file()?;
+            print!("{}", summary(&load_network(path)?));
+            Ok(())
+        }
+        "dot" => {
+            print!("{}", load_network(options.single_file()?)?.to_dot());
+            Ok(())
+        }
+        "convert" => convert(&options),
+        "train" => train(&options),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        _ => Err(format!("unknown command `{}`\n{}", command, USAGE)),
+    }
+}
+
+/// The positional arguments and the values of the `--name value` options of a subcommand.
+struct Options {
+    files: Vec<String>,
+    values: BTreeMap<String, String>,
+}
+
+impl Options {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut files = Vec::new();
+        let mut values = BTreeMap::new();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.strip_prefix("--") {
+                Some(name) => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| format!("missing value of `--{}`", name))?;
+                    values.insert(name.to_string(), value.clone());
+                }
+                None => files.push(arg.clone()),
+            }
+        }
+        Ok(Options { files, values })
+    }
+
+    /// Returns the only positional argument.
+    fn single_file(&self) -> Result<&str, String> {
+        match &self.files[..] {
+            [file] => Ok(file),
+            _ => Err(format!("expected exactly one file\n{}", USAGE)),
+        }
+    }
+
+    fn get(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(String::as_str)
+    }
+}
+
+/// Loads a network from the binary format or, if the file ends with `.json`, from json.
+fn load_network(path: &str) -> Result<NeuralNetwork, String> {
+    let result = if is_json(path) {
+        std::fs::read_to_string(path)
+            .map_err(Error::from)
+            .and_then(|json| NeuralNetwork::from_json(&json))
+    } else {
+        NeuralNetwork::load_from(path)
+    };
+    result.map_err(|err| format!("cannot load `{}`: {}", path, err))
+}
+
+fn is_json(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .is_some_and(|extension| extension == "json")
+}
+
+/// Returns a human-readable summary of the size and the topology of the network.
+fn summary(nn: &NeuralNetwork) -> String {
+    let mut activations: BTreeMap<String, usize> = BTreeMap::new();
+    for node in nn.nodes() {
+        *activations
+            .entry(format!("{:?}", node.activation))
+            .or_insert(0) += 1;
+    }
+    let activations: Vec<String> = activations
+        .iter()
+        .map(|(activation, count)| format!("{} {}", count, activation))
+        .collect();
+    let problems = nn.validate();
+    let mut summary = format!(
+        "id: {}\n{}\nactivations: {}\nfitness: {}\n",
+        nn.id(),
+        nn.summary(),
+        activations.join(", "),
+        nn.fitness(),
+    );
+    if problems.is_empty() {
+        summary.push_str("valid: yes\n");
+    } else {
+        summary.push_str("valid: no\n");
+        for problem in problems {
+            summary.push_str(&format!("    {}\n", problem));
+        }
+    }
+    summary
+}
+
+fn convert(options: &Options) -> Result<(), String> {
+    let nn = load_network(options.single_file()?)?;
+    let output = options.get("output");
+    match options.get("to") {
+        Some("json") => {
+            let json = nn.to_json().map_err(|err| err.to_string())?;
+            match output {
+                Some(path) => std::fs::write(path, json).map_err(|err| err.to_string()),
+                None => {
+                    println!("{}", json);
+                    Ok(())
+                }
+            }
+        }
+        Some("binary") => {
+            let path = output.ok_or("the binary format needs an `--output` file")?;
+            nn.save_as(path).map_err(|err| err.to_string())
+        }
+        _ => Err(format!("expected `--to json` or `--to binary`\n{}", USAGE)),
+    }
+}
+
+fn train(options: &Options) -> Result<(), String> {
+    let config = match options.get("config") {
+        Some(path) => Config::from_toml_file(path)
+            .map_err(|err| format!("cannot load the config `{}`: {}", path, err))?,
+        None => Config::default(),
+    };
+    let generations = match options.get("generations") {
+        Some(n) => n
+            .parse()
+            .map_err(|_| format!("invalid number of generations `{}`", n))?,
+        None => 300,
+    };
+    let report = match options.get("problem") {
+        Some("xor") => {
+            let mut solver = Solver::with_config(2, 1, config);
+            // like the xor example, tanh solves the problem much faster than the default
+            for nn in solver.neural_nets() {
+                *nn = nn.clone().with_activation(Activation::Tanh);
+            }
+            train_solver(
+                &mut solver,
+                &XorProblem,
+                generations,
+                XorProblem::TARGET_FITNESS,
+                options,
+            )?
+        }
+        Some("cartpole") => {
+            let mut solver = Solver::with_config(4, 1, config);
+            train_solver(
+                &mut solver,
+                &CartPoleProblem::default(),
+                generations,
+                1.0,
+                options,
+            )?
+        }
+        _ => {
+            return Err(format!(
+                "expected `--problem xor` or `--problem cartpole`\n{}",
+                USAGE
+            ))
+        }
+    };
+    println!(
+        "stopped after {} generations ({:?}) with a fitness of {}",
+        report.history.len(),
+        report.stop_reason,
+        report.champion.fitness()
+    );
+    print!("{}", summary(&report.champion));
+    if let Some(path) = options.get("output") {
+        report
+            .champion
+            .save_as(path)
+            .map_err(|err| format!("cannot save `{}`: {}", path, err))?;
+        println!("saved the champion to `{}`", path);
+    }
+    Ok(())
+}
+
+/// Trains until the target fitness or the maximum number of generations is reached, optionally
+/// logging the statistics of every generation.
+fn train_solver<E: FitnessEvaluator>(
+    solver: &mut Solver,
+    evaluator: &E,
+    generations: usize,
+    target_fitness: f32,
+    options: &Options,
+) -> Result<TrainReport, String> {
+    let train_options = TrainOptions {
+        max_generations: Some(generations),
+        target_fitness: Some(target_fitness),
+        ..TrainOptions::default()
+    };
+    match options.get("log") {
+        Some(path) => {
+            let format = if path.ends_with(".jsonl") {
+                LogFormat::JsonLines
+            } else {
+                LogFormat::Csv
+            };
+            let mut logger = StatsLogger::create(path, format)
+                .map_err(|err| format!("cannot create `{}`: {}", path, err))?;
+            let report = solver.train_with_observer(evaluator, &train_options, &mut logger);
+            logger
+                .finish()
+                .map_err(|err| format!("cannot write `{}`: {}", path, err))?;
+            Ok(report)
+        }
+        None => Ok(solver.train(evaluator, &train_options)),
+    }
+}
@@ -0,0 +1,260 @@
+use crate::activation::{self, Activation};
+use crate::float::Float;
+use crate::phenotype::Phenotype;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Immutable representation of a neural network optimized for computing outputs, created with
+/// `NeuralNetwork::compile()`.
+///
+/// The nodes are stored in topological order in contiguous arrays and every node stores its
+/// incoming enabled edges, so a computation walks through the arrays once without any lookup.
+/// Disabled edges are left out. The results are the same as the ones of `NeuralNetwork::compute`.
+///
+/// Computing only needs `&self`, the node values are written into a separate `CompiledState`.
+/// So one compiled network can be shared between many threads, each of them using its own state.
+///
+/// # Example:
+/// ```
+/// use neaters::NeuralNetwork;
+/// let mut nn = NeuralNetwork::with_size(2, 1);
+/// let compiled = nn.compile();
+/// assert_eq!(compiled.compute(&[0.5, 0.5]), nn.compute([0.5, 0.5]));
+/// let compiled = &compiled;
+/// std::thread::scope(|s| {
+///     for _ in 0..4 {
+///         s.spawn(|| {
+///             let mut state = compiled.state();
+///             let mut output = [0.0];
+///             compiled.compute_with(&mut state, &[0.5, 0.5], &mut output);
+///         });
+///     }
+/// });
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct CompiledNetwork<F = f32> {
+    /// The number of regular input nodes without the bias node and the presence indicators.
+    inputs: usize,
+    /// The number of presence indicator nodes, they follow directly after the input nodes and are
+    /// always set to **1.0**.
+    indicators: usize,
+    /// The factor and the offset of every input, empty if the inputs are not scaled.
+    input_scaling: Vec<(F, F)>,
+    /// The activation function of each node.
+    activations: Vec<Activation>,
+    /// The incoming edges of node `i` are stored at `starts[i]..starts[i + 1]` in `sources` and
+    /// `weights`.
+    starts: Vec<usize>,
+    /// The source node of each incoming edge.
+    sources: Vec<usize>,
+    /// The weight of each incoming edge.
+    weights: Vec<F>,
+    /// The incoming recurrent edges in the same layout as the other edges, their sources pass on
+    /// the value of the previous computation. Empty if the network has no recurrent edge.
+    recurrent_starts: Vec<usize>,
+    /// The source node of each incoming recurrent edge.
+    recurrent_sources: Vec<usize>,
+    /// The weight of each incoming recurrent edge.
+    recurrent_weights: Vec<F>,
+    /// The nodes of the outputs of the network.
+    outputs: Vec<usize>,
+    /// Whether the outputs are normalized by the softmax function.
+    softmax: bool,
+}
+
+/// The node values of a computation of a `CompiledNetwork`, created with `CompiledNetwork::state`.
+///
+/// The values of a recurrent network are passed on to the next computation using the same state,
+/// for feed-forward networks the state is only a buffer, so computing does not allocate.
+#[derive(Debug, PartialEq, Clone)]
+pub struct CompiledState<F = f32> {
+    /// The values of every node of the last computation.
+    values: Vec<F>,
+    /// The values of the computation before, only used by recurrent networks.
+    previous: Vec<F>,
+}
+
+impl<F: Float> CompiledState<F> {
+    /// Forgets the node values of the previous computations, so the next computation behaves like
+    /// the first one, see `NeuralNetwork::reset_state`.
+    pub fn reset(&mut self) {
+        self.values.fill(F::ZERO);
+    }
+}
+
+impl<F: Float> CompiledNetwork<F> {
+    /// Creates the compiled network from the phenotype of a network.
+    ///
+    /// The input nodes are moved to the front of the topological order, which is possible because
+    /// they have no incoming edges. The incoming edges of a node are sorted by the position of
+    /// their source in the order of the phenotype, so the values are summed up in the same order
+    /// as in `Phenotype::propagate` and the results are exactly the same.
+    pub(crate) fn from_phenotype(pt: &Phenotype<F>) -> Self {
+        let fixed = 1 + pt.inputs + pt.indicators;
+        let mut order: Vec<usize> = (0..fixed).collect();
+        order.extend(pt.topo_order.iter().copied().filter(|&node| node >= fixed));
+        let mut position = vec![0; order.len()];
+        for (pos, &node) in order.iter().enumerate() {
+            position[node] = pos;
+        }
+        let mut phenotype_position = vec![0; pt.topo_order.len()];
+        for (pos, &node) in pt.topo_order.iter().enumerate() {
+            phenotype_position[node] = pos;
+        }
+        // incoming edges of every node as (sort key, source, weight)
+        let mut incoming: Vec<Vec<(usize, usize, F)>> = vec![Vec::new(); order.len()];
+        let mut recurrent_incoming: Vec<Vec<(usize, usize, F)>> = vec![Vec::new(); order.len()];
+        for from in 0..pt.edges.len() {
+            for &(to, weight) in pt.edges[from].iter() {
+                incoming[position[to]].push((phenotype_position[from], position[from], weight));
+            }
+            for &(to, weight) in pt.recurrent_edges[from].iter() {
+                recurrent_incoming[position[to]].push((from, position[from], weight));
+            }
+        }
+        let (starts, sources, weights) = flatten(incoming);
+        let (recurrent_starts, recurrent_sources, recurrent_weights) = if pt.recurrent {
+            flatten(recurrent_incoming)
+        } else {
+            (Vec::new(), Vec::new(), Vec::new())
+        };
+        CompiledNetwork {
+            inputs: pt.inputs,
+            indicators: pt.indicators,
+            input_scaling: pt.input_scaling.clone(),
+            activations: order.iter().map(|&node| pt.activations[node]).collect(),
+            starts,
+            sources,
+            weights,
+            recurrent_starts,
+            recurrent_sources,
+            recurrent_weights,
+            outputs: pt.outputs.iter().map(|&node| position[node]).collect(),
+            softmax: pt.softmax,
+        }
+    }
+
+    /// Returns the number of inputs and the number of outputs of the network.
+    pub fn size(&self) -> (usize, usize) {
+        (self.inputs, self.outputs.len())
+    }
+
+    /// Returns whether the network has any enabled recurrent edge, so its outputs depend on the
+    /// previous computations with the same state.
+    pub fn is_recurrent(&self) -> bool {
+        !self.recurrent_starts.is_empty()
+    }
+
+    /// Creates a new state for computations with this network, where every node value is
+    /// **0.0**.
+    pub fn state(&self) -> CompiledState<F> {
+        let nodes = self.activations.len();
+        CompiledState {
+            values: vec![F::ZERO; nodes],
+            previous: if self.is_recurrent() {
+                vec![F::ZERO; nodes]
+            } else {
+                Vec::new()
+            },
+        }
+    }
+
+    /// Computes the outputs of the network for the given inputs with a new state, so recurrent
+    /// edges behave like in the first computation of the network.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of inputs does not match the network.
+    pub fn compute(&self, inputs: &[F]) -> Vec<F> {
+        let mut outputs = vec![F::ZERO; self.outputs.len()];
+        self.compute_with(&mut self.state(), inputs, &mut outputs);
+        outputs
+    }
+
+    /// Computes the outputs of the network for the given inputs and writes them into the output
+    /// slice. The node values are stored in the state, recurrent edges pass on the values of the
+    /// previous computation with the same state.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of inputs or outputs does not match the network or if the state has
+    /// been created by a different network.
+    pub fn compute_with(&self, state: &mut CompiledState<F>, inputs: &[F], outputs: &mut [F]) {
+        assert_eq!(
+            inputs.len(),
+            self.inputs,
+            "the input needs exactly one entry for every input"
+        );
+        assert_eq!(
+            outputs.len(),
+            self.outputs.len(),
+            "the output buffer needs exactly one entry for every output"
+        );
+        assert_eq!(
+            state.values.len(),
+            self.activations.len(),
+            "the state belongs to a different network"
+        );
+        let recurrent = self.is_recurrent();
+        if recurrent {
+            core::mem::swap(&mut state.values, &mut state.previous);
+        }
+        let fixed = 1 + self.inputs + self.indicators;
+        for node in 0..fixed {
+            let value = if node == 0 || node > self.inputs {
+                F::ONE
+            } else {
+                match self.input_scaling.get(node - 1) {
+                    Some(&(factor, offset)) => inputs[node - 1] * factor + offset,
+                    None => inputs[node - 1],
+                }
+            };
+            state.values[node] = self.activations[node].apply(value);
+        }
+        for node in fixed..self.activations.len() {
+            let mut sum = F::ZERO;
+            if recurrent {
+                let edges = self.recurrent_starts[node]..self.recurrent_starts[node + 1];
+                for (&from, &weight) in self.recurrent_sources[edges.clone()]
+                    .iter()
+                    .zip(self.recurrent_weights[edges].iter())
+                {
+                    sum += weight * state.previous[from];
+                }
+            }
+            let edges = self.starts[node]..self.starts[node + 1];
+            for (&from, &weight) in self.sources[edges.clone()]
+                .iter()
+                .zip(self.weights[edges].iter())
+            {
+                sum += weight * state.values[from];
+            }
+            state.values[node] = self.activations[node].apply(sum);
+        }
+        for (output, &node) in outputs.iter_mut().zip(self.outputs.iter()) {
+            *output = state.values[node];
+        }
+        if self.softmax {
+            activation::softmax(outputs);
+        }
+    }
+}
+
+/// Sorts the incoming edges of every node by their key and stores them in contiguous arrays,
+/// returning the start of the edges of every node, their sources and their weights.
+fn flatten<F: Float>(incoming: Vec<Vec<(usize, usize, F)>>) -> (Vec<usize>, Vec<usize>, Vec<F>) {
+    let mut starts: Vec<usize> = Vec::with_capacity(incoming.len() + 1);
+    let mut sources: Vec<usize> = Vec::new();
+    let mut weights: Vec<F> = Vec::new();
+    starts.push(0);
+    for mut edges in incoming {
+        // a stable sort keeps the order of multiple edges with the same source
+        edges.sort_by_key(|&(key, _, _)| key);
+        for (_, from, weight) in edges {
+            sources.push(from);
+            weights.push(weight);
+        }
+        starts.push(sources.len());
+    }
+    (starts, sources, weights)
+}
@@ -1,23 +1,1311 @@
+use crate::activation::{Activation, OutputActivation};
+#[cfg(feature = "std")]
+use crate::error::Error;
+use crate::math;
+use crate::random;
+use alloc::vec;
+use alloc::vec::Vec;
+use rand::Rng;
+use rand_distr::{Distribution, Normal};
 use serde::{Deserialize, Serialize};
+#[cfg(all(feature = "toml", feature = "std-fs"))]
+use std::path::Path;
 
 /// Configuration for training, all sorts of parameters are stored in here
+///
+/// Use `Config::builder()` to change single parameters and keep the default values for the rest.
+///
+/// # Example:
+/// ```
+/// use neaters::Config;
+/// let config = Config::builder()
+///     .generation_size(50)
+///     .add_node_rate(0.05)
+///     .elitism(2)
+///     .build();
+/// # #[cfg(feature = "std")]
+/// let solver = neaters::Solver::with_config(2, 1, config);
+/// ```
 #[derive(Copy, Deserialize, Debug, Serialize, Clone, PartialEq)]
+#[serde(default)]
 pub struct Config {
+    /// The number of networks in one generation
+    pub generation_size: usize,
+    /// The initial compatibility distance up to which two networks belong to the same species
+    pub distance_threshold: f32,
     /// Determines how much the number of excess genes influences the compatibility distance
     pub c1: f32,
     /// Determines how much the number of disjoint genes influences the compatibility distance
     pub c2: f32,
     /// Determines how much the average weight difference of matching edges influences the compatibility distance
     pub c3: f32,
+    /// The number of species the distance threshold is adjusted towards after every generation,
+    /// `None` keeps the distance threshold fixed
+    pub target_species: Option<usize>,
+    /// The amount by which the distance threshold changes if there are too many or too few species
+    pub threshold_adjustment: f32,
+    /// The lower bound of the distance threshold when it is being adjusted
+    pub min_distance_threshold: f32,
+    /// The distance between networks which decides whether they belong to the same species
+    pub speciation_metric: SpeciationMetric,
+    /// The member of every species which becomes its representative in the next generation
+    pub representative: RepresentativeSelection,
+    /// The fraction of each species (sorted by fitness) which survives and is allowed to reproduce
+    pub survival_threshold: f32,
+    /// The scheme for selecting the parents of an offspring among the survivors of a species
+    pub selection: SelectionStrategy,
+    /// The normalization of the fitness values of a generation before they are shared within the
+    /// species and used for the selection
+    pub fitness_transform: FitnessTransform,
+    /// Whether a higher or a lower fitness is better
+    pub direction: Direction,
+    /// The number of generations without improvement after which a species goes extinct
+    pub stagnation_limit: usize,
+    /// Species which have been created less than this number of generations ago are young
+    pub young_species_age: usize,
+    /// The factor by which the adjusted fitness of the members of young species is multiplied,
+    /// so new topologies get time to optimize their weights before being out-competed
+    pub young_species_boost: f32,
+    /// Species which have been created at least this number of generations ago are old
+    pub old_species_age: usize,
+    /// The factor by which the adjusted fitness of the members of old species is multiplied, a
+    /// value below **1.0** makes room for newer species
+    pub old_species_penalty: f32,
+    /// The number of best networks of the whole generation which are copied into the next
+    /// generation unchanged
+    pub elitism: usize,
+    /// The number of best networks of every species which are copied into the next generation
+    /// unchanged
+    pub species_elitism: usize,
+    /// The number of members a species needs to exceed for `species_elitism` to apply, the
+    /// original NEAT paper only keeps the champions of species with more than **5** networks
+    pub species_elitism_min_size: usize,
+    /// The minimum number of offspring of every surviving species, so new species are not
+    /// eliminated before they had the chance to optimize their structure
+    pub min_species_offspring: usize,
+    /// The probability that an offspring is created through crossover instead of only mutation
+    pub crossover_rate: f32,
+    /// The probability that a gene which is disabled in one of the parents gets enabled again in
+    /// the offspring of a crossover
+    pub reenable_rate: f32,
+    /// The probability that the second parent of a crossover is selected from another species
+    pub interspecies_mating_rate: f32,
+    /// The probability that the weight of an edge gets perturbed when mutating a network
+    pub weight_mutation_rate: f32,
+    /// The probability that the weight of an edge gets replaced by a new random value when
+    /// mutating a network
+    pub weight_replace_rate: f32,
+    /// The distribution of the amount by which a weight gets perturbed
+    pub weight_perturbation: Perturbation,
+    /// The distribution of the weights of the initial networks and of new connections
+    pub weight_init: WeightInit,
+    /// Which of the initial connections between the inputs and the outputs exist
+    pub initial_topology: InitialTopology,
+    /// The probability that a new hidden node is added by splitting an edge when mutating a network
+    pub add_node_rate: f32,
+    /// The probability that a new connection is added when mutating a network
+    pub add_connection_rate: f32,
+    /// The probability that the activation function of a node is changed when mutating a network
+    pub activation_mutation_rate: f32,
+    /// The activation functions a node can get when its activation function is mutated
+    pub activation_set: ActivationSet,
+    /// The activation of the output nodes, see `NeuralNetwork::with_output_activation`
+    pub output_activation: OutputActivation,
+    /// The probability that the enabled flag of a random edge is toggled when mutating a network
+    pub toggle_connection_rate: f32,
+    /// The probability that a random disabled edge is enabled again when mutating a network
+    pub reenable_connection_rate: f32,
+    /// The probability that a new module is defined and instantiated when mutating a network,
+    /// see `NeuralNetwork::mutate_add_module`
+    pub add_module_rate: f32,
+    /// The number of inputs of the modules which are defined when mutating a network
+    pub module_inputs: usize,
+    /// The probability that a module is instantiated once more when mutating a network, see
+    /// `NeuralNetwork::mutate_add_module_instance`
+    pub add_module_instance_rate: f32,
+    /// The probability that a new node is added to a module when mutating a network, see
+    /// `NeuralNetwork::mutate_module_add_node`
+    pub module_add_node_rate: f32,
+    /// How the mutation rates change over the generations
+    pub mutation_schedule: RateSchedule,
+    /// The learning rate of self-adaptive mutation rates, `None` mutates every network with the
+    /// rates of the config. Otherwise every network carries its own mutation rates, which are
+    /// inherited from its fitter parent and multiplied by a log-normal factor with this standard
+    /// deviation before the network is mutated, see `MutationRates`
+    pub self_adaptive_mutation: Option<f32>,
+    /// Whether the networks are recurrent, see `NeuralNetwork::with_recurrent`
+    pub recurrent: bool,
+    /// Whether every new hidden node gets its own bias, see `NeuralNetwork::with_node_biases`
+    pub node_biases: bool,
+    /// The number of best networks ever seen which are kept in the hall of fame
+    pub hall_of_fame_size: usize,
+    /// The maximum number of times an offspring is created if the offspring validator of the
+    /// solver rejects it, see `Solver::set_offspring_validator`
+    pub offspring_attempts: usize,
+    /// Whether the solver records the parents and the mutations of every network, see
+    /// `Solver::lineage`
+    pub track_lineage: bool,
+    /// The amount by which the fitness is reduced for every hidden node of a network during the
+    /// selection, so smaller networks are preferred
+    pub node_penalty: f32,
+    /// The amount by which the fitness is reduced for every enabled edge of a network during the
+    /// selection, so smaller networks are preferred
+    pub edge_penalty: f32,
+    /// Whether networks with the same fitness are ranked by their size during the selection, the
+    /// network with fewer hidden nodes and enabled edges first
+    pub prefer_smaller_networks: bool,
+    /// The number of nearest neighbours whose average behavior distance is the novelty of a
+    /// network, see `Solver::assign_novelty_fitness`
+    pub novelty_neighbors: usize,
+    /// How much the novelty contributes to the fitness in novelty search, **0.0** only uses the
+    /// fitness of the evaluator and **1.0** only uses the novelty
+    pub novelty_weight: f32,
+    /// The number of most novel behaviors of every generation which are added to the novelty
+    /// archive
+    pub novelty_archive_additions: usize,
+    /// The seed of the random number generator of the solver, `None` uses a random seed. With the
+    /// same seed and the same fitness values every run creates exactly the same generations
+    pub seed: Option<u64>,
 }
 
 // TODO: find useful default parameters
 impl Default for Config {
     fn default() -> Self {
         Config {
+            generation_size: 150,
+            distance_threshold: 1.0,
             c1: 1.0,
             c2: 1.0,
             c3: 1.0,
+            target_species: Some(10),
+            threshold_adjustment: 0.1,
+            min_distance_threshold: 0.1,
+            speciation_metric: SpeciationMetric::Genetic,
+            representative: RepresentativeSelection::Random,
+            survival_threshold: 0.2,
+            selection: SelectionStrategy::Roulette,
+            fitness_transform: FitnessTransform::Shift,
+            direction: Direction::Maximize,
+            stagnation_limit: 15,
+            young_species_age: 10,
+            young_species_boost: 1.0,
+            old_species_age: 50,
+            old_species_penalty: 1.0,
+            elitism: 1,
+            species_elitism: 0,
+            species_elitism_min_size: 0,
+            min_species_offspring: 0,
+            crossover_rate: 0.75,
+            reenable_rate: 0.25,
+            interspecies_mating_rate: 0.001,
+            weight_mutation_rate: 0.8,
+            weight_replace_rate: 0.1,
+            weight_perturbation: Perturbation::Uniform(0.5),
+            weight_init: WeightInit::Uniform(1.0),
+            initial_topology: InitialTopology::FullyConnected,
+            add_node_rate: 0.03,
+            add_connection_rate: 0.05,
+            activation_mutation_rate: 0.0,
+            activation_set: ActivationSet::All,
+            output_activation: OutputActivation::Evolved,
+            toggle_connection_rate: 0.0,
+            reenable_connection_rate: 0.0,
+            add_module_rate: 0.0,
+            module_inputs: 2,
+            add_module_instance_rate: 0.0,
+            module_add_node_rate: 0.0,
+            mutation_schedule: RateSchedule::Constant,
+            self_adaptive_mutation: None,
+            recurrent: false,
+            node_biases: false,
+            hall_of_fame_size: 10,
+            offspring_attempts: 10,
+            track_lineage: false,
+            node_penalty: 0.0,
+            edge_penalty: 0.0,
+            prefer_smaller_networks: false,
+            novelty_neighbors: 15,
+            novelty_weight: 1.0,
+            novelty_archive_additions: 1,
+            seed: None,
         }
     }
 }
+
+impl Config {
+    /// Creates a builder starting with the default configuration.
+    pub fn builder() -> ConfigBuilder {
+        ConfigBuilder::default()
+    }
+
+    /// Returns the config whose mutation rates are multiplied by the factor of the
+    /// `mutation_schedule` for the given generation.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::{Config, RateSchedule};
+    /// let config = Config::builder()
+    ///     .add_node_rate(0.1)
+    ///     .mutation_schedule(RateSchedule::Linear {
+    ///         final_factor: 0.5,
+    ///         generations: 100,
+    ///     })
+    ///     .build();
+    /// assert_eq!(config.at_generation(0).add_node_rate, 0.1);
+    /// assert_eq!(config.at_generation(50).add_node_rate, 0.075);
+    /// assert_eq!(config.at_generation(200).add_node_rate, 0.05);
+    /// ```
+    pub fn at_generation(&self, generation: usize) -> Config {
+        MutationRates::of(self)
+            .scaled(self.mutation_schedule.factor(generation))
+            .apply_to(self)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Config {
+    /// Checks that every parameter is within its valid range: probabilities and fractions are in
+    /// the range from **0.0** to **1.0**, coefficients, factors and penalties are not negative and
+    /// every generation has at least one network. Returns an `Error::InvalidConfig` describing
+    /// every invalid parameter otherwise.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::{Config, Error};
+    /// assert!(Config::default().validate().is_ok());
+    /// let config = Config::builder().crossover_rate(1.5).build();
+    /// let Err(Error::InvalidConfig(problems)) = config.validate() else { panic!() };
+    /// assert_eq!(problems, "crossover_rate is 1.5, but it has to be between 0 and 1");
+    /// ```
+    pub fn validate(&self) -> Result<(), Error> {
+        let mut problems: Vec<String> = Vec::new();
+        let probabilities = [
+            ("survival_threshold", self.survival_threshold),
+            ("crossover_rate", self.crossover_rate),
+            ("reenable_rate", self.reenable_rate),
+            ("interspecies_mating_rate", self.interspecies_mating_rate),
+            ("weight_mutation_rate", self.weight_mutation_rate),
+            ("weight_replace_rate", self.weight_replace_rate),
+            ("add_node_rate", self.add_node_rate),
+            ("add_connection_rate", self.add_connection_rate),
+            ("activation_mutation_rate", self.activation_mutation_rate),
+            ("toggle_connection_rate", self.toggle_connection_rate),
+            ("reenable_connection_rate", self.reenable_connection_rate),
+            ("add_module_rate", self.add_module_rate),
+            ("add_module_instance_rate", self.add_module_instance_rate),
+            ("module_add_node_rate", self.module_add_node_rate),
+            ("novelty_weight", self.novelty_weight),
+        ];
+        let mut probabilities = probabilities.to_vec();
+        match self.mutation_schedule {
+            RateSchedule::Constant => {}
+            RateSchedule::Linear { final_factor, .. } => {
+                probabilities.push(("the final factor of the mutation schedule", final_factor))
+            }
+            RateSchedule::Exponential { decay, min_factor } => {
+                probabilities.push(("the decay of the mutation schedule", decay));
+                probabilities.push(("the minimum factor of the mutation schedule", min_factor));
+            }
+        }
+        for (name, value) in probabilities {
+            if !(0.0..=1.0).contains(&value) {
+                problems.push(format!(
+                    "{} is {}, but it has to be between 0 and 1",
+                    name, value
+                ));
+            }
+        }
+        let mut non_negative = vec![
+            ("distance_threshold", self.distance_threshold),
+            ("c1", self.c1),
+            ("c2", self.c2),
+            ("c3", self.c3),
+            ("threshold_adjustment", self.threshold_adjustment),
+            ("min_distance_threshold", self.min_distance_threshold),
+            ("young_species_boost", self.young_species_boost),
+            ("old_species_penalty", self.old_species_penalty),
+            ("node_penalty", self.node_penalty),
+            ("edge_penalty", self.edge_penalty),
+        ];
+        if let Some(learning_rate) = self.self_adaptive_mutation {
+            non_negative.push(("self_adaptive_mutation", learning_rate));
+        }
+        match self.weight_perturbation {
+            Perturbation::Uniform(x) | Perturbation::Gaussian(x) => {
+                non_negative.push(("weight_perturbation", x))
+            }
+        }
+        match self.weight_init {
+            WeightInit::Uniform(x) => non_negative.push(("weight_init", x)),
+            WeightInit::Gaussian { std_dev, .. } => non_negative.push(("weight_init", std_dev)),
+            WeightInit::Constant(_) | WeightInit::Xavier => {}
+        }
+        for (name, value) in non_negative {
+            if value.is_nan() || value < 0.0 {
+                problems.push(format!(
+                    "{} is {}, but it must not be negative",
+                    name, value
+                ));
+            }
+        }
+        if self.generation_size == 0 {
+            problems.push("generation_size is 0, but it has to be at least 1".to_string());
+        }
+        if self.add_module_rate > 0.0 && self.module_inputs == 0 {
+            problems.push("module_inputs is 0, but modules need at least 1 input".to_string());
+        }
+        if self.offspring_attempts == 0 {
+            problems.push("offspring_attempts is 0, but it has to be at least 1".to_string());
+        }
+        match self.selection {
+            SelectionStrategy::Tournament(0) => {
+                problems.push("the tournament size is 0, but it has to be at least 1".to_string())
+            }
+            SelectionStrategy::Truncation(fraction) if !(0.0..=1.0).contains(&fraction) => problems
+                .push(format!(
+                    "the truncation fraction is {}, but it has to be between 0 and 1",
+                    fraction
+                )),
+            _ => {}
+        }
+        if let SpeciationMetric::Blend(weight) = self.speciation_metric {
+            if !(0.0..=1.0).contains(&weight) {
+                problems.push(format!(
+                    "the weight of the behavioral distance is {}, but it has to be between 0 and 1",
+                    weight
+                ));
+            }
+        }
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::InvalidConfig(problems.join("; ")))
+        }
+    }
+
+    /// Reads a config from its toml representation and validates it, so hyperparameters can be
+    /// tuned without recompiling. Every parameter which is missing keeps its default value.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::{Config, InitialTopology};
+    /// let config = Config::from_toml_str(
+    ///     "generation_size = 50\nseed = 7\ninitial_topology = { Sparse = 2 }",
+    /// )?;
+    /// assert_eq!(config.generation_size, 50);
+    /// assert_eq!(config.seed, Some(7));
+    /// assert_eq!(config.initial_topology, InitialTopology::Sparse(2));
+    /// assert_eq!(config.crossover_rate, Config::default().crossover_rate);
+    /// assert!(Config::from_toml_str("add_node_rate = -0.1").is_err());
+    /// # Ok::<(), neaters::Error>(())
+    /// ```
+    #[cfg(feature = "toml")]
+    pub fn from_toml_str(toml: &str) -> Result<Self, Error> {
+        let config: Config = toml::from_str(toml)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Reads a config from a toml file and validates it, see `from_toml_str`.
+    #[cfg(all(feature = "toml", feature = "std-fs"))]
+    pub fn from_toml_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Config::from_toml_str(&std::fs::read_to_string(path)?)
+    }
+}
+
+/// Builder to create a `Config` fluently, every parameter which is not set keeps its default
+/// value.
+#[derive(Copy, Debug, Clone, PartialEq, Default)]
+pub struct ConfigBuilder {
+    config: Config,
+}
+
+impl ConfigBuilder {
+    /// Sets `generation_size`, the number of networks in one generation
+    pub fn generation_size(mut self, generation_size: usize) -> Self {
+        self.config.generation_size = generation_size;
+        self
+    }
+
+    /// Sets `distance_threshold`, the initial compatibility distance up to which two networks
+    /// belong to the same species
+    pub fn distance_threshold(mut self, distance_threshold: f32) -> Self {
+        self.config.distance_threshold = distance_threshold;
+        self
+    }
+
+    /// Sets `c1` which determines how much the number of excess genes influences the compatibility
+    /// distance
+    pub fn c1(mut self, c1: f32) -> Self {
+        self.config.c1 = c1;
+        self
+    }
+
+    /// Sets `c2` which determines how much the number of disjoint genes influences the
+    /// compatibility distance
+    pub fn c2(mut self, c2: f32) -> Self {
+        self.config.c2 = c2;
+        self
+    }
+
+    /// Sets `c3` which determines how much the average weight difference of matching edges
+    /// influences the compatibility distance
+    pub fn c3(mut self, c3: f32) -> Self {
+        self.config.c3 = c3;
+        self
+    }
+
+    /// Sets `target_species`, the number of species the distance threshold is adjusted towards
+    /// after every generation, `None` keeps the distance threshold fixed
+    pub fn target_species(mut self, target_species: Option<usize>) -> Self {
+        self.config.target_species = target_species;
+        self
+    }
+
+    /// Sets `threshold_adjustment`, the amount by which the distance threshold changes if there are
+    /// too many or too few species
+    pub fn threshold_adjustment(mut self, threshold_adjustment: f32) -> Self {
+        self.config.threshold_adjustment = threshold_adjustment;
+        self
+    }
+
+    /// Sets `min_distance_threshold`, the lower bound of the distance threshold when it is being
+    /// adjusted
+    pub fn min_distance_threshold(mut self, min_distance_threshold: f32) -> Self {
+        self.config.min_distance_threshold = min_distance_threshold;
+        self
+    }
+
+    /// Sets `speciation_metric`, the distance between networks which decides whether they belong
+    /// to the same species
+    pub fn speciation_metric(mut self, speciation_metric: SpeciationMetric) -> Self {
+        self.config.speciation_metric = speciation_metric;
+        self
+    }
+
+    /// Sets `representative`, the member of every species which becomes its representative in
+    /// the next generation
+    pub fn representative(mut self, representative: RepresentativeSelection) -> Self {
+        self.config.representative = representative;
+        self
+    }
+
+    /// Sets `survival_threshold`, the fraction of each species (sorted by fitness) which survives
+    /// and is allowed to reproduce
+    pub fn survival_threshold(mut self, survival_threshold: f32) -> Self {
+        self.config.survival_threshold = survival_threshold;
+        self
+    }
+
+    /// Sets `stagnation_limit`, the number of generations without improvement after which a species
+    /// goes extinct
+    pub fn stagnation_limit(mut self, stagnation_limit: usize) -> Self {
+        self.config.stagnation_limit = stagnation_limit;
+        self
+    }
+
+    /// Sets `young_species_age`, the number of generations during which a new species counts as
+    /// young
+    pub fn young_species_age(mut self, young_species_age: usize) -> Self {
+        self.config.young_species_age = young_species_age;
+        self
+    }
+
+    /// Sets `young_species_boost`, the factor by which the adjusted fitness of the members of
+    /// young species is multiplied
+    pub fn young_species_boost(mut self, young_species_boost: f32) -> Self {
+        self.config.young_species_boost = young_species_boost;
+        self
+    }
+
+    /// Sets `old_species_age`, the number of generations after which a species counts as old
+    pub fn old_species_age(mut self, old_species_age: usize) -> Self {
+        self.config.old_species_age = old_species_age;
+        self
+    }
+
+    /// Sets `old_species_penalty`, the factor by which the adjusted fitness of the members of old
+    /// species is multiplied
+    pub fn old_species_penalty(mut self, old_species_penalty: f32) -> Self {
+        self.config.old_species_penalty = old_species_penalty;
+        self
+    }
+
+    /// Sets `elitism`, the number of best networks of the whole generation which are copied into
+    /// the next generation unchanged
+    pub fn elitism(mut self, elitism: usize) -> Self {
+        self.config.elitism = elitism;
+        self
+    }
+
+    /// Sets `species_elitism`, the number of best networks of every species which are copied into
+    /// the next generation unchanged
+    pub fn species_elitism(mut self, species_elitism: usize) -> Self {
+        self.config.species_elitism = species_elitism;
+        self
+    }
+
+    /// Sets `species_elitism_min_size`, the number of members a species needs to exceed for
+    /// `species_elitism` to apply
+    pub fn species_elitism_min_size(mut self, species_elitism_min_size: usize) -> Self {
+        self.config.species_elitism_min_size = species_elitism_min_size;
+        self
+    }
+
+    /// Sets `min_species_offspring`, the minimum number of offspring of every surviving species
+    pub fn min_species_offspring(mut self, min_species_offspring: usize) -> Self {
+        self.config.min_species_offspring = min_species_offspring;
+        self
+    }
+
+    /// Sets `selection`, the scheme for selecting the parents of an offspring among the survivors
+    /// of a species
+    pub fn selection(mut self, selection: SelectionStrategy) -> Self {
+        self.config.selection = selection;
+        self
+    }
+
+    /// Sets `fitness_transform`, the normalization of the fitness values of a generation before
+    /// they are shared within the species and used for the selection
+    pub fn fitness_transform(mut self, fitness_transform: FitnessTransform) -> Self {
+        self.config.fitness_transform = fitness_transform;
+        self
+    }
+
+    /// Sets `direction`, whether a higher or a lower fitness is better
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.config.direction = direction;
+        self
+    }
+
+    /// Sets `crossover_rate`, the probability that an offspring is created through crossover
+    /// instead of only mutation
+    pub fn crossover_rate(mut self, crossover_rate: f32) -> Self {
+        self.config.crossover_rate = crossover_rate;
+        self
+    }
+
+    /// Sets `interspecies_mating_rate`, the probability that the second parent of a crossover is
+    /// selected from another species
+    pub fn interspecies_mating_rate(mut self, interspecies_mating_rate: f32) -> Self {
+        self.config.interspecies_mating_rate = interspecies_mating_rate;
+        self
+    }
+
+    /// Sets `reenable_rate`, the probability that a gene which is disabled in one of the parents
+    /// gets enabled again in the offspring of a crossover
+    pub fn reenable_rate(mut self, reenable_rate: f32) -> Self {
+        self.config.reenable_rate = reenable_rate;
+        self
+    }
+
+    /// Sets `weight_mutation_rate`, the probability that the weight of an edge gets perturbed when
+    /// mutating a network
+    pub fn weight_mutation_rate(mut self, weight_mutation_rate: f32) -> Self {
+        self.config.weight_mutation_rate = weight_mutation_rate;
+        self
+    }
+
+    /// Sets `weight_replace_rate`, the probability that the weight of an edge gets replaced by a
+    /// new random value when mutating a network
+    pub fn weight_replace_rate(mut self, weight_replace_rate: f32) -> Self {
+        self.config.weight_replace_rate = weight_replace_rate;
+        self
+    }
+
+    /// Sets `weight_perturbation`, the distribution of the amount by which a weight gets perturbed
+    pub fn weight_perturbation(mut self, weight_perturbation: Perturbation) -> Self {
+        self.config.weight_perturbation = weight_perturbation;
+        self
+    }
+
+    /// Sets `add_node_rate`, the probability that a new hidden node is added by splitting an edge
+    /// when mutating a network
+    pub fn add_node_rate(mut self, add_node_rate: f32) -> Self {
+        self.config.add_node_rate = add_node_rate;
+        self
+    }
+
+    /// Sets `add_connection_rate`, the probability that a new connection is added when mutating a
+    /// network
+    pub fn add_connection_rate(mut self, add_connection_rate: f32) -> Self {
+        self.config.add_connection_rate = add_connection_rate;
+        self
+    }
+
+    /// Sets `activation_mutation_rate`, the probability that the activation function of a node is
+    /// changed when mutating a network
+    pub fn activation_mutation_rate(mut self, activation_mutation_rate: f32) -> Self {
+        self.config.activation_mutation_rate = activation_mutation_rate;
+        self
+    }
+
+    /// Sets `activation_set`, the activation functions a node can get when its activation
+    /// function is mutated
+    pub fn activation_set(mut self, activation_set: ActivationSet) -> Self {
+        self.config.activation_set = activation_set;
+        self
+    }
+
+    /// Sets `output_activation`, the activation of the output nodes
+    pub fn output_activation(mut self, output_activation: OutputActivation) -> Self {
+        self.config.output_activation = output_activation;
+        self
+    }
+
+    /// Sets `toggle_connection_rate`, the probability that the enabled flag of a random edge is
+    /// toggled when mutating a network
+    pub fn toggle_connection_rate(mut self, toggle_connection_rate: f32) -> Self {
+        self.config.toggle_connection_rate = toggle_connection_rate;
+        self
+    }
+
+    /// Sets `reenable_connection_rate`, the probability that a random disabled edge is enabled
+    /// again when mutating a network
+    pub fn reenable_connection_rate(mut self, reenable_connection_rate: f32) -> Self {
+        self.config.reenable_connection_rate = reenable_connection_rate;
+        self
+    }
+
+    /// Sets `add_module_rate`, the probability that a new module is defined and instantiated
+    /// when mutating a network
+    pub fn add_module_rate(mut self, add_module_rate: f32) -> Self {
+        self.config.add_module_rate = add_module_rate;
+        self
+    }
+
+    /// Sets `module_inputs`, the number of inputs of the modules which are defined when mutating
+    /// a network
+    pub fn module_inputs(mut self, module_inputs: usize) -> Self {
+        self.config.module_inputs = module_inputs;
+        self
+    }
+
+    /// Sets `add_module_instance_rate`, the probability that a module is instantiated once more
+    /// when mutating a network
+    pub fn add_module_instance_rate(mut self, add_module_instance_rate: f32) -> Self {
+        self.config.add_module_instance_rate = add_module_instance_rate;
+        self
+    }
+
+    /// Sets `module_add_node_rate`, the probability that a new node is added to a module when
+    /// mutating a network
+    pub fn module_add_node_rate(mut self, module_add_node_rate: f32) -> Self {
+        self.config.module_add_node_rate = module_add_node_rate;
+        self
+    }
+
+    /// Sets `mutation_schedule`, how the mutation rates change over the generations
+    pub fn mutation_schedule(mut self, mutation_schedule: RateSchedule) -> Self {
+        self.config.mutation_schedule = mutation_schedule;
+        self
+    }
+
+    /// Sets `self_adaptive_mutation`, the learning rate of the mutation rates which every network
+    /// carries and evolves itself
+    pub fn self_adaptive_mutation(mut self, learning_rate: Option<f32>) -> Self {
+        self.config.self_adaptive_mutation = learning_rate;
+        self
+    }
+
+    /// Sets `recurrent`, whether the networks are recurrent, see `NeuralNetwork::with_recurrent`
+    pub fn recurrent(mut self, recurrent: bool) -> Self {
+        self.config.recurrent = recurrent;
+        self
+    }
+
+    /// Sets `node_biases`, whether every new hidden node gets its own bias, see
+    /// `NeuralNetwork::with_node_biases`
+    pub fn node_biases(mut self, node_biases: bool) -> Self {
+        self.config.node_biases = node_biases;
+        self
+    }
+
+    /// Sets `track_lineage`, whether the solver records the parents and the mutations of every
+    /// network, see `Solver::lineage`
+    pub fn track_lineage(mut self, track_lineage: bool) -> Self {
+        self.config.track_lineage = track_lineage;
+        self
+    }
+
+    /// Sets `hall_of_fame_size`, the number of best networks ever seen which are kept in the hall
+    /// of fame
+    pub fn hall_of_fame_size(mut self, hall_of_fame_size: usize) -> Self {
+        self.config.hall_of_fame_size = hall_of_fame_size;
+        self
+    }
+
+    /// Sets `offspring_attempts`, the maximum number of times an offspring is created if the
+    /// offspring validator of the solver rejects it
+    pub fn offspring_attempts(mut self, offspring_attempts: usize) -> Self {
+        self.config.offspring_attempts = offspring_attempts;
+        self
+    }
+
+    /// Sets `weight_init`, the distribution of the weights of the initial networks and of new
+    /// connections
+    pub fn weight_init(mut self, weight_init: WeightInit) -> Self {
+        self.config.weight_init = weight_init;
+        self
+    }
+
+    /// Sets `initial_topology`, which of the initial connections between the inputs and the
+    /// outputs exist
+    pub fn initial_topology(mut self, initial_topology: InitialTopology) -> Self {
+        self.config.initial_topology = initial_topology;
+        self
+    }
+
+    /// Sets `node_penalty`, the amount by which the fitness is reduced for every hidden node
+    /// during the selection
+    pub fn node_penalty(mut self, node_penalty: f32) -> Self {
+        self.config.node_penalty = node_penalty;
+        self
+    }
+
+    /// Sets `edge_penalty`, the amount by which the fitness is reduced for every enabled edge
+    /// during the selection
+    pub fn edge_penalty(mut self, edge_penalty: f32) -> Self {
+        self.config.edge_penalty = edge_penalty;
+        self
+    }
+
+    /// Sets `prefer_smaller_networks`, whether networks with the same fitness are ranked by
+    /// their size during the selection
+    pub fn prefer_smaller_networks(mut self, prefer_smaller_networks: bool) -> Self {
+        self.config.prefer_smaller_networks = prefer_smaller_networks;
+        self
+    }
+
+    /// Sets `novelty_neighbors`, the number of nearest neighbours whose average behavior distance
+    /// is the novelty of a network
+    pub fn novelty_neighbors(mut self, novelty_neighbors: usize) -> Self {
+        self.config.novelty_neighbors = novelty_neighbors;
+        self
+    }
+
+    /// Sets `novelty_weight`, how much the novelty contributes to the fitness in novelty search
+    pub fn novelty_weight(mut self, novelty_weight: f32) -> Self {
+        self.config.novelty_weight = novelty_weight;
+        self
+    }
+
+    /// Sets `novelty_archive_additions`, the number of most novel behaviors of every generation
+    /// which are added to the novelty archive
+    pub fn novelty_archive_additions(mut self, novelty_archive_additions: usize) -> Self {
+        self.config.novelty_archive_additions = novelty_archive_additions;
+        self
+    }
+
+    /// Sets `seed`, the seed of the random number generator of the solver, so every run with the
+    /// same fitness values creates exactly the same generations
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.config.seed = Some(seed);
+        self
+    }
+
+    /// Returns the finished configuration.
+    pub fn build(self) -> Config {
+        self.config
+    }
+}
+
+/// Distribution of the random delta which is added to a weight when perturbing it
+#[derive(Copy, Deserialize, Debug, Serialize, Clone, PartialEq)]
+pub enum Perturbation {
+    /// Uniformly distributed delta in the range from `-x` to `x`
+    Uniform(f32),
+    /// Normally distributed delta with a mean of **0.0** and the given standard deviation
+    Gaussian(f32),
+}
+
+impl Perturbation {
+    /// Samples a random delta from the distribution
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> f32 {
+        match *self {
+            Perturbation::Uniform(x) => rng.gen_range(-x..=x),
+            Perturbation::Gaussian(std) => match Normal::new(0.0, std) {
+                Ok(normal) => normal.sample(rng),
+                // a negative or invalid standard deviation does not perturb the weight
+                Err(_) => 0.0,
+            },
+        }
+    }
+}
+
+/// How the mutation rates of the config change over the generations, e.g. to explore with high
+/// rates first and to fine-tune with lower rates later
+#[derive(Copy, Deserialize, Debug, Serialize, Clone, PartialEq, Default)]
+pub enum RateSchedule {
+    /// The rates stay the same in every generation
+    #[default]
+    Constant,
+    /// The rates decrease linearly to `final_factor` times their value within the given number of
+    /// generations and stay there afterwards
+    Linear {
+        /// The factor of the rates after the given number of generations
+        final_factor: f32,
+        /// The number of generations until the final factor is reached
+        generations: usize,
+    },
+    /// The rates are multiplied by `decay` in every generation, but never drop below `min_factor`
+    /// times their value
+    Exponential {
+        /// The factor by which the rates decrease in every generation
+        decay: f32,
+        /// The smallest factor of the rates
+        min_factor: f32,
+    },
+}
+
+impl RateSchedule {
+    /// Returns the factor of the mutation rates in the given generation, which is **1.0** in the
+    /// first generation
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::RateSchedule;
+    /// let schedule = RateSchedule::Exponential {
+    ///     decay: 0.5,
+    ///     min_factor: 0.1,
+    /// };
+    /// assert_eq!(schedule.factor(0), 1.0);
+    /// assert_eq!(schedule.factor(2), 0.25);
+    /// assert_eq!(schedule.factor(10), 0.1);
+    /// ```
+    pub fn factor(&self, generation: usize) -> f32 {
+        match *self {
+            RateSchedule::Constant => 1.0,
+            RateSchedule::Linear {
+                final_factor,
+                generations,
+            } => {
+                let progress = match generations {
+                    0 => 1.0,
+                    generations => (generation as f32 / generations as f32).min(1.0),
+                };
+                1.0 + (final_factor - 1.0) * progress
+            }
+            RateSchedule::Exponential { decay, min_factor } => {
+                math::powi(decay, generation).max(min_factor)
+            }
+        }
+    }
+}
+
+/// The mutation rates of a network, which evolve together with the network if the config enables
+/// `self_adaptive_mutation`. Every field is the rate of the config with the same name
+#[derive(Copy, Deserialize, Debug, Serialize, Clone, PartialEq)]
+pub struct MutationRates {
+    /// See `Config::weight_mutation_rate`
+    pub weight_mutation_rate: f32,
+    /// See `Config::weight_replace_rate`
+    pub weight_replace_rate: f32,
+    /// See `Config::add_node_rate`
+    pub add_node_rate: f32,
+    /// See `Config::add_connection_rate`
+    pub add_connection_rate: f32,
+    /// See `Config::activation_mutation_rate`
+    pub activation_mutation_rate: f32,
+    /// See `Config::toggle_connection_rate`
+    pub toggle_connection_rate: f32,
+    /// See `Config::reenable_connection_rate`
+    pub reenable_connection_rate: f32,
+}
+
+impl MutationRates {
+    /// Returns the mutation rates of the config
+    pub fn of(config: &Config) -> Self {
+        MutationRates {
+            weight_mutation_rate: config.weight_mutation_rate,
+            weight_replace_rate: config.weight_replace_rate,
+            add_node_rate: config.add_node_rate,
+            add_connection_rate: config.add_connection_rate,
+            activation_mutation_rate: config.activation_mutation_rate,
+            toggle_connection_rate: config.toggle_connection_rate,
+            reenable_connection_rate: config.reenable_connection_rate,
+        }
+    }
+
+    /// Returns the config with these mutation rates
+    pub fn apply_to(&self, config: &Config) -> Config {
+        Config {
+            weight_mutation_rate: self.weight_mutation_rate,
+            weight_replace_rate: self.weight_replace_rate,
+            add_node_rate: self.add_node_rate,
+            add_connection_rate: self.add_connection_rate,
+            activation_mutation_rate: self.activation_mutation_rate,
+            toggle_connection_rate: self.toggle_connection_rate,
+            reenable_connection_rate: self.reenable_connection_rate,
+            ..*config
+        }
+    }
+
+    /// Returns the rates multiplied by the factor
+    pub fn scaled(&self, factor: f32) -> Self {
+        self.map(|rate| rate * factor)
+    }
+
+    /// Returns the rates where every rate is multiplied by its own random factor `exp(x)`, where
+    /// `x` is normally distributed with the learning rate as standard deviation, and limited to
+    /// the range from **0.0** to **1.0**
+    pub fn perturbed<R: Rng + ?Sized>(&self, learning_rate: f32, rng: &mut R) -> Self {
+        let perturbation = Perturbation::Gaussian(learning_rate);
+        self.map(|rate| (rate * math::exp(perturbation.sample(rng))).clamp(0.0, 1.0))
+    }
+
+    /// Applies the function to every rate in the order of the fields
+    fn map(&self, mut f: impl FnMut(f32) -> f32) -> Self {
+        MutationRates {
+            weight_mutation_rate: f(self.weight_mutation_rate),
+            weight_replace_rate: f(self.weight_replace_rate),
+            add_node_rate: f(self.add_node_rate),
+            add_connection_rate: f(self.add_connection_rate),
+            activation_mutation_rate: f(self.activation_mutation_rate),
+            toggle_connection_rate: f(self.toggle_connection_rate),
+            reenable_connection_rate: f(self.reenable_connection_rate),
+        }
+    }
+}
+
+/// The activation functions which are chosen from when the activation function of a node is
+/// mutated
+#[derive(Copy, Deserialize, Debug, Serialize, Clone, PartialEq, Default)]
+pub enum ActivationSet {
+    /// Every activation function, see `Activation::ALL`
+    #[default]
+    All,
+    /// The activation functions of compositional pattern-producing networks, see
+    /// `Activation::CPPN`, used to evolve the CPPNs of HyperNEAT
+    Cppn,
+}
+
+impl ActivationSet {
+    /// Returns the activation functions of the set.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::{Activation, ActivationSet};
+    /// assert!(ActivationSet::Cppn.functions().contains(&Activation::Sine));
+    /// assert!(!ActivationSet::Cppn.functions().contains(&Activation::ReLU));
+    /// ```
+    pub fn functions(&self) -> &'static [Activation] {
+        match self {
+            ActivationSet::All => &Activation::ALL,
+            ActivationSet::Cppn => &Activation::CPPN,
+        }
+    }
+}
+
+/// The distance between two networks used by the speciation, the networks of a generation join a
+/// species if their distance to its representative is at most the distance threshold
+///
+/// The behavioral distance is the mean absolute difference between the outputs of the networks
+/// on the probe inputs given with `Solver::set_probe_inputs`, so networks which compute the same
+/// function belong to the same species even if their genomes differ. The distance threshold
+/// applies to the chosen distance, so it may need a different initial value
+#[derive(Copy, Deserialize, Debug, Serialize, Clone, PartialEq, Default)]
+pub enum SpeciationMetric {
+    /// The compatibility distance of the genomes of the original NEAT paper, weighted by `c1`,
+    /// `c2` and `c3`
+    #[default]
+    Genetic,
+    /// The behavioral distance on the probe inputs
+    Behavioral,
+    /// The sum of the genetic distance weighted by `1 - weight` and the behavioral distance
+    /// weighted by the given weight between **0.0** and **1.0**
+    Blend(f32),
+}
+
+/// The member of a species which becomes its representative, the networks of the next generation
+/// join a species if they are close enough to its representative
+#[derive(Copy, Deserialize, Debug, Serialize, Clone, PartialEq, Default)]
+pub enum RepresentativeSelection {
+    /// A random member, as in the original NEAT paper
+    #[default]
+    Random,
+    /// The member with the highest fitness
+    Best,
+    /// The member with the smallest sum of distances to all other members, which is the most
+    /// central member of the species. Finding it takes quadratic time in the size of the species
+    Medoid,
+}
+
+/// The scheme for selecting a parent among the survivors of a species based on their adjusted
+/// fitness
+#[derive(Copy, Deserialize, Debug, Serialize, Clone, PartialEq, Default)]
+pub enum SelectionStrategy {
+    /// The probability of a network is proportional to its adjusted fitness, every network is
+    /// equally likely if all of them have a fitness of **0.0**
+    #[default]
+    Roulette,
+    /// The fittest of the given number of uniformly selected networks, bigger tournaments favour
+    /// fitter networks more strongly
+    Tournament(usize),
+    /// The probability of a network is proportional to its rank, which is **1** for the least fit
+    /// network, so only the order of the fitness values matters
+    Rank,
+    /// Uniform selection among the given fraction of the fittest networks, at least one network
+    /// is always included
+    Truncation(f32),
+}
+
+impl SelectionStrategy {
+    /// Selects the index of a parent, where `fitness` contains the adjusted fitness of every
+    /// candidate
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no candidate.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::SelectionStrategy;
+    /// let fitness = [1.0, 4.0, 2.0, 3.0];
+    /// let mut rng = rand::rngs::mock::StepRng::new(0, 1 << 40);
+    /// assert_eq!(SelectionStrategy::Truncation(0.25).select(&fitness, &mut rng), 1);
+    /// assert!(SelectionStrategy::Tournament(3).select(&fitness, &mut rng) < 4);
+    /// ```
+    pub fn select<R: Rng + ?Sized>(&self, fitness: &[f32], rng: &mut R) -> usize {
+        assert!(
+            !fitness.is_empty(),
+            "there needs to be at least one candidate"
+        );
+        match *self {
+            SelectionStrategy::Roulette => roulette(fitness, rng),
+            SelectionStrategy::Tournament(size) => {
+                let mut best = random::index(rng, fitness.len());
+                for _ in 1..size {
+                    let idx = random::index(rng, fitness.len());
+                    if fitness[idx] > fitness[best] {
+                        best = idx;
+                    }
+                }
+                best
+            }
+            SelectionStrategy::Rank => {
+                let order = ranking(fitness);
+                let mut ranks = vec![0.0; fitness.len()];
+                for (rank, &idx) in order.iter().enumerate() {
+                    ranks[idx] = (fitness.len() - rank) as f32;
+                }
+                roulette(&ranks, rng)
+            }
+            SelectionStrategy::Truncation(fraction) => {
+                let count = math::ceil(fraction * fitness.len() as f32) as usize;
+                let order = ranking(fitness);
+                order[random::index(rng, count.clamp(1, fitness.len()))]
+            }
+        }
+    }
+}
+
+/// Whether the solver maximizes or minimizes the fitness, problems which are naturally expressed
+/// as a cost like an error can be minimized without inverting it.
+///
+/// The direction affects every comparison of the solver: the best network, the champion of a
+/// training and its `target_fitness`, the selection, elitism, crossover, the hall of fame and the
+/// stagnation of species. The fitness values of the networks and the statistics are not changed.
+/// The fitness assigned by the Pareto ranking and by novelty search is negated when minimizing,
+/// so it is minimized as well. Networks which have not been evaluated yet keep the fitness
+/// `f32::MIN`, which is the worst fitness in both directions
+#[derive(Copy, Deserialize, Debug, Serialize, Clone, PartialEq, Eq, Default)]
+pub enum Direction {
+    /// A higher fitness is better
+    #[default]
+    Maximize,
+    /// A lower fitness is better
+    Minimize,
+}
+
+impl Direction {
+    /// Returns the fitness converted to a score where higher is better: the fitness itself when
+    /// maximizing and the negated fitness when minimizing, except for `f32::MIN`. Converting a
+    /// score again returns the original fitness
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::Direction;
+    /// assert_eq!(Direction::Minimize.score(2.5), -2.5);
+    /// assert_eq!(Direction::Minimize.score(f32::MIN), f32::MIN);
+    /// assert!(Direction::Minimize.is_better(1.0, 3.0));
+    /// ```
+    pub fn score(&self, fitness: f32) -> f32 {
+        match self {
+            Direction::Maximize => fitness,
+            Direction::Minimize if fitness == f32::MIN => fitness,
+            Direction::Minimize => -fitness,
+        }
+    }
+
+    /// Returns whether fitness `a` is strictly better than fitness `b`
+    pub fn is_better(&self, a: f32, b: f32) -> bool {
+        self.score(a) > self.score(b)
+    }
+}
+
+/// The normalization of the fitness values of a generation before the selection, the selection
+/// needs values which are not negative and fitness-proportional selection works best if the
+/// values are not dominated by their offset or their scale
+#[derive(Copy, Deserialize, Debug, Serialize, Clone, PartialEq, Default)]
+pub enum FitnessTransform {
+    /// The values are shifted so the worst network has a fitness of **0.0**, the differences stay
+    /// unchanged
+    #[default]
+    Shift,
+    /// The values are replaced by their rank divided by the number of networks, which is **1.0**
+    /// for the best network. Networks with the same fitness share their mean rank, so only the
+    /// order of the fitness values matters
+    Rank,
+    /// Sigma scaling: the values are replaced by `1 + (f - mean) / (2 * std_dev)` and negative
+    /// results are raised to **0.0**, so the selection pressure stays constant when the fitness
+    /// values converge. If all values are equal every network gets **1.0**
+    SigmaScaling,
+    /// The values are mapped linearly from the range of the generation to the range from **0.0**
+    /// to **1.0**. If all values are equal every network gets **1.0**
+    MinMax,
+}
+
+impl FitnessTransform {
+    /// Returns the transformed fitness values, which are never negative
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::FitnessTransform;
+    /// let fitness = [-30.0, -10.0, -10.0, 10.0];
+    /// assert_eq!(FitnessTransform::Shift.apply(&fitness), vec![0.0, 20.0, 20.0, 40.0]);
+    /// assert_eq!(FitnessTransform::Rank.apply(&fitness), vec![0.25, 0.625, 0.625, 1.0]);
+    /// assert_eq!(FitnessTransform::MinMax.apply(&fitness), vec![0.0, 0.5, 0.5, 1.0]);
+    /// ```
+    pub fn apply(&self, fitness: &[f32]) -> Vec<f32> {
+        let n = fitness.len();
+        let min = fitness.iter().copied().fold(f32::INFINITY, f32::min);
+        match self {
+            FitnessTransform::Shift => fitness.iter().map(|f| f - min).collect(),
+            FitnessTransform::Rank => {
+                let order = ranking(fitness);
+                let mut ranks = vec![0.0; n];
+                let mut start = 0;
+                while start < n {
+                    let mut end = start + 1;
+                    while end < n && fitness[order[end]] == fitness[order[start]] {
+                        end += 1;
+                    }
+                    // the positions start..end have the ranks n - start down to n - end + 1
+                    let rank = n as f32 - (start + end - 1) as f32 / 2.0;
+                    for &idx in order[start..end].iter() {
+                        ranks[idx] = rank / n as f32;
+                    }
+                    start = end;
+                }
+                ranks
+            }
+            FitnessTransform::SigmaScaling => {
+                let mean = fitness.iter().sum::<f32>() / n.max(1) as f32;
+                let variance =
+                    fitness.iter().map(|f| (f - mean) * (f - mean)).sum::<f32>() / n.max(1) as f32;
+                let std_dev = math::sqrt(variance);
+                if std_dev > 0.0 && std_dev.is_finite() {
+                    fitness
+                        .iter()
+                        .map(|f| (1.0 + (f - mean) / (2.0 * std_dev)).max(0.0))
+                        .collect()
+                } else {
+                    vec![1.0; n]
+                }
+            }
+            FitnessTransform::MinMax => {
+                let range = fitness.iter().copied().fold(f32::NEG_INFINITY, f32::max) - min;
+                if range > 0.0 && range.is_finite() {
+                    fitness.iter().map(|f| (f - min) / range).collect()
+                } else {
+                    vec![1.0; n]
+                }
+            }
+        }
+    }
+}
+
+/// Selects an index randomly where the probability of each index is proportional to its weight.
+/// If all weights are **0.0** every index is equally likely.
+fn roulette<R: Rng + ?Sized>(weights: &[f32], rng: &mut R) -> usize {
+    let total: f32 = weights.iter().sum();
+    if total <= 0.0 {
+        return random::index(rng, weights.len());
+    }
+    let mut target = rng.gen::<f32>() * total;
+    for (idx, &weight) in weights.iter().enumerate() {
+        if target < weight {
+            return idx;
+        }
+        target -= weight;
+    }
+    weights.len() - 1
+}
+
+/// Returns the indices sorted by their fitness, the fittest first. Indices with the same fitness
+/// keep their order.
+fn ranking(fitness: &[f32]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..fitness.len()).collect();
+    order.sort_by(|&a, &b| fitness[b].total_cmp(&fitness[a]));
+    order
+}
+
+/// Distribution of the weights of new edges, both for the initial networks and for connections
+/// which are added by mutations
+#[derive(Copy, Deserialize, Debug, Serialize, Clone, PartialEq)]
+pub enum WeightInit {
+    /// Every weight has the same value
+    Constant(f32),
+    /// Uniformly distributed weights in the range from `-x` to `x`
+    Uniform(f32),
+    /// Normally distributed weights with the given mean and standard deviation
+    Gaussian {
+        /// The mean of the weights
+        mean: f32,
+        /// The standard deviation of the weights
+        std_dev: f32,
+    },
+    /// Uniformly distributed weights in the range from `-x` to `x` with
+    /// `x = sqrt(6 / (fan_in + fan_out))`, where `fan_in` is the number of edges leading into
+    /// the destination node and `fan_out` is the number of edges leaving the source node
+    Xavier,
+}
+
+impl Default for WeightInit {
+    fn default() -> Self {
+        WeightInit::Uniform(1.0)
+    }
+}
+
+impl WeightInit {
+    /// Samples a random weight for an edge between nodes with the given number of connections
+    pub fn sample<R: Rng + ?Sized>(&self, fan_in: usize, fan_out: usize, rng: &mut R) -> f32 {
+        match *self {
+            WeightInit::Constant(weight) => weight,
+            WeightInit::Uniform(x) => rng.gen_range(-x..=x),
+            // a negative or invalid standard deviation always gives the mean
+            WeightInit::Gaussian { mean, std_dev } if std_dev.is_nan() || std_dev <= 0.0 => mean,
+            WeightInit::Gaussian { mean, std_dev } => match Normal::new(mean, std_dev) {
+                Ok(normal) => normal.sample(rng),
+                Err(_) => mean,
+            },
+            WeightInit::Xavier => {
+                let x = math::sqrt(6.0 / (fan_in + fan_out).max(1) as f32);
+                rng.gen_range(-x..=x)
+            }
+        }
+    }
+}
+
+/// The connections of the initial networks, see `NeuralNetwork::with_initial_topology`
+#[derive(Copy, Deserialize, Debug, Serialize, Clone, PartialEq, Default)]
+pub enum InitialTopology {
+    /// Every input node (including the bias node) is connected to every output node
+    #[default]
+    FullyConnected,
+    /// Every output node is connected to the given number of random input nodes
+    Sparse(usize),
+    /// There are no connections at all, they all need to be added by mutations (FS-NEAT)
+    Unconnected,
+}
@@ -0,0 +1,126 @@
+use crate::activation::Activation;
+use crate::hyperneat;
+use crate::neuralnetwork::{NeuralNetwork, NodeType};
+use crate::random;
+use rand::Rng;
+
+/// Using networks as compositional pattern-producing networks (CPPNs): functions over the
+/// coordinates of points or connections, whose nodes use different activation functions like
+/// `Sine` or `Gaussian` (see `Activation::CPPN`), so the produced patterns contain symmetry and
+/// repetition. CPPNs are evolved like any other network, the activation functions of their nodes
+/// change with the mutation `activation_mutation_rate` using the `ActivationSet::Cppn`.
+impl NeuralNetwork {
+    /// Constructor for a CPPN where every output node has a random activation function of
+    /// `Activation::CPPN`.
+    ///
+    /// A CPPN of points has **2** inputs `(x, y)` or **3** inputs `(x, y, d)` with the distance `d`
+    /// to the center, see `query_point`. A CPPN of connections has **4** inputs, see
+    /// `query_connection`.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::{Activation, NeuralNetwork};
+    /// let mut cppn = NeuralNetwork::cppn(3, 1, &mut rand::thread_rng());
+    /// assert!(Activation::CPPN.contains(&cppn.nodes()[4].activation));
+    /// let pattern = cppn.sample_pattern(16, 16);
+    /// assert_eq!(pattern.len(), 256);
+    /// ```
+    pub fn cppn<R: Rng + ?Sized>(input_nodes: usize, output_nodes: usize, rng: &mut R) -> Self {
+        NeuralNetwork::with_size(input_nodes, output_nodes)
+            .with_random_activations(&Activation::CPPN, rng)
+    }
+
+    /// Returns the network where every hidden and output node has a random one of the given
+    /// activation functions, e.g. for the initial networks of a solver evolving CPPNs. The input
+    /// nodes, output nodes with a fixed `output_activation` and the activation function of nodes
+    /// added later by mutations are unchanged.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no activation function is given.
+    pub fn with_random_activations<R: Rng + ?Sized>(
+        mut self,
+        activations: &[Activation],
+        rng: &mut R,
+    ) -> Self {
+        assert!(
+            !activations.is_empty(),
+            "at least one activation function is needed"
+        );
+        let outputs_fixed = self.output_activation.fixed_activation().is_some();
+        for node in self.nodes.iter_mut() {
+            let fixed = node.node_type == NodeType::Output && outputs_fixed;
+            if !node.node_type.is_input_or_bias() && !fixed {
+                node.activation = activations[random::index(rng, activations.len())];
+            }
+        }
+        self.genome_changed();
+        self
+    }
+
+    /// Computes the outputs of a CPPN of points at the coordinates `(x, y)`. A network with
+    /// **3** inputs additionally gets the distance `sqrt(x^2 + y^2)` to the center, which makes
+    /// radial patterns much easier to evolve.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the network does not have **2** or **3** inputs.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::NeuralNetwork;
+    /// let mut cppn = NeuralNetwork::with_size(3, 1);
+    /// assert_eq!(cppn.query_point(0.6, 0.8), cppn.compute([0.6, 0.8, 1.0]));
+    /// ```
+    pub fn query_point(&mut self, x: f32, y: f32) -> Vec<f32> {
+        match self.size().0 {
+            2 => self.compute([x, y]),
+            3 => self.compute([x, y, (x * x + y * y).sqrt()]),
+            inputs => panic!("a CPPN of points needs 2 or 3 inputs, not {}", inputs),
+        }
+    }
+
+    /// Computes the outputs of a CPPN of connections for the connection between the points
+    /// `from = (x1, y1)` and `to = (x2, y2)`, as used by `hyperneat::Substrate`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the network does not have **4** inputs.
+    pub fn query_connection(&mut self, from: [f32; 2], to: [f32; 2]) -> Vec<f32> {
+        assert_eq!(self.size().0, 4, "a CPPN of connections needs 4 inputs");
+        self.compute([from[0], from[1], to[0], to[1]])
+    }
+
+    /// Samples the first output of a CPPN of points, see `query_point`, on a grid of
+    /// `width * height` points spread evenly over the square from **-1.0** to **1.0**. The values
+    /// are returned row by row, starting with the row at `y = -1.0`.
+    pub fn sample_pattern(&mut self, width: usize, height: usize) -> Vec<f32> {
+        hyperneat::grid(width, height)
+            .into_iter()
+            .map(|[x, y]| self.query_point(x, y)[0])
+            .collect()
+    }
+
+    /// Renders the pattern of `sample_pattern` as a grayscale image in the binary PGM format,
+    /// which can be written into a `.pgm` file and opened by most image viewers. Values from
+    /// **-1.0** to **1.0** are mapped from black to white, values outside of the range are
+    /// clamped and NaN is black.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::{Activation, NeuralNetwork};
+    /// let mut cppn = NeuralNetwork::with_size(2, 1).with_activation(Activation::Sine);
+    /// let image = cppn.render_pgm(4, 3);
+    /// assert!(image.starts_with(b"P5\n4 3\n255\n"));
+    /// assert_eq!(image.len(), 11 + 4 * 3);
+    /// ```
+    pub fn render_pgm(&mut self, width: usize, height: usize) -> Vec<u8> {
+        let mut image = format!("P5\n{} {}\n255\n", width, height).into_bytes();
+        image.extend(
+            self.sample_pattern(width, height)
+                .into_iter()
+                .map(|value| ((value.clamp(-1.0, 1.0) + 1.0) * 127.5).round() as u8),
+        );
+        image
+    }
+}
@@ -0,0 +1,460 @@
+//! Evaluating the networks of a generation on remote worker processes over TCP.
+//!
+//! The evaluation of a generation is embarrassingly parallel, so expensive evaluations like long
+//! simulations can be spread over a cluster. A `Coordinator` listens for workers and sends them
+//! the networks to evaluate, every `Worker` connects to the coordinator and evaluates the networks
+//! it receives with its `FitnessEvaluator`.
+//!
+//! Workers register with their name and send a heartbeat in regular intervals, even while they
+//! evaluate a network. A worker which disconnects or has not sent anything within the timeout of
+//! the coordinator is considered lost, its network is sent to another worker. Workers may connect
+//! at any time, they get networks as soon as they have registered.
+//!
+//! The messages are length-prefixed MessagePack, the networks are sent in their byte
+//! representation, so coordinator and workers need to use compatible versions of this crate.
+//!
+//! # Example
+//! ```rust
+//! use neaters::distributed::{Coordinator, Worker};
+//! use neaters::{NeuralNetwork, Solver};
+//! use std::time::Duration;
+//! let mut coordinator = Coordinator::bind("127.0.0.1:0")?;
+//! let address = coordinator.local_addr()?;
+//! // the workers usually are processes on other machines
+//! let worker = std::thread::spawn(move || {
+//!     Worker::new("simulator-1").run(address, &|nn: &mut NeuralNetwork| nn.compute([0.5])[0])
+//! });
+//! coordinator.wait_for_workers(1, Duration::from_secs(10))?;
+//! let mut solver = Solver::with_size(1, 1, 10);
+//! solver.evaluate_generation_distributed(&mut coordinator)?;
+//! solver.new_generation();
+//! // dropping the coordinator shuts the workers down
+//! drop(coordinator);
+//! assert_eq!(worker.join().unwrap()?, 10);
+//! # Ok::<(), neaters::Error>(())
+//! ```
+
+use crate::error::Error;
+use crate::evaluator::FitnessEvaluator;
+use crate::neuralnetwork::NeuralNetwork;
+use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The largest message which is accepted, larger length prefixes are treated as corrupted data.
+/// The largest messages contain a network, this leaves room for networks with more than a hundred
+/// thousand connections.
+const MAX_MESSAGE_SIZE: usize = 1 << 24;
+
+/// How long the coordinator waits for a message before it checks for new connections and lost
+/// workers again.
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// The messages between the coordinator and the workers.
+#[derive(Debug, Serialize, Deserialize)]
+enum Message {
+    /// Sent by a worker after connecting.
+    Register { name: String },
+    /// Sent by a worker in regular intervals to show that it is still alive.
+    Heartbeat,
+    /// Sent by the coordinator, the byte representation of a network which should be evaluated.
+    Evaluate { task: u64, network: Vec<u8> },
+    /// Sent by a worker, the fitness of the network of a task.
+    Fitness { task: u64, fitness: f32 },
+    /// Sent by the coordinator when it stops, the worker returns afterwards.
+    Shutdown,
+}
+
+/// Writes a message with its length as prefix.
+fn send<W: Write>(writer: &mut W, message: &Message) -> Result<(), Error> {
+    let payload = rmp_serde::to_vec(message)?;
+    let mut frame = Vec::with_capacity(payload.len() + 4);
+    frame.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    frame.extend_from_slice(&payload);
+    writer.write_all(&frame)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Reads the next message, returns `None` if the connection has been closed before it.
+///
+/// The payload is read as it arrives instead of allocating the length of the prefix up front, so
+/// a peer cannot reserve memory it does not send.
+fn receive<R: Read>(reader: &mut R) -> Result<Option<Message>, Error> {
+    let mut length = [0; 4];
+    match reader.read_exact(&mut length) {
+        Ok(()) => {}
+        Err(err) if err.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+        Err(err) => return Err(err.into()),
+    }
+    let length = u32::from_le_bytes(length) as usize;
+    if length > MAX_MESSAGE_SIZE {
+        return Err(Error::Corrupted);
+    }
+    let mut payload = Vec::new();
+    reader.take(length as u64).read_to_end(&mut payload)?;
+    if payload.len() < length {
+        return Err(io::Error::from(ErrorKind::UnexpectedEof).into());
+    }
+    Ok(Some(rmp_serde::from_slice(&payload)?))
+}
+
+/// What the reading threads of the coordinator report about the connection of a worker.
+enum Event {
+    Received(usize, Message),
+    Disconnected(usize),
+}
+
+/// A connection of the coordinator to a worker.
+struct Connection {
+    /// The name of the worker, `None` until it has registered.
+    name: Option<String>,
+    /// The stream the messages to the worker are written to.
+    stream: TcpStream,
+    /// When the last message of the worker has been received.
+    last_seen: Instant,
+    /// The task the worker currently evaluates.
+    task: Option<u64>,
+}
+
+/// Distributes the evaluation of networks over the workers which are connected to it, see the
+/// module documentation.
+///
+/// Dropping the coordinator sends every worker the message to shut down.
+pub struct Coordinator {
+    listener: TcpListener,
+    /// The sender which is cloned for the reading thread of every connection.
+    sender: Sender<Event>,
+    events: Receiver<Event>,
+    /// The connected workers by the number of their connection.
+    workers: BTreeMap<usize, Connection>,
+    next_worker: usize,
+    next_task: u64,
+    timeout: Duration,
+    max_attempts: usize,
+}
+
+impl Coordinator {
+    /// Constructor for a coordinator which listens for workers at the given address, e.g.
+    /// `"0.0.0.0:7878"`. A worker is lost if it does not send anything for **10** seconds and a
+    /// network is sent to at most **3** workers.
+    pub fn bind<A: ToSocketAddrs>(address: A) -> Result<Self, Error> {
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        let (sender, events) = mpsc::channel();
+        Ok(Coordinator {
+            listener,
+            sender,
+            events,
+            workers: BTreeMap::new(),
+            next_worker: 0,
+            next_task: 0,
+            timeout: Duration::from_secs(10),
+            max_attempts: 3,
+        })
+    }
+
+    /// Sets the time after which a worker which has not sent a message is considered lost. It has
+    /// to be longer than the heartbeat interval of the workers. A message to a worker which does
+    /// not accept it within this time is not sent either, the worker is lost as well.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets how many workers a network is sent to before the evaluation fails, because every one
+    /// of them has been lost while evaluating it.
+    pub fn with_max_attempts(mut self, max_attempts: usize) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Returns the address the coordinator listens at, e.g. to find the port if it has been bound
+    /// to port **0**.
+    pub fn local_addr(&self) -> Result<SocketAddr, Error> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Returns the names of the registered workers in the order they have connected.
+    pub fn workers(&self) -> Vec<&str> {
+        self.workers
+            .values()
+            .filter_map(|worker| worker.name.as_deref())
+            .collect()
+    }
+
+    /// Waits until at least `count` workers have registered or the timeout has passed and
+    /// returns the number of registered workers.
+    pub fn wait_for_workers(&mut self, count: usize, timeout: Duration) -> Result<usize, Error> {
+        let start = Instant::now();
+        let mut finished = Vec::new();
+        let mut lost = Vec::new();
+        while self.workers().len() < count && start.elapsed() < timeout {
+            self.poll(&mut finished, &mut lost)?;
+        }
+        Ok(self.workers().len())
+    }
+
+    /// Evaluates the networks on the workers and assigns the resulting fitness to them. Every
+    /// worker evaluates one network at a time, the networks of lost workers are sent to other
+    /// workers.
+    ///
+    /// This waits until every network has been evaluated, also if no worker is connected. Returns
+    /// an error if a network could not be encoded or if it has been lost `max_attempts` times.
+    pub fn evaluate(&mut self, networks: &mut [NeuralNetwork]) -> Result<(), Error> {
+        let mut queue: VecDeque<usize> = (0..networks.len()).collect();
+        let mut in_flight: FxHashMap<u64, usize> = FxHashMap::default();
+        let mut attempts = vec![0; networks.len()];
+        let mut remaining = networks.len();
+        let mut finished = Vec::new();
+        let mut lost = Vec::new();
+        while remaining > 0 {
+            self.assign(&mut queue, &mut in_flight, networks)?;
+            self.poll(&mut finished, &mut lost)?;
+            for (task, fitness) in finished.drain(..) {
+                if let Some(index) = in_flight.remove(&task) {
+                    networks[index].set_fitness(fitness);
+                    remaining -= 1;
+                }
+            }
+            for task in lost.drain(..) {
+                if let Some(index) = in_flight.remove(&task) {
+                    attempts[index] += 1;
+                    if attempts[index] >= self.max_attempts {
+                        return Err(Error::Distributed(format!(
+                            "network {} has been lost by {} workers",
+                            index, attempts[index]
+                        )));
+                    }
+                    queue.push_front(index);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Sends the next networks of the queue to the registered workers without a task.
+    fn assign(
+        &mut self,
+        queue: &mut VecDeque<usize>,
+        in_flight: &mut FxHashMap<u64, usize>,
+        networks: &[NeuralNetwork],
+    ) -> Result<(), Error> {
+        let idle: Vec<usize> = self
+            .workers
+            .iter()
+            .filter(|(_, worker)| worker.name.is_some() && worker.task.is_none())
+            .map(|(&id, _)| id)
+            .collect();
+        for id in idle {
+            let Some(index) = queue.pop_front() else {
+                break;
+            };
+            let task = self.next_task;
+            self.next_task += 1;
+            let message = Message::Evaluate {
+                task,
+                network: networks[index].as_byte_representation()?,
+            };
+            let worker = self.workers.get_mut(&id).expect("the worker is connected");
+            match send(&mut worker.stream, &message) {
+                Ok(()) => {
+                    worker.task = Some(task);
+                    in_flight.insert(task, index);
+                }
+                // the network never reached the worker, so it is not counted as an attempt
+                Err(_) => {
+                    self.disconnect(id);
+                    queue.push_front(index);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Accepts new workers, handles the next message and disconnects lost workers. The results
+    /// which arrived and the tasks of lost workers are added to the lists.
+    fn poll(&mut self, finished: &mut Vec<(u64, f32)>, lost: &mut Vec<u64>) -> Result<(), Error> {
+        self.accept()?;
+        match self.events.recv_timeout(POLL_INTERVAL) {
+            Ok(Event::Received(id, message)) => {
+                if let Some(worker) = self.workers.get_mut(&id) {
+                    worker.last_seen = Instant::now();
+                    match message {
+                        Message::Register { name } => worker.name = Some(name),
+                        Message::Fitness { task, fitness } if worker.task == Some(task) => {
+                            worker.task = None;
+                            finished.push((task, fitness));
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            Ok(Event::Disconnected(id)) => lost.extend(self.disconnect(id)),
+            Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => {}
+        }
+        let timeout = self.timeout;
+        let silent: Vec<usize> = self
+            .workers
+            .iter()
+            .filter(|(_, worker)| worker.last_seen.elapsed() > timeout)
+            .map(|(&id, _)| id)
+            .collect();
+        for id in silent {
+            lost.extend(self.disconnect(id));
+        }
+        Ok(())
+    }
+
+    /// Accepts every pending connection and starts a thread reading its messages.
+    fn accept(&mut self) -> Result<(), Error> {
+        loop {
+            let stream = match self.listener.accept() {
+                Ok((stream, _)) => stream,
+                Err(err) if err.kind() == ErrorKind::WouldBlock => return Ok(()),
+                Err(err) => return Err(err.into()),
+            };
+            stream.set_nonblocking(false)?;
+            stream.set_nodelay(true)?;
+            // a worker which stops reading is lost instead of blocking the generation
+            stream.set_write_timeout(Some(self.timeout))?;
+            let id = self.next_worker;
+            self.next_worker += 1;
+            let mut reader = stream.try_clone()?;
+            let sender = self.sender.clone();
+            thread::spawn(move || loop {
+                let event = match receive(&mut reader) {
+                    Ok(Some(message)) => Event::Received(id, message),
+                    Ok(None) | Err(_) => Event::Disconnected(id),
+                };
+                let disconnected = matches!(event, Event::Disconnected(_));
+                if sender.send(event).is_err() || disconnected {
+                    break;
+                }
+            });
+            self.workers.insert(
+                id,
+                Connection {
+                    name: None,
+                    stream,
+                    last_seen: Instant::now(),
+                    task: None,
+                },
+            );
+        }
+    }
+
+    /// Closes the connection to the worker and returns the task it was evaluating.
+    fn disconnect(&mut self, id: usize) -> Option<u64> {
+        let worker = self.workers.remove(&id)?;
+        let _ = worker.stream.shutdown(Shutdown::Both);
+        worker.task
+    }
+}
+
+impl Drop for Coordinator {
+    fn drop(&mut self) {
+        for worker in self.workers.values_mut() {
+            let _ = send(&mut worker.stream, &Message::Shutdown);
+            let _ = worker.stream.shutdown(Shutdown::Write);
+        }
+    }
+}
+
+/// A worker process which evaluates the networks sent by a `Coordinator`, see the module
+/// documentation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Worker {
+    name: String,
+    heartbeat_interval: Duration,
+}
+
+impl Worker {
+    /// Constructor for a worker with the given name, e.g. the host name, which sends a heartbeat
+    /// every second.
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        Worker {
+            name: name.into(),
+            heartbeat_interval: Duration::from_secs(1),
+        }
+    }
+
+    /// Sets the interval in which the worker sends a heartbeat to the coordinator.
+    pub fn with_heartbeat_interval(mut self, interval: Duration) -> Self {
+        self.heartbeat_interval = interval;
+        self
+    }
+
+    /// Connects to the coordinator at the given address and evaluates the networks it sends with
+    /// the evaluator until the coordinator shuts down or closes the connection. Returns the number
+    /// of evaluated networks.
+    ///
+    /// Returns an error if the connection fails or if a received network cannot be decoded.
+    pub fn run<A, E>(&self, coordinator: A, evaluator: &E) -> Result<usize, Error>
+    where
+        A: ToSocketAddrs,
+        E: FitnessEvaluator + ?Sized,
+    {
+        let mut reader = TcpStream::connect(coordinator)?;
+        reader.set_nodelay(true)?;
+        let writer = Arc::new(Mutex::new(reader.try_clone()?));
+        send(
+            &mut *lock(&writer)?,
+            &Message::Register {
+                name: self.name.clone(),
+            },
+        )?;
+        // the heartbeat stops when the sender is dropped at the end of this function
+        let (stop, stopped) = mpsc::channel::<()>();
+        let heartbeat_writer = Arc::clone(&writer);
+        let interval = self.heartbeat_interval;
+        let heartbeat = thread::spawn(move || {
+            while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+                let sent = lock(&heartbeat_writer)
+                    .and_then(|mut writer| send(&mut *writer, &Message::Heartbeat));
+                if sent.is_err() {
+                    break;
+                }
+            }
+        });
+        let result = self.evaluate_tasks(&mut reader, &writer, evaluator);
+        drop(stop);
+        let _ = heartbeat.join();
+        result
+    }
+
+    /// Evaluates the received networks, see `run`.
+    fn evaluate_tasks<E: FitnessEvaluator + ?Sized>(
+        &self,
+        reader: &mut TcpStream,
+        writer: &Mutex<TcpStream>,
+        evaluator: &E,
+    ) -> Result<usize, Error> {
+        let mut evaluated = 0;
+        loop {
+            match receive(reader)? {
+                Some(Message::Evaluate { task, network }) => {
+                    let mut nn = NeuralNetwork::from_bytes(&network)?;
+                    let fitness = evaluator.evaluate(&mut nn);
+                    send(&mut *lock(writer)?, &Message::Fitness { task, fitness })?;
+                    evaluated += 1;
+                }
+                Some(Message::Shutdown) | None => return Ok(evaluated),
+                Some(_) => {}
+            }
+        }
+    }
+}
+
+/// Locks the stream, a poisoned lock is reported as an io error.
+fn lock(stream: &Mutex<TcpStream>) -> Result<std::sync::MutexGuard<'_, TcpStream>, Error> {
+    stream
+        .lock()
+        .map_err(|_| io::Error::other("the connection has been poisoned").into())
+}
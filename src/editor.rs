@@ -0,0 +1,202 @@
+use crate::activation::Activation;
+use crate::error::Error;
+use crate::neuralnetwork::{Edge, NeuralNetwork, Node, NodeType};
+
+/// Changes the genome of a neural network while keeping it valid, created with
+/// `NeuralNetwork::edit`.
+///
+/// Every change is checked before it is applied, so node ids stay unique, edges never reference
+/// missing nodes and feed-forward networks never contain a cycle. Invalid changes return an
+/// `Error::InvalidGenome` and leave the network unchanged. The cached phenotype of the network is
+/// updated by new weights and connections and built again after every other change.
+///
+/// Connections are identified by the ids of their source and destination node, because there is
+/// at most one connection between two nodes. Frozen nodes and connections cannot be changed, see
+/// `NeuralNetwork::freeze_node`.
+///
+/// # Example:
+/// ```
+/// use neaters::{Activation, NeuralNetwork};
+/// let mut nn = NeuralNetwork::with_size(1, 1);
+/// let mut editor = nn.edit();
+/// let hidden = editor.add_node(Activation::ReLU);
+/// editor.add_connection(1, hidden, 0.5).unwrap();
+/// editor.add_connection(hidden, 2, -1.0).unwrap();
+/// editor.set_weight(1, 2, 2.0).unwrap();
+/// // the output is not allowed to feed back into the hidden node
+/// assert!(editor.add_connection(2, hidden, 1.0).is_err());
+/// assert_eq!(nn.edges().len(), 4);
+/// ```
+#[derive(Debug)]
+pub struct GenomeEditor<'a> {
+    nn: &'a mut NeuralNetwork,
+}
+
+impl<'a> GenomeEditor<'a> {
+    /// Constructor for an editor of the given network.
+    pub(crate) fn new(nn: &'a mut NeuralNetwork) -> Self {
+        GenomeEditor { nn }
+    }
+
+    /// Adds a new hidden node without any connection and returns its id, which is bigger than
+    /// all ids of the network.
+    pub fn add_node(&mut self, activation: Activation) -> usize {
+        let id = self.nn.next_node_id();
+        self.nn.nodes.push(Node {
+            id,
+            node_type: NodeType::Hidden,
+            activation,
+        });
+        self.nn.genome_changed();
+        id
+    }
+
+    /// Adds a new enabled connection between two nodes and returns its innovation number, which
+    /// is bigger than all innovation numbers of the network.
+    ///
+    /// In a recurrent network a connection which forms a cycle becomes a recurrent connection,
+    /// in a feed-forward network it is rejected. Connections into input nodes and connections
+    /// which already exist or lead into the node of a module instance are rejected as well.
+    pub fn add_connection(&mut self, from: usize, to: usize, weight: f32) -> Result<usize, Error> {
+        self.node(from)?;
+        if self.node(to)?.node_type.is_input_or_bias() {
+            return Err(Error::InvalidGenome(format!(
+                "the input node {} cannot be the destination of a connection",
+                to
+            )));
+        }
+        if self.nn.is_module_node(to) {
+            return Err(Error::InvalidGenome(format!(
+                "the node {} of a module instance cannot be the destination of a connection",
+                to
+            )));
+        }
+        if self.find(from, to).is_some() {
+            return Err(Error::InvalidGenome(format!(
+                "the connection {} -> {} already exists",
+                from, to
+            )));
+        }
+        let cycle = from == to || self.nn.has_path(to, from);
+        if cycle && !self.nn.recurrent {
+            return Err(Error::InvalidGenome(format!(
+                "the connection {} -> {} would form a cycle",
+                from, to
+            )));
+        }
+        let innovation = self.nn.next_innovation();
+        let pt = self.nn.take_current_phenotype();
+        let idx = self.nn.insert_edge(Edge {
+            from,
+            to,
+            weight,
+            enabled: true,
+            innovation,
+            recurrent: cycle,
+        });
+        self.nn.pt = pt.map(|mut pt| {
+            pt.add_edge(self.nn, idx);
+            pt
+        });
+        Ok(innovation)
+    }
+
+    /// Sets the weight of the connection between two nodes.
+    pub fn set_weight(&mut self, from: usize, to: usize, weight: f32) -> Result<(), Error> {
+        let idx = self.unfrozen_connection(from, to)?;
+        let pt = self.nn.take_current_phenotype();
+        self.nn.edges[idx].weight = weight;
+        self.nn.genome_changed();
+        self.nn.pt = pt.map(|mut pt| {
+            pt.update_weight(self.nn, idx);
+            pt
+        });
+        Ok(())
+    }
+
+    /// Enables or disables the connection between two nodes.
+    pub fn set_enabled(&mut self, from: usize, to: usize, enabled: bool) -> Result<(), Error> {
+        let idx = self.unfrozen_connection(from, to)?;
+        self.nn.edges[idx].enabled = enabled;
+        self.nn.genome_changed();
+        Ok(())
+    }
+
+    /// Sets the activation function of a hidden or output node.
+    pub fn set_activation(&mut self, id: usize, activation: Activation) -> Result<(), Error> {
+        let idx = self
+            .nn
+            .nodes
+            .iter()
+            .position(|node| node.id == id)
+            .ok_or_else(|| Error::InvalidGenome(format!("the node {} does not exist", id)))?;
+        if self.nn.nodes[idx].node_type.is_input_or_bias() {
+            return Err(Error::InvalidGenome(format!(
+                "the activation of the input node {} is never applied",
+                id
+            )));
+        }
+        if self.nn.is_node_frozen(id) {
+            return Err(Error::InvalidGenome(format!("the node {} is frozen", id)));
+        }
+        if self.nn.is_module_node(id) {
+            return Err(Error::InvalidGenome(format!(
+                "the activation of the node {} is defined by its module",
+                id
+            )));
+        }
+        self.nn.nodes[idx].activation = activation;
+        self.nn.genome_changed();
+        Ok(())
+    }
+
+    /// Removes the connection between two nodes and returns it.
+    pub fn remove_connection(&mut self, from: usize, to: usize) -> Result<Edge, Error> {
+        let idx = self.unfrozen_connection(from, to)?;
+        let edge = self.nn.edges.remove(idx);
+        self.nn.genome_changed();
+        Ok(edge)
+    }
+
+    /// Returns the node with the given id or an error if it does not exist.
+    fn node(&self, id: usize) -> Result<&Node, Error> {
+        self.nn
+            .nodes
+            .iter()
+            .find(|node| node.id == id)
+            .ok_or_else(|| Error::InvalidGenome(format!("the node {} does not exist", id)))
+    }
+
+    /// Returns the index of the connection between two nodes, if it exists.
+    fn find(&self, from: usize, to: usize) -> Option<usize> {
+        self.nn
+            .edges
+            .iter()
+            .position(|edge| edge.from == from && edge.to == to)
+    }
+
+    /// Returns the index of the connection between two nodes or an error if it does not exist.
+    fn connection(&self, from: usize, to: usize) -> Result<usize, Error> {
+        self.find(from, to).ok_or_else(|| {
+            Error::InvalidGenome(format!("the connection {} -> {} does not exist", from, to))
+        })
+    }
+
+    /// Returns the index of the connection between two nodes, or an error if it is frozen or
+    /// defined by a module.
+    fn unfrozen_connection(&self, from: usize, to: usize) -> Result<usize, Error> {
+        if self.nn.is_connection_frozen(from, to) {
+            return Err(Error::InvalidGenome(format!(
+                "the connection {} -> {} is frozen",
+                from, to
+            )));
+        }
+        if self.nn.is_module_node(to) {
+            return Err(Error::InvalidGenome(format!(
+                "the connection {} -> {} is defined by a module",
+                from, to
+            )));
+        }
+        self.connection(from, to)
+    }
+}
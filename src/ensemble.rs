@@ -0,0 +1,183 @@
+use crate::activation::first_highest;
+#[cfg(feature = "std-fs")]
+use crate::error::Error;
+use crate::float::Float;
+use crate::neuralnetwork::NeuralNetwork;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+#[cfg(feature = "std-fs")]
+use std::path::Path;
+
+/// How an `Ensemble` combines the outputs of its members.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Aggregation {
+    /// Every output is the average of the outputs of the members.
+    #[default]
+    Mean,
+    /// Every output is the median of the outputs of the members, so a single member with an
+    /// extreme output does not change the result. For an even number of members it is the
+    /// average of the two middle values.
+    Median,
+    /// Every member votes for its output with the highest value and every output is the share of
+    /// the members which voted for it, so the output with the highest value has the most votes.
+    /// This is meant for networks whose outputs score classes or actions.
+    MajorityVote,
+}
+
+/// Multiple networks of the same size which compute their outputs together, e.g. the champions
+/// of several trainings or the networks of the hall of fame.
+///
+/// A single evolved controller can react badly to inputs it has never seen in the training. The
+/// combined outputs of several independently evolved networks are usually more stable, so an
+/// ensemble is useful when the networks are deployed.
+///
+/// # Example
+/// ```rust
+/// use neaters::{Aggregation, Ensemble, NeuralNetwork};
+/// let members = vec![NeuralNetwork::with_size(2, 1), NeuralNetwork::with_size(2, 1)];
+/// let mut single = members[0].clone();
+/// let mut ensemble = Ensemble::new(members, Aggregation::Median);
+/// assert_eq!(ensemble.compute([0.5, 0.5]), single.compute([0.5, 0.5]));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Ensemble<F = f32> {
+    members: Vec<NeuralNetwork<F>>,
+    aggregation: Aggregation,
+    /// The outputs of every member of the last computation, member by member.
+    outputs: Vec<F>,
+}
+
+impl<F: Float> Ensemble<F> {
+    /// Constructor for an ensemble combining the outputs of the given networks.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no network or if the networks do not have the same numbers of inputs
+    /// and outputs.
+    pub fn new(members: Vec<NeuralNetwork<F>>, aggregation: Aggregation) -> Self {
+        assert!(
+            !members.is_empty(),
+            "an ensemble needs at least one network"
+        );
+        let size = members[0].size();
+        assert!(
+            members.iter().all(|nn| nn.size() == size),
+            "the networks of an ensemble need the same numbers of inputs and outputs"
+        );
+        Ensemble {
+            outputs: vec![F::ZERO; members.len() * size.1],
+            members,
+            aggregation,
+        }
+    }
+
+    /// Returns the networks of the ensemble.
+    pub fn members(&self) -> &[NeuralNetwork<F>] {
+        &self.members
+    }
+
+    /// Returns how the outputs of the members are combined.
+    pub fn aggregation(&self) -> Aggregation {
+        self.aggregation
+    }
+
+    /// Returns the numbers of inputs and outputs of the networks of the ensemble.
+    pub fn size(&self) -> (usize, usize) {
+        self.members[0].size()
+    }
+
+    /// Computes the outputs of every member for the input and combines them with the aggregation
+    /// of the ensemble.
+    ///
+    /// # Example
+    /// ```rust
+    /// use neaters::{Aggregation, Ensemble, NeuralNetwork};
+    /// let members = (0..3).map(|_| NeuralNetwork::with_size(1, 2)).collect();
+    /// let mut ensemble = Ensemble::new(members, Aggregation::MajorityVote);
+    /// let votes = ensemble.compute([0.5]);
+    /// // equal outputs vote for the first one
+    /// assert_eq!(votes, vec![1.0, 0.0]);
+    /// ```
+    pub fn compute<I: AsRef<[F]>>(&mut self, input: I) -> Vec<F> {
+        let outputs = self.size().1;
+        for (nn, output) in self
+            .members
+            .iter_mut()
+            .zip(self.outputs.chunks_mut(outputs))
+        {
+            nn.compute_into(input.as_ref(), output);
+        }
+        let count = F::from_f64(self.members.len() as f64);
+        let member_outputs = self.outputs.chunks(outputs);
+        match self.aggregation {
+            Aggregation::Mean => {
+                let mut sums = vec![F::ZERO; outputs];
+                for output in member_outputs {
+                    for (sum, &value) in sums.iter_mut().zip(output) {
+                        *sum += value;
+                    }
+                }
+                sums.into_iter().map(|sum| sum / count).collect()
+            }
+            Aggregation::Median => (0..outputs)
+                .map(|i| {
+                    let mut values: Vec<F> = member_outputs.clone().map(|o| o[i]).collect();
+                    median(&mut values)
+                })
+                .collect(),
+            Aggregation::MajorityVote => {
+                let mut votes = vec![F::ZERO; outputs];
+                for output in member_outputs {
+                    votes[first_highest(output)] += F::ONE;
+                }
+                votes.into_iter().map(|vote| vote / count).collect()
+            }
+        }
+    }
+
+    /// Resets the state of every member, see `NeuralNetwork::reset_state`.
+    pub fn reset_state(&mut self) {
+        for nn in self.members.iter_mut() {
+            nn.reset_state();
+        }
+    }
+}
+
+#[cfg(feature = "std-fs")]
+impl Ensemble {
+    /// Loads the networks of an ensemble from the files at the given paths, see
+    /// `NeuralNetwork::load_from`.
+    ///
+    /// Returns an error if a file cannot be loaded, if no path is given or if the networks do not
+    /// have the same numbers of inputs and outputs.
+    pub fn load_from<P: AsRef<Path>>(paths: &[P], aggregation: Aggregation) -> Result<Self, Error> {
+        let members = paths
+            .iter()
+            .map(NeuralNetwork::load_from)
+            .collect::<Result<Vec<NeuralNetwork>, Error>>()?;
+        let size = members
+            .first()
+            .ok_or_else(|| Error::InvalidGenome("an ensemble needs at least one network".into()))?
+            .size();
+        if let Some(nn) = members.iter().find(|nn| nn.size() != size) {
+            return Err(Error::InvalidGenome(format!(
+                "a network has {} inputs and {} outputs, but the first one has {} and {}",
+                nn.size().0,
+                nn.size().1,
+                size.0,
+                size.1
+            )));
+        }
+        Ok(Ensemble::new(members, aggregation))
+    }
+}
+
+/// Returns the median of the values, which are sorted in place.
+fn median<F: Float>(values: &mut [F]) -> F {
+    values.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    match values.len() {
+        n if n % 2 == 0 => (values[n / 2 - 1] + values[n / 2]) / (F::ONE + F::ONE),
+        n => values[n / 2],
+    }
+}
@@ -0,0 +1,76 @@
+use crate::neuralnetwork::NeuralNetwork;
+
+/// An episodic task in which a network acts step by step, like the environments of
+/// reinforcement learning: every episode starts with `reset` and the network chooses an action
+/// for every observation until the environment reports that the episode is done.
+///
+/// The rewards of all steps of an episode are summed up, `Solver::evaluate_in_env` uses the
+/// average reward of multiple episodes as the fitness of a network.
+///
+/// # Example
+/// ```rust
+/// use neaters::{Environment, Solver};
+/// /// The network should keep its output close to a target that changes in every step.
+/// struct Follow {
+///     target: f32,
+/// }
+///
+/// impl Environment for Follow {
+///     type Observation = [f32; 1];
+///
+///     fn reset(&mut self) -> [f32; 1] {
+///         self.target = 0.0;
+///         [self.target]
+///     }
+///
+///     fn step(&mut self, action: &[f32]) -> ([f32; 1], f32, bool) {
+///         let reward = 1.0 - (action[0] - self.target).abs();
+///         self.target += 0.1;
+///         ([self.target], reward, self.target > 0.5)
+///     }
+/// }
+///
+/// let mut solver = Solver::with_size(1, 1, 10);
+/// solver.evaluate_in_env(&mut Follow { target: 0.0 }, 3, 100);
+/// solver.new_generation();
+/// ```
+pub trait Environment {
+    /// The values the network gets as its inputs.
+    type Observation: AsRef<[f32]>;
+
+    /// Starts a new episode and returns the first observation.
+    fn reset(&mut self) -> Self::Observation;
+
+    /// Performs the action, which are the outputs of the network for the last observation, and
+    /// returns the next observation, the reward for the action and whether the episode is done.
+    fn step(&mut self, action: &[f32]) -> (Self::Observation, f32, bool);
+}
+
+/// Lets the network act in the given number of episodes of the environment and returns the
+/// average total reward of an episode. An episode ends after at most `max_steps` steps, the
+/// state of the network is reset before every episode.
+pub(crate) fn average_episode_reward<E: Environment + ?Sized>(
+    nn: &mut NeuralNetwork,
+    env: &mut E,
+    episodes: usize,
+    max_steps: usize,
+) -> f32 {
+    if episodes == 0 {
+        return 0.0;
+    }
+    let mut total = 0.0;
+    for _ in 0..episodes {
+        nn.reset_state();
+        let mut observation = env.reset();
+        for _ in 0..max_steps {
+            let action = nn.compute(observation.as_ref());
+            let (next, reward, done) = env.step(&action);
+            total += reward;
+            if done {
+                break;
+            }
+            observation = next;
+        }
+    }
+    total / episodes as f32
+}
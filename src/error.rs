@@ -0,0 +1,126 @@
+use std::fmt;
+
+/// The error type of this crate, returned when storing or loading networks and solvers fails.
+///
+/// Some variants only exist with certain features, which another crate in the dependency graph
+/// can turn on, so a match on the error always needs a wildcard arm.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// Reading or writing a file failed, e.g. because the file does not exist or because of
+    /// missing permissions.
+    Io(std::io::Error),
+    /// The data could not be encoded or decoded, e.g. because a file does not contain a network
+    /// or because it is corrupted. Contains the error of the binary encoding.
+    Serialization(Box<dyn std::error::Error + Send + Sync>),
+    /// The json representation could not be encoded or decoded.
+    #[cfg(feature = "serde_json")]
+    Json(serde_json::Error),
+    /// The toml representation of a config could not be decoded.
+    #[cfg(feature = "toml")]
+    Toml(toml::de::Error),
+    /// The data does not start with the header of a stored network or solver, e.g. because it is
+    /// a different kind of file.
+    UnknownFormat,
+    /// The checksum of the data does not match, e.g. because a file has been truncated while it
+    /// was written or has been damaged afterwards.
+    Corrupted,
+    /// The data has been stored with a format version which is not supported, e.g. because it has
+    /// been written by a newer version of this crate.
+    UnsupportedVersion(u32),
+    /// The data could be decoded, but it does not describe a valid network, or a change of a
+    /// `GenomeEditor` would make the network invalid. The string describes the problem.
+    InvalidGenome(String),
+    /// A parameter of a config is out of its valid range, see `Config::validate`. The string
+    /// describes every invalid parameter.
+    InvalidConfig(String),
+    /// A dataset could not be read or its samples do not fit together, e.g. because a row of a
+    /// csv file has a different number of columns. The string describes the problem.
+    InvalidDataset(String),
+    /// A distributed evaluation failed, e.g. because a network has been lost by too many workers.
+    /// The string describes the problem.
+    #[cfg(feature = "distributed")]
+    Distributed(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(err) => write!(f, "io error: {}", err),
+            Error::Serialization(err) => write!(f, "serialization error: {}", err),
+            #[cfg(feature = "serde_json")]
+            Error::Json(err) => write!(f, "json error: {}", err),
+            #[cfg(feature = "toml")]
+            Error::Toml(err) => write!(f, "toml error: {}", err),
+            Error::UnknownFormat => write!(f, "unknown format: missing file header"),
+            Error::Corrupted => write!(f, "corrupted data: the checksum does not match"),
+            Error::UnsupportedVersion(version) => {
+                write!(f, "unsupported format version: {}", version)
+            }
+            Error::InvalidGenome(problem) => write!(f, "invalid genome: {}", problem),
+            Error::InvalidConfig(problems) => write!(f, "invalid config: {}", problems),
+            Error::InvalidDataset(problem) => write!(f, "invalid dataset: {}", problem),
+            #[cfg(feature = "distributed")]
+            Error::Distributed(problem) => write!(f, "distributed evaluation failed: {}", problem),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            Error::Serialization(err) => Some(err.as_ref()),
+            #[cfg(feature = "serde_json")]
+            Error::Json(err) => Some(err),
+            #[cfg(feature = "toml")]
+            Error::Toml(err) => Some(err),
+            Error::UnknownFormat
+            | Error::Corrupted
+            | Error::UnsupportedVersion(_)
+            | Error::InvalidGenome(_)
+            | Error::InvalidConfig(_)
+            | Error::InvalidDataset(_) => None,
+            #[cfg(feature = "distributed")]
+            Error::Distributed(_) => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<bincode::Error> for Error {
+    fn from(err: bincode::Error) -> Self {
+        Error::Serialization(err)
+    }
+}
+
+impl From<rmp_serde::encode::Error> for Error {
+    fn from(err: rmp_serde::encode::Error) -> Self {
+        Error::Serialization(Box::new(err))
+    }
+}
+
+impl From<rmp_serde::decode::Error> for Error {
+    fn from(err: rmp_serde::decode::Error) -> Self {
+        Error::Serialization(Box::new(err))
+    }
+}
+
+#[cfg(feature = "serde_json")]
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Json(err)
+    }
+}
+
+#[cfg(feature = "toml")]
+impl From<toml::de::Error> for Error {
+    fn from(err: toml::de::Error) -> Self {
+        Error::Toml(err)
+    }
+}
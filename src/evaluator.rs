@@ -0,0 +1,149 @@
+use crate::neuralnetwork::NeuralNetwork;
+#[cfg(feature = "async")]
+use core::future::Future;
+
+/// Computes the fitness of a neural network, e.g. by letting it solve a problem and rating its
+/// results. A higher fitness means better.
+///
+/// The evaluator gets mutable access to the network, because computing results with it caches
+/// its phenotype.
+///
+/// Every closure taking a `&mut NeuralNetwork` and returning a `f32` is an evaluator as well.
+///
+/// # Example
+/// ```rust
+/// use neaters::{FitnessEvaluator, NeuralNetwork};
+/// struct Target(f32);
+///
+/// impl FitnessEvaluator for Target {
+///     fn evaluate(&self, nn: &mut NeuralNetwork) -> f32 {
+///         let res = nn.compute(vec![0.5])[0];
+///         -(res - self.0).abs()
+///     }
+/// }
+///
+/// let mut nn = NeuralNetwork::with_size(1, 1);
+/// assert!(Target(0.5).evaluate(&mut nn) < 0.0);
+/// ```
+pub trait FitnessEvaluator {
+    /// Computes the fitness of the given network.
+    fn evaluate(&self, nn: &mut NeuralNetwork) -> f32;
+}
+
+impl<F> FitnessEvaluator for F
+where
+    F: Fn(&mut NeuralNetwork) -> f32,
+{
+    fn evaluate(&self, nn: &mut NeuralNetwork) -> f32 {
+        self(nn)
+    }
+}
+
+/// Computes multiple objectives of a neural network, e.g. its accuracy and its size. A higher
+/// value means better in every objective, so objectives which should be minimized need to be
+/// negated.
+///
+/// Every closure taking a `&mut NeuralNetwork` and returning a `Vec<f32>` is an evaluator as
+/// well.
+///
+/// # Example
+/// ```rust
+/// use neaters::{MultiObjectiveEvaluator, NeuralNetwork};
+/// let evaluator = |nn: &mut NeuralNetwork| {
+///     let error = (nn.compute([0.5])[0] - 0.5).abs();
+///     vec![-error, -(nn.edges().len() as f32)]
+/// };
+/// let mut nn = NeuralNetwork::with_size(1, 1);
+/// assert_eq!(evaluator.evaluate(&mut nn).len(), 2);
+/// ```
+pub trait MultiObjectiveEvaluator {
+    /// Computes the objectives of the given network.
+    fn evaluate(&self, nn: &mut NeuralNetwork) -> Vec<f32>;
+}
+
+impl<F> MultiObjectiveEvaluator for F
+where
+    F: Fn(&mut NeuralNetwork) -> Vec<f32>,
+{
+    fn evaluate(&self, nn: &mut NeuralNetwork) -> Vec<f32> {
+        self(nn)
+    }
+}
+
+/// Computes the fitness and the behavior descriptor of a neural network for novelty search. The
+/// descriptor characterizes what the network does, the novelty of a network is the distance of
+/// its descriptor to the descriptors of other networks.
+///
+/// Every closure taking a `&mut NeuralNetwork` and returning a `(f32, Vec<f32>)` is an evaluator
+/// as well.
+///
+/// # Example
+/// ```rust
+/// use neaters::{NeuralNetwork, NoveltyEvaluator};
+/// let evaluator = |nn: &mut NeuralNetwork| {
+///     let outputs = nn.compute([0.5, -0.5]);
+///     (outputs[0], outputs)
+/// };
+/// let mut nn = NeuralNetwork::with_size(2, 2);
+/// let (_, behavior) = evaluator.evaluate(&mut nn);
+/// assert_eq!(behavior.len(), 2);
+/// ```
+pub trait NoveltyEvaluator {
+    /// Computes the fitness and the behavior descriptor of the given network.
+    fn evaluate(&self, nn: &mut NeuralNetwork) -> (f32, Vec<f32>);
+}
+
+impl<F> NoveltyEvaluator for F
+where
+    F: Fn(&mut NeuralNetwork) -> (f32, Vec<f32>),
+{
+    fn evaluate(&self, nn: &mut NeuralNetwork) -> (f32, Vec<f32>) {
+        self(nn)
+    }
+}
+
+/// Computes the fitness of a neural network asynchronously, e.g. by letting it control a remote
+/// simulator, see `Solver::evaluate_generation_async`. A higher fitness means better.
+///
+/// The returned future may keep the mutable access to the network, so it can compute outputs
+/// whenever the simulator answers. Every closure taking a `&mut NeuralNetwork` and returning a
+/// future of a `f32` is an evaluator as well, but its future cannot borrow the network.
+///
+/// # Example
+/// ```rust
+/// use neaters::{AsyncFitnessEvaluator, NeuralNetwork};
+/// /// Stands in for a client sending the outputs to a remote simulator.
+/// struct Simulator;
+///
+/// impl Simulator {
+///     async fn step(&self, action: f32) -> f32 {
+///         action * 0.5
+///     }
+/// }
+///
+/// impl AsyncFitnessEvaluator for Simulator {
+///     async fn evaluate(&self, nn: &mut NeuralNetwork) -> f32 {
+///         let mut observation = 1.0;
+///         for _ in 0..3 {
+///             observation = self.step(nn.compute([observation])[0]).await;
+///         }
+///         observation
+///     }
+/// }
+/// ```
+#[cfg(feature = "async")]
+pub trait AsyncFitnessEvaluator {
+    /// Returns a future computing the fitness of the given network.
+    fn evaluate(&self, nn: &mut NeuralNetwork) -> impl Future<Output = f32>;
+}
+
+#[cfg(feature = "async")]
+impl<F, T> AsyncFitnessEvaluator for F
+where
+    F: Fn(&mut NeuralNetwork) -> T,
+    T: Future<Output = f32>,
+{
+    fn evaluate(&self, nn: &mut NeuralNetwork) -> impl Future<Output = f32> {
+        self(nn)
+    }
+}
@@ -0,0 +1,77 @@
+use crate::error::Error;
+#[cfg(feature = "gzip")]
+use crate::format;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Counts the temporary files created by this process, so concurrent saves to the same path, e.g.
+/// of parallel checkpoints, never write into the same temporary file.
+static TEMPORARY_FILES: AtomicUsize = AtomicUsize::new(0);
+
+/// Writes the bytes to the file at the given path without ever leaving a partially written file
+/// behind.
+///
+/// The bytes are written to a temporary file in the same directory first, which then replaces
+/// the target file by renaming it. If the program crashes in between, the old file stays intact.
+/// The directory is synced afterwards, so the renamed file has reached the disk as well.
+fn write_atomically(at: &Path, bytes: &[u8]) -> Result<(), Error> {
+    let tmp = temporary_path(at);
+    let result = write_synced(&tmp, bytes).and_then(|_| fs::rename(&tmp, at));
+    if result.is_err() {
+        // the temporary file may not exist, if creating it failed
+        let _ = fs::remove_file(&tmp);
+    }
+    result?;
+    Ok(sync_directory(at)?)
+}
+
+/// Writes a stored network or solver to the file at the given path like `write_atomically`. With
+/// the `gzip` feature the bytes are compressed if the file name ends with `.gz`.
+pub(crate) fn save(at: &Path, bytes: &[u8]) -> Result<(), Error> {
+    #[cfg(feature = "gzip")]
+    if at.extension().is_some_and(|extension| extension == "gz") {
+        return write_atomically(at, &format::compress(bytes)?);
+    }
+    write_atomically(at, bytes)
+}
+
+/// Writes the bytes to a new file and makes sure they have reached the disk.
+fn write_synced(at: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(at)?;
+    file.write_all(bytes)?;
+    file.sync_all()
+}
+
+/// Returns the path of a new temporary file next to the given file, renaming is only atomic
+/// within the same file system.
+fn temporary_path(at: &Path) -> PathBuf {
+    let count = TEMPORARY_FILES.fetch_add(1, Ordering::Relaxed);
+    let mut name = std::ffi::OsString::from(".");
+    name.push(at.file_name().unwrap_or_default());
+    name.push(format!(".{}.{}.tmp", std::process::id(), count));
+    at.with_file_name(name)
+}
+
+/// Makes sure the entries of the directory containing the given file have reached the disk, e.g.
+/// after the file has been renamed.
+#[cfg(unix)]
+fn sync_directory(at: &Path) -> std::io::Result<()> {
+    let dir = match at.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    fs::File::open(dir)?.sync_all()
+}
+
+/// Directories cannot be opened to sync them on other systems, so only the file itself is synced
+/// there.
+#[cfg(not(unix))]
+fn sync_directory(_: &Path) -> std::io::Result<()> {
+    Ok(())
+}
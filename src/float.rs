@@ -0,0 +1,158 @@
+use crate::math;
+use core::fmt::{Debug, Display};
+use core::ops::{Add, AddAssign, Div, Mul, Neg, Sub};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Floating point type of the weights of a network and of the values computed with it, it is
+/// implemented for `f32` and `f64`.
+///
+/// Networks use `f32` by default. Networks computing with `f64` are created with
+/// `NeuralNetwork::cast`, e.g. if small differences of the outputs matter for the fitness. The
+/// solver evolves `f32` networks, so a trained network is cast after the training.
+///
+/// # Example:
+/// ```
+/// use neaters::NeuralNetwork;
+/// let mut nn = NeuralNetwork::with_size(1, 1).cast::<f64>();
+/// let output: f64 = nn.compute([0.5])[0];
+/// assert!((output - 5.0 / 11.0).abs() < 1e-15);
+/// ```
+pub trait Float:
+    Copy
+    + Default
+    + PartialOrd
+    + Debug
+    + Display
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+    + AddAssign
+    + Serialize
+    + DeserializeOwned
+    + Send
+    + Sync
+    + 'static
+{
+    /// The value **0.0**.
+    const ZERO: Self;
+    /// The value **1.0**.
+    const ONE: Self;
+    /// The name of the type, used in the source code generated by `NeuralNetwork::codegen_rust`.
+    const NAME: &'static str;
+
+    /// Converts a `f64` to this type, rounding it if needed.
+    fn from_f64(x: f64) -> Self;
+    /// Converts the value to a `f64`, which is always exact.
+    fn to_f64(self) -> f64;
+    /// Returns the absolute value.
+    fn abs(self) -> Self;
+    /// Returns `e^x`.
+    fn exp(self) -> Self;
+    /// Returns the sine of the value in radians.
+    fn sin(self) -> Self;
+    /// Returns the cosine of the value in radians.
+    fn cos(self) -> Self;
+    /// Returns the hyperbolic tangent of the value.
+    fn tanh(self) -> Self;
+    /// Returns the maximum of both values, ignoring NaN.
+    fn max(self, other: Self) -> Self;
+    /// Returns whether the value is neither infinite nor NaN.
+    fn is_finite(self) -> bool;
+    /// Returns whether the value is NaN.
+    fn is_nan(self) -> bool;
+}
+
+impl Float for f32 {
+    const ZERO: Self = 0.0;
+    const ONE: Self = 1.0;
+    const NAME: &'static str = "f32";
+
+    fn from_f64(x: f64) -> Self {
+        x as f32
+    }
+
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+
+    fn abs(self) -> Self {
+        math::abs(self)
+    }
+
+    fn exp(self) -> Self {
+        math::exp(self)
+    }
+
+    fn sin(self) -> Self {
+        math::sin(self)
+    }
+
+    fn cos(self) -> Self {
+        math::cos(self)
+    }
+
+    fn tanh(self) -> Self {
+        math::tanh(self)
+    }
+
+    fn max(self, other: Self) -> Self {
+        f32::max(self, other)
+    }
+
+    fn is_finite(self) -> bool {
+        f32::is_finite(self)
+    }
+
+    fn is_nan(self) -> bool {
+        f32::is_nan(self)
+    }
+}
+
+impl Float for f64 {
+    const ZERO: Self = 0.0;
+    const ONE: Self = 1.0;
+    const NAME: &'static str = "f64";
+
+    fn from_f64(x: f64) -> Self {
+        x
+    }
+
+    fn to_f64(self) -> f64 {
+        self
+    }
+
+    fn abs(self) -> Self {
+        math::abs_f64(self)
+    }
+
+    fn exp(self) -> Self {
+        math::exp_f64(self)
+    }
+
+    fn sin(self) -> Self {
+        math::sin_f64(self)
+    }
+
+    fn cos(self) -> Self {
+        math::cos_f64(self)
+    }
+
+    fn tanh(self) -> Self {
+        math::tanh_f64(self)
+    }
+
+    fn max(self, other: Self) -> Self {
+        f64::max(self, other)
+    }
+
+    fn is_finite(self) -> bool {
+        f64::is_finite(self)
+    }
+
+    fn is_nan(self) -> bool {
+        f64::is_nan(self)
+    }
+}
@@ -0,0 +1,150 @@
+use crate::error::Error;
+use crate::float::Float;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::borrow::Cow;
+#[cfg(feature = "gzip")]
+use std::io::Read;
+#[cfg(all(feature = "gzip", feature = "std-fs"))]
+use std::io::Write;
+
+/// Magic bytes at the start of every stored neural network.
+pub(crate) const NETWORK_MAGIC: [u8; 4] = *b"NEAN";
+/// Magic bytes at the start of every stored neural network with `f64` weights.
+pub(crate) const NETWORK_F64_MAGIC: [u8; 4] = *b"NEAD";
+/// Magic bytes at the start of every stored solver.
+pub(crate) const SOLVER_MAGIC: [u8; 4] = *b"NEAS";
+/// The version of the format which is being written, see `encode`. It has to be increased
+/// whenever the encoding of a network or a solver changes in a way that older versions would
+/// decode wrongly, e.g. when a field is renamed or changes its meaning, together with a migration
+/// from the old version. Adding a field with a serde default does not need a new version.
+pub(crate) const FORMAT_VERSION: u32 = 3;
+/// The version of the files written before the format had a header, which only contain the
+/// bincode of a network with `f32` weights. It is never written into a header, the files are
+/// recognized by not starting with any magic bytes, see `has_header`.
+pub(crate) const HEADERLESS_VERSION: u32 = 0;
+/// The first version of the format, whose payload has been encoded with bincode. Bincode stores
+/// the fields without their names, so the payload can only be decoded with the layout of the
+/// structs it has been written with.
+///
+/// The layout changed several times while this version was written, without the version being
+/// increased. Only networks with its last layout can be decoded, see `BincodeNetwork`, networks
+/// with an earlier layout fail to decode or decode wrongly. Solvers of this version are rejected,
+/// their layout is not known anymore.
+pub(crate) const BINCODE_VERSION: u32 = 1;
+/// The version of the format whose payload is encoded like the current one, but whose header does
+/// not contain a checksum.
+pub(crate) const UNCHECKED_VERSION: u32 = 2;
+
+/// The length of the header: the magic bytes followed by the format version as little endian.
+const HEADER_LEN: usize = 8;
+/// The length of the header of the current version, which additionally contains the CRC-32
+/// checksum of the header and the payload as little endian.
+const CHECKED_HEADER_LEN: usize = HEADER_LEN + 4;
+/// Magic bytes at the start of gzip data, which can never be the start of a header.
+#[cfg(feature = "gzip")]
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Returns the magic bytes of stored neural networks with weights of the given type.
+pub(crate) fn network_magic<F: Float>() -> [u8; 4] {
+    if F::NAME == f64::NAME {
+        NETWORK_F64_MAGIC
+    } else {
+        NETWORK_MAGIC
+    }
+}
+
+/// Prepends the header with the given magic bytes, the current format version and the checksum
+/// to the payload.
+pub(crate) fn with_header(magic: [u8; 4], payload: Vec<u8>) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(CHECKED_HEADER_LEN + payload.len());
+    bytes.extend_from_slice(&magic);
+    bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+    bytes.extend_from_slice(&checksum(&bytes, &payload).to_le_bytes());
+    bytes.extend(payload);
+    bytes
+}
+
+/// Returns whether the stored bytes start with the magic bytes of a network or a solver. Bytes
+/// without them have been written before the format had a header.
+pub(crate) fn has_header(bytes: &[u8]) -> bool {
+    [NETWORK_MAGIC, NETWORK_F64_MAGIC, SOLVER_MAGIC]
+        .iter()
+        .any(|magic| bytes.starts_with(magic))
+}
+
+/// Splits the stored bytes into their format version and the payload.
+///
+/// Returns an error if the bytes do not start with the given magic bytes, if they have been
+/// written by a newer version of this crate or if their checksum does not match, e.g. because a
+/// file has been truncated while it was written.
+pub(crate) fn split_header(magic: [u8; 4], bytes: &[u8]) -> Result<(u32, &[u8]), Error> {
+    if bytes.len() < HEADER_LEN || bytes[..4] != magic {
+        return Err(Error::UnknownFormat);
+    }
+    let version = u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+    if version == 0 || version > FORMAT_VERSION {
+        return Err(Error::UnsupportedVersion(version));
+    }
+    if version < FORMAT_VERSION {
+        return Ok((version, &bytes[HEADER_LEN..]));
+    }
+    if bytes.len() < CHECKED_HEADER_LEN {
+        return Err(Error::Corrupted);
+    }
+    let stored = u32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]);
+    let payload = &bytes[CHECKED_HEADER_LEN..];
+    if checksum(&bytes[..HEADER_LEN], payload) != stored {
+        return Err(Error::Corrupted);
+    }
+    Ok((version, payload))
+}
+
+/// Computes the CRC-32 checksum of the header without the checksum and the payload.
+fn checksum(header: &[u8], payload: &[u8]) -> u32 {
+    let mut hasher = crc32fast::Hasher::new();
+    hasher.update(header);
+    hasher.update(payload);
+    hasher.finalize()
+}
+
+/// Encodes the payload of the current format version as MessagePack, where every struct is a map
+/// from the names of its fields to their values.
+///
+/// This keeps stored networks and solvers loadable across versions of this crate: `decode` skips
+/// fields it does not know, e.g. because they have been added by a newer version, and fields which
+/// are missing in the payload get their serde default.
+pub(crate) fn encode<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, Error> {
+    Ok(rmp_serde::to_vec_named(value)?)
+}
+
+/// Decodes the payload of the current format version written by `encode`.
+pub(crate) fn decode<T: DeserializeOwned>(payload: &[u8]) -> Result<T, Error> {
+    Ok(rmp_serde::from_slice(payload)?)
+}
+
+/// Compresses stored bytes with gzip, see `decompress`.
+#[cfg(all(feature = "gzip", feature = "std-fs"))]
+pub(crate) fn compress(bytes: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(bytes)?;
+    Ok(encoder.finish()?)
+}
+
+/// Returns the decompressed bytes if they are compressed with gzip, otherwise the bytes are
+/// returned unchanged. Compressed data is detected by its own magic bytes, so it is loaded from
+/// any file or reader, no matter how it is named.
+///
+/// Without the `gzip` feature compressed bytes are returned unchanged and are rejected as an
+/// unknown format.
+pub(crate) fn decompress(bytes: &[u8]) -> Result<Cow<'_, [u8]>, Error> {
+    #[cfg(feature = "gzip")]
+    if bytes.starts_with(&GZIP_MAGIC) {
+        let mut decompressed = Vec::new();
+        flate2::read::GzDecoder::new(bytes)
+            .read_to_end(&mut decompressed)
+            .map_err(|err| Error::Serialization(Box::new(err)))?;
+        return Ok(Cow::Owned(decompressed));
+    }
+    Ok(Cow::Borrowed(bytes))
+}
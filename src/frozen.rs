@@ -0,0 +1,160 @@
+#[cfg(feature = "std")]
+use crate::error::Error;
+use crate::float::Float;
+use crate::neuralnetwork::NeuralNetwork;
+use alloc::collections::BTreeSet;
+use serde::{Deserialize, Serialize};
+
+/// The nodes and connections of a network which are frozen, see `NeuralNetwork::freeze_node`
+/// and `NeuralNetwork::freeze_connection`. Connections are identified by the ids of their source
+/// and destination node.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct Frozen {
+    nodes: BTreeSet<usize>,
+    connections: BTreeSet<(usize, usize)>,
+}
+
+impl Frozen {
+    /// Returns whether the node is frozen.
+    pub(crate) fn node(&self, id: usize) -> bool {
+        self.nodes.contains(&id)
+    }
+
+    /// Returns whether the connection is frozen.
+    pub(crate) fn connection(&self, from: usize, to: usize) -> bool {
+        self.connections.contains(&(from, to))
+    }
+}
+
+impl<F: Float> NeuralNetwork<F> {
+    /// Returns whether the node with the id is frozen, see `freeze_node`.
+    pub fn is_node_frozen(&self, id: usize) -> bool {
+        self.frozen.node(id)
+    }
+
+    /// Returns whether the connection between two nodes is frozen, see `freeze_connection`.
+    pub fn is_connection_frozen(&self, from: usize, to: usize) -> bool {
+        self.frozen.connection(from, to)
+    }
+
+    /// Returns the ids of the frozen nodes in ascending order.
+    pub fn frozen_nodes(&self) -> impl Iterator<Item = usize> + '_ {
+        self.frozen.nodes.iter().copied()
+    }
+
+    /// Returns the frozen connections as pairs of the ids of their source and destination node,
+    /// in ascending order.
+    pub fn frozen_connections(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.frozen.connections.iter().copied()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<F: Float> NeuralNetwork<F> {
+    /// Freezes a node, so its activation function is never mutated and the node is never removed
+    /// by `prune`. New connections can still lead into and out of it, so a hand-designed skeleton
+    /// is preserved while the rest of the network evolves around it. Returns an
+    /// `Error::InvalidGenome` if the network has no node with the id.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::NeuralNetwork;
+    /// let mut nn = NeuralNetwork::with_size(2, 1);
+    /// nn.freeze_node(3)?;
+    /// for _ in 0..10 {
+    ///     nn.mutate_activation(&mut rand::thread_rng());
+    /// }
+    /// assert_eq!(nn.nodes()[3].activation, NeuralNetwork::with_size(2, 1).nodes()[3].activation);
+    /// assert!(nn.freeze_node(7).is_err());
+    /// # Ok::<(), neaters::Error>(())
+    /// ```
+    pub fn freeze_node(&mut self, id: usize) -> Result<(), Error> {
+        if !self.nodes.iter().any(|node| node.id == id) {
+            return Err(Error::InvalidGenome(format!(
+                "the node {} does not exist",
+                id
+            )));
+        }
+        self.frozen.nodes.insert(id);
+        Ok(())
+    }
+
+    /// Freezes the connection between two nodes, so its weight and its enabled flag are never
+    /// mutated, it is never split by a new node and it is never removed by `prune`, even if it is
+    /// disabled. A crossover always inherits the connection from the fitter parent. Returns an
+    /// `Error::InvalidGenome` if the nodes are not connected.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::{Config, NeuralNetwork};
+    /// let mut nn = NeuralNetwork::with_size(2, 1);
+    /// nn.freeze_connection(1, 3)?;
+    /// let config = Config::builder().weight_mutation_rate(1.0).build();
+    /// nn.mutate_weights(&config, &mut rand::thread_rng());
+    /// assert_eq!(nn.edges()[1].weight, 1.0);
+    /// assert!(nn.freeze_connection(3, 1).is_err());
+    /// # Ok::<(), neaters::Error>(())
+    /// ```
+    pub fn freeze_connection(&mut self, from: usize, to: usize) -> Result<(), Error> {
+        if !self
+            .edges
+            .iter()
+            .any(|edge| edge.from == from && edge.to == to)
+        {
+            return Err(Error::InvalidGenome(format!(
+                "there is no connection from {} to {}",
+                from, to
+            )));
+        }
+        self.frozen.connections.insert((from, to));
+        Ok(())
+    }
+
+    /// Freezes every hidden and output node and every connection of the network, e.g. after a
+    /// skeleton network has been designed with `edit`, so the evolution only adds to it.
+    pub fn freeze_all(&mut self) {
+        let nodes = self
+            .nodes
+            .iter()
+            .filter(|node| !node.node_type.is_input_or_bias())
+            .map(|node| node.id);
+        self.frozen.nodes.extend(nodes);
+        let connections = self.edges.iter().map(|edge| (edge.from, edge.to));
+        self.frozen.connections.extend(connections);
+    }
+
+    /// Unfreezes a node, returns whether it has been frozen.
+    pub fn unfreeze_node(&mut self, id: usize) -> bool {
+        self.frozen.nodes.remove(&id)
+    }
+
+    /// Unfreezes the connection between two nodes, returns whether it has been frozen.
+    pub fn unfreeze_connection(&mut self, from: usize, to: usize) -> bool {
+        self.frozen.connections.remove(&(from, to))
+    }
+
+    /// Changes the node ids of the frozen marks, e.g. when the network is adopted by a solver.
+    pub(crate) fn remap_frozen(&mut self, id: impl Fn(usize) -> usize) {
+        let frozen = core::mem::take(&mut self.frozen);
+        self.frozen = Frozen {
+            nodes: frozen.nodes.into_iter().map(&id).collect(),
+            connections: frozen
+                .connections
+                .into_iter()
+                .map(|(from, to)| (id(from), id(to)))
+                .collect(),
+        };
+    }
+
+    /// Removes the frozen marks of nodes and connections which are not part of the network
+    /// anymore, e.g. after an output has been removed.
+    pub(crate) fn retain_frozen(&mut self) {
+        let (nodes, edges) = (&self.nodes, &self.edges);
+        self.frozen
+            .nodes
+            .retain(|&id| nodes.iter().any(|node| node.id == id));
+        self.frozen
+            .connections
+            .retain(|&(from, to)| edges.iter().any(|edge| edge.from == from && edge.to == to));
+    }
+}
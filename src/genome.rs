@@ -0,0 +1,1051 @@
+//! Alignment of the genes of two genomes by their innovation numbers, see `align`.
+//!
+//! The module also contains the changes of the genome used by the training: mutations, crossover
+//! and structural changes of the inputs and outputs. They need the `std` feature, unlike the
+//! computation of the outputs.
+use crate::activation::Activation;
+use crate::config::{Config, Direction, InitialTopology, MutationRates, WeightInit};
+use crate::editor::GenomeEditor;
+use crate::float::Float;
+use crate::innovation::InnovationTracker;
+use crate::lineage::Mutation;
+use crate::neuralnetwork::{Edge, NeuralNetwork, Node, NodeType, PruneReport};
+use crate::phenotype::Phenotype;
+use crate::random;
+use rand::seq::index;
+use rand::Rng;
+use rustc_hash::FxHashSet;
+
+/// A gene of two aligned genomes, see `align`.
+///
+/// Disjoint genes are missing in the other genome although it has genes with both lower and
+/// higher innovation numbers, excess genes have a higher innovation number than every gene of
+/// the other genome.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GeneMatch<'a, F = f32> {
+    /// Both genomes have an edge with the same innovation number, the edge of the first genome
+    /// comes first
+    Matching(&'a Edge<F>, &'a Edge<F>),
+    /// A disjoint edge of the first genome
+    DisjointFirst(&'a Edge<F>),
+    /// A disjoint edge of the second genome
+    DisjointSecond(&'a Edge<F>),
+    /// An excess edge of the first genome
+    ExcessFirst(&'a Edge<F>),
+    /// An excess edge of the second genome
+    ExcessSecond(&'a Edge<F>),
+}
+
+/// Iterates through the edges of both genomes ordered by their innovation number and tells for
+/// every edge whether it is matching, disjoint or excess.
+///
+/// The edges of both genomes need to be sorted by their innovation number, which is the case for
+/// every network of the solver and for every valid network. The speciation distance and the
+/// crossover are based on this alignment.
+///
+/// # Example:
+/// ```
+/// use neaters::genome::{align, GeneMatch};
+/// use neaters::{InnovationTracker, NeuralNetwork};
+/// let a = NeuralNetwork::with_size(1, 1);
+/// let mut b = a.clone();
+/// let mut innovations = InnovationTracker::for_network(&b);
+/// b.mutate_add_node(&mut innovations, &mut rand::thread_rng());
+/// let genes: Vec<GeneMatch> = align(&a, &b).collect();
+/// // the two edges of the initial network match, the new node added two excess edges
+/// assert!(matches!(genes[0], GeneMatch::Matching(_, _)));
+/// assert!(matches!(genes[3], GeneMatch::ExcessSecond(_)));
+/// assert_eq!(genes.len(), 4);
+/// ```
+pub fn align<'a, F: Float>(
+    a: &'a NeuralNetwork<F>,
+    b: &'a NeuralNetwork<F>,
+) -> impl Iterator<Item = GeneMatch<'a, F>> + 'a {
+    let (a, b) = (&a.edges, &b.edges);
+    let mut a_pointer = 0;
+    let mut b_pointer = 0;
+    core::iter::from_fn(move || {
+        let gene = match (a.get(a_pointer), b.get(b_pointer)) {
+            (Some(a_edge), Some(b_edge)) => match a_edge.innovation.cmp(&b_edge.innovation) {
+                std::cmp::Ordering::Less => GeneMatch::DisjointFirst(a_edge),
+                std::cmp::Ordering::Equal => GeneMatch::Matching(a_edge, b_edge),
+                std::cmp::Ordering::Greater => GeneMatch::DisjointSecond(b_edge),
+            },
+            (Some(a_edge), None) => GeneMatch::ExcessFirst(a_edge),
+            (None, Some(b_edge)) => GeneMatch::ExcessSecond(b_edge),
+            (None, None) => return None,
+        };
+        match gene {
+            GeneMatch::Matching(_, _) => {
+                a_pointer += 1;
+                b_pointer += 1;
+            }
+            GeneMatch::DisjointFirst(_) | GeneMatch::ExcessFirst(_) => a_pointer += 1,
+            GeneMatch::DisjointSecond(_) | GeneMatch::ExcessSecond(_) => b_pointer += 1,
+        }
+        Some(gene)
+    })
+}
+
+/// The innovation numbers and weights of the edges of a genome in two arrays ordered by the
+/// innovation number. A genome which is compared to many others, like during the speciation, is
+/// converted once, so every comparison only walks through two compact arrays.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct GeneIndex {
+    innovations: Vec<usize>,
+    weights: Vec<f32>,
+}
+
+impl GeneIndex {
+    /// Creates the index of the edges of the network, which need to be sorted by their innovation
+    /// number.
+    pub(crate) fn new(nn: &NeuralNetwork) -> Self {
+        GeneIndex {
+            innovations: nn.edges.iter().map(|edge| edge.innovation).collect(),
+            weights: nn.edges.iter().map(|edge| edge.weight).collect(),
+        }
+    }
+
+    /// Returns the compatibility distance of the two genomes: the weighted numbers of excess and
+    /// disjoint genes relative to the size of the larger genome and the weighted average weight
+    /// difference of the matching genes. It is the same distance `align` would give.
+    pub(crate) fn distance(&self, other: &GeneIndex, config: &Config) -> f32 {
+        let (a, b) = (&self.innovations, &other.innovations);
+        let (mut i, mut j) = (0, 0);
+        let mut disjoint = 0;
+        let mut matching = 0;
+        let mut weight_diff = 0.0;
+        while i < a.len() && j < b.len() {
+            match a[i].cmp(&b[j]) {
+                std::cmp::Ordering::Less => {
+                    disjoint += 1;
+                    i += 1;
+                }
+                std::cmp::Ordering::Equal => {
+                    weight_diff += f32::abs(self.weights[i] - other.weights[j]);
+                    matching += 1;
+                    i += 1;
+                    j += 1;
+                }
+                std::cmp::Ordering::Greater => {
+                    disjoint += 1;
+                    j += 1;
+                }
+            }
+        }
+        // the remaining genes of one genome are behind every gene of the other one
+        let excess = (a.len() - i) + (b.len() - j);
+        // networks without any edge are identical
+        let n = usize::max(a.len(), b.len()).max(1) as f32;
+        config.c1 * (excess as f32) / n
+            + config.c2 * (disjoint as f32) / n
+            + config.c3 * weight_diff / matching.max(1) as f32
+    }
+}
+
+impl NeuralNetwork {
+    /// Returns the network where the weight of every edge is sampled from the given distribution.
+    /// Connections which are added later by mutations use this distribution as well.
+    ///
+    /// The constructors give every edge a weight of **1.0**, so all networks created with the same
+    /// size are equal. The solver uses this function to start with different networks.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::{NeuralNetwork, WeightInit};
+    /// let nn = NeuralNetwork::with_size(3, 2).with_weight_init(
+    ///     WeightInit::Gaussian { mean: 0.0, std_dev: 0.5 },
+    ///     &mut rand::thread_rng(),
+    /// );
+    /// assert!(nn.edges().iter().any(|edge| edge.weight != 1.0));
+    /// ```
+    pub fn with_weight_init<R: Rng + ?Sized>(
+        mut self,
+        weight_init: WeightInit,
+        rng: &mut R,
+    ) -> Self {
+        self.weight_init = weight_init;
+        for idx in 0..self.edges.len() {
+            let (from, to) = (self.edges[idx].from, self.edges[idx].to);
+            self.edges[idx].weight = self.sample_weight(from, to, 0, rng);
+        }
+        self.genome_changed();
+        self
+    }
+
+    /// Returns the network where only some of the initial connections between the input and the
+    /// output nodes exist.
+    ///
+    /// For problems with many inputs a fully connected network is already very large, starting
+    /// with a sparse or an unconnected network lets the evolution select the relevant inputs.
+    /// The removed connections keep their innovation numbers if they are added again later.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::{InitialTopology, NeuralNetwork};
+    /// let mut rng = rand::thread_rng();
+    /// let nn = NeuralNetwork::with_size(10, 2).with_initial_topology(InitialTopology::Sparse(3), &mut rng);
+    /// assert_eq!(nn.edges().len(), 6);
+    /// let nn = NeuralNetwork::with_size(10, 2).with_initial_topology(InitialTopology::Unconnected, &mut rng);
+    /// assert!(nn.edges().is_empty());
+    /// ```
+    pub fn with_initial_topology<R: Rng + ?Sized>(
+        mut self,
+        topology: InitialTopology,
+        rng: &mut R,
+    ) -> Self {
+        match topology {
+            InitialTopology::FullyConnected => {}
+            InitialTopology::Sparse(k) => {
+                let inputs: Vec<usize> = self
+                    .nodes
+                    .iter()
+                    .filter(|node| node.node_type.is_input_or_bias())
+                    .map(|node| node.id)
+                    .collect();
+                let outputs: Vec<usize> = self
+                    .nodes
+                    .iter()
+                    .filter(|node| node.node_type == NodeType::Output)
+                    .map(|node| node.id)
+                    .collect();
+                let mut kept: FxHashSet<(usize, usize)> = FxHashSet::default();
+                for to in outputs {
+                    for idx in index::sample(rng, inputs.len(), k.min(inputs.len())) {
+                        kept.insert((inputs[idx], to));
+                    }
+                }
+                self.edges
+                    .retain(|edge| kept.contains(&(edge.from, edge.to)));
+            }
+            InitialTopology::Unconnected => self.edges.clear(),
+        }
+        self.genome_changed();
+        self
+    }
+
+    /// Samples the weight of an edge between the given nodes from the weight distribution of the
+    /// network. `additional` is the number of edges between the nodes which do not exist yet.
+    fn sample_weight<R: Rng + ?Sized>(
+        &self,
+        from: usize,
+        to: usize,
+        additional: usize,
+        rng: &mut R,
+    ) -> f32 {
+        let fan_in = self.edges.iter().filter(|edge| edge.to == to).count() + additional;
+        let fan_out = self.edges.iter().filter(|edge| edge.from == from).count() + additional;
+        self.weight_init.sample(fan_in, fan_out, rng)
+    }
+
+    /// Removes every part of the network which does not contribute to its outputs: disabled
+    /// edges, hidden nodes which cannot be reached from the inputs and hidden nodes without a path
+    /// to any output, together with their edges. Input and output nodes are never removed, neither
+    /// are frozen nodes and frozen connections together with their nodes or module instances.
+    ///
+    /// Hidden nodes which cannot be reached from the inputs still pass on a constant value, e.g.
+    /// **1.0** for a gaussian activation. This value is added to the edge from the bias node to
+    /// the destination, so a feed-forward network computes the same outputs after
+    /// pruning. If such an edge does not exist yet, it gets a new innovation number which is not
+    /// known to any `InnovationTracker`, so this function is meant to be used on a trained
+    /// network, e.g. before deploying it. A network without a bias node keeps these nodes.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::{InnovationTracker, NeuralNetwork};
+    /// let mut nn = NeuralNetwork::with_size(2, 1);
+    /// let mut innovations = InnovationTracker::for_network(&nn);
+    /// nn.mutate_add_node(&mut innovations, &mut rand::thread_rng());
+    /// let before = nn.compute([0.3, 0.6]);
+    /// // the split edge is disabled and not needed anymore
+    /// let report = nn.prune();
+    /// assert_eq!(report.removed_edges.len(), 1);
+    /// assert!(report.removed_nodes.is_empty());
+    /// assert_eq!(nn.compute([0.3, 0.6]), before);
+    /// ```
+    pub fn prune(&mut self) -> PruneReport {
+        let inputs: Vec<usize> = self
+            .nodes
+            .iter()
+            .filter(|node| node.node_type.is_input_or_bias())
+            .map(|node| node.id)
+            .collect();
+        let outputs: Vec<usize> = self
+            .nodes
+            .iter()
+            .filter(|node| node.node_type == NodeType::Output)
+            .map(|node| node.id)
+            .collect();
+        let reachable = self.reachable(&inputs, false);
+        let useful = self.reachable(&outputs, true);
+        let bias_node = self.bias_node();
+        // the endpoints of frozen connections are kept together with the connections
+        let anchored: FxHashSet<usize> = self
+            .frozen_connections()
+            .flat_map(|(from, to)| [from, to])
+            .chain(
+                self.module_instances()
+                    .iter()
+                    .flat_map(|i| i.nodes().to_vec()),
+            )
+            .collect();
+        let kept = |node: &Node| {
+            node.node_type != NodeType::Hidden
+                || self.frozen.node(node.id)
+                || anchored.contains(&node.id)
+                || (useful.contains(&node.id)
+                    && (reachable.contains(&node.id) || bias_node.is_none()))
+        };
+        // the constant values passed on by nodes which cannot be reached from the inputs
+        let mut pt = Phenotype::from_nn(self);
+        pt.compute(&vec![0.0; self.size.0]);
+        let mut bias: Vec<(usize, f32)> = Vec::new();
+        for edge in self.edges.iter().filter(|edge| edge.enabled) {
+            let source = self.nodes.iter().position(|node| node.id == edge.from);
+            let destination = self.nodes.iter().find(|node| node.id == edge.to);
+            if let (Some(idx), Some(destination)) = (source, destination) {
+                if !reachable.contains(&edge.from) && !kept(&self.nodes[idx]) && kept(destination) {
+                    bias.push((edge.to, edge.weight * pt.node_value_array[idx]));
+                }
+            }
+        }
+        let mut report = PruneReport::default();
+        let removed: FxHashSet<usize> = self
+            .nodes
+            .iter()
+            .filter(|node| !kept(node))
+            .map(|node| node.id)
+            .collect();
+        report.removed_nodes = self
+            .nodes
+            .iter()
+            .filter(|node| removed.contains(&node.id))
+            .map(|node| node.id)
+            .collect();
+        self.nodes.retain(|node| !removed.contains(&node.id));
+        let (frozen, modules) = (&self.frozen, &self.modules);
+        self.edges.retain(|edge| {
+            let locked = frozen.connection(edge.from, edge.to) || modules.owns(edge.to);
+            let keep = (edge.enabled || locked)
+                && !removed.contains(&edge.from)
+                && !removed.contains(&edge.to);
+            if !keep {
+                report.removed_edges.push(edge.innovation);
+            }
+            keep
+        });
+        // without a bias node the nodes passing on constant values have been kept
+        if let Some(bias_node) = bias_node {
+            for (to, value) in bias.into_iter().filter(|&(_, value)| value != 0.0) {
+                match self
+                    .edges
+                    .iter_mut()
+                    .find(|edge| edge.from == bias_node && edge.to == to)
+                {
+                    Some(edge) => edge.weight += value,
+                    None => {
+                        let innovation = self.next_innovation();
+                        let mut edge = Edge::initial_from_to(bias_node, to, innovation);
+                        edge.weight = value;
+                        self.insert_edge(edge);
+                    }
+                }
+            }
+        }
+        self.genome_changed();
+        report
+    }
+
+    /// Returns an editor to change the nodes and edges of the network, which checks every change
+    /// so the genome stays valid. See `GenomeEditor` for an example.
+    pub fn edit(&mut self) -> GenomeEditor<'_> {
+        GenomeEditor::new(self)
+    }
+
+    /// Returns the network with `n` additional output nodes.
+    ///
+    /// The hidden structure of the network is being preserved. Every new output node gets a single
+    /// connection with a random weight from a random input node (or the bias node), so
+    /// that the network stays minimal and the new outputs can be evolved from there.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::NeuralNetwork;
+    /// let mut nn = NeuralNetwork::with_size(2, 1).with_added_outputs(2);
+    /// assert_eq!(nn.size(), (2, 3));
+    /// assert_eq!(nn.compute(vec![0.5, 0.5]).len(), 3);
+    /// ```
+    pub fn with_added_outputs(mut self, n: usize) -> Self {
+        let mut innovations = InnovationTracker::for_network(&self);
+        let ids: Vec<usize> = (0..n).map(|_| innovations.new_node_id()).collect();
+        self.add_outputs(&ids, &mut innovations, &mut rand::thread_rng());
+        self
+    }
+
+    /// Removes the output node with the given id together with all edges connected to it.
+    ///
+    /// The remaining outputs keep their relative order.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::NeuralNetwork;
+    /// let mut nn = NeuralNetwork::with_size(2, 2);
+    /// // The output nodes come directly after the bias node and the two input nodes.
+    /// nn.remove_output(3);
+    /// assert_eq!(nn.size(), (2, 1));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the network has no output node with the given id.
+    pub fn remove_output(&mut self, id: usize) {
+        let idx = self
+            .nodes
+            .iter()
+            .position(|node| node.id == id && node.node_type == NodeType::Output)
+            .expect("the network has no output node with this id");
+        self.nodes.remove(idx);
+        self.edges.retain(|edge| edge.from != id && edge.to != id);
+        self.size.1 -= 1;
+        self.retain_frozen();
+        self.retain_modules();
+        self.genome_changed();
+    }
+
+    /// Returns the network with `n` additional inputs appended after the existing ones.
+    ///
+    /// The new inputs start weakly connected, every new input node gets a connection with a small
+    /// random weight to every output node, so the trained behaviour of the network is mostly
+    /// preserved.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::NeuralNetwork;
+    /// let mut nn = NeuralNetwork::with_size(2, 1).with_extended_inputs(1);
+    /// assert_eq!(nn.size(), (3, 1));
+    /// let res = nn.compute(vec![0.1, 0.2, 0.3]);
+    /// ```
+    pub fn with_extended_inputs(mut self, n: usize) -> Self {
+        let mapping: Vec<Option<usize>> = (0..self.size.0)
+            .map(Some)
+            .chain(std::iter::repeat_n(None, n))
+            .collect();
+        self.remap_inputs(&mapping);
+        self
+    }
+
+    /// Changes the input layout of the network, so that a network trained on one input layout can
+    /// be transferred to a problem with reordered, added or removed inputs.
+    ///
+    /// The mapping contains one entry for every input of the new layout: `Some(i)` means that the
+    /// new input is the old input with index `i` and `None` means that the new input did not
+    /// exist before. New inputs start weakly connected like in `with_extended_inputs`. Old inputs
+    /// which are not part of the mapping are being removed together with their edges. The input
+    /// scaling is remapped in the same way, new inputs are not scaled.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::NeuralNetwork;
+    /// let mut nn = NeuralNetwork::with_size(3, 1);
+    /// // swap the first two inputs, remove the third one and add a new input at the end
+    /// nn.remap_inputs(&[Some(1), Some(0), None]);
+    /// assert_eq!(nn.size(), (3, 1));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the mapping references an input which does not exist or references an input more
+    /// than once.
+    pub fn remap_inputs(&mut self, mapping: &[Option<usize>]) {
+        let mut innovations = InnovationTracker::for_network(self);
+        let blocks = if self.presence_indicators { 2 } else { 1 };
+        let new_inputs = mapping.iter().filter(|target| target.is_none()).count();
+        let ids: Vec<usize> = (0..blocks * new_inputs)
+            .map(|_| innovations.new_node_id())
+            .collect();
+        self.remap_inputs_with(mapping, &ids, &mut innovations, &mut rand::thread_rng());
+    }
+
+    /// Changes the input layout like `remap_inputs` where the new input nodes (and their presence
+    /// indicators) get the given ids and their connections get innovation numbers from the
+    /// tracker.
+    pub(crate) fn remap_inputs_with<R: Rng + ?Sized>(
+        &mut self,
+        mapping: &[Option<usize>],
+        ids: &[usize],
+        innovations: &mut InnovationTracker,
+        rng: &mut R,
+    ) {
+        let old_inputs = self.size.0;
+        // the presence indicator nodes are being remapped in the same way as the inputs
+        let blocks = if self.presence_indicators { 2 } else { 1 };
+        let outputs: Vec<usize> = self
+            .nodes
+            .iter()
+            .filter(|node| node.node_type == NodeType::Output)
+            .map(|node| node.id)
+            .collect();
+        // the input nodes are always stored at the front of the node list, after the bias node
+        let rest = self.nodes.split_off(1 + blocks * old_inputs);
+        let old = self.nodes.split_off(1);
+        let mut used = vec![false; old.len()];
+        let mut ids = ids.iter();
+        for block in 0..blocks {
+            for target in mapping.iter() {
+                match *target {
+                    Some(i) => {
+                        assert!(i < old_inputs, "the mapping references a missing input");
+                        let idx = block * old_inputs + i;
+                        assert!(!used[idx], "the mapping references an input twice");
+                        used[idx] = true;
+                        self.nodes.push(old[idx]);
+                    }
+                    None => {
+                        let id = *ids.next().expect("an id is needed for every new input");
+                        self.nodes.push(Node::input_with_id(id));
+                        for &to in outputs.iter() {
+                            let innovation = innovations.connection(id, to);
+                            let mut edge = Edge::initial_from_to(id, to, innovation);
+                            edge.weight = rng.gen_range(-0.1..=0.1);
+                            self.insert_edge(edge);
+                        }
+                    }
+                }
+            }
+        }
+        for (node, used) in old.iter().zip(used) {
+            if !used {
+                self.edges.retain(|edge| edge.from != node.id);
+            }
+        }
+        self.nodes.extend(rest);
+        self.size.0 = mapping.len();
+        self.input_scaling = self.input_scaling.remap(mapping);
+        self.retain_frozen();
+        self.retain_modules();
+        self.genome_changed();
+    }
+
+    /// Adds an output node for every given id. The new connections get their innovation numbers
+    /// from the tracker, so networks of one solver get the same innovation numbers for the same
+    /// connections.
+    pub(crate) fn add_outputs<R: Rng + ?Sized>(
+        &mut self,
+        ids: &[usize],
+        innovations: &mut InnovationTracker,
+        rng: &mut R,
+    ) {
+        let sources = self.input_node_count() + 1;
+        for &id in ids.iter() {
+            let mut node = Node::output_with_id(id);
+            if let Some(activation) = self.output_activation.fixed_activation() {
+                node.activation = activation;
+            }
+            self.nodes.push(node);
+            // the input nodes are always stored at the front of the node list
+            let from = self.nodes[random::index(rng, sources)].id;
+            let mut edge = Edge::initial_from_to(from, id, innovations.connection(from, id));
+            edge.weight = self.sample_weight(from, id, 1, rng);
+            self.insert_edge(edge);
+        }
+        self.size.1 += ids.len();
+        self.genome_changed();
+    }
+
+    /// Returns the smallest node id which is bigger than all node ids of the network.
+    pub(crate) fn next_node_id(&self) -> usize {
+        self.nodes.iter().map(|node| node.id + 1).max().unwrap_or(0)
+    }
+
+    /// Returns the smallest innovation number which is bigger than all innovation numbers of the
+    /// network.
+    pub(crate) fn next_innovation(&self) -> usize {
+        self.edges
+            .iter()
+            .map(|edge| edge.innovation + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Creates an offspring of this network and another network.
+    ///
+    /// The genes of both networks are aligned by their innovation number. Matching genes are
+    /// inherited randomly from either parent, disjoint and excess genes are only inherited from the
+    /// fitter parent. If both networks have the same fitness, this network is treated as the fitter
+    /// one. A gene which is disabled in one of the parents is enabled again in the offspring with a
+    /// probability of **0.25**.
+    ///
+    /// Both networks should have been created by the same solver (or use the same
+    /// `InnovationTracker`), otherwise the innovation numbers cannot be aligned.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::NeuralNetwork;
+    /// let mut a = NeuralNetwork::with_size(2, 1);
+    /// let b = NeuralNetwork::with_size(2, 1);
+    /// a.assign_fitness(2.0);
+    /// let child = a.crossover(&b, &mut rand::thread_rng());
+    /// assert_eq!(child.edges().len(), a.edges().len());
+    /// ```
+    pub fn crossover<R: Rng + ?Sized>(&self, other: &Self, rng: &mut R) -> Self {
+        self.crossover_with_rate(
+            other,
+            Config::default().reenable_rate,
+            Direction::Maximize,
+            rng,
+        )
+    }
+
+    /// Creates an offspring like `crossover` where disabled genes get enabled again with the
+    /// probability `reenable_rate` and the fitter parent is determined by the direction.
+    pub(crate) fn crossover_with_rate<R: Rng + ?Sized>(
+        &self,
+        other: &Self,
+        reenable_rate: f32,
+        direction: Direction,
+        rng: &mut R,
+    ) -> Self {
+        let (fitter, other) = if direction.is_better(other.fitness, self.fitness) {
+            (other, self)
+        } else {
+            (self, other)
+        };
+        // every gene of the offspring is a gene of the fitter parent, so it has the same nodes
+        let edges = align(fitter, other)
+            .filter_map(|gene| match gene {
+                GeneMatch::Matching(&edge, &matching) => {
+                    let disabled = !edge.enabled || !matching.enabled;
+                    let mut inherited = if rng.gen::<bool>() {
+                        // the structure of the fitter parent decides whether the edge is recurrent
+                        Edge {
+                            recurrent: edge.recurrent,
+                            ..matching
+                        }
+                    } else {
+                        edge
+                    };
+                    if disabled {
+                        inherited.enabled = rng.gen::<f32>() < reenable_rate;
+                    }
+                    if fitter.is_locked(edge.from, edge.to) {
+                        inherited = edge;
+                    }
+                    Some(inherited)
+                }
+                GeneMatch::DisjointFirst(&edge) | GeneMatch::ExcessFirst(&edge) => Some(edge),
+                GeneMatch::DisjointSecond(_) | GeneMatch::ExcessSecond(_) => None,
+            })
+            .collect();
+        let mut child = fitter.clone();
+        child.edges = edges;
+        child.fitness = f32::MIN;
+        child.objectives.clear();
+        child.behavior.clear();
+        child.genome_changed();
+        child
+    }
+
+    /// Mutates the network according to the mutation rates of the config.
+    ///
+    /// At first the weights are mutated using `mutate_weights`. Afterwards a new node is added
+    /// with the probability `add_node_rate`, a new connection is added with the probability
+    /// `add_connection_rate` and the activation function of a node is changed with the probability
+    /// `activation_mutation_rate`. At last a random edge is toggled with the probability
+    /// `toggle_connection_rate` and a disabled edge is enabled again with the probability
+    /// `reenable_connection_rate`. Finally the modules are mutated with the probabilities
+    /// `add_module_rate`, `add_module_instance_rate` and `module_add_node_rate`.
+    ///
+    /// If the config enables `self_adaptive_mutation`, the rates of the network are perturbed
+    /// first and used instead of the rates of the config, see `mutation_rates`.
+    pub fn mutate<R: Rng + ?Sized>(
+        &mut self,
+        config: &Config,
+        innovations: &mut InnovationTracker,
+        rng: &mut R,
+    ) {
+        self.mutate_recorded(config, 0, innovations, rng);
+    }
+
+    /// Mutates the network like `mutate` with the mutation rates of the given generation and
+    /// returns the structural mutations which changed it, for the lineage of the solver.
+    pub(crate) fn mutate_recorded<R: Rng + ?Sized>(
+        &mut self,
+        config: &Config,
+        generation: usize,
+        innovations: &mut InnovationTracker,
+        rng: &mut R,
+    ) -> Vec<Mutation> {
+        let config = &match config.self_adaptive_mutation {
+            Some(learning_rate) => {
+                let rates = self
+                    .mutation_rates
+                    .unwrap_or_else(|| MutationRates::of(config))
+                    .perturbed(learning_rate, rng);
+                self.mutation_rates = Some(rates);
+                rates
+                    .scaled(config.mutation_schedule.factor(generation))
+                    .apply_to(config)
+            }
+            None => config.at_generation(generation),
+        };
+        let mut mutations = Vec::new();
+        self.mutate_weights(config, rng);
+        if rng.gen::<f32>() < config.add_node_rate && self.mutate_add_node(innovations, rng) {
+            mutations.push(Mutation::AddNode);
+        }
+        if rng.gen::<f32>() < config.add_connection_rate
+            && self.mutate_add_connection(innovations, rng)
+        {
+            mutations.push(Mutation::AddConnection);
+        }
+        if rng.gen::<f32>() < config.activation_mutation_rate
+            && self.mutate_activation_from(config.activation_set.functions(), rng)
+        {
+            mutations.push(Mutation::Activation);
+        }
+        if rng.gen::<f32>() < config.toggle_connection_rate && self.mutate_toggle_connection(rng) {
+            mutations.push(Mutation::ToggleConnection);
+        }
+        if rng.gen::<f32>() < config.reenable_connection_rate
+            && self.mutate_reenable_connection(rng)
+        {
+            mutations.push(Mutation::ReenableConnection);
+        }
+        // the module mutations are skipped without drawing, so disabling them keeps the mutations
+        // of a seeded run the same
+        if config.add_module_rate > 0.0
+            && rng.gen::<f32>() < config.add_module_rate
+            && self.mutate_add_module(config.module_inputs, innovations, rng)
+        {
+            mutations.push(Mutation::AddModule);
+        }
+        if config.add_module_instance_rate > 0.0
+            && rng.gen::<f32>() < config.add_module_instance_rate
+            && self.mutate_add_module_instance(innovations, rng)
+        {
+            mutations.push(Mutation::AddModuleInstance);
+        }
+        if config.module_add_node_rate > 0.0
+            && rng.gen::<f32>() < config.module_add_node_rate
+            && self.mutate_module_add_node(innovations, rng)
+        {
+            mutations.push(Mutation::ModuleAddNode);
+        }
+        mutations
+    }
+
+    /// Mutates the weights of the edges.
+    ///
+    /// Every edge weight gets replaced by a new random value sampled from `weight_init` with the
+    /// probability `weight_replace_rate`. Otherwise it gets perturbed by a random delta sampled
+    /// from `weight_perturbation` with the probability `weight_mutation_rate`. The weights of
+    /// frozen connections stay the same and the connections of module instances get the mutated
+    /// weights of their module, see `define_module`.
+    ///
+    /// A cached phenotype is updated with the new weights instead of being dropped, see
+    /// `Phenotype::update_weight`.
+    pub fn mutate_weights<R: Rng + ?Sized>(&mut self, config: &Config, rng: &mut R) {
+        let mut pt = self.take_current_phenotype();
+        for idx in 0..self.edges.len() {
+            let r = rng.gen::<f32>();
+            if self.is_locked_edge(idx) {
+                continue;
+            }
+            if r < config.weight_replace_rate {
+                let (from, to) = (self.edges[idx].from, self.edges[idx].to);
+                self.edges[idx].weight = self.sample_weight(from, to, 0, rng);
+            } else if r < config.weight_replace_rate + config.weight_mutation_rate {
+                self.edges[idx].weight += config.weight_perturbation.sample(rng);
+            } else {
+                continue;
+            }
+            self.genome_changed();
+            if let Some(pt) = &mut pt {
+                pt.update_weight(self, idx);
+            }
+        }
+        self.pt = pt;
+        if !self.modules().is_empty() {
+            self.mutate_module_weights(config, rng);
+        }
+    }
+
+    /// Adds a new hidden node by splitting a random enabled edge into two edges.
+    ///
+    /// The split edge gets disabled, the edge leading into the new node gets the weight **1.0**
+    /// and the edge leading out of the new node gets the weight of the split edge, so the
+    /// behaviour of the network stays almost the same. Returns `false` if the network has no
+    /// enabled edge which could be split, frozen connections and the connections of module
+    /// instances are never split.
+    ///
+    /// If the network uses node biases, see `with_node_biases`, the new node additionally gets a
+    /// connection from the bias node with the weight **0.0**, which is its own bias.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::{InnovationTracker, NeuralNetwork};
+    /// let mut nn = NeuralNetwork::with_size(2, 1);
+    /// let mut innovations = InnovationTracker::for_network(&nn);
+    /// assert!(nn.mutate_add_node(&mut innovations, &mut rand::thread_rng()));
+    /// assert_eq!(nn.nodes().len(), 5);
+    /// assert_eq!(nn.edges().len(), 5);
+    /// ```
+    pub fn mutate_add_node<R: Rng + ?Sized>(
+        &mut self,
+        innovations: &mut InnovationTracker,
+        rng: &mut R,
+    ) -> bool {
+        let enabled: Vec<usize> = (0..self.edges.len())
+            .filter(|&idx| self.edges[idx].enabled && !self.is_locked_edge(idx))
+            .collect();
+        if enabled.is_empty() {
+            return false;
+        }
+        let idx = enabled[random::index(rng, enabled.len())];
+        let old = self.edges[idx];
+        self.edges[idx].enabled = false;
+        let mut id = innovations.split(old.innovation);
+        // the same edge could have been split before in this network, e.g. if it was re-enabled
+        if self.nodes.iter().any(|node| node.id == id) {
+            id = innovations.new_node_id();
+        }
+        let mut node = Node::hidden_with_id(id);
+        node.activation = self.activation;
+        self.nodes.push(node);
+        let mut into = Edge::initial_from_to(old.from, id, innovations.connection(old.from, id));
+        into.weight = 1.0;
+        // splitting a recurrent edge keeps the delay on the first half, so no cycle is formed
+        into.recurrent = old.recurrent;
+        let mut out = Edge::initial_from_to(id, old.to, innovations.connection(id, old.to));
+        out.weight = old.weight;
+        self.insert_edge(into);
+        self.insert_edge(out);
+        if let Some(bias_node) = self
+            .bias_node()
+            .filter(|&bias| self.node_biases && bias != old.from)
+        {
+            let mut bias =
+                Edge::initial_from_to(bias_node, id, innovations.connection(bias_node, id));
+            bias.weight = 0.0;
+            self.insert_edge(bias);
+        }
+        self.genome_changed();
+        true
+    }
+
+    /// Adds a new connection between two nodes which have not been connected before. Its weight is
+    /// sampled from `weight_init`.
+    ///
+    /// Connections never lead into an input node or into the node of a module instance and they
+    /// are never allowed to create a cycle, even when taking disabled edges into account, so that
+    /// the network stays feed-forward.
+    /// In recurrent networks a connection which would create a cycle, including a connection from
+    /// a node to itself, is added as a recurrent edge instead. Returns `false` if no valid
+    /// connection has been found.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::{InnovationTracker, NeuralNetwork};
+    /// let mut nn = NeuralNetwork::with_size(2, 1);
+    /// let mut innovations = InnovationTracker::for_network(&nn);
+    /// let mut rng = rand::thread_rng();
+    /// nn.mutate_add_node(&mut innovations, &mut rng);
+    /// // the new hidden node is not yet connected to the other inputs
+    /// assert!(nn.mutate_add_connection(&mut innovations, &mut rng));
+    /// assert_eq!(nn.edges().len(), 6);
+    /// ```
+    pub fn mutate_add_connection<R: Rng + ?Sized>(
+        &mut self,
+        innovations: &mut InnovationTracker,
+        rng: &mut R,
+    ) -> bool {
+        let existing: FxHashSet<(usize, usize)> =
+            self.edges.iter().map(|edge| (edge.from, edge.to)).collect();
+        let mut candidates: Vec<(usize, usize)> = Vec::new();
+        for from in self.nodes.iter() {
+            for to in self.nodes.iter() {
+                if !to.node_type.is_input_or_bias()
+                    && !self.is_module_node(to.id)
+                    && (from.id != to.id || self.recurrent)
+                    && !existing.contains(&(from.id, to.id))
+                {
+                    candidates.push((from.id, to.id));
+                }
+            }
+        }
+        // try the candidates in random order until one does not create a cycle
+        while !candidates.is_empty() {
+            let (from, to) = candidates.swap_remove(random::index(rng, candidates.len()));
+            let cycle = self.has_path(to, from);
+            if !cycle || self.recurrent {
+                let mut edge = Edge::initial_from_to(from, to, innovations.connection(from, to));
+                edge.weight = self.sample_weight(from, to, 1, rng);
+                edge.recurrent = cycle;
+                let pt = self.take_current_phenotype();
+                let idx = self.insert_edge(edge);
+                self.pt = pt.map(|mut pt| {
+                    pt.add_edge(self, idx);
+                    pt
+                });
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Changes the activation function of a random hidden or output node to a random activation
+    /// function. Output nodes are left out if their activation is fixed by the
+    /// `output_activation`. Returns `false` if there is no node which could be changed.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::NeuralNetwork;
+    /// let mut nn = NeuralNetwork::with_size(2, 1);
+    /// assert!(nn.mutate_activation(&mut rand::thread_rng()));
+    /// ```
+    pub fn mutate_activation<R: Rng + ?Sized>(&mut self, rng: &mut R) -> bool {
+        self.mutate_activation_from(&Activation::ALL, rng)
+    }
+
+    /// Changes the activation function of a random hidden or output node to a random one of the
+    /// given activation functions, see `mutate_activation`. Returns `false` if the network has no
+    /// hidden or output node which is not frozen or part of a module instance or if no activation
+    /// function is given.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::{Activation, NeuralNetwork};
+    /// let mut nn = NeuralNetwork::with_size(2, 1);
+    /// assert!(nn.mutate_activation_from(&[Activation::Sine], &mut rand::thread_rng()));
+    /// assert_eq!(nn.nodes()[3].activation, Activation::Sine);
+    /// ```
+    pub fn mutate_activation_from<R: Rng + ?Sized>(
+        &mut self,
+        activations: &[Activation],
+        rng: &mut R,
+    ) -> bool {
+        if activations.is_empty() {
+            return false;
+        }
+        let outputs_fixed = self.output_activation.fixed_activation().is_some();
+        let candidates: Vec<usize> = (0..self.nodes.len())
+            .filter(|&idx| match self.nodes[idx].node_type {
+                _ if self.frozen.node(self.nodes[idx].id) => false,
+                _ if self.is_module_node(self.nodes[idx].id) => false,
+                NodeType::Input | NodeType::Bias => false,
+                NodeType::Output => !outputs_fixed,
+                NodeType::Hidden => true,
+            })
+            .collect();
+        if candidates.is_empty() {
+            return false;
+        }
+        let idx = candidates[random::index(rng, candidates.len())];
+        self.nodes[idx].activation = activations[random::index(rng, activations.len())];
+        self.genome_changed();
+        true
+    }
+
+    /// Toggles the enabled flag of a random edge, so connections can be pruned and restored.
+    /// Returns `false` if the network has no edges which are not frozen or part of a module
+    /// instance.
+    ///
+    /// Toggling never creates a cycle, because disabled edges are taken into account when new
+    /// connections are added.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::NeuralNetwork;
+    /// let mut nn = NeuralNetwork::with_size(2, 1);
+    /// assert!(nn.mutate_toggle_connection(&mut rand::thread_rng()));
+    /// assert_eq!(nn.edges().iter().filter(|edge| !edge.enabled).count(), 1);
+    /// ```
+    pub fn mutate_toggle_connection<R: Rng + ?Sized>(&mut self, rng: &mut R) -> bool {
+        let candidates: Vec<usize> = (0..self.edges.len())
+            .filter(|&idx| !self.is_locked_edge(idx))
+            .collect();
+        if candidates.is_empty() {
+            return false;
+        }
+        let idx = candidates[random::index(rng, candidates.len())];
+        self.edges[idx].enabled = !self.edges[idx].enabled;
+        self.genome_changed();
+        true
+    }
+
+    /// Enables a random disabled edge again. Returns `false` if every edge is enabled, frozen or
+    /// part of a module instance.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::NeuralNetwork;
+    /// let mut nn = NeuralNetwork::with_size(2, 1);
+    /// assert!(!nn.mutate_reenable_connection(&mut rand::thread_rng()));
+    /// nn.edit().set_enabled(1, 3, false).unwrap();
+    /// assert!(nn.mutate_reenable_connection(&mut rand::thread_rng()));
+    /// assert!(nn.edges()[1].enabled);
+    /// ```
+    pub fn mutate_reenable_connection<R: Rng + ?Sized>(&mut self, rng: &mut R) -> bool {
+        let disabled: Vec<usize> = (0..self.edges.len())
+            .filter(|&idx| !self.edges[idx].enabled && !self.is_locked_edge(idx))
+            .collect();
+        if disabled.is_empty() {
+            return false;
+        }
+        let idx = disabled[random::index(rng, disabled.len())];
+        self.edges[idx].enabled = true;
+        self.genome_changed();
+        true
+    }
+
+    /// Returns whether the edge at the index is frozen or part of a module instance.
+    pub(crate) fn is_locked_edge(&self, idx: usize) -> bool {
+        let edge = &self.edges[idx];
+        self.is_locked(edge.from, edge.to)
+    }
+
+    /// Returns whether the connection between two nodes is frozen or part of a module instance,
+    /// so it is never changed on its own.
+    fn is_locked(&self, from: usize, to: usize) -> bool {
+        self.frozen.connection(from, to) || self.is_module_node(to)
+    }
+
+    /// Inserts an edge so that the edge list stays sorted by innovation number.
+    pub(crate) fn insert_edge(&mut self, edge: Edge) -> usize {
+        let idx = self
+            .edges
+            .partition_point(|other| other.innovation < edge.innovation);
+        self.edges.insert(idx, edge);
+        self.genome_changed();
+        idx
+    }
+
+    /// Checks whether there is a path from one node to another one, disabled edges are taken into
+    /// account as well. Recurrent edges are ignored, because they cannot form a cycle.
+    pub(crate) fn has_path(&self, from: usize, to: usize) -> bool {
+        let mut stack = vec![from];
+        let mut visited = FxHashSet::default();
+        visited.insert(from);
+        while let Some(node) = stack.pop() {
+            if node == to {
+                return true;
+            }
+            for edge in self
+                .edges
+                .iter()
+                .filter(|edge| edge.from == node && !edge.recurrent)
+            {
+                if visited.insert(edge.to) {
+                    stack.push(edge.to);
+                }
+            }
+        }
+        false
+    }
+
+    /// Checks whether both networks have the same structure and weights, no matter their id and
+    /// fitness.
+    pub(crate) fn same_genome(&self, other: &Self) -> bool {
+        self.nodes == other.nodes && self.edges == other.edges
+    }
+}
@@ -0,0 +1,92 @@
+use crate::config::Direction;
+use crate::neuralnetwork::NeuralNetwork;
+use serde::{Deserialize, Serialize};
+
+/// Stores the best networks which have ever been seen during the training, so they are not lost
+/// once the solver creates a new generation.
+///
+/// The networks are sorted by fitness, the best one first. Every genome is only stored once, if
+/// it appears in multiple generations (e.g. because of elitism) the entry with the highest fitness
+/// is kept.
+///
+/// # Example:
+/// ```
+/// use neaters::{NeuralNetwork, Solver};
+/// let mut solver = Solver::with_size(1, 1, 10);
+/// for _ in 0..3 {
+///     solver.evaluate_generation(&|nn: &mut NeuralNetwork| nn.compute([0.5])[0]);
+///     solver.new_generation();
+/// }
+/// let best = solver.hall_of_fame().best().unwrap();
+/// assert!(solver.hall_of_fame().networks().iter().all(|nn| nn.fitness() <= best.fitness()));
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HallOfFame {
+    /// The maximum number of networks which are stored.
+    capacity: usize,
+    /// The stored networks, sorted by fitness in descending order.
+    networks: Vec<NeuralNetwork>,
+}
+
+impl HallOfFame {
+    /// Constructor for an empty hall of fame which stores up to `capacity` networks.
+    pub fn with_capacity(capacity: usize) -> Self {
+        HallOfFame {
+            capacity,
+            networks: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Returns the maximum number of networks which are stored.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the stored networks, the best one first.
+    pub fn networks(&self) -> &[NeuralNetwork] {
+        &self.networks
+    }
+
+    /// Returns the stored networks mutably, used when a stored solver is loaded.
+    pub(crate) fn networks_mut(&mut self) -> &mut [NeuralNetwork] {
+        &mut self.networks
+    }
+
+    /// Returns the best network which has ever been seen, `None` if no evaluated network has been
+    /// recorded yet.
+    pub fn best(&self) -> Option<&NeuralNetwork> {
+        self.networks.first()
+    }
+
+    /// Returns the number of stored networks.
+    pub fn len(&self) -> usize {
+        self.networks.len()
+    }
+
+    /// Returns whether no network has been recorded yet.
+    pub fn is_empty(&self) -> bool {
+        self.networks.is_empty()
+    }
+
+    /// Records the evaluated networks of a generation, the direction decides which fitness is
+    /// better. Networks without an assigned fitness are ignored.
+    pub(crate) fn update(&mut self, networks: &[NeuralNetwork], direction: Direction) {
+        for nn in networks.iter().filter(|nn| nn.fitness() > f32::MIN) {
+            let pos = self
+                .networks
+                .partition_point(|other| !direction.is_better(nn.fitness, other.fitness));
+            if pos >= self.capacity {
+                continue;
+            }
+            if let Some(idx) = self.networks.iter().position(|other| other.same_genome(nn)) {
+                if idx < pos {
+                    // the genome is already stored with a higher fitness
+                    continue;
+                }
+                self.networks.remove(idx);
+            }
+            self.networks.insert(pos, nn.clone());
+            self.networks.truncate(self.capacity);
+        }
+    }
+}
@@ -0,0 +1,323 @@
+//! HyperNEAT: evolving networks which paint the weights of large networks.
+//!
+//! Instead of the network itself, the evolved genome is a compositional pattern-producing network
+//! (CPPN). It gets the coordinates of two nodes of a `Substrate` and returns the weight of the
+//! connection between them, so the regularities of the inputs (e.g. the pixels of an image or the
+//! squares of a board) can be exploited by large networks with a regular structure.
+//!
+//! The CPPNs are trained with a regular `Solver` of the size `Substrate::CPPN_INPUTS` and
+//! `Substrate::CPPN_OUTPUTS`, which is created by `Substrate::solver`. The evaluator `HyperNeat`
+//! creates the network of every CPPN and evaluates it with the actual fitness function.
+//!
+//! # Example:
+//! ```
+//! use neaters::hyperneat::{self, HyperNeat, Substrate};
+//! use neaters::{ActivationSet, Config, NeuralNetwork, TrainOptions};
+//! // a 5x5 image is mapped onto 5x5 hidden nodes and a single output
+//! let substrate = Substrate::builder()
+//!     .inputs(hyperneat::grid(5, 5))
+//!     .hidden_layer(hyperneat::grid(5, 5))
+//!     .outputs(vec![[0.0, 0.0]])
+//!     .build();
+//! let image = vec![0.5; 25];
+//! let evaluator = HyperNeat::new(substrate, |nn: &mut NeuralNetwork| nn.compute(&image)[0]);
+//! let config = Config::builder()
+//!     .generation_size(20)
+//!     .activation_set(ActivationSet::Cppn)
+//!     .activation_mutation_rate(0.2)
+//!     .build();
+//! let mut solver = evaluator.substrate().solver(config);
+//! let options = TrainOptions {
+//!     max_generations: Some(3),
+//!     ..TrainOptions::default()
+//! };
+//! let report = solver.train(&evaluator, &options);
+//! let network = evaluator.substrate().network(&mut report.champion.clone());
+//! assert_eq!(network.size(), (25, 1));
+//! ```
+use crate::activation::Activation;
+use crate::config::Config;
+use crate::evaluator::FitnessEvaluator;
+use crate::neuralnetwork::{Edge, NeuralNetwork, Node, NodeType};
+use crate::solver::Solver;
+
+/// The layers of nodes of the networks created by HyperNEAT, every node has a position in the
+/// plane. Created with `Substrate::builder`.
+///
+/// The nodes of every layer are connected to the nodes of the next layer: the inputs to the first
+/// hidden layer, every hidden layer to the next one and the last hidden layer to the outputs. The
+/// weight of a connection is computed by the CPPN from the coordinates `(x1, y1, x2, y2)` of its
+/// source and its destination. The CPPN only sees the coordinates, so layers should use different
+/// coordinates if their connections should get different weight patterns.
+///
+/// The first output of the CPPN is the weight, the second one the bias of the destination node.
+/// Outputs whose magnitude is below the weight threshold do not create a connection, the other
+/// outputs are scaled to a weight between `-max_weight` and `max_weight`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Substrate {
+    /// The coordinates of the input nodes.
+    inputs: Vec<[f32; 2]>,
+    /// The coordinates of the nodes of every hidden layer.
+    hidden: Vec<Vec<[f32; 2]>>,
+    /// The coordinates of the output nodes.
+    outputs: Vec<[f32; 2]>,
+    /// CPPN outputs whose magnitude is at most this threshold do not create a connection.
+    weight_threshold: f32,
+    /// The maximum magnitude of a weight of the network.
+    max_weight: f32,
+    /// The activation function of the input and the hidden nodes.
+    hidden_activation: Activation,
+    /// The activation function of the output nodes.
+    output_activation: Activation,
+}
+
+/// Builder for a `Substrate`, created with `Substrate::builder`. Every parameter which is not set
+/// keeps its default value: no nodes, a weight threshold of **0.2**, a maximum weight of **3.0**
+/// and the activation function `Sigmoid` for all nodes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubstrateBuilder {
+    substrate: Substrate,
+}
+
+impl Substrate {
+    /// The number of inputs of a CPPN: the coordinates of the source and the destination node.
+    pub const CPPN_INPUTS: usize = 4;
+    /// The number of outputs of a CPPN: the weight of the connection and the bias of the
+    /// destination node.
+    pub const CPPN_OUTPUTS: usize = 2;
+
+    /// Returns a builder for a substrate without any nodes.
+    pub fn builder() -> SubstrateBuilder {
+        SubstrateBuilder {
+            substrate: Substrate {
+                inputs: Vec::new(),
+                hidden: Vec::new(),
+                outputs: Vec::new(),
+                weight_threshold: 0.2,
+                max_weight: 3.0,
+                hidden_activation: Activation::Sigmoid,
+                output_activation: Activation::Sigmoid,
+            },
+        }
+    }
+
+    /// Returns the number of inputs and the number of outputs of the created networks.
+    pub fn size(&self) -> (usize, usize) {
+        (self.inputs.len(), self.outputs.len())
+    }
+
+    /// Creates a solver for CPPNs of this substrate with the given config.
+    ///
+    /// The config should use `ActivationSet::Cppn` and a positive `activation_mutation_rate`, so
+    /// the nodes of the CPPNs get the activation functions which produce regular patterns.
+    pub fn solver(&self, config: Config) -> Solver {
+        Solver::with_config(Substrate::CPPN_INPUTS, Substrate::CPPN_OUTPUTS, config)
+    }
+
+    /// Creates the network which is encoded by the CPPN, its nodes are ordered like the
+    /// substrate: at first the bias node, then the inputs, the outputs and at last the hidden
+    /// nodes layer by layer. The bias node supplies the bias of the other nodes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the CPPN does not have `CPPN_INPUTS` inputs and `CPPN_OUTPUTS` outputs.
+    pub fn network(&self, cppn: &mut NeuralNetwork) -> NeuralNetwork {
+        assert_eq!(
+            cppn.size(),
+            (Substrate::CPPN_INPUTS, Substrate::CPPN_OUTPUTS),
+            "the CPPN needs 4 inputs and 2 outputs"
+        );
+        cppn.reset_state();
+        let (inputs, outputs) = self.size();
+        let mut nn =
+            NeuralNetwork::with_size(inputs, outputs).with_activation(self.hidden_activation);
+        nn.edges.clear();
+        for node in nn.nodes.iter_mut() {
+            if node.node_type == NodeType::Output {
+                node.activation = self.output_activation;
+            }
+        }
+        // the ids of the nodes of every layer, the hidden nodes follow after the outputs
+        let mut layers: Vec<Vec<usize>> = vec![(1..=inputs).collect()];
+        let mut next_id = inputs + outputs + 1;
+        for layer in self.hidden.iter() {
+            layers.push((next_id..next_id + layer.len()).collect());
+            for id in next_id..next_id + layer.len() {
+                let mut node = Node::hidden_with_id(id);
+                node.activation = self.hidden_activation;
+                nn.nodes.push(node);
+            }
+            next_id += layer.len();
+        }
+        layers.push((inputs + 1..=inputs + outputs).collect());
+        let coordinates: Vec<&[[f32; 2]]> = core::iter::once(&self.inputs[..])
+            .chain(self.hidden.iter().map(|layer| &layer[..]))
+            .chain(core::iter::once(&self.outputs[..]))
+            .collect();
+        for (layer, ids) in layers.iter().enumerate().skip(1) {
+            for (&[x2, y2], &to) in coordinates[layer].iter().zip(ids.iter()) {
+                let bias = cppn.query_connection([0.0, 0.0], [x2, y2])[1];
+                if let Some(weight) = self.scale(bias) {
+                    self.push_edge(&mut nn, 0, to, weight);
+                }
+                for (&[x1, y1], &from) in
+                    coordinates[layer - 1].iter().zip(layers[layer - 1].iter())
+                {
+                    if let Some(weight) = self.scale(cppn.query_connection([x1, y1], [x2, y2])[0]) {
+                        self.push_edge(&mut nn, from, to, weight);
+                    }
+                }
+            }
+        }
+        nn
+    }
+
+    /// Returns the weight for the output of the CPPN, `None` if its magnitude does not exceed the
+    /// weight threshold.
+    fn scale(&self, output: f32) -> Option<f32> {
+        let magnitude = output.abs().min(1.0);
+        if magnitude <= self.weight_threshold {
+            return None;
+        }
+        let scaled = (magnitude - self.weight_threshold) / (1.0 - self.weight_threshold);
+        Some(scaled.copysign(output) * self.max_weight)
+    }
+
+    /// Adds an edge to the network, its innovation number is its position.
+    fn push_edge(&self, nn: &mut NeuralNetwork, from: usize, to: usize, weight: f32) {
+        let mut edge = Edge::initial_from_to(from, to, nn.edges.len());
+        edge.weight = weight;
+        nn.edges.push(edge);
+    }
+}
+
+impl SubstrateBuilder {
+    /// Sets the coordinates of the input nodes.
+    pub fn inputs(mut self, inputs: Vec<[f32; 2]>) -> Self {
+        self.substrate.inputs = inputs;
+        self
+    }
+
+    /// Adds a hidden layer with the given coordinates after the hidden layers added before.
+    pub fn hidden_layer(mut self, layer: Vec<[f32; 2]>) -> Self {
+        self.substrate.hidden.push(layer);
+        self
+    }
+
+    /// Sets the coordinates of the output nodes.
+    pub fn outputs(mut self, outputs: Vec<[f32; 2]>) -> Self {
+        self.substrate.outputs = outputs;
+        self
+    }
+
+    /// Sets the weight threshold, CPPN outputs whose magnitude does not exceed it do not create a
+    /// connection. A higher threshold creates sparser networks.
+    pub fn weight_threshold(mut self, weight_threshold: f32) -> Self {
+        self.substrate.weight_threshold = weight_threshold;
+        self
+    }
+
+    /// Sets the maximum magnitude of a weight of the network.
+    pub fn max_weight(mut self, max_weight: f32) -> Self {
+        self.substrate.max_weight = max_weight;
+        self
+    }
+
+    /// Sets the activation function of the hidden nodes, which is used for the input nodes as
+    /// well.
+    pub fn hidden_activation(mut self, activation: Activation) -> Self {
+        self.substrate.hidden_activation = activation;
+        self
+    }
+
+    /// Sets the activation function of the output nodes.
+    pub fn output_activation(mut self, activation: Activation) -> Self {
+        self.substrate.output_activation = activation;
+        self
+    }
+
+    /// Returns the substrate.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the weight threshold is not at least **0.0** and below **1.0**.
+    pub fn build(self) -> Substrate {
+        assert!(
+            (0.0..1.0).contains(&self.substrate.weight_threshold),
+            "the weight threshold has to be at least 0 and below 1"
+        );
+        self.substrate
+    }
+}
+
+/// Evaluator of CPPNs, which creates the network of every CPPN with the substrate and evaluates
+/// it with the given evaluator. The fitness of a CPPN is the fitness of its network.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HyperNeat<E> {
+    substrate: Substrate,
+    evaluator: E,
+}
+
+impl<E: FitnessEvaluator> HyperNeat<E> {
+    /// Constructor for an evaluator of the networks of the substrate.
+    pub fn new(substrate: Substrate, evaluator: E) -> Self {
+        HyperNeat {
+            substrate,
+            evaluator,
+        }
+    }
+
+    /// Returns the substrate the networks are created with.
+    pub fn substrate(&self) -> &Substrate {
+        &self.substrate
+    }
+}
+
+impl<E: FitnessEvaluator> FitnessEvaluator for HyperNeat<E> {
+    fn evaluate(&self, cppn: &mut NeuralNetwork) -> f32 {
+        let mut nn = self.substrate.network(cppn);
+        self.evaluator.evaluate(&mut nn)
+    }
+}
+
+/// Returns the coordinates of a grid of nodes spread evenly over the square from **-1.0** to
+/// **1.0**, row by row. A single column or row is placed at **0.0**.
+///
+/// # Example:
+/// ```
+/// use neaters::hyperneat;
+/// assert_eq!(
+///     hyperneat::grid(3, 2),
+///     vec![[-1.0, -1.0], [0.0, -1.0], [1.0, -1.0], [-1.0, 1.0], [0.0, 1.0], [1.0, 1.0]]
+/// );
+/// ```
+pub fn grid(columns: usize, rows: usize) -> Vec<[f32; 2]> {
+    let mut coordinates = Vec::with_capacity(columns * rows);
+    for row in 0..rows {
+        for column in 0..columns {
+            coordinates.push([spread(column, columns), spread(row, rows)]);
+        }
+    }
+    coordinates
+}
+
+/// Returns the coordinates of a row of nodes spread evenly from **-1.0** to **1.0** at the given
+/// height, e.g. for the layers of a network whose layers are stacked vertically.
+///
+/// # Example:
+/// ```
+/// use neaters::hyperneat;
+/// assert_eq!(hyperneat::row(2, 0.5), vec![[-1.0, 0.5], [1.0, 0.5]]);
+/// ```
+pub fn row(nodes: usize, y: f32) -> Vec<[f32; 2]> {
+    (0..nodes).map(|i| [spread(i, nodes), y]).collect()
+}
+
+/// Returns the coordinate of the `i`-th of `n` positions spread evenly from **-1.0** to **1.0**.
+fn spread(i: usize, n: usize) -> f32 {
+    if n <= 1 {
+        0.0
+    } else {
+        2.0 * i as f32 / (n - 1) as f32 - 1.0
+    }
+}
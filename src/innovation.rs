@@ -0,0 +1,153 @@
+use crate::neuralnetwork::NeuralNetwork;
+use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
+
+/// Keeps track of the innovation numbers and node ids which have been given out during training.
+///
+/// Structural mutations which happen independently in different networks should result in the
+/// same genes, so that the networks can still be aligned when computing their distance or when
+/// crossing them over. Because of that every new connection between two nodes gets the same
+/// innovation number and every split of the same edge creates a node with the same id.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct InnovationTracker {
+    /// The next innovation number which has not been given out yet.
+    next_innovation: usize,
+    /// The next node id which has not been given out yet.
+    next_node_id: usize,
+    /// The innovation numbers of all connections which have been created, indexed by the ids of
+    /// their source and destination node.
+    #[serde(with = "pair_map")]
+    connections: FxHashMap<(usize, usize), usize>,
+    /// The ids of the nodes which have been created by splitting an edge, indexed by the
+    /// innovation number of the split edge.
+    splits: FxHashMap<usize, usize>,
+}
+
+impl InnovationTracker {
+    /// Constructor for a tracker which starts giving out innovation numbers and node ids after
+    /// the ones being used in the given network.
+    pub fn for_network(nn: &NeuralNetwork) -> Self {
+        let mut connections = FxHashMap::default();
+        for edge in nn.edges.iter() {
+            connections.insert((edge.from, edge.to), edge.innovation);
+        }
+        InnovationTracker {
+            next_innovation: nn.next_innovation(),
+            next_node_id: nn.next_node_id(),
+            connections,
+            splits: FxHashMap::default(),
+        }
+    }
+
+    /// Returns the innovation number of the connection between the two given nodes. If this
+    /// connection has not been created before, a new innovation number is being assigned to it.
+    pub fn connection(&mut self, from: usize, to: usize) -> usize {
+        let next_innovation = &mut self.next_innovation;
+        *self.connections.entry((from, to)).or_insert_with(|| {
+            *next_innovation += 1;
+            *next_innovation - 1
+        })
+    }
+
+    /// Returns the id of the node which is being created by splitting the edge with the given
+    /// innovation number. If this edge has not been split before, a new node id is being assigned.
+    pub fn split(&mut self, innovation: usize) -> usize {
+        let next_node_id = &mut self.next_node_id;
+        *self.splits.entry(innovation).or_insert_with(|| {
+            *next_node_id += 1;
+            *next_node_id - 1
+        })
+    }
+
+    /// Returns a node id which has not been given out before.
+    pub fn new_node_id(&mut self) -> usize {
+        self.next_node_id += 1;
+        self.next_node_id - 1
+    }
+
+    /// Merges the splits of another tracker into this one and returns the ids of this tracker for
+    /// the nodes of the other one, see `Solver::merge_innovations`. The given map contains the
+    /// ids of the nodes which both trackers have in common, like the input and output nodes.
+    ///
+    /// A split of a connection which this tracker has split as well results in the existing node
+    /// of this tracker, all other splits are added with new node ids. Splits of connections
+    /// between nodes which cannot be matched are skipped.
+    pub(crate) fn merge(
+        &mut self,
+        other: &InnovationTracker,
+        mut nodes: FxHashMap<usize, usize>,
+    ) -> FxHashMap<usize, usize> {
+        let connections: FxHashMap<usize, (usize, usize)> = other
+            .connections
+            .iter()
+            .map(|(&pair, &innovation)| (innovation, pair))
+            .collect();
+        // the endpoints of a split connection have always been created before the split node
+        let mut splits: Vec<(usize, usize)> = other
+            .splits
+            .iter()
+            .map(|(&innovation, &node)| (node, innovation))
+            .collect();
+        splits.sort_unstable();
+        for (node, innovation) in splits {
+            let Some(&(from, to)) = connections.get(&innovation) else {
+                continue;
+            };
+            if let (Some(&from), Some(&to)) = (nodes.get(&from), nodes.get(&to)) {
+                let innovation = self.connection(from, to);
+                let id = self.split(innovation);
+                nodes.insert(node, id);
+            }
+        }
+        nodes
+    }
+}
+
+/// Translates the ids of the hidden nodes of another solver into the ids of a solver whose
+/// innovations have been merged with them, returned by `Solver::merge_innovations`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InnovationMapping {
+    /// The id in this solver of every hidden node of the other solver.
+    pub(crate) nodes: FxHashMap<usize, usize>,
+}
+
+impl InnovationMapping {
+    /// Returns the id in this solver of the hidden node with the given id in the other solver.
+    pub fn node(&self, id: usize) -> Option<usize> {
+        self.nodes.get(&id).copied()
+    }
+
+    /// Returns the number of translated hidden nodes.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns whether no hidden node is translated.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+}
+
+/// Serializes a map with pairs as keys as a list of entries, because formats like json only
+/// support strings as keys.
+mod pair_map {
+    use rustc_hash::FxHashMap;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        map: &FxHashMap<(usize, usize), usize>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut entries: Vec<(&(usize, usize), &usize)> = map.iter().collect();
+        // sorting the entries makes the representation independent of the hash map
+        entries.sort();
+        entries.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<FxHashMap<(usize, usize), usize>, D::Error> {
+        let entries: Vec<((usize, usize), usize)> = Vec::deserialize(deserializer)?;
+        Ok(entries.into_iter().collect())
+    }
+}
@@ -1,10 +1,127 @@
-// TODO: remove after finishing neuralnetwork
+#![cfg_attr(not(feature = "std"), no_std)]
+extern crate alloc;
+
+mod activation;
+#[cfg(feature = "bench_support")]
+pub mod bench_support;
+mod compiled;
 mod config;
+#[cfg(feature = "std")]
+mod cppn;
+#[cfg(feature = "distributed")]
+pub mod distributed;
+#[cfg(feature = "std")]
+mod editor;
+mod ensemble;
+#[cfg(feature = "std")]
+mod environment;
+#[cfg(feature = "std")]
+mod error;
+#[cfg(feature = "std")]
+mod evaluator;
+#[cfg(feature = "std-fs")]
+mod file;
+mod float;
+#[cfg(feature = "std")]
+mod format;
+mod frozen;
+#[cfg(feature = "std")]
+pub mod genome;
+#[cfg(feature = "std")]
+mod hall_of_fame;
+#[cfg(feature = "std")]
+pub mod hyperneat;
+#[cfg(feature = "std")]
+mod innovation;
+#[cfg(feature = "std")]
+mod lineage;
+#[cfg(feature = "std")]
+mod local_search;
+#[cfg(feature = "std")]
+mod logger;
+mod math;
+mod module;
 pub mod neuralnetwork;
+#[cfg(feature = "std")]
+mod novelty;
+#[cfg(feature = "std")]
+mod observer;
+#[cfg(feature = "std")]
+mod offspring;
+#[cfg(feature = "std")]
+mod pareto;
 mod phenotype;
+#[cfg(feature = "std")]
+pub mod problems;
+mod random;
+mod scaling;
+#[cfg(feature = "std")]
+mod simulation;
+#[cfg(feature = "std")]
 mod solver;
+#[cfg(feature = "std")]
 mod species;
-pub use neuralnetwork::NeuralNetwork;
+mod summary;
+#[cfg(feature = "std")]
+mod sweep;
+#[cfg(feature = "std")]
+mod train;
+#[cfg(feature = "std")]
+mod validation;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub use activation::{Activation, OutputActivation};
+pub use compiled::{CompiledNetwork, CompiledState};
+pub use config::{
+    ActivationSet, Config, ConfigBuilder, Direction, FitnessTransform, InitialTopology,
+    MutationRates, Perturbation, RateSchedule, RepresentativeSelection, SelectionStrategy,
+    SpeciationMetric, WeightInit,
+};
+#[cfg(feature = "std")]
+pub use editor::GenomeEditor;
+pub use ensemble::{Aggregation, Ensemble};
+#[cfg(feature = "std")]
+pub use environment::Environment;
+#[cfg(feature = "std")]
+pub use error::Error;
+#[cfg(feature = "async")]
+pub use evaluator::AsyncFitnessEvaluator;
+#[cfg(feature = "std")]
+pub use evaluator::{FitnessEvaluator, MultiObjectiveEvaluator, NoveltyEvaluator};
+pub use float::Float;
+#[cfg(feature = "std")]
+pub use hall_of_fame::HallOfFame;
+#[cfg(feature = "std")]
+pub use innovation::{InnovationMapping, InnovationTracker};
+#[cfg(feature = "std")]
+pub use lineage::{GenomeId, Lineage, LineageRecord, Mutation, Origin};
+#[cfg(feature = "std")]
+pub use local_search::{DifferentiableLoss, Inheritance, LocalSearch, MeanSquaredError};
+#[cfg(feature = "std")]
+pub use logger::{LogFormat, StatsLogger};
+pub use module::{Module, ModuleEdge, ModuleInstance};
+pub use neuralnetwork::{NeuralNetwork, PruneReport};
+#[cfg(feature = "std")]
+pub use observer::TrainingObserver;
+#[cfg(feature = "std")]
+pub use offspring::{OffspringValidator, OffspringVerdict};
+pub use phenotype::{Phenotype, PhenotypeArena};
+pub use scaling::InputScaling;
+#[cfg(feature = "std")]
+pub use simulation::AgentPool;
+#[cfg(feature = "std")]
 pub use solver::Solver;
-#[cfg(test)]
+#[cfg(feature = "std")]
+pub use species::SpeciesInfo;
+pub use summary::{NetworkSummary, WeightSummary};
+#[cfg(feature = "std")]
+pub use sweep::{Sweep, SweepResult};
+#[cfg(feature = "std")]
+pub use train::{
+    CancellationHandle, DiversityMetrics, GenerationStats, StopReason, TrainOptions, TrainReport,
+    TrialAggregation,
+};
+#[cfg(feature = "std")]
+pub use validation::GenomeProblem;
+#[cfg(all(test, feature = "std"))]
 mod tests;
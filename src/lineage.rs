@@ -0,0 +1,191 @@
+use crate::neuralnetwork::NeuralNetwork;
+use rustc_hash::FxHashSet;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::VecDeque;
+
+/// Identifies a network across generations: the generation it belongs to and its id, which is
+/// its position in that generation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct GenomeId {
+    /// The generation of the network, starting at **0**.
+    pub generation: usize,
+    /// The id of the network in its generation, see `NeuralNetwork::id`.
+    pub id: usize,
+}
+
+/// How a network of the lineage has been created.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Origin {
+    /// The network belongs to the initial generation of the solver.
+    Initial,
+    /// The network is an unchanged copy of an elite of the previous generation.
+    Elite,
+    /// The network is a mutated copy of a single parent.
+    Mutation,
+    /// The network is a mutated crossover of two parents.
+    Crossover,
+    /// The network has been inserted from outside of the evolution, e.g. with
+    /// `Solver::insert_network` or `Solver::import_population`.
+    Inserted,
+}
+
+/// A structural mutation which has been applied to a network, see `NeuralNetwork::mutate`. The
+/// weights are mutated in every generation, so they are not recorded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Mutation {
+    /// A node has been added by splitting a connection.
+    AddNode,
+    /// A connection has been added.
+    AddConnection,
+    /// The activation function of a node has been changed.
+    Activation,
+    /// A connection has been enabled or disabled.
+    ToggleConnection,
+    /// A disabled connection has been enabled again.
+    ReenableConnection,
+    /// A new module has been defined and instantiated.
+    AddModule,
+    /// A module has been instantiated once more.
+    AddModuleInstance,
+    /// A node has been added to a module by splitting one of its connections.
+    ModuleAddNode,
+}
+
+/// How one network of the lineage has been created.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LineageRecord {
+    /// The network the record is about.
+    pub genome: GenomeId,
+    /// The parents of the network in the previous generation, one for a copy and two for a
+    /// crossover, where the first one is the parent which has been selected first.
+    pub parents: Vec<GenomeId>,
+    /// How the network has been created.
+    pub origin: Origin,
+    /// The structural mutations which have been applied after the network has been copied or
+    /// crossed over, in the order they have been applied.
+    pub mutations: Vec<Mutation>,
+    /// The fitness of the network, `f32::MIN` until its generation has been replaced.
+    pub fitness: f32,
+}
+
+/// The ancestry of every network which has been created by a solver, recorded if the config
+/// enables `track_lineage`. It is stored together with the solver, so it can be analyzed after
+/// the training, e.g. to describe how a solution evolved.
+///
+/// # Example
+/// ```rust
+/// use neaters::{Config, NeuralNetwork, Origin, Solver, TrainOptions};
+/// let config = Config::builder().generation_size(20).track_lineage(true).build();
+/// let mut solver = Solver::with_config(1, 1, config);
+/// let options = TrainOptions {
+///     max_generations: Some(5),
+///     ..TrainOptions::default()
+/// };
+/// let report = solver.train(&|nn: &mut NeuralNetwork| nn.compute([0.5])[0], &options);
+/// let ancestry = solver.lineage().ancestry_of(report.champion_id);
+/// // the ancestry goes back to a network of the initial generation
+/// assert_eq!(ancestry[0].genome, report.champion_id);
+/// assert_eq!(ancestry.last().unwrap().origin, Origin::Initial);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Lineage {
+    /// The records ordered by their genome.
+    records: Vec<LineageRecord>,
+}
+
+impl Lineage {
+    /// Returns every record ordered by the generation and the id of its network.
+    pub fn records(&self) -> &[LineageRecord] {
+        &self.records
+    }
+
+    /// Returns whether nothing has been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Returns the record of the network, `None` if it has not been recorded.
+    pub fn record(&self, genome: GenomeId) -> Option<&LineageRecord> {
+        self.position(genome).ok().map(|idx| &self.records[idx])
+    }
+
+    /// Returns the records of every network of the generation ordered by their ids.
+    pub fn generation(&self, generation: usize) -> &[LineageRecord] {
+        let start = self
+            .records
+            .partition_point(|r| r.genome.generation < generation);
+        let end = self
+            .records
+            .partition_point(|r| r.genome.generation <= generation);
+        &self.records[start..end]
+    }
+
+    /// Returns the record of the network followed by the records of all of its recorded
+    /// ancestors, ordered from the latest generation to the earliest one. Every ancestor is
+    /// contained once, even if it is reached through multiple parents.
+    pub fn ancestry_of(&self, genome: GenomeId) -> Vec<&LineageRecord> {
+        let mut ancestry: Vec<&LineageRecord> = Vec::new();
+        let mut visited: FxHashSet<GenomeId> = FxHashSet::default();
+        let mut queue: VecDeque<GenomeId> = VecDeque::from([genome]);
+        while let Some(genome) = queue.pop_front() {
+            if !visited.insert(genome) {
+                continue;
+            }
+            if let Some(record) = self.record(genome) {
+                queue.extend(record.parents.iter().copied());
+                ancestry.push(record);
+            }
+        }
+        ancestry.sort_by_key(|record| Reverse(record.genome.generation));
+        ancestry
+    }
+
+    /// Records a network, replacing an earlier record of the same genome.
+    pub(crate) fn insert(&mut self, record: LineageRecord) {
+        match self.position(record.genome) {
+            Ok(idx) => self.records[idx] = record,
+            Err(idx) => self.records.insert(idx, record),
+        }
+    }
+
+    /// Records the networks of a generation without parents.
+    pub(crate) fn insert_generation(
+        &mut self,
+        generation: usize,
+        networks: &[NeuralNetwork],
+        origin: Origin,
+    ) {
+        for nn in networks {
+            self.insert(LineageRecord {
+                genome: GenomeId {
+                    generation,
+                    id: nn.id,
+                },
+                parents: Vec::new(),
+                origin,
+                mutations: Vec::new(),
+                fitness: f32::MIN,
+            });
+        }
+    }
+
+    /// Stores the fitness of the evaluated networks of the generation in their records.
+    pub(crate) fn set_fitness(&mut self, generation: usize, networks: &[NeuralNetwork]) {
+        for nn in networks {
+            let genome = GenomeId {
+                generation,
+                id: nn.id,
+            };
+            if let Ok(idx) = self.position(genome) {
+                self.records[idx].fitness = nn.fitness;
+            }
+        }
+    }
+
+    /// Finds the record of the genome, or the position where it would be inserted.
+    fn position(&self, genome: GenomeId) -> Result<usize, usize> {
+        self.records
+            .binary_search_by_key(&genome, |record| record.genome)
+    }
+}
@@ -0,0 +1,263 @@
+use crate::error::Error;
+use crate::neuralnetwork::NeuralNetwork;
+use crate::observer::TrainingObserver;
+use crate::solver::Solver;
+use crate::train::GenerationStats;
+#[cfg(feature = "std-fs")]
+use std::fs::File;
+#[cfg(feature = "std-fs")]
+use std::io::BufWriter;
+use std::io::Write;
+use std::ops::ControlFlow;
+#[cfg(feature = "std-fs")]
+use std::path::{Path, PathBuf};
+
+/// The file format written by a `StatsLogger`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Comma separated values with a header row, the species sizes are separated by semicolons.
+    Csv,
+    /// One json object per line, which can be read with `pandas.read_json(path, lines=True)`.
+    JsonLines,
+}
+
+/// Observer which writes the statistics of every generation of a training as one row of a log,
+/// so the progress can be plotted with tools like pandas or a spreadsheet.
+///
+/// The logger is passed to `Solver::train_with_observer` or one of the other training functions.
+/// Writing errors cannot be returned from the callbacks, so the first error stops the training
+/// and is returned by `finish`. With `with_checkpoints` the champion is saved whenever it has
+/// improved and every row contains the path of the latest saved champion.
+///
+/// # Example
+/// ```rust
+/// use neaters::{LogFormat, NeuralNetwork, Solver, StatsLogger, TrainOptions};
+/// let mut solver = Solver::with_size(2, 1, 20);
+/// let mut logger = StatsLogger::new(Vec::new(), LogFormat::Csv);
+/// let options = TrainOptions {
+///     max_generations: Some(3),
+///     ..TrainOptions::default()
+/// };
+/// solver.train_with_observer(
+///     &|nn: &mut NeuralNetwork| nn.compute([0.5, 0.5])[0],
+///     &options,
+///     &mut logger,
+/// );
+/// let log = String::from_utf8(logger.finish()?).unwrap();
+/// assert!(log.starts_with("generation,best_fitness,"));
+/// assert_eq!(log.lines().count(), 4);
+/// # Ok::<(), neaters::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct StatsLogger<W: Write> {
+    writer: W,
+    format: LogFormat,
+    /// Whether the header of a csv log has been written already.
+    header_written: bool,
+    /// The directory the champions are saved in, `None` if checkpointing is disabled.
+    #[cfg(feature = "std-fs")]
+    checkpoints: Option<PathBuf>,
+    /// The best network of the training which has not been saved yet.
+    #[cfg(feature = "std-fs")]
+    unsaved_champion: Option<NeuralNetwork>,
+    /// The file the latest champion has been saved to.
+    #[cfg(feature = "std-fs")]
+    champion_path: Option<PathBuf>,
+    /// The first error which occurred while writing, it stops the training.
+    error: Option<Error>,
+}
+
+#[cfg(feature = "std-fs")]
+impl StatsLogger<BufWriter<File>> {
+    /// Creates the log file at the given path, an existing file is replaced.
+    pub fn create<P: AsRef<Path>>(path: P, format: LogFormat) -> Result<Self, Error> {
+        Ok(StatsLogger::new(
+            BufWriter::new(File::create(path)?),
+            format,
+        ))
+    }
+}
+
+impl<W: Write> StatsLogger<W> {
+    /// Constructor for a logger writing rows of the given format into the writer.
+    pub fn new(writer: W, format: LogFormat) -> Self {
+        StatsLogger {
+            writer,
+            format,
+            header_written: false,
+            #[cfg(feature = "std-fs")]
+            checkpoints: None,
+            #[cfg(feature = "std-fs")]
+            unsaved_champion: None,
+            #[cfg(feature = "std-fs")]
+            champion_path: None,
+            error: None,
+        }
+    }
+
+    /// Enables checkpointing: at the end of every generation in which the champion improved, it
+    /// is saved as `champion-<generation>.bin` in the given directory, which has to exist. Every
+    /// row gets an additional `champion` column with the path of the latest saved champion.
+    #[cfg(feature = "std-fs")]
+    pub fn with_checkpoints<P: AsRef<Path>>(mut self, directory: P) -> Self {
+        self.checkpoints = Some(directory.as_ref().to_path_buf());
+        self
+    }
+
+    /// Flushes the log and returns the writer, or the first error which occurred while writing
+    /// the log or saving a champion.
+    pub fn finish(mut self) -> Result<W, Error> {
+        if let Some(err) = self.error {
+            return Err(err);
+        }
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    /// Saves the champion if it improved and writes the row of the generation.
+    fn log(&mut self, stats: &GenerationStats) -> Result<(), Error> {
+        #[cfg(feature = "std-fs")]
+        if let (Some(directory), Some(champion)) = (&self.checkpoints, &self.unsaved_champion) {
+            let path = directory.join(format!("champion-{}.bin", stats.generation));
+            champion.save_as(&path)?;
+            self.champion_path = Some(path);
+            self.unsaved_champion = None;
+        }
+        #[cfg(feature = "std-fs")]
+        let champion = self.checkpoints.as_ref().map(|_| {
+            self.champion_path
+                .as_ref()
+                .map_or(String::new(), |path| path.to_string_lossy().into_owned())
+        });
+        #[cfg(not(feature = "std-fs"))]
+        let champion: Option<String> = None;
+        let row = match self.format {
+            LogFormat::Csv => {
+                if !self.header_written {
+                    writeln!(self.writer, "{}", csv_header(champion.is_some()))?;
+                    self.header_written = true;
+                }
+                csv_row(stats, champion.as_deref())
+            }
+            LogFormat::JsonLines => json_row(stats, champion.as_deref()),
+        };
+        writeln!(self.writer, "{}", row)?;
+        Ok(())
+    }
+}
+
+impl<W: Write> TrainingObserver for StatsLogger<W> {
+    fn on_generation_end(&mut self, _: &Solver, stats: &GenerationStats) -> ControlFlow<()> {
+        if self.error.is_none() {
+            if let Err(err) = self.log(stats) {
+                self.error = Some(err);
+            }
+        }
+        if self.error.is_some() {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    }
+
+    fn on_new_best(&mut self, nn: &NeuralNetwork) {
+        #[cfg(feature = "std-fs")]
+        if self.checkpoints.is_some() {
+            self.unsaved_champion = Some(nn.clone());
+        }
+        #[cfg(not(feature = "std-fs"))]
+        let _ = nn;
+    }
+}
+
+/// The names of the columns of a csv log.
+const COLUMNS: [&str; 12] = [
+    "generation",
+    "best_fitness",
+    "average_fitness",
+    "median_fitness",
+    "fitness_std_dev",
+    "species_count",
+    "species_sizes",
+    "average_nodes",
+    "average_edges",
+    "mean_distance",
+    "genotypic_entropy",
+    "unique_topologies",
+];
+
+/// Returns the header row of a csv log, with the `champion` column if checkpointing is enabled.
+fn csv_header(with_champion: bool) -> String {
+    let mut header = COLUMNS.join(",");
+    if with_champion {
+        header.push_str(",champion");
+    }
+    header
+}
+
+/// Returns the fields of the statistics in the order of `COLUMNS`, the species sizes are
+/// separated by the given separator.
+fn fields(stats: &GenerationStats, separator: &str) -> [String; 12] {
+    let species_sizes: Vec<String> = stats.species_sizes.iter().map(usize::to_string).collect();
+    [
+        stats.generation.to_string(),
+        stats.best_fitness.to_string(),
+        stats.average_fitness.to_string(),
+        stats.median_fitness.to_string(),
+        stats.fitness_std_dev.to_string(),
+        stats.species_count.to_string(),
+        species_sizes.join(separator),
+        stats.average_nodes.to_string(),
+        stats.average_edges.to_string(),
+        stats.diversity.mean_distance.to_string(),
+        stats.diversity.genotypic_entropy.to_string(),
+        stats.diversity.unique_topologies.to_string(),
+    ]
+}
+
+/// Returns the row of a csv log, the path of the champion is quoted if necessary.
+fn csv_row(stats: &GenerationStats, champion: Option<&str>) -> String {
+    let mut row = fields(stats, ";").join(",");
+    if let Some(champion) = champion {
+        row.push(',');
+        if champion.contains([',', '"', '\n', '\r']) {
+            row.push_str(&format!("\"{}\"", champion.replace('"', "\"\"")));
+        } else {
+            row.push_str(champion);
+        }
+    }
+    row
+}
+
+/// Returns the row of a json lines log. Json has no representation of infinite values, so they
+/// are written as `null`.
+fn json_row(stats: &GenerationStats, champion: Option<&str>) -> String {
+    let fields = fields(stats, ",");
+    let values = COLUMNS.iter().zip(fields.iter()).map(|(column, field)| {
+        let value = match *column {
+            "species_sizes" => format!("[{}]", field),
+            _ if field.parse::<f32>().is_ok_and(|value| !value.is_finite()) => "null".to_string(),
+            _ => field.clone(),
+        };
+        format!("\"{}\":{}", column, value)
+    });
+    let mut values: Vec<String> = values.collect();
+    if let Some(champion) = champion {
+        values.push(format!("\"champion\":\"{}\"", json_escape(champion)));
+    }
+    format!("{{{}}}", values.join(","))
+}
+
+/// Escapes the quotes, backslashes and control characters of a json string.
+fn json_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
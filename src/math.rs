@@ -0,0 +1,142 @@
+// Floating point functions which are not part of `core`, they are taken from the standard library
+// if it is available and from `libm` otherwise.
+
+#[cfg(feature = "std")]
+pub(crate) fn abs(x: f32) -> f32 {
+    x.abs()
+}
+
+#[cfg(not(feature = "std"))]
+pub(crate) fn abs(x: f32) -> f32 {
+    libm::fabsf(x)
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn ceil(x: f32) -> f32 {
+    x.ceil()
+}
+
+#[cfg(not(feature = "std"))]
+pub(crate) fn ceil(x: f32) -> f32 {
+    libm::ceilf(x)
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn exp(x: f32) -> f32 {
+    x.exp()
+}
+
+#[cfg(not(feature = "std"))]
+pub(crate) fn exp(x: f32) -> f32 {
+    libm::expf(x)
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn powi(x: f32, n: usize) -> f32 {
+    x.powf(n as f32)
+}
+
+#[cfg(not(feature = "std"))]
+pub(crate) fn powi(x: f32, n: usize) -> f32 {
+    libm::powf(x, n as f32)
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn sin(x: f32) -> f32 {
+    x.sin()
+}
+
+#[cfg(not(feature = "std"))]
+pub(crate) fn sin(x: f32) -> f32 {
+    libm::sinf(x)
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn cos(x: f32) -> f32 {
+    x.cos()
+}
+
+#[cfg(not(feature = "std"))]
+pub(crate) fn cos(x: f32) -> f32 {
+    libm::cosf(x)
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn sqrt(x: f32) -> f32 {
+    x.sqrt()
+}
+
+#[cfg(not(feature = "std"))]
+pub(crate) fn sqrt(x: f32) -> f32 {
+    libm::sqrtf(x)
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn tanh(x: f32) -> f32 {
+    x.tanh()
+}
+
+#[cfg(not(feature = "std"))]
+pub(crate) fn tanh(x: f32) -> f32 {
+    libm::tanhf(x)
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn abs_f64(x: f64) -> f64 {
+    x.abs()
+}
+
+#[cfg(not(feature = "std"))]
+pub(crate) fn abs_f64(x: f64) -> f64 {
+    libm::fabs(x)
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn exp_f64(x: f64) -> f64 {
+    x.exp()
+}
+
+#[cfg(not(feature = "std"))]
+pub(crate) fn exp_f64(x: f64) -> f64 {
+    libm::exp(x)
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn sin_f64(x: f64) -> f64 {
+    x.sin()
+}
+
+#[cfg(not(feature = "std"))]
+pub(crate) fn sin_f64(x: f64) -> f64 {
+    libm::sin(x)
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn cos_f64(x: f64) -> f64 {
+    x.cos()
+}
+
+#[cfg(not(feature = "std"))]
+pub(crate) fn cos_f64(x: f64) -> f64 {
+    libm::cos(x)
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn tanh_f64(x: f64) -> f64 {
+    x.tanh()
+}
+
+#[cfg(not(feature = "std"))]
+pub(crate) fn tanh_f64(x: f64) -> f64 {
+    libm::tanh(x)
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn sqrt_f64(x: f64) -> f64 {
+    x.sqrt()
+}
+
+#[cfg(not(feature = "std"))]
+pub(crate) fn sqrt_f64(x: f64) -> f64 {
+    libm::sqrt(x)
+}
@@ -0,0 +1,607 @@
+use crate::activation::Activation;
+#[cfg(feature = "std")]
+use crate::config::Config;
+#[cfg(feature = "std")]
+use crate::error::Error;
+use crate::float::Float;
+#[cfg(feature = "std")]
+use crate::innovation::InnovationTracker;
+use crate::neuralnetwork::NeuralNetwork;
+#[cfg(feature = "std")]
+use crate::neuralnetwork::{Edge, Node, NodeType};
+#[cfg(feature = "std")]
+use crate::random;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use rand::seq::index;
+#[cfg(feature = "std")]
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// A subnetwork which is defined once and instantiated multiple times within a network, e.g. the
+/// controller of one leg of a multi-legged robot, see `NeuralNetwork::define_module`.
+///
+/// The nodes of a module are identified by their local index: the inputs come first, followed
+/// by the outputs and the hidden nodes. Every instance binds the inputs of the module to nodes of
+/// the network and gets its own copy of the other nodes, while the connections of the module are
+/// shared. Mutating a module changes every instance in the same way, so the repeated structure
+/// is evolved as a whole.
+///
+/// # Example
+/// ```rust
+/// use neaters::{Activation, Module};
+/// let mut module = Module::new(2, 1);
+/// assert_eq!(module.edges().len(), 2);
+/// # #[cfg(feature = "std")]
+/// # {
+/// let hidden = module.add_node(Activation::Tanh);
+/// module.add_connection(0, hidden, 0.5).unwrap();
+/// module.add_connection(hidden, 2, -1.0).unwrap();
+/// assert_eq!(module.node_count(), 4);
+/// // connections never lead into an input of the module
+/// assert!(module.add_connection(hidden, 1, 1.0).is_err());
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Module {
+    inputs: usize,
+    outputs: usize,
+    /// The activation functions of the outputs and the hidden nodes.
+    activations: Vec<Activation>,
+    edges: Vec<ModuleEdge>,
+}
+
+/// A connection between two nodes of a module, identified by their local index.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModuleEdge {
+    /// The local index of the source node.
+    pub from: usize,
+    /// The local index of the destination node.
+    pub to: usize,
+    /// The weight which is shared by every instance of the connection.
+    pub weight: f32,
+    /// Whether the connection is enabled in every instance.
+    pub enabled: bool,
+}
+
+/// An instance of a module within a network, see `NeuralNetwork::instantiate_module`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModuleInstance {
+    module: usize,
+    /// The ids of the nodes of the network, indexed by the local index of the module nodes.
+    nodes: Vec<usize>,
+}
+
+/// The modules of a network together with their instances.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct ModuleGenes {
+    modules: Vec<Module>,
+    instances: Vec<ModuleInstance>,
+}
+
+impl Module {
+    /// Constructor for a module where every input is connected to every output with the weight
+    /// **1.0**.
+    pub fn new(inputs: usize, outputs: usize) -> Self {
+        let edges = (0..inputs)
+            .flat_map(|from| {
+                (inputs..inputs + outputs).map(move |to| ModuleEdge {
+                    from,
+                    to,
+                    weight: 1.0,
+                    enabled: true,
+                })
+            })
+            .collect();
+        Module {
+            inputs,
+            outputs,
+            activations: alloc::vec![Activation::default(); outputs],
+            edges,
+        }
+    }
+
+    /// Returns the number of inputs of the module.
+    pub fn inputs(&self) -> usize {
+        self.inputs
+    }
+
+    /// Returns the number of outputs of the module.
+    pub fn outputs(&self) -> usize {
+        self.outputs
+    }
+
+    /// Returns the number of nodes of the module, including its inputs and outputs.
+    pub fn node_count(&self) -> usize {
+        self.inputs + self.activations.len()
+    }
+
+    /// Returns the connections of the module.
+    pub fn edges(&self) -> &[ModuleEdge] {
+        &self.edges
+    }
+
+    /// Returns the activation function of the node with the local index, `None` for the inputs
+    /// and for missing nodes.
+    pub fn activation(&self, local: usize) -> Option<Activation> {
+        local
+            .checked_sub(self.inputs)
+            .and_then(|idx| self.activations.get(idx).copied())
+    }
+}
+
+#[cfg(feature = "std")]
+impl Module {
+    /// Adds a hidden node to the module and returns its local index.
+    pub fn add_node(&mut self, activation: Activation) -> usize {
+        self.activations.push(activation);
+        self.node_count() - 1
+    }
+
+    /// Adds a connection between two nodes of the module. Returns an `Error::InvalidGenome` if a
+    /// node does not exist, if the connection leads into an input, if the nodes are already
+    /// connected or if the connection would create a cycle.
+    pub fn add_connection(&mut self, from: usize, to: usize, weight: f32) -> Result<(), Error> {
+        if from >= self.node_count() || to >= self.node_count() {
+            return Err(Error::InvalidGenome(format!(
+                "the module has no node {}",
+                from.max(to)
+            )));
+        }
+        if to < self.inputs {
+            return Err(Error::InvalidGenome(format!(
+                "the connection {} -> {} leads into an input of the module",
+                from, to
+            )));
+        }
+        if self.find(from, to).is_some() {
+            return Err(Error::InvalidGenome(format!(
+                "the connection {} -> {} already exists",
+                from, to
+            )));
+        }
+        if from == to || self.has_path(to, from) {
+            return Err(Error::InvalidGenome(format!(
+                "the connection {} -> {} would form a cycle",
+                from, to
+            )));
+        }
+        self.edges.push(ModuleEdge {
+            from,
+            to,
+            weight,
+            enabled: true,
+        });
+        Ok(())
+    }
+
+    /// Sets the weight of the connection between two nodes of the module.
+    pub fn set_weight(&mut self, from: usize, to: usize, weight: f32) -> Result<(), Error> {
+        let idx = self.find(from, to).ok_or_else(|| {
+            Error::InvalidGenome(format!("the connection {} -> {} does not exist", from, to))
+        })?;
+        self.edges[idx].weight = weight;
+        Ok(())
+    }
+
+    /// Returns the index of the connection between two nodes, if it exists.
+    fn find(&self, from: usize, to: usize) -> Option<usize> {
+        self.edges
+            .iter()
+            .position(|edge| edge.from == from && edge.to == to)
+    }
+
+    /// Checks whether there is a path from one node to another one, disabled edges are taken into
+    /// account as well.
+    fn has_path(&self, from: usize, to: usize) -> bool {
+        let mut stack = vec![from];
+        let mut visited = vec![false; self.node_count()];
+        visited[from] = true;
+        while let Some(node) = stack.pop() {
+            if node == to {
+                return true;
+            }
+            for edge in self.edges.iter().filter(|edge| edge.from == node) {
+                if !visited[edge.to] {
+                    visited[edge.to] = true;
+                    stack.push(edge.to);
+                }
+            }
+        }
+        false
+    }
+}
+
+impl ModuleInstance {
+    /// Returns the index of the module, see `NeuralNetwork::modules`.
+    pub fn module(&self) -> usize {
+        self.module
+    }
+
+    /// Returns the ids of the nodes of the network which belong to the instance, indexed by the
+    /// local index of the module nodes. The inputs are the nodes the instance is bound to.
+    pub fn nodes(&self) -> &[usize] {
+        &self.nodes
+    }
+}
+
+impl ModuleGenes {
+    /// Returns whether the node has been created for an instance of a module.
+    pub(crate) fn owns(&self, id: usize) -> bool {
+        self.instances.iter().any(|instance| {
+            let inputs = self.modules[instance.module].inputs;
+            instance.nodes[inputs..].contains(&id)
+        })
+    }
+}
+
+impl<F: Float> NeuralNetwork<F> {
+    /// Returns the modules of the network, see `define_module`.
+    pub fn modules(&self) -> &[Module] {
+        &self.modules.modules
+    }
+
+    /// Returns the instances of the modules, see `instantiate_module`.
+    pub fn module_instances(&self) -> &[ModuleInstance] {
+        &self.modules.instances
+    }
+
+    /// Returns whether the node with the id has been created for an instance of a module. Its
+    /// activation function and the connections leading into it are defined by the module.
+    pub fn is_module_node(&self, id: usize) -> bool {
+        self.modules.owns(id)
+    }
+}
+
+#[cfg(feature = "std")]
+impl NeuralNetwork {
+    /// Adds a module to the network without instantiating it and returns its index.
+    pub fn define_module(&mut self, module: Module) -> usize {
+        self.modules.modules.push(module);
+        self.modules.modules.len() - 1
+    }
+
+    /// Instantiates a module and returns the index of the instance. The inputs of the module are
+    /// bound to the given nodes and every output of the module gets a connection with the weight
+    /// **1.0** to the given node of the network.
+    ///
+    /// The connections of the instance are never mutated on their own and no other connections
+    /// lead into its nodes. The evolution changes them together with every other instance by
+    /// mutating the module: its weights are mutated by `mutate_weights` and its connections are
+    /// split by `mutate_module_add_node`.
+    ///
+    /// Returns an `Error::InvalidGenome` if the module or a node does not exist, if the number of
+    /// nodes does not match the module, if an output leads into an input node or into another
+    /// module or if the instance would create a cycle.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::{Module, NeuralNetwork};
+    /// // two legs with two sensors each, the first two outputs are the motors of the first leg
+    /// let mut nn = NeuralNetwork::with_size(4, 4);
+    /// let leg = nn.define_module(Module::new(2, 2));
+    /// nn.instantiate_module(leg, &[1, 2], &[5, 6])?;
+    /// nn.instantiate_module(leg, &[3, 4], &[7, 8])?;
+    /// assert_eq!(nn.hidden_node_count(), 4);
+    /// assert_eq!(nn.module_instances().len(), 2);
+    /// # Ok::<(), neaters::Error>(())
+    /// ```
+    pub fn instantiate_module(
+        &mut self,
+        module: usize,
+        inputs: &[usize],
+        outputs: &[usize],
+    ) -> Result<usize, Error> {
+        let definition =
+            self.modules.modules.get(module).ok_or_else(|| {
+                Error::InvalidGenome(format!("the module {} does not exist", module))
+            })?;
+        if inputs.len() != definition.inputs || outputs.len() != definition.outputs {
+            return Err(Error::InvalidGenome(format!(
+                "the module {} has {} inputs and {} outputs, but {} and {} nodes are given",
+                module,
+                definition.inputs,
+                definition.outputs,
+                inputs.len(),
+                outputs.len()
+            )));
+        }
+        for &id in inputs.iter().chain(outputs) {
+            if !self.nodes.iter().any(|node| node.id == id) {
+                return Err(Error::InvalidGenome(format!(
+                    "the node {} does not exist",
+                    id
+                )));
+            }
+        }
+        for &id in outputs {
+            let node = self.nodes.iter().find(|node| node.id == id);
+            if node.is_some_and(|node| node.node_type.is_input_or_bias()) || self.is_module_node(id)
+            {
+                return Err(Error::InvalidGenome(format!(
+                    "an output of the module cannot lead into the node {}",
+                    id
+                )));
+            }
+            if let Some(&input) = inputs.iter().find(|&&input| self.has_path(id, input)) {
+                return Err(Error::InvalidGenome(format!(
+                    "the instance would create a cycle from the node {} to the node {}",
+                    input, id
+                )));
+            }
+        }
+        let mut innovations = InnovationTracker::for_network(self);
+        Ok(self.instantiate_with(module, inputs, outputs, &mut innovations))
+    }
+
+    /// Mutates the weights of the modules like `mutate_weights` and passes them on to every
+    /// instance.
+    pub(crate) fn mutate_module_weights<R: Rng + ?Sized>(&mut self, config: &Config, rng: &mut R) {
+        for module in self.modules.modules.iter_mut() {
+            for idx in 0..module.edges.len() {
+                let r = rng.gen::<f32>();
+                if r < config.weight_replace_rate {
+                    let edge = module.edges[idx];
+                    let fan_in = module.edges.iter().filter(|e| e.to == edge.to).count();
+                    let fan_out = module.edges.iter().filter(|e| e.from == edge.from).count();
+                    module.edges[idx].weight = self.weight_init.sample(fan_in, fan_out, rng);
+                } else if r < config.weight_replace_rate + config.weight_mutation_rate {
+                    module.edges[idx].weight += config.weight_perturbation.sample(rng);
+                }
+            }
+        }
+        let mut pt = self.take_current_phenotype();
+        for i in 0..self.modules.instances.len() {
+            let module = self.modules.instances[i].module;
+            for j in 0..self.modules.modules[module].edges.len() {
+                let edge = self.modules.modules[module].edges[j];
+                let nodes = &self.modules.instances[i].nodes;
+                let (from, to) = (nodes[edge.from], nodes[edge.to]);
+                let Some(idx) = self
+                    .edges
+                    .iter()
+                    .position(|other| other.from == from && other.to == to)
+                else {
+                    continue;
+                };
+                if self.edges[idx].weight != edge.weight {
+                    self.edges[idx].weight = edge.weight;
+                    self.genome_changed();
+                    if let Some(pt) = &mut pt {
+                        pt.update_weight(self, idx);
+                    }
+                }
+            }
+        }
+        self.pt = pt;
+    }
+
+    /// Defines a new module where `inputs` inputs are connected to a single output with weights
+    /// sampled from `weight_init`, and instantiates it on random input nodes of the network.
+    /// Returns `false` if the network has fewer input nodes or no inputs are requested.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::{InnovationTracker, NeuralNetwork};
+    /// let mut nn = NeuralNetwork::with_size(4, 2);
+    /// let mut innovations = InnovationTracker::for_network(&nn);
+    /// assert!(nn.mutate_add_module(2, &mut innovations, &mut rand::thread_rng()));
+    /// assert_eq!(nn.modules().len(), 1);
+    /// assert!(!nn.mutate_add_module(5, &mut innovations, &mut rand::thread_rng()));
+    /// ```
+    pub fn mutate_add_module<R: Rng + ?Sized>(
+        &mut self,
+        inputs: usize,
+        innovations: &mut InnovationTracker,
+        rng: &mut R,
+    ) -> bool {
+        if inputs == 0 || inputs > self.input_node_count() || self.size.1 == 0 {
+            return false;
+        }
+        let mut module = Module::new(inputs, 1);
+        for edge in module.edges.iter_mut() {
+            edge.weight = self.weight_init.sample(inputs, 1, rng);
+        }
+        let module = self.define_module(module);
+        self.instantiate_randomly(module, innovations, rng)
+    }
+
+    /// Instantiates a random module once more, bound to random input nodes of the network and
+    /// leading into random output nodes, so the structure of the module is repeated. Returns
+    /// `false` if the network has no modules.
+    pub fn mutate_add_module_instance<R: Rng + ?Sized>(
+        &mut self,
+        innovations: &mut InnovationTracker,
+        rng: &mut R,
+    ) -> bool {
+        if self.modules.modules.is_empty() {
+            return false;
+        }
+        let module = random::index(rng, self.modules.modules.len());
+        self.instantiate_randomly(module, innovations, rng)
+    }
+
+    /// Splits a random enabled connection of a module which has been instantiated, in the same way
+    /// as `mutate_add_node`, and adds the new node to every instance of the module. Returns
+    /// `false` if no connection of an instantiated module could be split.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::{InnovationTracker, Module, NeuralNetwork};
+    /// let mut nn = NeuralNetwork::with_size(2, 2);
+    /// let module = nn.define_module(Module::new(1, 1));
+    /// nn.instantiate_module(module, &[1], &[3])?;
+    /// nn.instantiate_module(module, &[2], &[4])?;
+    /// let mut innovations = InnovationTracker::for_network(&nn);
+    /// assert!(nn.mutate_module_add_node(&mut innovations, &mut rand::thread_rng()));
+    /// assert_eq!(nn.modules()[module].node_count(), 3);
+    /// assert_eq!(nn.hidden_node_count(), 4);
+    /// # Ok::<(), neaters::Error>(())
+    /// ```
+    pub fn mutate_module_add_node<R: Rng + ?Sized>(
+        &mut self,
+        innovations: &mut InnovationTracker,
+        rng: &mut R,
+    ) -> bool {
+        let instances = &self.modules.instances;
+        let candidates: Vec<(usize, usize)> = (0..self.modules.modules.len())
+            .filter(|&module| instances.iter().any(|instance| instance.module == module))
+            .flat_map(|module| {
+                let edges = &self.modules.modules[module].edges;
+                (0..edges.len())
+                    .filter(|&idx| edges[idx].enabled)
+                    .map(move |idx| (module, idx))
+            })
+            .collect();
+        if candidates.is_empty() {
+            return false;
+        }
+        let (module, idx) = candidates[random::index(rng, candidates.len())];
+        let definition = &mut self.modules.modules[module];
+        let old = definition.edges[idx];
+        definition.edges[idx].enabled = false;
+        let local = definition.add_node(self.activation);
+        definition.edges.push(ModuleEdge {
+            from: old.from,
+            to: local,
+            weight: 1.0,
+            enabled: true,
+        });
+        definition.edges.push(ModuleEdge {
+            from: local,
+            to: old.to,
+            ..old
+        });
+        for i in 0..self.modules.instances.len() {
+            if self.modules.instances[i].module != module {
+                continue;
+            }
+            let nodes = &self.modules.instances[i].nodes;
+            let (from, to) = (nodes[old.from], nodes[old.to]);
+            let Some(split) = self
+                .edges
+                .iter()
+                .position(|edge| edge.from == from && edge.to == to)
+            else {
+                continue;
+            };
+            self.edges[split].enabled = false;
+            let mut id = innovations.split(self.edges[split].innovation);
+            if self.nodes.iter().any(|node| node.id == id) {
+                id = innovations.new_node_id();
+            }
+            let mut node = Node::hidden_with_id(id);
+            node.activation = self.activation;
+            self.nodes.push(node);
+            self.modules.instances[i].nodes.push(id);
+            let mut into = Edge::initial_from_to(from, id, innovations.connection(from, id));
+            into.weight = 1.0;
+            let mut out = Edge::initial_from_to(id, to, innovations.connection(id, to));
+            out.weight = old.weight;
+            self.insert_edge(into);
+            self.insert_edge(out);
+        }
+        self.genome_changed();
+        true
+    }
+
+    /// Changes the node ids of the module instances, e.g. when the network is adopted by a
+    /// solver.
+    pub(crate) fn remap_modules(&mut self, id: impl Fn(usize) -> usize) {
+        for instance in self.modules.instances.iter_mut() {
+            for node in instance.nodes.iter_mut() {
+                *node = id(*node);
+            }
+        }
+    }
+
+    /// Removes the instances whose nodes or connections are not part of the network anymore,
+    /// e.g. after an input has been removed. Their remaining nodes become ordinary hidden nodes.
+    pub(crate) fn retain_modules(&mut self) {
+        let (nodes, edges, modules) = (&self.nodes, &self.edges, &self.modules.modules);
+        self.modules.instances.retain(|instance| {
+            instance
+                .nodes
+                .iter()
+                .all(|&id| nodes.iter().any(|node| node.id == id))
+                && modules[instance.module].edges.iter().all(|edge| {
+                    let (from, to) = (instance.nodes[edge.from], instance.nodes[edge.to]);
+                    edges.iter().any(|edge| edge.from == from && edge.to == to)
+                })
+        });
+    }
+
+    /// Instantiates the module on random distinct input nodes, every output of the module leads
+    /// into a random output node. Returns `false` if the network has too few input nodes.
+    fn instantiate_randomly<R: Rng + ?Sized>(
+        &mut self,
+        module: usize,
+        innovations: &mut InnovationTracker,
+        rng: &mut R,
+    ) -> bool {
+        let sources: Vec<usize> = self
+            .nodes
+            .iter()
+            .filter(|node| node.node_type == NodeType::Input)
+            .map(|node| node.id)
+            .collect();
+        let targets: Vec<usize> = self
+            .nodes
+            .iter()
+            .filter(|node| node.node_type == NodeType::Output)
+            .map(|node| node.id)
+            .collect();
+        let definition = &self.modules.modules[module];
+        if definition.inputs > sources.len() || targets.is_empty() {
+            return false;
+        }
+        let inputs: Vec<usize> = index::sample(rng, sources.len(), definition.inputs)
+            .into_iter()
+            .map(|idx| sources[idx])
+            .collect();
+        let outputs: Vec<usize> = (0..definition.outputs)
+            .map(|_| targets[random::index(rng, targets.len())])
+            .collect();
+        self.instantiate_with(module, &inputs, &outputs, innovations);
+        true
+    }
+
+    /// Instantiates the module without any checks, the new nodes and connections get their ids
+    /// and innovation numbers from the tracker.
+    fn instantiate_with(
+        &mut self,
+        module: usize,
+        inputs: &[usize],
+        outputs: &[usize],
+        innovations: &mut InnovationTracker,
+    ) -> usize {
+        let definition = self.modules.modules[module].clone();
+        let mut nodes = inputs.to_vec();
+        for &activation in definition.activations.iter() {
+            let mut node = Node::hidden_with_id(innovations.new_node_id());
+            node.activation = activation;
+            nodes.push(node.id);
+            self.nodes.push(node);
+        }
+        for edge in definition.edges.iter() {
+            let (from, to) = (nodes[edge.from], nodes[edge.to]);
+            let mut new = Edge::initial_from_to(from, to, innovations.connection(from, to));
+            new.weight = edge.weight;
+            new.enabled = edge.enabled;
+            self.insert_edge(new);
+        }
+        for (i, &to) in outputs.iter().enumerate() {
+            let from = nodes[definition.inputs + i];
+            self.insert_edge(Edge::initial_from_to(
+                from,
+                to,
+                innovations.connection(from, to),
+            ));
+        }
+        self.modules
+            .instances
+            .push(ModuleInstance { module, nodes });
+        self.genome_changed();
+        self.modules.instances.len() - 1
+    }
+}
@@ -1,42 +1,91 @@
+use crate::activation::{self, Activation, OutputActivation};
+use crate::compiled::CompiledNetwork;
+use crate::config::{MutationRates, WeightInit};
+#[cfg(feature = "std")]
+use crate::error::Error;
+#[cfg(feature = "std-fs")]
+use crate::file;
+use crate::float::Float;
+#[cfg(feature = "std")]
+use crate::format;
+use crate::frozen::Frozen;
+use crate::module::ModuleGenes;
 use crate::phenotype::Phenotype;
-use bincode;
+#[cfg(feature = "std")]
+use crate::phenotype::PhenotypeArena;
+use crate::scaling::InputScaling;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use bincode::{self, Options};
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "std-fs")]
 use std::fs::OpenOptions;
-use std::io::{BufReader, Read, Write};
-/// Represents a node in the neural network with a specific id and a type (either Input, Hidden or
-/// Output).
+#[cfg(feature = "std-fs")]
+use std::io::BufReader;
+#[cfg(feature = "std")]
+use std::io::{Read, Write};
+#[cfg(feature = "std-fs")]
+use std::path::Path;
+/// Represents a node in the neural network with a specific id and a type (either Bias, Input,
+/// Hidden or Output).
 #[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Copy)]
 pub struct Node {
     /// The id of the node, needed to transform the network into its phenotype to compute the
     /// output.
     pub id: usize,
-    /// The type of the node, one of Input, Hidden, Output:
+    /// The type of the node, one of Bias, Input, Hidden, Output:
     ///
+    /// - The bias node always has the value **1.0**, its connections are the biases of the nodes.
     /// - Input nodes are the ones whose value is being set at the start of the computation.
     /// - Output nodes are the ones where the final computed values can be extracted.
     /// - Hidden nodes are the ones where the magic and computation happens. They are responsible
-    /// for the creative computation.
+    ///   for the creative computation.
     pub node_type: NodeType,
+    /// The activation function which is applied to the value of the node before it is passed on.
+    #[serde(default)]
+    pub activation: Activation,
 }
 
 impl Node {
+    /// Constructor for the bias node with the given id. Used to create node objects.
+    pub(crate) fn bias_with_id(id: usize) -> Self {
+        Node {
+            id,
+            node_type: NodeType::Bias,
+            activation: Activation::default(),
+        }
+    }
     /// Constructor for an input node with the given id. Used to create node objects.
-    fn input_with_id(id: usize) -> Self {
+    pub(crate) fn input_with_id(id: usize) -> Self {
         Node {
             id,
             node_type: NodeType::Input,
+            activation: Activation::default(),
+        }
+    }
+    /// Constructor for a hidden node with the given id. Used to create node objects.
+    #[cfg(feature = "std")]
+    pub(crate) fn hidden_with_id(id: usize) -> Self {
+        Node {
+            id,
+            node_type: NodeType::Hidden,
+            activation: Activation::default(),
         }
     }
     /// Constructor for an output node with the given id. Used to create node objects.
-    fn output_with_id(id: usize) -> Self {
+    pub(crate) fn output_with_id(id: usize) -> Self {
         Node {
             id,
             node_type: NodeType::Output,
+            activation: Activation::default(),
         }
     }
 }
 
-/// type of a node, one of Input, Hidden, Output
+/// type of a node, one of Bias, Input, Hidden, Output
 #[derive(Debug, Deserialize, PartialEq, Serialize, Clone, Copy)]
 pub enum NodeType {
     /// Type of a node whose value is being set at the start of the computation. Their number is
@@ -48,70 +97,202 @@ pub enum NodeType {
     /// Output nodes are used to extract the final results of the computation. Their number is set
     /// at the creation of a neural network and cannot be changed.
     Output,
+    /// Type of the node whose value is always **1.0**, so the weight of its connection into a
+    /// node is the bias of that node. It is stored in front of the input nodes and no value is
+    /// supplied for it in the computation. Networks stored by earlier versions of this crate
+    /// contain it as their first input node, it becomes the bias node when they are loaded.
+    Bias,
+}
+
+impl NodeType {
+    /// Returns whether the value of the node is set at the start of the computation instead of
+    /// being computed from its connections, which is the case for the input nodes and the bias
+    /// node. These nodes never have incoming connections.
+    pub fn is_input_or_bias(&self) -> bool {
+        matches!(self, NodeType::Input | NodeType::Bias)
+    }
 }
 
 /// Struct used to represent an edge in the neural network. Is converted to an adjacency list in
 /// the phenotype representation.
+///
+/// The weight has the floating point type of the network, `f32` by default.
 #[derive(Debug, Deserialize, PartialEq, Serialize, Copy, Clone)]
-pub struct Edge {
+pub struct Edge<F = f32> {
     /// The id of the source node to which the edge origin is connected.
     pub from: usize,
     /// The id of the destination node, where the edge leads to.
     pub to: usize,
     /// The factor of the edge with which the source nodes value will be multiplied. The weight
     /// will change as the network is being trained to produce better outputs.
-    pub weight: f32,
+    pub weight: F,
     /// A field to tell whether this edge is disabled in the current network or not. The edge might
     /// become disabled later during training.
+    #[serde(default = "enabled_by_default")]
     pub enabled: bool,
     /// The innovation number being used by the evolution algorithm to make an efficient merge of
     /// two networks possible.
     pub innovation: usize,
+    /// A recurrent edge passes on the value its source node had in the previous computation, so
+    /// it can form a cycle without making the computation impossible. Only recurrent networks
+    /// contain recurrent edges.
+    #[serde(default)]
+    pub recurrent: bool,
 }
 
-impl Edge {
+impl<F: Float> Edge<F> {
     /// Constructor for creating a default edge with weight **1.0**. This edge is always enabled.
-    fn initial_from_to(from: usize, to: usize, inno_number: usize) -> Self {
+    pub(crate) fn initial_from_to(from: usize, to: usize, inno_number: usize) -> Self {
         Edge {
             from,
             to,
-            weight: 1.0,
+            weight: F::ONE,
             enabled: true,
             innovation: inno_number,
+            recurrent: false,
         }
     }
 }
 
+/// Edges which are stored without the `enabled` field are enabled.
+fn enabled_by_default() -> bool {
+    true
+}
+
+/// Networks which are stored without a fitness have not been evaluated yet.
+fn unevaluated_fitness() -> f32 {
+    f32::MIN
+}
+
+/// The parts of a network which have been removed by `NeuralNetwork::prune`.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct PruneReport {
+    /// The ids of the removed hidden nodes.
+    pub removed_nodes: Vec<usize>,
+    /// The innovation numbers of the removed edges.
+    pub removed_edges: Vec<usize>,
+}
+
+impl PruneReport {
+    /// Returns whether nothing has been removed.
+    pub fn is_empty(&self) -> bool {
+        self.removed_nodes.is_empty() && self.removed_edges.is_empty()
+    }
+}
+
 /// The structure being used to create, load and save a network as well as to compute outputs from
 /// given inputs.
 ///
 /// This is the main object which is being trained. After the trainging you can extract the best
 /// instance from the solver. The solver is the only structure more high level than the neural
 /// network.
-#[derive(Debug, Deserialize, PartialEq, Serialize, Clone)]
-pub struct NeuralNetwork {
+///
+/// A network has no interior mutability, the cached phenotype is only changed through `&mut self`.
+/// So it is `Send` and `Sync` and can be shared between threads by reference, where every thread
+/// computes outputs with its own `phenotype()`.
+///
+/// The weights and the computed values are `f32` by default, see `Float` for networks computing
+/// with `f64`.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct NeuralNetwork<F = f32> {
     /// Storing a list of all nodes with their id's and their node types (Input, Hidden, Output).
-    pub nodes: Vec<Node>,
+    ///
+    /// The fields describing the genome are only readable through `nodes()`, `edges()`, `id()`
+    /// and `size()`, changes are made with `edit()` so the genome always stays valid.
+    pub(crate) nodes: Vec<Node>,
     /// Storing a list of all edges with their destinations and other fields.
     ///
     /// This edge list will be converted to an adjacency list to be more efficient when computing the output.
-    pub edges: Vec<Edge>,
+    pub(crate) edges: Vec<Edge<F>>,
     /// The id of the network is being used to identify the network within the solver, so an
     /// individual fitness value can be assigned to the exact network.
-    pub id: usize,
+    #[serde(default)]
+    pub(crate) id: usize,
     /// The size of the network, the first part is the number of input nodes and the second part is
     /// the number of output nodes.
-    pub size: (usize, usize),
+    pub(crate) size: (usize, usize),
     /// The fitness of the network, higher means better and its initial value is `f32::MIN`.
-    pub fitness: f32,
+    ///
+    /// Use `fitness()` and `set_fitness()` to access it, so it can never become NaN.
+    #[serde(default = "unevaluated_fitness")]
+    pub(crate) fitness: f32,
+    /// The values of the objectives of the network, if it has been evaluated with multiple
+    /// objectives, see `Solver::evaluate_generation_objectives`.
+    #[serde(default)]
+    pub(crate) objectives: Vec<f32>,
+    /// The behavior descriptor of the network used by novelty search, see
+    /// `Solver::evaluate_generation_novelty`.
+    #[serde(default)]
+    pub(crate) behavior: Vec<f32>,
+    /// The activation function which is used for new nodes of the network.
+    #[serde(default)]
+    pub(crate) activation: Activation,
+    /// The activation of the output nodes, see `with_output_activation`.
+    #[serde(default)]
+    pub(crate) output_activation: OutputActivation,
+    /// Whether the network has an additional presence indicator input node for every input.
+    ///
+    /// The indicator node of an input is set to **1.0** if the input is present and to **0.0** if
+    /// it has been masked out in `compute_masked`.
+    #[serde(default)]
+    pub(crate) presence_indicators: bool,
+    /// Whether the network is allowed to form cycles using recurrent edges, see `with_recurrent`.
+    #[serde(default)]
+    pub(crate) recurrent: bool,
+    /// The distribution of the weights of connections which are added by mutations.
+    #[serde(default)]
+    pub(crate) weight_init: WeightInit,
+    /// Whether every hidden node gets its own bias when it is added, see `with_node_biases`.
+    #[serde(default)]
+    pub(crate) node_biases: bool,
+    /// The scaling of the inputs before they are computed, see `set_input_scaling`.
+    #[serde(default)]
+    pub(crate) input_scaling: InputScaling<F>,
+    /// The evolved mutation rates of the network, if the config enables `self_adaptive_mutation`.
+    #[serde(default)]
+    pub(crate) mutation_rates: Option<MutationRates>,
+    /// The nodes and connections which are never changed by the evolution, see `freeze_node`.
+    #[serde(default)]
+    pub(crate) frozen: Frozen,
+    /// The modules of the network and their instances, see `define_module`.
+    #[serde(default)]
+    pub(crate) modules: ModuleGenes,
+    /// Counts the changes of the genome, so a cached phenotype which has been built before a
+    /// change is detected and built again, see `genome_changed`.
+    #[serde(skip)]
+    pub(crate) genome_version: u64,
     // optionally store the phenotype if needed for multiple computations
     #[serde(skip)]
-    pt: Option<Phenotype>,
+    pub(crate) pt: Option<Phenotype<F>>,
+}
+
+// Comparing networks without the cached phenotype, because it is not part of the genome and is
+// lost when storing the network
+impl<F: Float> PartialEq for NeuralNetwork<F> {
+    fn eq(&self, other: &Self) -> bool {
+        self.nodes == other.nodes
+            && self.edges == other.edges
+            && self.id == other.id
+            && self.size == other.size
+            && self.fitness == other.fitness
+            && self.objectives == other.objectives
+            && self.behavior == other.behavior
+            && self.activation == other.activation
+            && self.output_activation == other.output_activation
+            && self.presence_indicators == other.presence_indicators
+            && self.recurrent == other.recurrent
+            && self.weight_init == other.weight_init
+            && self.node_biases == other.node_biases
+            && self.input_scaling == other.input_scaling
+            && self.mutation_rates == other.mutation_rates
+            && self.frozen == other.frozen
+            && self.modules == other.modules
+    }
 }
 
 // Ordering networks by their fitness
-impl PartialOrd for NeuralNetwork {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+impl<F: Float> PartialOrd for NeuralNetwork<F> {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
         Some(self.fitness.total_cmp(&other.fitness))
     }
 }
@@ -120,30 +301,68 @@ impl NeuralNetwork {
     /// Constructor for a neural network with a given number of input nodes, output nodes and a
     /// given id.
     ///
-    /// Note that this constructor will always add an additional input node whose value will always
-    /// be **1.0** when computing the outputs. This is needed in case all inputs are **0.0** because the
-    /// network should still be able to output **1.0**.
+    /// Note that this constructor will always add a bias node (`NodeType::Bias`, see `bias_node`)
+    /// in front of the input nodes, whose value is always **1.0** when computing the outputs. This
+    /// is needed in case all inputs are **0.0** because the network should still be able to output
+    /// **1.0**.
     ///
     /// The number of input and output nodes should not be **0** because otherwise it is not possible
     /// to create a network which can compute an output.
     // TODO: sanitize input (output_nodes = 0?)
     pub fn with_size_and_id(input_nodes: usize, output_nodes: usize, id: usize) -> Self {
-        let mut nodes = Vec::with_capacity(input_nodes + 1 + output_nodes);
-        let mut edges = Vec::with_capacity((input_nodes + 1) * output_nodes);
-        // creating input nodes + edges
-        // it also creates a default input node with its input as a constant 1.0
+        NeuralNetwork::create(input_nodes, output_nodes, id, false)
+    }
+
+    /// Constructor for a neural network which has an additional presence indicator input node for
+    /// every regular input node.
+    ///
+    /// The indicator nodes are being set automatically when computing the outputs: **1.0** if the
+    /// input is present and **0.0** if it has been masked out using `compute_masked`. This way the
+    /// network can learn to distinguish between an input of **0.0** and a missing input.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::NeuralNetwork;
+    /// let nn = NeuralNetwork::with_presence_indicators(2, 1, 0);
+    /// // The network still expects two inputs per computation.
+    /// assert_eq!(nn.size(), (2, 1));
+    /// ```
+    pub fn with_presence_indicators(input_nodes: usize, output_nodes: usize, id: usize) -> Self {
+        NeuralNetwork::create(input_nodes, output_nodes, id, true)
+    }
+
+    /// Creates the initial fully connected network. If presence indicators are used, they are
+    /// inserted as additional input nodes directly after the regular input nodes.
+    fn create(
+        input_nodes: usize,
+        output_nodes: usize,
+        id: usize,
+        presence_indicators: bool,
+    ) -> Self {
+        let input_count = if presence_indicators {
+            2 * input_nodes
+        } else {
+            input_nodes
+        };
+        let mut nodes = Vec::with_capacity(input_count + 1 + output_nodes);
+        let mut edges = Vec::with_capacity((input_count + 1) * output_nodes);
+        // creating the bias node and the input nodes + edges
         let mut inno_number = 0;
-        for i in 0..=input_nodes {
-            // add input node
-            nodes.push(Node::input_with_id(i));
+        for i in 0..=input_count {
+            // add the bias node in front of the input nodes
+            nodes.push(if i == 0 {
+                Node::bias_with_id(i)
+            } else {
+                Node::input_with_id(i)
+            });
             // for this input node add a default edge with weight 1.0 to every output node
-            for j in (input_nodes + 1)..=(input_nodes + output_nodes) {
+            for j in (input_count + 1)..=(input_count + output_nodes) {
                 edges.push(Edge::initial_from_to(i, j, inno_number));
                 inno_number += 1;
             }
         }
         // add output nodes
-        for i in (input_nodes + 1)..=(input_nodes + output_nodes) {
+        for i in (input_count + 1)..=(input_count + output_nodes) {
             nodes.push(Node::output_with_id(i));
         }
         NeuralNetwork {
@@ -152,6 +371,19 @@ impl NeuralNetwork {
             id,
             size: (input_nodes, output_nodes),
             fitness: f32::MIN,
+            objectives: Vec::new(),
+            behavior: Vec::new(),
+            activation: Activation::default(),
+            output_activation: OutputActivation::default(),
+            presence_indicators,
+            recurrent: false,
+            weight_init: WeightInit::default(),
+            node_biases: false,
+            input_scaling: InputScaling::None,
+            mutation_rates: None,
+            frozen: Frozen::default(),
+            modules: ModuleGenes::default(),
+            genome_version: 0,
             pt: None,
         }
     }
@@ -165,11 +397,323 @@ impl NeuralNetwork {
     pub fn with_size(input_nodes: usize, output_nodes: usize) -> Self {
         NeuralNetwork::with_size_and_id(input_nodes, output_nodes, 0)
     }
+}
+
+impl<F: Float> NeuralNetwork<F> {
+    /// Returns a copy of the network whose weights and computations use another floating point
+    /// type, e.g. `f64` for a higher precision. The cached phenotype is not copied.
+    ///
+    /// Casting a network to `f64` and back to `f32` gives the original network again.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::NeuralNetwork;
+    /// let nn = NeuralNetwork::with_size(2, 1);
+    /// let precise = nn.cast::<f64>();
+    /// assert_eq!(precise.edges()[0].weight, 1.0f64);
+    /// assert_eq!(precise.cast::<f32>(), nn);
+    /// ```
+    pub fn cast<G: Float>(&self) -> NeuralNetwork<G> {
+        NeuralNetwork {
+            nodes: self.nodes.clone(),
+            edges: self
+                .edges
+                .iter()
+                .map(|edge| Edge {
+                    from: edge.from,
+                    to: edge.to,
+                    weight: G::from_f64(edge.weight.to_f64()),
+                    enabled: edge.enabled,
+                    innovation: edge.innovation,
+                    recurrent: edge.recurrent,
+                })
+                .collect(),
+            id: self.id,
+            size: self.size,
+            fitness: self.fitness,
+            objectives: self.objectives.clone(),
+            behavior: self.behavior.clone(),
+            activation: self.activation,
+            output_activation: self.output_activation,
+            presence_indicators: self.presence_indicators,
+            recurrent: self.recurrent,
+            weight_init: self.weight_init,
+            node_biases: self.node_biases,
+            input_scaling: self.input_scaling.cast(),
+            mutation_rates: self.mutation_rates,
+            frozen: self.frozen.clone(),
+            modules: self.modules.clone(),
+            genome_version: 0,
+            pt: None,
+        }
+    }
+
+    /// Returns the network where every node uses the given activation function. Nodes which are
+    /// added later by mutations use this activation function as well. Output nodes keep their
+    /// activation function if it is fixed by the `output_activation`.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::{Activation, NeuralNetwork};
+    /// let mut nn = NeuralNetwork::with_size(1, 1).with_activation(Activation::Identity);
+    /// // the bias node and the input are added without being changed
+    /// assert_eq!(nn.compute(vec![0.5]), vec![1.5]);
+    /// ```
+    pub fn with_activation(mut self, activation: Activation) -> Self {
+        self.activation = activation;
+        let fixed = self.output_activation.fixed_activation().is_some();
+        for node in self.nodes.iter_mut() {
+            if !(fixed && node.node_type == NodeType::Output) {
+                node.activation = activation;
+            }
+        }
+        self.genome_changed();
+        self
+    }
+
+    /// Returns the network where the output nodes use the given output activation, which can
+    /// differ from the activation function of the hidden nodes. A fixed activation function is
+    /// set for every output node and it is never changed by mutations.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::{Activation, NeuralNetwork, OutputActivation};
+    /// let mut nn = NeuralNetwork::with_size(1, 1)
+    ///     .with_output_activation(OutputActivation::Fixed(Activation::Identity));
+    /// // the output is not bounded by the sigmoid anymore
+    /// assert!(nn.compute([2.0])[0] > 1.0);
+    /// let mut classifier =
+    ///     NeuralNetwork::with_size(1, 3).with_output_activation(OutputActivation::Softmax);
+    /// let probabilities = classifier.compute([0.5]);
+    /// assert!((probabilities.iter().sum::<f32>() - 1.0).abs() < 1e-6);
+    /// ```
+    pub fn with_output_activation(mut self, output_activation: OutputActivation) -> Self {
+        self.output_activation = output_activation;
+        if let Some(activation) = output_activation.fixed_activation() {
+            for node in self.nodes.iter_mut() {
+                if node.node_type == NodeType::Output {
+                    node.activation = activation;
+                }
+            }
+        }
+        self.genome_changed();
+        self
+    }
+
+    /// Returns the network with the recurrent mode turned on or off.
+    ///
+    /// In recurrent mode `mutate_add_connection` may also add connections which would form a
+    /// cycle. These are stored as recurrent edges, which pass on the value their source node had
+    /// in the previous call of `compute`. The node values persist between the computations, so
+    /// the network has a memory, which is needed for time series or control problems. Use
+    /// `reset_state` to forget the previous values, e.g. at the start of a new episode.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::NeuralNetwork;
+    /// let mut nn = NeuralNetwork::with_size(1, 1).with_recurrent(true);
+    /// # #[cfg(feature = "std")]
+    /// # {
+    /// let mut innovations = neaters::InnovationTracker::for_network(&nn);
+    /// let mut rng = rand::thread_rng();
+    /// // the only possible new connection is the one from the output to itself
+    /// assert!(nn.mutate_add_connection(&mut innovations, &mut rng));
+    /// assert!(nn.edges()[2].recurrent);
+    /// # }
+    /// ```
+    pub fn with_recurrent(mut self, recurrent: bool) -> Self {
+        self.recurrent = recurrent;
+        self
+    }
+
+    /// Returns the network where every hidden node added by `mutate_add_node` gets its own bias:
+    /// a connection from the bias node with the weight **0.0**, which is mutated like every other
+    /// weight afterwards. Without node biases, a hidden node only gets a bias if a mutation adds
+    /// the connection from the bias node by chance. The output nodes are connected to the bias
+    /// node from the start, see `bias`.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::NeuralNetwork;
+    /// let mut nn = NeuralNetwork::with_size(2, 1).with_node_biases(true);
+    /// # #[cfg(feature = "std")]
+    /// # {
+    /// // only the connections from the inputs can be split
+    /// nn.edit().set_enabled(0, 3, false).unwrap();
+    /// let mut innovations = neaters::InnovationTracker::for_network(&nn);
+    /// nn.mutate_add_node(&mut innovations, &mut rand::thread_rng());
+    /// let hidden = nn.nodes()[4].id;
+    /// assert_eq!(nn.bias(hidden), Some(0.0));
+    /// # }
+    /// ```
+    pub fn with_node_biases(mut self, node_biases: bool) -> Self {
+        self.node_biases = node_biases;
+        self
+    }
+
+    /// Sets the scaling which is applied to the inputs before every computation, e.g. with the
+    /// statistics of the training data, see `InputScaling`. It is stored together with the
+    /// genome, is copied to the offspring of the network and is applied by the phenotype, the
+    /// compiled network and the generated code as well.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the scaling does not have statistics for exactly the inputs of the network.
+    pub fn set_input_scaling(&mut self, scaling: InputScaling<F>) {
+        if let Some(inputs) = scaling.inputs() {
+            assert_eq!(
+                inputs, self.size.0,
+                "the scaling needs exactly one entry for every input"
+            );
+        }
+        self.input_scaling = scaling;
+        self.genome_changed();
+    }
+
+    /// Returns the scaling which is applied to the inputs, see `set_input_scaling`.
+    pub fn input_scaling(&self) -> &InputScaling<F> {
+        &self.input_scaling
+    }
+
+    /// Returns the evolved mutation rates of the network, `None` if the network has not been
+    /// mutated with `self_adaptive_mutation` enabled in the config.
+    pub fn mutation_rates(&self) -> Option<&MutationRates> {
+        self.mutation_rates.as_ref()
+    }
+
+    /// Forgets the node values of the previous computations of a recurrent network, so the next
+    /// computation behaves like the first one. This has no effect on feed-forward networks.
+    pub fn reset_state(&mut self) {
+        if let Some(pt) = &mut self.pt {
+            pt.reset_state();
+        }
+    }
+
+    /// Returns the nodes of the network, the bias node and the input nodes always come first.
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    /// Returns the id of the bias node, `None` if the network does not have one. Networks created
+    /// by this crate always have a bias node, but a network which has been deserialized directly
+    /// from the representation of an earlier version still contains it as its first input node
+    /// until it is repaired, see `repair`.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::neuralnetwork::{NeuralNetwork, NodeType};
+    /// let nn = NeuralNetwork::with_size(2, 1);
+    /// assert_eq!(nn.bias_node(), Some(0));
+    /// assert_eq!(nn.nodes()[0].node_type, NodeType::Bias);
+    /// // the bias node is not counted as an input
+    /// assert_eq!(nn.size(), (2, 1));
+    /// ```
+    pub fn bias_node(&self) -> Option<usize> {
+        self.nodes
+            .first()
+            .filter(|node| node.node_type == NodeType::Bias)
+            .map(|node| node.id)
+    }
+
+    /// Returns the bias of the node with the given id, which is the weight of the enabled
+    /// connection from the bias node into it. Returns `None` if there is no such connection.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::NeuralNetwork;
+    /// let nn = NeuralNetwork::with_size(2, 1);
+    /// // the output node 3 is connected to the bias node with the weight 1.0
+    /// assert_eq!(nn.bias(3), Some(1.0));
+    /// assert_eq!(nn.bias(1), None);
+    /// ```
+    pub fn bias(&self, id: usize) -> Option<F> {
+        let bias_node = self.bias_node()?;
+        self.edges
+            .iter()
+            .find(|edge| edge.enabled && edge.from == bias_node && edge.to == id)
+            .map(|edge| edge.weight)
+    }
+
+    /// Returns the edges of the network, sorted by their innovation number.
+    pub fn edges(&self) -> &[Edge<F>] {
+        &self.edges
+    }
+
+    /// Returns the id of the network within its solver.
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    /// Returns the number of input nodes and the number of output nodes of the network.
+    pub fn size(&self) -> (usize, usize) {
+        self.size
+    }
+
+    /// Returns whether the network has an additional presence indicator input node for every
+    /// input, see `with_presence_indicators`.
+    pub fn presence_indicators(&self) -> bool {
+        self.presence_indicators
+    }
+
+    /// Returns whether the network is allowed to form cycles using recurrent edges, see
+    /// `with_recurrent`.
+    pub fn recurrent(&self) -> bool {
+        self.recurrent
+    }
+
+    /// Returns the activation function which is used for new nodes of the network, see
+    /// `with_activation`.
+    pub fn activation(&self) -> Activation {
+        self.activation
+    }
+
+    /// Returns the activation of the output nodes, see `with_output_activation`.
+    pub fn output_activation(&self) -> OutputActivation {
+        self.output_activation
+    }
+
+    /// Returns the distribution of the weights of connections which are added by mutations, see
+    /// `with_weight_init`.
+    pub fn weight_init(&self) -> WeightInit {
+        self.weight_init
+    }
+
+    /// Returns whether every hidden node gets its own bias when it is added, see
+    /// `with_node_biases`.
+    pub fn node_biases(&self) -> bool {
+        self.node_biases
+    }
+
+    /// Returns the number of hidden nodes of the network.
+    pub fn hidden_node_count(&self) -> usize {
+        self.nodes
+            .iter()
+            .filter(|node| node.node_type == NodeType::Hidden)
+            .count()
+    }
+
+    /// Returns the number of enabled edges of the network.
+    pub fn enabled_edge_count(&self) -> usize {
+        self.edges.iter().filter(|edge| edge.enabled).count()
+    }
+
+    /// Returns the number of input nodes of the network without the bias node. This
+    /// includes the presence indicator nodes if the network uses them.
+    #[cfg(feature = "std")]
+    pub(crate) fn input_node_count(&self) -> usize {
+        if self.presence_indicators {
+            2 * self.size.0
+        } else {
+            self.size.0
+        }
+    }
 
     /// Function for computing the output of the network with a given input.
     ///
     /// Use this function to get the result from the network by giving it a `f32` for every input
-    /// node you specified (with the number of input nodes when creating).
+    /// node you specified (with the number of input nodes when creating), or a `f64` if the
+    /// network has been cast to `f64`. The input can be anything which can be viewed as a slice,
+    /// e.g. a `Vec<f32>`, an array or a slice.
     ///
     /// # Example:
     ///
@@ -181,34 +725,448 @@ impl NeuralNetwork {
     /// let res: Vec<f32> = nn.compute(vec![0.5]);
     /// // For the default neural net without training the output should be 0.4 (depends on the sigmoid function)
     /// assert_eq!(res, vec![0.45454547]);
+    /// assert_eq!(nn.compute([0.5]), res);
     /// ```
     ///
     /// This function creates a phenotype to then compute the result and automatically caches it so
     /// it does not need to be created again.
-    // TODO: sanitize input (length of input correct?)
-    pub fn compute(&mut self, input: Vec<f32>) -> Vec<f32> {
-        if let Some(pt) = &mut self.pt {
-            pt.reset();
-            pt.compute(input)
-        } else {
-            let mut pt = Phenotype::from_nn(self);
-            let res = pt.compute(input);
-            self.pt = Some(pt);
-            res
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of the input does not match the number of inputs of the network.
+    pub fn compute<I: AsRef<[F]>>(&mut self, input: I) -> Vec<F> {
+        let mut output = vec![F::ZERO; self.size.1];
+        self.compute_into(input.as_ref(), &mut output);
+        output
+    }
+
+    /// Function for computing the output of the network and writing it into the given output
+    /// buffer instead of allocating a new vector.
+    ///
+    /// This gives the same results as `compute`, but nothing is allocated once the phenotype has
+    /// been cached, so it is well suited for hot loops evaluating the network many times.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// use neaters::NeuralNetwork;
+    /// let mut nn = NeuralNetwork::with_size(2, 1);
+    /// let mut output = [0.0];
+    /// for input in [[0.0, 0.0], [0.0, 1.0], [1.0, 0.0], [1.0, 1.0]] {
+    ///     nn.compute_into(&input, &mut output);
+    ///     assert_eq!(output.to_vec(), nn.compute(input));
+    /// }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of the input or of the output buffer does not match the number of
+    /// inputs or outputs of the network.
+    pub fn compute_into(&mut self, input: &[F], output: &mut [F]) {
+        self.compute_prepared(input, None, output);
+    }
+
+    /// Function for computing the output of the network where some inputs may be absent.
+    ///
+    /// The mask needs to contain one entry for every input, `true` means the input is present and
+    /// `false` means the input is absent. Absent inputs contribute **0.0** to the computation no
+    /// matter which value has been supplied for them. If the network has been created with
+    /// presence indicators, the indicator node of every absent input is set to **0.0**.
+    ///
+    /// This is useful for sensor dropouts or observations of variable size, as the same network
+    /// can be used without having to train separate networks for every input layout.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// use neaters::NeuralNetwork;
+    /// let mut nn = NeuralNetwork::with_size(2, 1);
+    /// // The second input is absent, so its value is being ignored.
+    /// let res = nn.compute_masked(vec![0.5, 100.0], &[true, false]);
+    /// assert_eq!(res, nn.compute(vec![0.5, 0.0]));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of the input or of the mask does not match the number of inputs of the
+    /// network.
+    pub fn compute_masked<I: AsRef<[F]>>(&mut self, input: I, mask: &[bool]) -> Vec<F> {
+        let mut output = vec![F::ZERO; self.size.1];
+        self.compute_prepared(input.as_ref(), Some(mask), &mut output);
+        output
+    }
+
+    /// Computes the output into the output buffer, creating the phenotype if needed.
+    fn compute_prepared(&mut self, input: &[F], mask: Option<&[bool]>, output: &mut [F]) {
+        self.phenotype_mut().compute_into(input, mask, output);
+    }
+
+    /// Creates a new phenotype of the network, which can compute outputs without borrowing the
+    /// network mutably.
+    ///
+    /// The `compute` functions of the network cache their phenotype, so they need `&mut self`.
+    /// If a network should be shared, e.g. between multiple threads, every user can create its
+    /// own phenotype instead. The phenotype does not change when the network is mutated
+    /// afterwards.
+    ///
+    /// If the network has already cached an up to date phenotype, it is copied without the node
+    /// values of previous computations instead of being built from the genome again.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::NeuralNetwork;
+    /// let nn = NeuralNetwork::with_size(1, 1);
+    /// let mut pt = nn.phenotype();
+    /// assert_eq!(pt.compute(&[0.5]), vec![0.45454547]);
+    /// ```
+    pub fn phenotype(&self) -> Phenotype<F> {
+        match &self.pt {
+            Some(pt) if pt.genome_version == self.genome_version => {
+                let mut pt = pt.clone();
+                pt.reset_state();
+                pt
+            }
+            _ => Phenotype::from_nn(self),
+        }
+    }
+
+    /// Returns the cached phenotype, creating it first if needed. The phenotype is created again
+    /// if the genome has been changed since it has been cached.
+    fn phenotype_mut(&mut self) -> &mut Phenotype<F> {
+        let version = self.genome_version;
+        if self
+            .pt
+            .as_ref()
+            .is_none_or(|pt| pt.genome_version != version)
+        {
+            self.pt = Some(Phenotype::from_nn(self));
         }
+        self.pt.as_mut().unwrap()
+    }
+
+    /// Removes the cached phenotype and returns it if it is up to date, so it can be updated
+    /// incrementally after a change of the genome and cached again.
+    #[cfg(feature = "std")]
+    pub(crate) fn take_current_phenotype(&mut self) -> Option<Phenotype<F>> {
+        let version = self.genome_version;
+        self.pt.take().filter(|pt| pt.genome_version == version)
+    }
+
+    /// Marks the genome as changed, so the cached phenotype is built again before the next
+    /// computation. Every function which changes the nodes, the edges or anything else the
+    /// phenotype is built from has to call this. A phenotype which is updated with the change
+    /// afterwards, e.g. by `Phenotype::update_weight`, takes over the new version and stays
+    /// cached.
+    pub(crate) fn genome_changed(&mut self) {
+        self.genome_version = self.genome_version.wrapping_add(1);
     }
 
+    /// Caches the phenotype of the network built in the buffers of the arena, unless an up to date
+    /// phenotype is cached already.
+    #[cfg(feature = "std")]
+    pub(crate) fn cache_phenotype(&mut self, arena: &mut PhenotypeArena<F>) {
+        let version = self.genome_version;
+        match self.pt.take() {
+            Some(pt) if pt.genome_version == version => self.pt = Some(pt),
+            stale => {
+                if let Some(pt) = stale {
+                    arena.recycle(pt);
+                }
+                self.pt = Some(arena.phenotype(self));
+            }
+        }
+    }
+
+    /// Clones the network, an up to date cached phenotype is copied into the buffers of the arena
+    /// instead of being cloned.
+    #[cfg(feature = "std")]
+    pub(crate) fn clone_in(&mut self, arena: &mut PhenotypeArena<F>) -> Self {
+        let pt = self.take_current_phenotype();
+        let mut copy = self.clone();
+        copy.pt = pt.as_ref().map(|pt| arena.copy(pt));
+        self.pt = pt;
+        copy
+    }
+
+    /// Moves the cached phenotype into the arena, so its buffers are reused for another network.
+    #[cfg(feature = "std")]
+    pub(crate) fn recycle_phenotype(&mut self, arena: &mut PhenotypeArena<F>) {
+        if let Some(pt) = self.pt.take() {
+            arena.recycle(pt);
+        }
+    }
+
+    /// Generates the source code of a dependency-free Rust function computing the outputs of the
+    /// network, e.g. to embed a trained controller in firmware without this crate.
+    ///
+    /// The generated function `fn forward(inputs: [f32; N]) -> [f32; M]` executes the nodes in
+    /// topological order as straight-line code with the weights baked in. Presence indicators are
+    /// always set to **1.0**, because the function has no mask. A recurrent network gets an
+    /// additional parameter `state: &mut [f32; K]` holding the node values of the previous call,
+    /// which needs to be filled with **0.0** initially.
+    ///
+    /// The activation functions `Tanh`, `Gaussian`, `Sine` and `Cosine` as well as the
+    /// `OutputActivation::Softmax` use methods of `f32` from the standard library. A network cast
+    /// to `f64` generates the same function with `f64` values.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::NeuralNetwork;
+    /// let nn = NeuralNetwork::with_size(2, 1);
+    /// let code = nn.codegen_rust();
+    /// assert!(code.starts_with("pub fn forward(inputs: [f32; 2]) -> [f32; 1] {"));
+    /// ```
+    pub fn codegen_rust(&self) -> String {
+        Phenotype::from_nn(self).codegen_rust()
+    }
+
+    /// Creates an immutable representation of the network which is optimized for computing
+    /// outputs, e.g. to deploy a trained network or to share it between many threads.
+    ///
+    /// The compiled network does not change when the network is mutated afterwards. See
+    /// `CompiledNetwork` for an example.
+    pub fn compile(&self) -> CompiledNetwork<F> {
+        CompiledNetwork::from_phenotype(&self.phenotype())
+    }
+
+    /// Drops the cached phenotype, so the next computation uses the current nodes and edges.
+    ///
+    /// All functions of the network which change its genome, including the `GenomeEditor`, mark
+    /// the cached phenotype as outdated automatically, so this is only needed to free the memory
+    /// of the phenotype.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::NeuralNetwork;
+    /// let mut nn = NeuralNetwork::with_size(1, 1);
+    /// let before = nn.compute([0.5]);
+    /// nn.invalidate_phenotype();
+    /// assert_eq!(nn.compute([0.5]), before);
+    /// ```
+    pub fn invalidate_phenotype(&mut self) {
+        self.pt = None;
+    }
+
+    /// Function for computing the outputs of the network for multiple inputs, e.g. all samples of
+    /// a data set. The result contains the outputs in the same order as the inputs.
+    ///
+    /// This gives the same results as calling `compute` for every input, but the buffers of the
+    /// phenotype are reused for all inputs, so evaluating many samples does not allocate for
+    /// every single one of them.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// use neaters::NeuralNetwork;
+    /// let mut nn = NeuralNetwork::with_size(2, 1);
+    /// let inputs = vec![vec![0.0, 0.0], vec![0.0, 1.0], vec![1.0, 0.0], vec![1.0, 1.0]];
+    /// let outputs = nn.compute_batch(&inputs);
+    /// assert_eq!(outputs.len(), 4);
+    /// assert_eq!(outputs[1], nn.compute(vec![0.0, 1.0]));
+    /// ```
+    pub fn compute_batch(&mut self, inputs: &[Vec<F>]) -> Vec<Vec<F>> {
+        self.phenotype_mut().compute_batch(inputs)
+    }
+
+    /// Function for computing the outputs of the network as the probabilities of classes, which
+    /// are normalized by the softmax function `exp(x_i / T) / sum(exp(x_j / T))` with the
+    /// temperature `T`, so they are positive and sum up to **1.0**. A temperature below **1.0**
+    /// makes the probabilities sharper, a temperature above **1.0** makes them more uniform.
+    ///
+    /// The largest output is subtracted before `exp`, so large outputs do not overflow. A network
+    /// with `OutputActivation::Softmax` is normalized with the temperature instead of twice.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// use neaters::NeuralNetwork;
+    /// let mut nn = NeuralNetwork::with_size(2, 3);
+    /// let probabilities = nn.compute_softmax([0.5, 1.0], 1.0);
+    /// assert!((probabilities.iter().sum::<f32>() - 1.0).abs() < 1e-6);
+    /// assert!(probabilities.iter().all(|&p| p > 0.0));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the temperature is not positive.
+    pub fn compute_softmax<I: AsRef<[F]>>(&mut self, input: I, temperature: F) -> Vec<F> {
+        assert!(
+            temperature > F::ZERO,
+            "the temperature needs to be positive"
+        );
+        let mut output = vec![F::ZERO; self.size.1];
+        self.phenotype_mut()
+            .compute_logits_into(input.as_ref(), None, &mut output);
+        for x in output.iter_mut() {
+            *x = *x / temperature;
+        }
+        activation::softmax(&mut output);
+        output
+    }
+
+    /// Function for computing the class of the input, which is the position of the highest
+    /// output of the network, the first one if several outputs are equally high. It is the class
+    /// with the highest probability of `compute_softmax` for every temperature, but does not need
+    /// to compute the probabilities.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// use neaters::NeuralNetwork;
+    /// let mut nn = NeuralNetwork::with_size(2, 3);
+    /// let outputs = nn.compute([0.5, 1.0]);
+    /// let class = nn.compute_class([0.5, 1.0]);
+    /// assert!(outputs.iter().all(|&output| output <= outputs[class]));
+    /// ```
+    pub fn compute_class<I: AsRef<[F]>>(&mut self, input: I) -> usize {
+        let mut output = vec![F::ZERO; self.size.1];
+        self.phenotype_mut()
+            .compute_logits_into(input.as_ref(), None, &mut output);
+        activation::first_highest(&output)
+    }
+
+    /// Returns a graphviz representation of the network in the DOT language, which can be used
+    /// to visualize the topology of the network, e.g. with `dot -Tpng network.dot -o network.png`.
+    ///
+    /// Input nodes are drawn blue and the bias node yellow at the top, output nodes green at the
+    /// bottom and hidden nodes grey in between. Every edge is labeled with its weight, disabled
+    /// edges are dashed and recurrent edges are red.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::NeuralNetwork;
+    /// let nn = NeuralNetwork::with_size(2, 1);
+    /// let dot = nn.to_dot();
+    /// assert!(dot.starts_with("digraph"));
+    /// assert!(dot.contains("0 -> 3"));
+    /// ```
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph {\n    rankdir=TB;\n");
+        let mut inputs = String::new();
+        let mut outputs = String::new();
+        for node in self.nodes.iter() {
+            let (color, label) = match node.node_type {
+                NodeType::Bias => {
+                    inputs.push_str(&format!(" {};", node.id));
+                    ("lightyellow", format!("{}\\nBias", node.id))
+                }
+                NodeType::Input => {
+                    inputs.push_str(&format!(" {};", node.id));
+                    ("lightblue", format!("{}", node.id))
+                }
+                NodeType::Hidden => ("lightgrey", format!("{}\\n{:?}", node.id, node.activation)),
+                NodeType::Output => {
+                    outputs.push_str(&format!(" {};", node.id));
+                    ("lightgreen", format!("{}\\n{:?}", node.id, node.activation))
+                }
+            };
+            dot.push_str(&format!(
+                "    {} [label=\"{}\", style=filled, fillcolor={}];\n",
+                node.id, label, color
+            ));
+        }
+        dot.push_str(&format!("    {{ rank=source;{} }}\n", inputs));
+        dot.push_str(&format!("    {{ rank=sink;{} }}\n", outputs));
+        for edge in self.edges.iter() {
+            let style = if edge.enabled { "solid" } else { "dashed" };
+            // recurrent edges are drawn red and do not influence the layout
+            let recurrent = if edge.recurrent {
+                ", color=red, constraint=false"
+            } else {
+                ""
+            };
+            dot.push_str(&format!(
+                "    {} -> {} [label=\"{:.3}\", style={}{}];\n",
+                edge.from, edge.to, edge.weight, style, recurrent
+            ));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Returns the fitness of this neural network.
+    ///
+    /// Its initial value is `f32::MIN` until a fitness has been assigned.
+    pub fn fitness(&self) -> f32 {
+        self.fitness
+    }
+
+    /// Sets the fitness of this neural network, a higher fitness means better.
+    ///
+    /// A fitness of NaN cannot be compared to other fitness values, so it is being replaced by the
+    /// lowest possible fitness `f32::MIN`.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::NeuralNetwork;
+    /// let mut nn = NeuralNetwork::with_size(1, 1);
+    /// nn.set_fitness(3.5);
+    /// assert_eq!(nn.fitness(), 3.5);
+    /// nn.set_fitness(f32::NAN);
+    /// assert_eq!(nn.fitness(), f32::MIN);
+    /// ```
+    pub fn set_fitness(&mut self, fitness: f32) {
+        self.fitness = if fitness.is_nan() { f32::MIN } else { fitness };
+    }
+
+    /// Returns the values of the objectives of this neural network, which are empty until they
+    /// have been assigned.
+    pub fn objectives(&self) -> &[f32] {
+        &self.objectives
+    }
+
+    /// Sets the values of the objectives of this neural network, a higher value means better in
+    /// every objective. Like in `set_fitness` NaN values are being replaced by `f32::MIN`.
+    ///
+    /// The fitness is not changed, it is computed from the objectives of all networks of a
+    /// generation by `Solver::assign_pareto_fitness`.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::NeuralNetwork;
+    /// let mut nn = NeuralNetwork::with_size(1, 1);
+    /// nn.set_objectives(vec![0.9, f32::NAN]);
+    /// assert_eq!(nn.objectives(), [0.9, f32::MIN]);
+    /// ```
+    pub fn set_objectives(&mut self, objectives: Vec<f32>) {
+        self.objectives = objectives
+            .into_iter()
+            .map(|value| if value.is_nan() { f32::MIN } else { value })
+            .collect();
+    }
+
+    /// Returns the behavior descriptor of this neural network, which is empty until it has been
+    /// assigned.
+    pub fn behavior(&self) -> &[f32] {
+        &self.behavior
+    }
+
+    /// Sets the behavior descriptor of this neural network, which describes what the network does
+    /// instead of how good it is, e.g. the final position of a robot in a maze. Networks with
+    /// distant descriptors behave differently, see `Solver::assign_novelty_fitness`.
+    pub fn set_behavior(&mut self, behavior: Vec<f32>) {
+        self.behavior = behavior;
+    }
+
+    /// Assign a fitness to this neural network. This is the same as `set_fitness`.
+    pub fn assign_fitness(&mut self, f: f32) {
+        self.set_fitness(f);
+    }
+}
+
+#[cfg(feature = "std")]
+impl<F: Float> NeuralNetwork<F> {
     /// Returning the encoded byte representation of the neural network. This function is needed in
     /// order to store the network on a disk, but it should not be used by a client.
+    ///
+    /// The bytes start with a header containing magic bytes and the format version, so that files
+    /// written by other versions of this crate can be detected when loading them. Networks with
+    /// `f64` weights use other magic bytes, so they are never loaded as networks with `f32` weights.
+    /// A checksum in the header detects truncated or damaged data, see `Error::Corrupted`.
     // NOTE: should this be public?
-    pub fn as_byte_representation(&self) -> Vec<u8> {
-        // encode neural network as binary
-        let encoded: Vec<u8> = match bincode::serialize(&self) {
-            Ok(bytes) => bytes,
-            // TODO: add clean error handling
-            Err(_) => Vec::new(),
-        };
-        encoded
+    pub fn as_byte_representation(&self) -> Result<Vec<u8>, Error> {
+        let encoded: Vec<u8> = format::encode(self)?;
+        Ok(format::with_header(format::network_magic::<F>(), encoded))
     }
 
     /// Saving the neural network at the specified address.
@@ -224,28 +1182,245 @@ impl NeuralNetwork {
     /// # }
     /// # let file_location = dir.path().join("example-network.nn");
     /// # let path = file_location.as_path().to_str().unwrap();
-    /// nn.save_as(path);
+    /// nn.save_as(path)?;
     /// # dir.close().unwrap();
+    /// # Ok::<(), neaters::Error>(())
     /// ```
     ///
     /// It is also possible to supply an absolute path instead of a relative path. Everything which
-    /// is being understood by rust's `File::open("path...")` will be fine.
-    pub fn save_as(&self, at: &str) {
-        let encoded = self.as_byte_representation();
-        // TODO: handle errors
-        let mut file = OpenOptions::new()
-            .write(true)
-            .create(true)
-            .open(at)
-            .unwrap();
-        file.write_all(&encoded).unwrap();
+    /// is being understood by rust's `File::open("path...")` will be fine. An error is returned if
+    /// the file cannot be written.
+    ///
+    /// The data is written to a temporary file in the same directory first, which then replaces an
+    /// existing file at once. So a crash while saving never corrupts a previously saved file.
+    ///
+    /// With the `gzip` feature the file is compressed if its name ends with `.gz`, e.g.
+    /// `checkpoint.sv.gz`. Compressed files are detected when they are loaded.
+    #[cfg(feature = "std-fs")]
+    pub fn save_as<P: AsRef<Path>>(&self, at: P) -> Result<(), Error> {
+        let encoded = self.as_byte_representation()?;
+        file::save(at.as_ref(), &encoded)
+    }
+
+    /// Writes the network into the given writer, e.g. an in-memory buffer or a network stream, in the
+    /// same format as `save_as`.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::NeuralNetwork;
+    /// let nn = NeuralNetwork::with_size(2, 1);
+    /// let mut buffer: Vec<u8> = Vec::new();
+    /// nn.save_to_writer(&mut buffer)?;
+    /// assert_eq!(NeuralNetwork::load_from_reader(&buffer[..])?, nn);
+    /// # Ok::<(), neaters::Error>(())
+    /// ```
+    pub fn save_to_writer<W: Write>(&self, mut writer: W) -> Result<(), Error> {
+        let encoded = self.as_byte_representation()?;
+        writer.write_all(&encoded)?;
+        Ok(())
+    }
+
+    /// Returns a human-readable json representation of the network.
+    ///
+    /// This is useful to inspect, diff or edit a network by hand or with external tools.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::NeuralNetwork;
+    /// let nn = NeuralNetwork::with_size(2, 1);
+    /// let json = nn.to_json()?;
+    /// assert_eq!(NeuralNetwork::from_json(&json)?, nn);
+    /// # Ok::<(), neaters::Error>(())
+    /// ```
+    #[cfg(feature = "serde_json")]
+    pub fn to_json(&self) -> Result<String, Error> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Decodes a network from the bytes returned by `as_byte_representation`.
+    ///
+    /// Unlike `load_from_reader` this also decodes networks with `f64` weights, the type of the
+    /// weights has to be given explicitly. Decoding a network with the wrong type of weights
+    /// returns `Error::UnknownFormat`. Networks which have been stored before the format had a
+    /// header are decoded as well.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::NeuralNetwork;
+    /// let nn = NeuralNetwork::with_size(2, 1).cast::<f64>();
+    /// let bytes = nn.as_byte_representation()?;
+    /// assert_eq!(NeuralNetwork::<f64>::from_bytes(&bytes)?, nn);
+    /// assert!(NeuralNetwork::<f32>::from_bytes(&bytes).is_err());
+    /// # Ok::<(), neaters::Error>(())
+    /// ```
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let bytes = format::decompress(bytes)?;
+        let (version, payload) = if F::NAME == f32::NAME && !format::has_header(&bytes) {
+            (format::HEADERLESS_VERSION, &bytes[..])
+        } else {
+            format::split_header(format::network_magic::<F>(), &bytes)?
+        };
+        let mut decoded = NeuralNetwork::migrate(version, payload)?;
+        decoded.mark_bias_node();
+        decoded.check_structure()?;
+        Ok(decoded)
+    }
+
+    /// Decodes a network which has been stored with the given format version.
+    ///
+    /// When the format changes, the decoding of the old versions is added here, converting them
+    /// to the current representation.
+    fn migrate(version: u32, payload: &[u8]) -> Result<Self, Error> {
+        match version {
+            // anything can be missing the header, so bytes which are no network are unknown
+            format::HEADERLESS_VERSION => bincode::DefaultOptions::new()
+                .with_fixint_encoding()
+                .deserialize::<HeaderlessNetwork>(payload)
+                .map(NeuralNetwork::from)
+                .map_err(|_| Error::UnknownFormat),
+            format::BINCODE_VERSION => {
+                Ok(bincode::deserialize::<BincodeNetwork<F>>(payload)?.into())
+            }
+            format::UNCHECKED_VERSION | format::FORMAT_VERSION => format::decode(payload),
+            _ => Err(Error::UnsupportedVersion(version)),
+        }
+    }
+}
+
+/// The layout of a network stored before the format had a header. The bias node is the first
+/// input node of these networks and every node uses the default activation function.
+#[cfg(feature = "std")]
+#[derive(Deserialize)]
+struct HeaderlessNetwork {
+    nodes: Vec<HeaderlessNode>,
+    edges: Vec<HeaderlessEdge>,
+    id: usize,
+    size: (usize, usize),
+    fitness: f32,
+}
+
+/// A node of a `HeaderlessNetwork`.
+#[cfg(feature = "std")]
+#[derive(Deserialize)]
+struct HeaderlessNode {
+    id: usize,
+    node_type: NodeType,
+}
+
+/// An edge of a `HeaderlessNetwork`.
+#[cfg(feature = "std")]
+#[derive(Deserialize)]
+struct HeaderlessEdge {
+    from: usize,
+    to: usize,
+    weight: f32,
+    enabled: bool,
+    innovation: usize,
+}
+
+#[cfg(feature = "std")]
+impl<F: Float> From<HeaderlessNetwork> for NeuralNetwork<F> {
+    fn from(nn: HeaderlessNetwork) -> Self {
+        let nodes = nn
+            .nodes
+            .iter()
+            .map(|node| Node {
+                id: node.id,
+                node_type: node.node_type,
+                activation: Activation::default(),
+            })
+            .collect();
+        let edges = nn
+            .edges
+            .iter()
+            .map(|edge| Edge {
+                from: edge.from,
+                to: edge.to,
+                weight: F::from_f64(edge.weight as f64),
+                enabled: edge.enabled,
+                innovation: edge.innovation,
+                recurrent: false,
+            })
+            .collect();
+        BincodeNetwork {
+            nodes,
+            edges,
+            id: nn.id,
+            size: nn.size,
+            fitness: nn.fitness,
+            objectives: Vec::new(),
+            behavior: Vec::new(),
+            activation: Activation::default(),
+            presence_indicators: false,
+            recurrent: false,
+            weight_init: WeightInit::default(),
+        }
+        .into()
+    }
+}
+
+/// The layout of a network in the first version of the format, which bincode decodes by the
+/// position of the fields. Fields added after it have to be missing here, the converted network
+/// gets their defaults.
+///
+/// This is the last layout before the second version replaced bincode, with recurrent edges, the
+/// weight initialization, the objectives and the behavior. Networks written with an earlier
+/// layout of the first version cannot be told apart from it, see `format::BINCODE_VERSION`.
+#[cfg(feature = "std")]
+#[derive(Deserialize)]
+struct BincodeNetwork<F> {
+    nodes: Vec<Node>,
+    edges: Vec<Edge<F>>,
+    id: usize,
+    size: (usize, usize),
+    fitness: f32,
+    objectives: Vec<f32>,
+    behavior: Vec<f32>,
+    activation: Activation,
+    presence_indicators: bool,
+    recurrent: bool,
+    weight_init: WeightInit,
+}
+
+#[cfg(feature = "std")]
+impl<F> From<BincodeNetwork<F>> for NeuralNetwork<F> {
+    fn from(nn: BincodeNetwork<F>) -> Self {
+        NeuralNetwork {
+            nodes: nn.nodes,
+            edges: nn.edges,
+            id: nn.id,
+            size: nn.size,
+            fitness: nn.fitness,
+            objectives: nn.objectives,
+            behavior: nn.behavior,
+            activation: nn.activation,
+            output_activation: OutputActivation::Evolved,
+            presence_indicators: nn.presence_indicators,
+            recurrent: nn.recurrent,
+            weight_init: nn.weight_init,
+            node_biases: false,
+            input_scaling: InputScaling::None,
+            mutation_rates: None,
+            frozen: Frozen::default(),
+            modules: ModuleGenes::default(),
+            genome_version: 0,
+            pt: None,
+        }
     }
+}
 
+#[cfg(feature = "std")]
+impl NeuralNetwork {
     /// Loading a neural network from a file.
     ///
     /// This is especially useful if you have already trained and saved your network because you
     /// can just load it into the program without having to create a new one and train it again.
     ///
+    /// Returns an error if the file cannot be read, if it cannot be decoded or if it does not
+    /// contain a valid network.
+    ///
+    /// Files compressed with the `gzip` feature are decompressed, see `save_as`.
+    ///
     /// # Example:
     /// ```
     /// use neaters::NeuralNetwork;
@@ -257,38 +1432,55 @@ impl NeuralNetwork {
     /// # }
     /// # let file_location = dir.path().join("example-network.nn");
     /// # let path = file_location.as_path().to_str().unwrap();
-    /// # nn2.save_as(path);
-    /// let nn = NeuralNetwork::load_from(path);
+    /// # nn2.save_as(path)?;
+    /// let nn = NeuralNetwork::load_from(path)?;
     /// // Now you can use the network to compute some output.
     /// # dir.close().unwrap();
+    /// # Ok::<(), neaters::Error>(())
     /// ```
+    #[cfg(feature = "std-fs")]
     // TODO: add compute usage example after compute functionality has been added.
-    pub fn load_from(at: &str) -> Self {
-        let bytes = NeuralNetwork::load_bytes_from(at);
+    pub fn load_from<P: AsRef<Path>>(at: P) -> Result<Self, Error> {
+        let bytes = NeuralNetwork::load_bytes_from(at)?;
         NeuralNetwork::create_from_bytes(bytes)
     }
 
+    /// Reads a network from the given reader, e.g. an in-memory buffer or a network stream, which
+    /// contains the data written by `save_to_writer` or `save_as`.
+    ///
+    /// Returns an error if the data cannot be read, if it cannot be decoded or if it does not
+    /// contain a valid network.
+    pub fn load_from_reader<R: Read>(mut reader: R) -> Result<Self, Error> {
+        let mut buffer: Vec<u8> = Vec::new();
+        reader.read_to_end(&mut buffer)?;
+        NeuralNetwork::create_from_bytes(buffer)
+    }
+
     /// This function loads the raw bytes from a file at the speficied location. It should not be
     /// used directly by the user. Use `NeuralNetwork::load_from(path)` instead.
+    #[cfg(feature = "std-fs")]
     // NOTE: should this be public?
     // NOTE: should we use BufReader or just a normal read from a File?
-    pub fn load_bytes_from(at: &str) -> Vec<u8> {
-        // TODO: handle io errors
-        let file = OpenOptions::new().read(true).open(at).unwrap();
+    pub fn load_bytes_from<P: AsRef<Path>>(at: P) -> Result<Vec<u8>, Error> {
+        let file = OpenOptions::new().read(true).open(at)?;
         let mut buf_reader = BufReader::new(file);
         let mut buffer: Vec<u8> = Vec::new();
-        buf_reader.read_to_end(&mut buffer).unwrap();
-        buffer
+        buf_reader.read_to_end(&mut buffer)?;
+        Ok(buffer)
     }
 
-    /// Assign a fitness to this neural network.
-    pub fn assign_fitness(&mut self, f: f32) {
-        self.fitness = f;
+    /// Creates a network from its json representation, see `to_json`.
+    ///
+    /// Returns an error if the json cannot be decoded or if it does not contain a valid network.
+    #[cfg(feature = "serde_json")]
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        let mut decoded: Self = serde_json::from_str(json)?;
+        decoded.mark_bias_node();
+        decoded.check_structure()?;
+        Ok(decoded)
     }
 
-    fn create_from_bytes(bytes: Vec<u8>) -> Self {
-        // TODO: handle serialization errors
-        let decoded: Self = bincode::deserialize(&bytes).unwrap();
-        decoded
+    pub(crate) fn create_from_bytes(bytes: Vec<u8>) -> Result<Self, Error> {
+        NeuralNetwork::from_bytes(&bytes)
     }
 }
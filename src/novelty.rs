@@ -0,0 +1,55 @@
+/// Returns the euclidean distance between two behavior descriptors.
+pub(crate) fn behavior_distance(a: &[f32], b: &[f32]) -> f32 {
+    a.iter()
+        .zip(b)
+        .map(|(a, b)| (a - b) * (a - b))
+        .sum::<f32>()
+        .sqrt()
+}
+
+/// Computes the novelty of every behavior, which is the average distance to its `k` nearest
+/// neighbours among the other behaviors and the behaviors stored in the archive.
+///
+/// A behavior without any neighbour has a novelty of **0.0**.
+pub(crate) fn novelty_scores(behaviors: &[&[f32]], archive: &[Vec<f32>], k: usize) -> Vec<f32> {
+    let mut distances: Vec<f32> = Vec::with_capacity(behaviors.len() + archive.len());
+    behaviors
+        .iter()
+        .enumerate()
+        .map(|(i, behavior)| {
+            distances.clear();
+            for (j, other) in behaviors.iter().enumerate() {
+                if i != j {
+                    distances.push(behavior_distance(behavior, other));
+                }
+            }
+            for other in archive.iter() {
+                distances.push(behavior_distance(behavior, other));
+            }
+            let k = k.min(distances.len());
+            if k == 0 {
+                return 0.0;
+            }
+            distances.select_nth_unstable_by(k - 1, f32::total_cmp);
+            distances[..k].iter().sum::<f32>() / k as f32
+        })
+        .collect()
+}
+
+/// Scales the values linearly into the range `[0, 1]`, if all values are the same they become
+/// **0.0**.
+pub(crate) fn normalize(values: &[f32]) -> Vec<f32> {
+    let min = values.iter().copied().fold(f32::INFINITY, f32::min);
+    let max = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let range = max - min;
+    values
+        .iter()
+        .map(|value| {
+            if range > 0.0 {
+                (value - min) / range
+            } else {
+                0.0
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,61 @@
+use crate::neuralnetwork::NeuralNetwork;
+use crate::solver::Solver;
+use crate::species::SpeciesInfo;
+use crate::train::GenerationStats;
+use std::ops::ControlFlow;
+
+/// Callbacks which are invoked by `Solver::train_with_observer` during the training.
+///
+/// Every callback has an empty default implementation, so only the interesting ones need to be
+/// implemented. Observers can be used to log the progress, to store checkpoints or to stop the
+/// training early.
+///
+/// # Example
+/// ```rust
+/// use neaters::{GenerationStats, NeuralNetwork, Solver, StopReason, TrainOptions, TrainingObserver};
+/// use std::ops::ControlFlow;
+///
+/// struct StopAfter(usize);
+///
+/// impl TrainingObserver for StopAfter {
+///     fn on_generation_end(&mut self, _: &Solver, stats: &GenerationStats) -> ControlFlow<()> {
+///         println!("generation {}: {}", stats.generation, stats.best_fitness);
+///         if stats.generation + 1 >= self.0 {
+///             ControlFlow::Break(())
+///         } else {
+///             ControlFlow::Continue(())
+///         }
+///     }
+/// }
+///
+/// let mut solver = Solver::with_size(1, 1, 10);
+/// let report = solver.train_with_observer(
+///     &|nn: &mut NeuralNetwork| nn.compute([0.5])[0],
+///     &TrainOptions::default(),
+///     &mut StopAfter(3),
+/// );
+/// assert_eq!(report.stop_reason, StopReason::Observer);
+/// assert_eq!(report.history.len(), 3);
+/// ```
+pub trait TrainingObserver {
+    /// Called after a generation has been evaluated and its statistics have been recorded, before
+    /// the next generation is created. Returning `ControlFlow::Break` stops the training.
+    fn on_generation_end(&mut self, solver: &Solver, stats: &GenerationStats) -> ControlFlow<()> {
+        let _ = (solver, stats);
+        ControlFlow::Continue(())
+    }
+
+    /// Called whenever a network is better than every network of the previous generations.
+    fn on_new_best(&mut self, nn: &NeuralNetwork) {
+        let _ = nn;
+    }
+
+    /// Called for every species which goes extinct, either because it stagnated or because no
+    /// network of the generation belongs to it anymore.
+    fn on_species_extinct(&mut self, species: &SpeciesInfo) {
+        let _ = species;
+    }
+}
+
+/// The observer which ignores every callback, used by `Solver::train`.
+impl TrainingObserver for () {}
@@ -0,0 +1,90 @@
+use crate::neuralnetwork::NeuralNetwork;
+use std::fmt;
+
+/// Whether an offspring is accepted by an `OffspringValidator`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OffspringVerdict {
+    /// The offspring becomes part of the next generation.
+    Accept,
+    /// The offspring is discarded and created again from newly selected parents.
+    Reject,
+}
+
+/// Enforces domain constraints on the offspring of the solver, e.g. a maximum number of hidden
+/// nodes or connections which must never leave certain inputs, see
+/// `Solver::set_offspring_validator`.
+///
+/// The validator is invoked for every offspring after its crossover and mutation. It can
+/// repair the offspring, e.g. by disabling forbidden connections with `NeuralNetwork::edit`, and
+/// accept it, or reject it so it is created again. The elites, the initial networks and networks
+/// which are inserted into the solver are not validated.
+///
+/// Every closure taking a `&mut NeuralNetwork` and returning an `OffspringVerdict` is a validator
+/// as well.
+///
+/// # Example
+/// ```rust
+/// use neaters::{NeuralNetwork, OffspringValidator, OffspringVerdict};
+/// struct MaxHiddenNodes(usize);
+///
+/// impl OffspringValidator for MaxHiddenNodes {
+///     fn validate(&self, offspring: &mut NeuralNetwork) -> OffspringVerdict {
+///         if offspring.hidden_node_count() <= self.0 {
+///             OffspringVerdict::Accept
+///         } else {
+///             OffspringVerdict::Reject
+///         }
+///     }
+/// }
+///
+/// let mut nn = NeuralNetwork::with_size(1, 1);
+/// assert_eq!(MaxHiddenNodes(0).validate(&mut nn), OffspringVerdict::Accept);
+/// ```
+pub trait OffspringValidator {
+    /// Checks and possibly repairs the offspring.
+    fn validate(&self, offspring: &mut NeuralNetwork) -> OffspringVerdict;
+}
+
+impl<F> OffspringValidator for F
+where
+    F: Fn(&mut NeuralNetwork) -> OffspringVerdict,
+{
+    fn validate(&self, offspring: &mut NeuralNetwork) -> OffspringVerdict {
+        self(offspring)
+    }
+}
+
+/// The validator of a solver, which is not stored together with the solver.
+#[derive(Default)]
+pub(crate) struct ValidatorSlot(Option<Box<dyn OffspringValidator + Send + Sync>>);
+
+impl ValidatorSlot {
+    /// Constructor for the slot of the validator.
+    pub(crate) fn new(validator: Option<Box<dyn OffspringValidator + Send + Sync>>) -> Self {
+        ValidatorSlot(validator)
+    }
+
+    /// Validates the offspring, every offspring is accepted without a validator.
+    pub(crate) fn check(&self, offspring: &mut NeuralNetwork) -> OffspringVerdict {
+        match &self.0 {
+            Some(validator) => validator.validate(offspring),
+            None => OffspringVerdict::Accept,
+        }
+    }
+}
+
+impl fmt::Debug for ValidatorSlot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(_) => f.write_str("Some(OffspringValidator)"),
+            None => f.write_str("None"),
+        }
+    }
+}
+
+// Validators cannot be compared and are not part of the stored solver, so all slots are equal
+impl PartialEq for ValidatorSlot {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
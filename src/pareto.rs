@@ -0,0 +1,94 @@
+/// Returns whether the objectives `a` dominate the objectives `b`: `a` is at least as good in
+/// every objective and better in at least one of them. Higher values are better.
+pub(crate) fn dominates(a: &[f32], b: &[f32]) -> bool {
+    a.iter().zip(b).all(|(a, b)| a >= b) && a.iter().zip(b).any(|(a, b)| a > b)
+}
+
+/// Sorts the given objective vectors into Pareto fronts. The first front contains the indices of
+/// all vectors which are not dominated by any other vector, the second front those which are only
+/// dominated by the first front and so on.
+pub(crate) fn non_dominated_fronts(objectives: &[&[f32]]) -> Vec<Vec<usize>> {
+    let n = objectives.len();
+    // the indices dominated by each vector and the number of vectors dominating each vector
+    let mut dominated: Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut domination_count: Vec<usize> = vec![0; n];
+    for i in 0..n {
+        for j in (i + 1)..n {
+            if dominates(objectives[i], objectives[j]) {
+                dominated[i].push(j);
+                domination_count[j] += 1;
+            } else if dominates(objectives[j], objectives[i]) {
+                dominated[j].push(i);
+                domination_count[i] += 1;
+            }
+        }
+    }
+    let mut fronts: Vec<Vec<usize>> = Vec::new();
+    let mut current: Vec<usize> = (0..n).filter(|&i| domination_count[i] == 0).collect();
+    while !current.is_empty() {
+        let mut next: Vec<usize> = Vec::new();
+        for &i in current.iter() {
+            for &j in dominated[i].iter() {
+                domination_count[j] -= 1;
+                if domination_count[j] == 0 {
+                    next.push(j);
+                }
+            }
+        }
+        fronts.push(current);
+        current = next;
+    }
+    fronts
+}
+
+/// Computes the crowding distance of every member of a front, which is the sum of the normalized
+/// distances to the neighbouring members in each objective. The members at the boundary of an
+/// objective get an infinite distance, so they are always preferred.
+pub(crate) fn crowding_distances(objectives: &[&[f32]], front: &[usize]) -> Vec<f32> {
+    let mut distances: Vec<f32> = vec![0.0; front.len()];
+    let dimensions = front.first().map_or(0, |&i| objectives[i].len());
+    let mut order: Vec<usize> = (0..front.len()).collect();
+    let values_per_objective = (0..dimensions).map(|d| {
+        front
+            .iter()
+            .map(|&i| objectives[i][d])
+            .collect::<Vec<f32>>()
+    });
+    for values in values_per_objective {
+        order.sort_by(|&a, &b| values[a].total_cmp(&values[b]));
+        let (first, last) = (order[0], order[order.len() - 1]);
+        distances[first] = f32::INFINITY;
+        distances[last] = f32::INFINITY;
+        let range = values[last] - values[first];
+        if range <= 0.0 {
+            continue;
+        }
+        for window in order.windows(3) {
+            distances[window[1]] += (values[window[2]] - values[window[0]]) / range;
+        }
+    }
+    distances
+}
+
+/// Turns the objectives into a single fitness value per vector, which orders the vectors like
+/// NSGA-II: by their Pareto front first and by their crowding distance within the same front.
+///
+/// The members of the first of `m` fronts get a fitness in the range `[m, m + 0.5]`, the members
+/// of the second front in the range `[m - 1, m - 0.5]` and so on, so the fitness stays positive.
+pub(crate) fn pareto_fitness(objectives: &[&[f32]]) -> Vec<f32> {
+    let fronts = non_dominated_fronts(objectives);
+    let mut fitness: Vec<f32> = vec![0.0; objectives.len()];
+    for (rank, front) in fronts.iter().enumerate() {
+        let base = (fronts.len() - rank) as f32;
+        for (&i, distance) in front.iter().zip(crowding_distances(objectives, front)) {
+            // maps the distance into [0, 0.5], so it never reaches the next front
+            let crowding = if distance.is_infinite() {
+                0.5
+            } else {
+                0.5 * distance / (1.0 + distance)
+            };
+            fitness[i] = base + crowding;
+        }
+    }
+    fitness
+}
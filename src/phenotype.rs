@@ -1,147 +1,803 @@
+use crate::activation::{self, Activation, OutputActivation};
+use crate::compiled::CompiledNetwork;
+#[cfg(feature = "std")]
+use crate::error::Error;
+use crate::float::Float;
 use crate::neuralnetwork::Node;
 use crate::neuralnetwork::NodeType;
 use crate::NeuralNetwork;
-use rustc_hash::{FxHashMap, FxHasher};
-use std::hash::BuildHasherDefault;
+#[cfg(not(feature = "std"))]
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use rustc_hash::FxHashMap;
+
+/// Mapping of node ids to their index, which is the position of the node in the node list of the
+/// network and in all buffers of the phenotype. Node ids are arbitrary unique numbers, they neither
+/// need to be contiguous nor sorted. Without the standard library a `BTreeMap` is used instead of
+/// a hashmap.
+#[cfg(feature = "std")]
+type NodeIndexMap = FxHashMap<usize, usize>;
+#[cfg(not(feature = "std"))]
+type NodeIndexMap = BTreeMap<usize, usize>;
 
 /// Graph representation of NeuralNetwork, used to compute its output.
-#[derive(Debug, PartialEq, Clone)]
-pub struct Phenotype {
+///
+/// A phenotype is created with `NeuralNetwork::phenotype()` and owns all buffers needed for the
+/// computation, so the network itself is only borrowed immutably. This makes it possible to share
+/// one network between multiple threads, each of them computing with its own phenotype.
+///
+/// # Example:
+/// ```
+/// use neaters::NeuralNetwork;
+/// let mut nn = NeuralNetwork::with_size(2, 1);
+/// let expected = nn.compute([0.5, 0.5]);
+/// // the network is only borrowed immutably from now on
+/// let nn = &nn;
+/// std::thread::scope(|s| {
+///     for _ in 0..4 {
+///         s.spawn(|| {
+///             let mut pt = nn.phenotype();
+///             assert_eq!(pt.compute(&[0.5, 0.5]), expected);
+///         });
+///     }
+/// });
+/// ```
+#[derive(Debug, PartialEq)]
+pub struct Phenotype<F = f32> {
     /// EdgeList with the destination and the weight of each edge for each node.
-    edges: Vec<Vec<(usize, f32)>>,
+    pub(crate) edges: Vec<Vec<(usize, F)>>,
+    /// EdgeList of the recurrent edges, they are not part of the topological order because they
+    /// pass on the values of the previous computation.
+    pub(crate) recurrent_edges: Vec<Vec<(usize, F)>>,
+    /// The activation function of each node.
+    pub(crate) activations: Vec<Activation>,
     /// Array used to store and mutate the values of each node.
-    pub node_value_array: Vec<f32>,
+    pub(crate) node_value_array: Vec<F>,
     /// Order in which the nodes need to be processed such that all predecessors of a node have
     /// been processed before it is being processed itself.
-    pub topo_order: Vec<usize>,
+    pub(crate) topo_order: Vec<usize>,
     /// List of indexes of the outputs of the network.
-    outputs: Vec<usize>,
+    pub(crate) outputs: Vec<usize>,
+    /// Whether the outputs are normalized by the softmax function, see `OutputActivation`.
+    pub(crate) softmax: bool,
+    /// The number of regular input nodes without the bias node and the presence indicators.
+    pub(crate) inputs: usize,
+    /// The number of presence indicator nodes, which follow directly after the input nodes.
+    pub(crate) indicators: usize,
+    /// The factor and the offset of every input, see `InputScaling`. Empty if the inputs are not
+    /// scaled.
+    pub(crate) input_scaling: Vec<(F, F)>,
+    /// The node values of the previous computation, empty if there has been none or if the
+    /// network has no recurrent edges.
+    previous_values: Vec<F>,
+    /// Whether the network has any enabled recurrent edge, only then the node values of the
+    /// previous computation need to be stored.
+    pub(crate) recurrent: bool,
+    /// The number of nodes and edges of the network the phenotype has been created from, used to
+    /// detect that it is updated with a different network.
+    pub(crate) genome_size: (usize, usize),
+    /// The version of the genome the phenotype has been created from or last been updated with,
+    /// used to detect that the network has been changed afterwards.
+    pub(crate) genome_version: u64,
+    /// Where the weight of every edge of the network is stored in the edge lists, in the order of
+    /// the edges of the network. Disabled edges are not stored.
+    edge_slots: Vec<Option<EdgeSlot>>,
+}
+
+/// The position of the weight of an edge of the network in the edge lists of a phenotype.
+#[derive(Debug, PartialEq, Clone, Copy)]
+struct EdgeSlot {
+    /// The index of the source node, whose edge list contains the edge.
+    from: usize,
+    /// The position of the edge in the edge list.
+    position: usize,
+    /// Whether the edge is in the list of the recurrent edges.
+    recurrent: bool,
+}
+
+// Cloning into an existing phenotype reuses its buffers, see `PhenotypeArena`
+impl<F: Clone> Clone for Phenotype<F> {
+    fn clone(&self) -> Self {
+        Phenotype {
+            edges: self.edges.clone(),
+            recurrent_edges: self.recurrent_edges.clone(),
+            activations: self.activations.clone(),
+            node_value_array: self.node_value_array.clone(),
+            topo_order: self.topo_order.clone(),
+            outputs: self.outputs.clone(),
+            softmax: self.softmax,
+            inputs: self.inputs,
+            indicators: self.indicators,
+            input_scaling: self.input_scaling.clone(),
+            previous_values: self.previous_values.clone(),
+            recurrent: self.recurrent,
+            genome_size: self.genome_size,
+            genome_version: self.genome_version,
+            edge_slots: self.edge_slots.clone(),
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.edges.clone_from(&source.edges);
+        self.recurrent_edges.clone_from(&source.recurrent_edges);
+        self.activations.clone_from(&source.activations);
+        self.node_value_array.clone_from(&source.node_value_array);
+        self.topo_order.clone_from(&source.topo_order);
+        self.outputs.clone_from(&source.outputs);
+        self.softmax = source.softmax;
+        self.inputs = source.inputs;
+        self.indicators = source.indicators;
+        self.input_scaling.clone_from(&source.input_scaling);
+        self.previous_values.clone_from(&source.previous_values);
+        self.recurrent = source.recurrent;
+        self.genome_size = source.genome_size;
+        self.genome_version = source.genome_version;
+        self.edge_slots.clone_from(&source.edge_slots);
+    }
 }
 
-impl Phenotype {
+impl<F: Float> Phenotype<F> {
     /// Construct a Phenotype from a NeuralNetwork.
     /// At first create an index mapping so that you know which NodeId you need to map to which
-    /// index. For this a fast FxHashMap is being used.
+    /// index. For this a fast FxHashMap is being used. Only the edges refer to nodes by their id,
+    /// everything else of the phenotype uses the index.
     ///
     /// Afterwards initialize an empty EdgeList and construct the outputs index array.
     /// Then populating the EdgeList with the correct values using the index mapping.
     /// At last creating the node value array and computing the topological order.
-    pub fn from_nn(nn: &NeuralNetwork) -> Self {
-        let input_length = nn.size.0;
-        let output_length = nn.size.1;
-        let node_index_map = Phenotype::create_node_index_mapping(&nn.nodes);
-        let mut edges: Vec<Vec<(usize, f32)>> = Vec::with_capacity(nn.nodes.len());
-        for _ in 0..nn.nodes.len() {
-            edges.push(Vec::new());
-        }
-        let mut outputs: Vec<usize> = Vec::with_capacity(output_length);
-        for node in nn.nodes.iter() {
-            match node.node_type {
-                NodeType::Hidden => break,
-                NodeType::Output => outputs.push(*node_index_map.get(&node.id).unwrap()),
-                NodeType::Input => (),
+    pub fn from_nn(nn: &NeuralNetwork<F>) -> Self {
+        let mut pt = Phenotype {
+            edges: Vec::new(),
+            recurrent_edges: Vec::new(),
+            activations: Vec::new(),
+            node_value_array: Vec::new(),
+            topo_order: Vec::new(),
+            outputs: Vec::new(),
+            softmax: false,
+            inputs: 0,
+            indicators: 0,
+            input_scaling: Vec::new(),
+            previous_values: Vec::new(),
+            recurrent: false,
+            genome_size: (0, 0),
+            genome_version: 0,
+            edge_slots: Vec::new(),
+        };
+        pt.rebuild(nn);
+        pt
+    }
+
+    /// Builds the phenotype again for the given network like `from_nn`, but the buffers of the
+    /// phenotype are reused, so this only allocates if the network is bigger than the one the
+    /// buffers have been used for before. The node values of previous computations are dropped.
+    pub(crate) fn rebuild(&mut self, nn: &NeuralNetwork<F>) {
+        let node_index_map = Self::create_node_index_mapping(&nn.nodes);
+        let nodes = nn.nodes.len();
+        // the edge lists of the nodes keep their capacity
+        for edge_lists in [&mut self.edges, &mut self.recurrent_edges] {
+            edge_lists.resize_with(nodes, Vec::new);
+            edge_lists.iter_mut().for_each(Vec::clear);
+        }
+        self.outputs.clear();
+        for (idx, node) in nn.nodes.iter().enumerate() {
+            if node.node_type == NodeType::Output {
+                self.outputs.push(idx);
             }
         }
+        self.edge_slots.clear();
         for edge in nn.edges.iter() {
-            let from = *node_index_map.get(&edge.from).unwrap();
-            let to = *node_index_map.get(&edge.to).unwrap();
-            if edge.enabled {
-                edges[from].push((to, edge.weight));
-            }
+            let from = node_index_map[&edge.from];
+            let to = node_index_map[&edge.to];
+            let slot = if edge.enabled {
+                let list = if edge.recurrent {
+                    &mut self.recurrent_edges[from]
+                } else {
+                    &mut self.edges[from]
+                };
+                list.push((to, edge.weight));
+                Some(EdgeSlot {
+                    from,
+                    position: list.len() - 1,
+                    recurrent: edge.recurrent,
+                })
+            } else {
+                None
+            };
+            self.edge_slots.push(slot);
         }
-        let node_value_array: Vec<f32> = Vec::with_capacity(nn.nodes.len());
-        let topo_order: Vec<usize> = Phenotype::create_topo_order(&edges, input_length);
-        Phenotype {
-            edges,
-            node_value_array,
-            topo_order,
-            outputs,
+        self.activations.clear();
+        self.activations
+            .extend(nn.nodes.iter().map(|node| node.activation));
+        self.node_value_array.clear();
+        self.node_value_array.reserve(nodes);
+        Self::place_nodes(&self.edges, true, &mut self.topo_order);
+        self.recurrent = self.recurrent_edges.iter().any(|edges| !edges.is_empty());
+        self.softmax = nn.output_activation == OutputActivation::Softmax;
+        self.inputs = nn.size.0;
+        self.indicators = if nn.presence_indicators { nn.size.0 } else { 0 };
+        self.input_scaling = nn.input_scaling.coefficients();
+        self.previous_values.clear();
+        self.genome_size = (nodes, nn.edges.len());
+        self.genome_version = nn.genome_version;
+    }
+
+    /// Updates the phenotype after the weight of the edge at the given position of
+    /// `NeuralNetwork::edges` has been changed, which is much faster than building the phenotype
+    /// again. Changes of disabled edges have no effect.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the network has a different number of nodes or edges than the one the phenotype
+    /// has been created from.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::NeuralNetwork;
+    /// let mut nn = NeuralNetwork::with_size(1, 1);
+    /// let mut pt = nn.phenotype();
+    /// # #[cfg(feature = "std")]
+    /// # {
+    /// nn.edit().set_weight(1, 2, -0.5).unwrap();
+    /// pt.update_weight(&nn, 1);
+    /// assert_eq!(pt.compute(&[1.0]), nn.compute([1.0]));
+    /// # }
+    /// ```
+    pub fn update_weight(&mut self, nn: &NeuralNetwork<F>, edge: usize) {
+        assert_eq!(
+            self.genome_size,
+            (nn.nodes.len(), nn.edges.len()),
+            "the phenotype has been created from a different network"
+        );
+        if let Some(slot) = self.edge_slots[edge] {
+            let lists = if slot.recurrent {
+                &mut self.recurrent_edges
+            } else {
+                &mut self.edges
+            };
+            lists[slot.from][slot.position].1 = nn.edges[edge].weight;
+        }
+        self.genome_version = nn.genome_version;
+    }
+
+    /// Updates the phenotype after an edge between existing nodes has been inserted at the given
+    /// position of `NeuralNetwork::edges`, so the phenotype does not need to be built again. The
+    /// topological order is only computed again for a new edge which is not recurrent.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the network without the new edge has a different number of nodes or edges than
+    /// the one the phenotype has been created from, or if the edge references a missing node.
+    pub fn add_edge(&mut self, nn: &NeuralNetwork<F>, edge: usize) {
+        assert_eq!(
+            self.genome_size,
+            (nn.nodes.len(), nn.edges.len() - 1),
+            "the phenotype has been created from a different network"
+        );
+        let new = &nn.edges[edge];
+        let index_of = |id: usize| {
+            nn.nodes
+                .iter()
+                .position(|node| node.id == id)
+                .expect("the edge references a node which does not exist")
+        };
+        let slot = if new.enabled {
+            let (from, to) = (index_of(new.from), index_of(new.to));
+            let in_list = |slot: &EdgeSlot| slot.from == from && slot.recurrent == new.recurrent;
+            // the edge lists are in the order of the edges of the network
+            let position = self.edge_slots[..edge]
+                .iter()
+                .flatten()
+                .filter(|slot| in_list(slot))
+                .count();
+            for slot in self.edge_slots[edge..].iter_mut().flatten() {
+                if in_list(slot) {
+                    slot.position += 1;
+                }
+            }
+            if new.recurrent {
+                self.recurrent_edges[from].insert(position, (to, new.weight));
+                self.recurrent = true;
+            } else {
+                self.edges[from].insert(position, (to, new.weight));
+                Self::place_nodes(&self.edges, true, &mut self.topo_order);
+            }
+            Some(EdgeSlot {
+                from,
+                position,
+                recurrent: new.recurrent,
+            })
+        } else {
+            None
+        };
+        self.edge_slots.insert(edge, slot);
+        self.genome_size.1 += 1;
+        self.genome_version = nn.genome_version;
+    }
+
+    /// Construct a Phenotype from a NeuralNetwork like `from_nn`, but a feed-forward network
+    /// whose edges form a cycle is rejected with `Error::InvalidGenome` instead of computing it in
+    /// an arbitrary order. Such a cycle needs a recurrent edge, see `NeuralNetwork::repair`.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::{NeuralNetwork, Phenotype};
+    /// let nn = NeuralNetwork::with_size(2, 1);
+    /// let mut pt = Phenotype::try_from_nn(&nn)?;
+    /// assert_eq!(pt.compute(&[0.5, 0.5]), nn.phenotype().compute(&[0.5, 0.5]));
+    /// # Ok::<(), neaters::Error>(())
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn try_from_nn(nn: &NeuralNetwork<F>) -> Result<Self, Error> {
+        let mut pt = Self::from_nn(nn);
+        if !nn.recurrent {
+            pt.topo_order = Self::create_topo_order(&pt.edges).map_err(|node| {
+                Error::InvalidGenome(format!(
+                    "node {} is part of a cycle, but the network is not recurrent",
+                    nn.nodes[node].id
+                ))
+            })?;
         }
+        Ok(pt)
     }
 
     /// Function to create the topological order for computation of the network without any
-    /// uncomputed predecessors. Using DFS to create the order.
-    fn create_topo_order(edges: &[Vec<(usize, f32)>], input_nodes: usize) -> Vec<usize> {
-        let mut stack: Vec<usize> = Vec::new();
-        // add every input node (one more than input bc of the constant) to the stack for dfs
-        for i in 0..=input_nodes {
-            stack.push(i);
-        }
-        let mut visited: Vec<bool> = Vec::with_capacity(edges.len());
+    /// uncomputed predecessors. Using Kahn's algorithm to create the order.
+    ///
+    /// Every node without incoming edges is placed first, in the order of the nodes, so the input
+    /// nodes come first. Afterwards a node is placed as soon as all of its predecessors have been
+    /// placed. If the edges form a cycle, its nodes can never be placed and the index of a node on
+    /// the cycle is returned instead.
+    #[cfg(feature = "std")]
+    pub(crate) fn create_topo_order(edges: &[Vec<(usize, F)>]) -> Result<Vec<usize>, usize> {
         let mut order: Vec<usize> = Vec::with_capacity(edges.len());
+        let in_degree = Self::place_nodes(edges, false, &mut order);
+        if order.len() == edges.len() {
+            return Ok(order);
+        }
+        // every node which has not been placed has a predecessor which has not been placed either,
+        // so walking back along them for as many steps as there are nodes ends on a cycle
+        let mut predecessor: Vec<usize> = vec![0; edges.len()];
+        for (from, node_edges) in edges.iter().enumerate() {
+            for &(to, _) in node_edges.iter() {
+                if in_degree[from] > 0 && in_degree[to] > 0 {
+                    predecessor[to] = from;
+                }
+            }
+        }
+        let mut node = in_degree.iter().position(|&d| d > 0).unwrap();
         for _ in 0..edges.len() {
-            visited.push(false);
-            order.push(0);
-        }
-        let mut idx: usize = edges.len() - 1;
-        while !stack.is_empty() {
-            let elem = stack[stack.len() - 1];
-            if visited[elem] {
-                stack.pop();
-                order[idx] = elem;
-                idx = idx.saturating_sub(1);
-                continue;
-            } else {
-                visited[elem] = true;
+            node = predecessor[node];
+        }
+        Err(node)
+    }
+
+    /// Kahn's algorithm, writes the placed nodes into the order and returns the number of incoming
+    /// edges from nodes which have not been placed for every node.
+    ///
+    /// With `break_cycles` the first of the remaining nodes is placed anyway whenever they form a
+    /// cycle, so every node is part of the order exactly once. The values passed on by the edges
+    /// closing a cycle arrive after their destination has been processed.
+    fn place_nodes(
+        edges: &[Vec<(usize, F)>],
+        break_cycles: bool,
+        order: &mut Vec<usize>,
+    ) -> Vec<usize> {
+        let mut in_degree: Vec<usize> = vec![0; edges.len()];
+        for &(to, _) in edges.iter().flatten() {
+            in_degree[to] += 1;
+        }
+        let mut placed: Vec<bool> = in_degree.iter().map(|&d| d == 0).collect();
+        order.clear();
+        order.extend((0..edges.len()).filter(|&n| placed[n]));
+        let mut next = 0;
+        loop {
+            while let Some(&node) = order.get(next) {
+                next += 1;
+                for &(to, _) in edges[node].iter() {
+                    in_degree[to] -= 1;
+                    if in_degree[to] == 0 && !placed[to] {
+                        placed[to] = true;
+                        order.push(to);
+                    }
+                }
             }
-            for edge in edges[elem].iter() {
-                let to = edge.0;
-                if !visited[to] {
-                    stack.push(to);
+            match placed.iter().position(|&p| !p) {
+                Some(node) if break_cycles => {
+                    placed[node] = true;
+                    order.push(node);
                 }
+                _ => return in_degree,
             }
         }
-        order
     }
 
-    /// Creating the node index mapping using a simple and very fast hashmap.
-    fn create_node_index_mapping(nodes: &[Node]) -> FxHashMap<usize, usize> {
-        let mut map: FxHashMap<usize, usize> = FxHashMap::with_capacity_and_hasher(
-            nodes.len(),
-            BuildHasherDefault::<FxHasher>::default(),
+    /// Creating the node index mapping (node id -> index) using a simple and very fast hashmap.
+    pub(crate) fn create_node_index_mapping(nodes: &[Node]) -> NodeIndexMap {
+        let mapping: NodeIndexMap = nodes
+            .iter()
+            .enumerate()
+            .map(|(idx, node)| (node.id, idx))
+            .collect();
+        debug_assert_eq!(mapping.len(), nodes.len(), "node ids need to be unique");
+        mapping
+    }
+
+    /// Computing the output of the network depending on the input values.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of inputs does not match the network.
+    pub fn compute(&mut self, inputs: &[F]) -> Vec<F> {
+        let mut outputs = vec![F::ZERO; self.outputs.len()];
+        self.compute_into(inputs, None, &mut outputs);
+        outputs
+    }
+
+    /// Computing the outputs for multiple inputs one after the other. The buffers of the
+    /// phenotype are reused for every input.
+    pub fn compute_batch(&mut self, inputs: &[Vec<F>]) -> Vec<Vec<F>> {
+        inputs.iter().map(|input| self.compute(input)).collect()
+    }
+
+    /// Computing the output of the network and writing it into the given output slice, which
+    /// needs to have one entry for every output node. Inputs which are absent according to the
+    /// mask are set to **0.0**, see `NeuralNetwork::compute_masked`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of the inputs, of the mask or of the output slice does not match the
+    /// number of inputs or outputs of the network.
+    pub fn compute_into(&mut self, inputs: &[F], mask: Option<&[bool]>, outputs: &mut [F]) {
+        self.compute_logits_into(inputs, mask, outputs);
+        if self.softmax {
+            activation::softmax(outputs);
+        }
+    }
+
+    /// Computing the values of the output nodes like `compute_into`, but without normalizing
+    /// them if the network uses `OutputActivation::Softmax`.
+    pub(crate) fn compute_logits_into(
+        &mut self,
+        inputs: &[F],
+        mask: Option<&[bool]>,
+        outputs: &mut [F],
+    ) {
+        assert_eq!(
+            outputs.len(),
+            self.outputs.len(),
+            "the output buffer needs exactly one entry for every output"
         );
-        for (idx, node) in nodes.iter().enumerate() {
-            map.insert(idx, node.id);
+        assert_eq!(
+            inputs.len(),
+            self.inputs,
+            "the input needs exactly one entry for every input"
+        );
+        if let Some(mask) = mask {
+            assert_eq!(
+                mask.len(),
+                self.inputs,
+                "the mask needs exactly one entry for every input"
+            );
+        }
+        self.propagate(inputs, mask, None);
+        for (output, o_idx) in outputs.iter_mut().zip(self.outputs.iter()) {
+            *output = self.node_value_array[*o_idx];
         }
-        map
     }
 
-    /// Computing the output of the network depending on the input values.
+    /// Propagating the input values through the network.
     ///
-    /// At first filling the node values, then traversing the network in topological order.
-    /// For each node in the beginning calculate the sigmoid value of its own value and then for
+    /// At first filling the node values (including the presence indicators) and adding the values
+    /// of the previous computation over the recurrent edges, then traversing the network in
+    /// topological order.
+    /// For each node in the beginning apply its activation function to its own value and then for
     /// each edge of that node add the edge weight times the node's value to the destination node.
-    pub fn compute(&mut self, inputs: Vec<f32>) -> Vec<f32> {
-        let mut outputs: Vec<f32> = Vec::with_capacity(self.outputs.len());
-        self.node_value_array.push(1.0);
-        let input_length = inputs.len();
-        for input in inputs.into_iter() {
-            self.node_value_array.push(input);
+    ///
+    /// The node value array is filled in place, so it only allocates for the first computation.
+    /// The sums of the nodes before their activation function is applied are written into `sums`
+    /// if it is given.
+    fn propagate(&mut self, inputs: &[F], mask: Option<&[bool]>, mut sums: Option<&mut [F]>) {
+        let present = |i: usize| mask.is_none_or(|m| m[i]);
+        self.node_value_array.clear();
+        self.node_value_array.push(F::ONE);
+        for (i, input) in inputs.iter().enumerate() {
+            let value = match self.input_scaling.get(i) {
+                Some(&(factor, offset)) => *input * factor + offset,
+                None => *input,
+            };
+            self.node_value_array
+                .push(if present(i) { value } else { F::ZERO });
+        }
+        for i in 0..self.indicators {
+            self.node_value_array
+                .push(if present(i) { F::ONE } else { F::ZERO });
         }
-        for _ in (input_length + 1)..self.edges.len() {
-            self.node_value_array.push(0.0);
+        self.node_value_array.resize(self.edges.len(), F::ZERO);
+        for (from, value) in self.previous_values.iter().enumerate() {
+            for (to, weight) in self.recurrent_edges[from].iter() {
+                self.node_value_array[*to] += *weight * *value;
+            }
         }
         for node in self.topo_order.iter() {
-            self.node_value_array[*node] = sigmoid(self.node_value_array[*node]);
+            if let Some(sums) = sums.as_deref_mut() {
+                sums[*node] = self.node_value_array[*node];
+            }
+            self.node_value_array[*node] =
+                self.activations[*node].apply(self.node_value_array[*node]);
+            let value = self.node_value_array[*node];
             for (to, weight) in self.edges[*node].iter() {
-                self.node_value_array[*to] += *weight * self.node_value_array[*node];
+                self.node_value_array[*to] += *weight * value;
             }
         }
-        for o_idx in self.outputs.iter() {
-            outputs.push(self.node_value_array[*o_idx]);
+        if self.recurrent {
+            self.previous_values.clone_from(&self.node_value_array);
         }
-        outputs
     }
 
-    /// Reset the phenotype for reused computation
-    pub fn reset(&mut self) {
-        self.node_value_array.clear();
+    /// Computes the outputs for the inputs like `compute` and adds the gradient of the loss with
+    /// respect to the weight of every edge to `gradients`, which has one entry for every edge of
+    /// `NeuralNetwork::edges`. The loss gets the outputs, writes its gradient with respect to them
+    /// into the given slice and returns its value, which is returned as well.
+    ///
+    /// The values passed on by recurrent edges are treated as constants, so disabled and
+    /// recurrent edges get no gradient.
+    #[cfg(feature = "std")]
+    pub(crate) fn backpropagate(
+        &mut self,
+        inputs: &[F],
+        loss: impl FnOnce(&[F], &mut [F]) -> F,
+        gradients: &mut [F],
+    ) -> F {
+        assert_eq!(
+            inputs.len(),
+            self.inputs,
+            "the input needs exactly one entry for every input"
+        );
+        let mut delta = vec![F::ZERO; self.edges.len()];
+        self.propagate(inputs, None, Some(&mut delta));
+        let sums = delta.clone();
+        let mut outputs: Vec<F> = self
+            .outputs
+            .iter()
+            .map(|&idx| self.node_value_array[idx])
+            .collect();
+        if self.softmax {
+            activation::softmax(&mut outputs);
+        }
+        let mut output_gradient = vec![F::ZERO; outputs.len()];
+        let value = loss(&outputs, &mut output_gradient);
+        if self.softmax {
+            // the gradient with respect to the values before the normalization
+            let mut dot = F::ZERO;
+            for (&g, &y) in output_gradient.iter().zip(outputs.iter()) {
+                dot += g * y;
+            }
+            for (g, &y) in output_gradient.iter_mut().zip(outputs.iter()) {
+                *g = y * (*g - dot);
+            }
+        }
+        delta.fill(F::ZERO);
+        for (&idx, &g) in self.outputs.iter().zip(output_gradient.iter()) {
+            delta[idx] += g;
+        }
+        // afterwards every entry is the gradient with respect to the sum of the node
+        for &node in self.topo_order.iter().rev() {
+            let mut d = delta[node];
+            for &(to, weight) in self.edges[node].iter() {
+                d += weight * delta[to];
+            }
+            delta[node] = d * self.activations[node].derivative(sums[node]);
+        }
+        for (gradient, slot) in gradients.iter_mut().zip(self.edge_slots.iter()) {
+            if let Some(slot) = slot.filter(|slot| !slot.recurrent) {
+                let to = self.edges[slot.from][slot.position].0;
+                *gradient += delta[to] * self.node_value_array[slot.from];
+            }
+        }
+        value
+    }
+
+    /// Generates the source code of a standalone Rust function computing the outputs, see
+    /// `NeuralNetwork::codegen_rust`.
+    ///
+    /// The function executes the same steps as `propagate` as straight-line code on an array of
+    /// node values, with the weights and activation functions baked in.
+    pub(crate) fn codegen_rust(&self) -> String {
+        let nodes = self.edges.len();
+        let float = F::NAME;
+        let mut code = String::new();
+        if self.recurrent {
+            code.push_str(&format!(
+                "pub fn forward(inputs: [{float}; {}], state: &mut [{float}; {}]) -> [{float}; {}] {{\n",
+                self.inputs,
+                nodes,
+                self.outputs.len()
+            ));
+        } else {
+            code.push_str(&format!(
+                "pub fn forward(inputs: [{float}; {}]) -> [{float}; {}] {{\n",
+                self.inputs,
+                self.outputs.len()
+            ));
+        }
+        code.push_str(&format!("    let mut v = [0.0{float}; {}];\n", nodes));
+        code.push_str("    v[0] = 1.0;\n");
+        for i in 0..self.inputs {
+            let input = match self.input_scaling.get(i) {
+                Some(&(factor, offset)) => {
+                    format!("inputs[{}] * {} + {}", i, literal(factor), literal(offset))
+                }
+                None => format!("inputs[{}]", i),
+            };
+            code.push_str(&format!("    v[{}] = {};\n", i + 1, input));
+        }
+        for i in 0..self.indicators {
+            code.push_str(&format!("    v[{}] = 1.0;\n", self.inputs + i + 1));
+        }
+        for (from, edges) in self.recurrent_edges.iter().enumerate() {
+            for (to, weight) in edges.iter() {
+                code.push_str(&format!(
+                    "    v[{}] += {} * state[{}];\n",
+                    to,
+                    literal(*weight),
+                    from
+                ));
+            }
+        }
+        for &node in self.topo_order.iter() {
+            let value = format!("v[{}]", node);
+            let activated = self.activations[node].rust_expression(&value);
+            if activated != value {
+                code.push_str(&format!("    {} = {};\n", value, activated));
+            }
+            for (to, weight) in self.edges[node].iter() {
+                code.push_str(&format!(
+                    "    v[{}] += {} * {};\n",
+                    to,
+                    literal(*weight),
+                    value
+                ));
+            }
+        }
+        if self.recurrent {
+            code.push_str("    *state = v;\n");
+        }
+        let outputs: Vec<String> = self
+            .outputs
+            .iter()
+            .map(|idx| format!("v[{}]", idx))
+            .collect();
+        if self.softmax {
+            code.push_str(&format!("    let mut o = [{}];\n", outputs.join(", ")));
+            code.push_str(&format!(
+                "    let m = o.iter().fold({float}::NEG_INFINITY, |m, &x| m.max(x));\n"
+            ));
+            code.push_str(&format!("    let mut s = 0.0{float};\n"));
+            code.push_str("    for x in o.iter_mut() {\n        *x = (*x - m).exp();\n        s += *x;\n    }\n");
+            code.push_str("    for x in o.iter_mut() {\n        *x /= s;\n    }\n");
+            code.push_str("    o\n}\n");
+        } else {
+            code.push_str(&format!("    [{}]\n}}\n", outputs.join(", ")));
+        }
+        code
+    }
+
+    /// Forget the values of the previous computation, see `NeuralNetwork::reset_state`.
+    pub fn reset_state(&mut self) {
+        self.previous_values.clear();
+    }
+}
+
+/// A pool of the buffers of phenotypes which are not needed anymore, so building the phenotypes of
+/// many networks one after the other reuses the buffers instead of allocating new ones.
+///
+/// A phenotype consists of many small vectors, one edge list for every node. Building a new
+/// phenotype for every network of a big population in every generation puts a lot of pressure on
+/// the allocator, so the `Solver` passes the phenotypes of a generation on to the next one.
+///
+/// # Example:
+/// ```
+/// use neaters::{NeuralNetwork, PhenotypeArena};
+/// let mut arena = PhenotypeArena::new();
+/// let population: Vec<NeuralNetwork> = (0..10).map(|_| NeuralNetwork::with_size(2, 1)).collect();
+/// for nn in population.iter() {
+///     let mut pt = arena.phenotype(nn);
+///     assert_eq!(pt.compute(&[0.5, 0.5]).len(), 1);
+///     arena.recycle(pt);
+/// }
+/// // all networks have used the buffers of the first one
+/// assert_eq!(arena.len(), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct PhenotypeArena<F = f32> {
+    /// The phenotypes whose buffers can be reused.
+    spare: Vec<Phenotype<F>>,
+}
+
+impl<F: Float> PhenotypeArena<F> {
+    /// Constructor for an empty arena, the first phenotypes are allocated as usual.
+    pub fn new() -> Self {
+        PhenotypeArena { spare: Vec::new() }
+    }
+
+    /// Returns the phenotype of the network like `NeuralNetwork::phenotype`, built in the buffers
+    /// of a recycled phenotype if there is one.
+    pub fn phenotype(&mut self, nn: &NeuralNetwork<F>) -> Phenotype<F> {
+        match self.spare.pop() {
+            Some(mut pt) => {
+                pt.rebuild(nn);
+                pt
+            }
+            None => Phenotype::from_nn(nn),
+        }
+    }
+
+    /// Returns a copy of the phenotype without the node values of previous computations, built in
+    /// the buffers of a recycled phenotype if there is one.
+    pub fn copy(&mut self, pt: &Phenotype<F>) -> Phenotype<F> {
+        let mut copy = match self.spare.pop() {
+            Some(mut copy) => {
+                copy.clone_from(pt);
+                copy
+            }
+            None => pt.clone(),
+        };
+        copy.node_value_array.clear();
+        copy.reset_state();
+        copy
+    }
+
+    /// Compiles the network like `NeuralNetwork::compile`, the phenotype needed for it is built
+    /// in recycled buffers and recycled again afterwards.
+    pub fn compile(&mut self, nn: &NeuralNetwork<F>) -> CompiledNetwork<F> {
+        let pt = self.phenotype(nn);
+        let compiled = CompiledNetwork::from_phenotype(&pt);
+        self.recycle(pt);
+        compiled
+    }
+
+    /// Keeps the buffers of a phenotype which is not needed anymore for the next phenotype.
+    pub fn recycle(&mut self, pt: Phenotype<F>) {
+        self.spare.push(pt);
+    }
+
+    /// Returns the number of phenotypes whose buffers can be reused.
+    pub fn len(&self) -> usize {
+        self.spare.len()
+    }
+
+    /// Returns whether there are no buffers to reuse.
+    pub fn is_empty(&self) -> bool {
+        self.spare.is_empty()
+    }
+}
+
+impl<F: Float> Default for PhenotypeArena<F> {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
-// we can also choose another activation function
-/// Sigmoid activation function
-/// Approximated by x/(1+|x|)
-pub fn sigmoid(x: f32) -> f32 {
-    x / (1.0 + x.abs())
+// The buffers of an arena never change any result, so all arenas are equal
+impl<F> PartialEq for PhenotypeArena<F> {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+/// Returns the Rust literal of the value, which also works for infinite values and NaN.
+fn literal<F: Float>(x: F) -> String {
+    if x.is_finite() {
+        format!("{:?}", x)
+    } else if x.is_nan() {
+        format!("{}::NAN", F::NAME)
+    } else if x > F::ZERO {
+        format!("{}::INFINITY", F::NAME)
+    } else {
+        format!("{}::NEG_INFINITY", F::NAME)
+    }
 }
@@ -0,0 +1,165 @@
+use crate::evaluator::FitnessEvaluator;
+use crate::neuralnetwork::NeuralNetwork;
+
+/// The acceleration of gravity in m/s².
+const GRAVITY: f32 = 9.8;
+/// The mass of the cart in kg.
+const CART_MASS: f32 = 1.0;
+/// The mass of the pole in kg.
+const POLE_MASS: f32 = 0.1;
+/// Half of the length of the pole in m.
+const POLE_HALF_LENGTH: f32 = 0.5;
+/// The force pushing the cart in N.
+const FORCE: f32 = 10.0;
+/// The duration of one simulation step in s.
+const TIME_STEP: f32 = 0.02;
+/// The simulation fails if the cart leaves the track, which ends at this position in m.
+const TRACK_LIMIT: f32 = 2.4;
+/// The simulation fails if the pole is tilted more than this angle in rad, which is 12°.
+const ANGLE_LIMIT: f32 = 0.209_439_5;
+
+/// Simulation of a pole balanced on a cart, with the same physics as the classic pole-balancing
+/// task: the cart moves on a track of **4.8 m** and is pushed left or right by **10 N** in every
+/// step of **0.02 s**. The simulation has failed once the cart leaves the track or the pole is
+/// tilted by more than **12°**.
+///
+/// # Example:
+/// ```
+/// use neaters::problems::CartPole;
+/// let mut cart = CartPole::new([0.0, 0.0, 0.05, 0.0]);
+/// let mut steps = 0;
+/// // always pushing to the right lets the pole fall over
+/// while cart.step(true) {
+///     steps += 1;
+/// }
+/// assert!(cart.has_failed());
+/// assert!(steps < 100);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CartPole {
+    /// The position of the cart in m, the velocity of the cart in m/s, the angle of the pole in
+    /// rad and the angular velocity of the pole in rad/s.
+    state: [f32; 4],
+}
+
+impl CartPole {
+    /// Constructor for a simulation starting with the given state, which contains the position
+    /// and the velocity of the cart as well as the angle and the angular velocity of the pole.
+    pub fn new(state: [f32; 4]) -> Self {
+        CartPole { state }
+    }
+
+    /// Returns the position and the velocity of the cart as well as the angle and the angular
+    /// velocity of the pole.
+    pub fn state(&self) -> [f32; 4] {
+        self.state
+    }
+
+    /// Returns the state scaled for the inputs of a network: the position and the angle are
+    /// divided by their limits, so they are in the range [-1, 1] until the simulation fails.
+    pub fn inputs(&self) -> [f32; 4] {
+        let [x, x_dot, theta, theta_dot] = self.state;
+        [x / TRACK_LIMIT, x_dot, theta / ANGLE_LIMIT, theta_dot]
+    }
+
+    /// Returns whether the cart has left the track or the pole has fallen over.
+    pub fn has_failed(&self) -> bool {
+        let [x, _, theta, _] = self.state;
+        x.abs() > TRACK_LIMIT || theta.abs() > ANGLE_LIMIT
+    }
+
+    /// Pushes the cart to the right or to the left for one time step and returns whether the
+    /// pole is still balanced afterwards.
+    pub fn step(&mut self, push_right: bool) -> bool {
+        let [x, x_dot, theta, theta_dot] = self.state;
+        let force = if push_right { FORCE } else { -FORCE };
+        let total_mass = CART_MASS + POLE_MASS;
+        let (sin, cos) = theta.sin_cos();
+        let temp =
+            (force + POLE_MASS * POLE_HALF_LENGTH * theta_dot * theta_dot * sin) / total_mass;
+        let theta_acc = (GRAVITY * sin - cos * temp)
+            / (POLE_HALF_LENGTH * (4.0 / 3.0 - POLE_MASS * cos * cos / total_mass));
+        let x_acc = temp - POLE_MASS * POLE_HALF_LENGTH * theta_acc * cos / total_mass;
+        self.state = [
+            x + TIME_STEP * x_dot,
+            x_dot + TIME_STEP * x_acc,
+            theta + TIME_STEP * theta_dot,
+            theta_dot + TIME_STEP * theta_acc,
+        ];
+        !self.has_failed()
+    }
+}
+
+/// Balancing a pole on a cart, the classic control task of the NEAT paper, see `CartPole`.
+///
+/// The network gets the four values of `CartPole::inputs` and pushes the cart to the right if
+/// its first output is positive and to the left otherwise. Every network is simulated once for
+/// every initial state, its state is reset before each run. The fitness is the fraction of the
+/// steps the pole has been balanced, so the maximum fitness of **1.0** is reached by balancing
+/// it for `max_steps` steps in every run.
+///
+/// # Example:
+/// ```
+/// use neaters::problems::CartPoleProblem;
+/// use neaters::{Solver, TrainOptions};
+/// let mut solver = Solver::with_size(4, 1, 100);
+/// let options = TrainOptions {
+///     max_generations: Some(5),
+///     target_fitness: Some(1.0),
+///     ..TrainOptions::default()
+/// };
+/// let report = solver.train(&CartPoleProblem::default(), &options);
+/// assert!(report.champion.fitness() <= 1.0);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct CartPoleProblem {
+    /// The number of steps after which a run is successful.
+    pub max_steps: usize,
+    /// The state every run starts with, see `CartPole::new`.
+    pub initial_states: Vec<[f32; 4]>,
+}
+
+impl Default for CartPoleProblem {
+    /// Runs with **500** steps starting with the pole slightly tilted to either side.
+    fn default() -> Self {
+        CartPoleProblem {
+            max_steps: 500,
+            initial_states: vec![
+                [0.0, 0.0, 0.05, 0.0],
+                [0.0, 0.0, -0.05, 0.0],
+                [-0.5, 0.2, 0.02, -0.1],
+                [0.5, -0.2, -0.02, 0.1],
+            ],
+        }
+    }
+}
+
+impl CartPoleProblem {
+    /// Simulates the network balancing the pole from the given state and returns the number of
+    /// steps until the simulation failed, at most `max_steps`.
+    pub fn balanced_steps(&self, nn: &mut NeuralNetwork, initial_state: [f32; 4]) -> usize {
+        nn.reset_state();
+        let mut cart = CartPole::new(initial_state);
+        for step in 0..self.max_steps {
+            let push_right = nn.compute(cart.inputs())[0] > 0.0;
+            if !cart.step(push_right) {
+                return step;
+            }
+        }
+        self.max_steps
+    }
+}
+
+impl FitnessEvaluator for CartPoleProblem {
+    fn evaluate(&self, nn: &mut NeuralNetwork) -> f32 {
+        if self.initial_states.is_empty() || self.max_steps == 0 {
+            return 0.0;
+        }
+        let steps: usize = self
+            .initial_states
+            .iter()
+            .map(|&state| self.balanced_steps(nn, state))
+            .sum();
+        steps as f32 / (self.max_steps * self.initial_states.len()) as f32
+    }
+}
@@ -0,0 +1,7 @@
+//! Classic problems for the NEAT algorithm with ready-to-use fitness evaluators, they serve as
+//! examples and to check that the solver works.
+mod cartpole;
+pub mod supervised;
+mod xor;
+pub use cartpole::{CartPole, CartPoleProblem};
+pub use xor::XorProblem;
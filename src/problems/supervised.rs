@@ -0,0 +1,792 @@
+//! Fitness evaluators for regression and classification on a dataset of inputs and their
+//! targets, so a supervised problem needs no custom evaluator.
+//!
+//! # Example:
+//! ```
+//! use neaters::problems::supervised::{CsvOptions, Dataset, SupervisedLoss, SupervisedProblem};
+//! use neaters::{Solver, TrainOptions};
+//! let csv = "x,y,target\n0,0,0\n0,1,1\n1,0,1\n1,1,0\n";
+//! let dataset = Dataset::from_csv_reader(csv.as_bytes(), &CsvOptions::default())?;
+//! assert_eq!((dataset.inputs(), dataset.targets()), (2, 1));
+//! let problem = SupervisedProblem::new(dataset, SupervisedLoss::MeanSquaredError);
+//! let mut solver = Solver::with_size(2, 1, 50);
+//! let options = TrainOptions {
+//!     max_generations: Some(5),
+//!     ..TrainOptions::default()
+//! };
+//! let mut report = solver.train(&problem, &options);
+//! assert!(problem.loss(&mut report.champion) < 1.0);
+//! # Ok::<(), neaters::Error>(())
+//! ```
+use crate::error::Error;
+use crate::evaluator::FitnessEvaluator;
+use crate::lineage::GenomeId;
+use crate::local_search::{DifferentiableLoss, MeanSquaredError};
+use crate::neuralnetwork::NeuralNetwork;
+use crate::observer::TrainingObserver;
+use crate::scaling::InputScaling;
+use crate::solver::Solver;
+use crate::train::{GenerationStats, TrainOptions, TrainReport};
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
+use std::io::{BufRead, BufReader, Read};
+use std::ops::ControlFlow;
+#[cfg(feature = "std-fs")]
+use std::path::Path;
+
+/// The smallest probability used by the cross-entropy, so a wrong output with the probability
+/// **0.0** has a finite loss.
+const MIN_PROBABILITY: f32 = 1e-7;
+
+/// Samples of inputs together with the targets a network should output for them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dataset {
+    samples: Vec<(Vec<f32>, Vec<f32>)>,
+    inputs: usize,
+    targets: usize,
+    classes: Vec<String>,
+    input_scaling: InputScaling,
+}
+
+impl Dataset {
+    /// Constructor for a dataset of samples of inputs and their targets. Returns an
+    /// `Error::InvalidDataset` if there are no samples or if the samples have different numbers
+    /// of inputs or targets.
+    pub fn new(samples: Vec<(Vec<f32>, Vec<f32>)>) -> Result<Self, Error> {
+        let (inputs, targets) = match samples.first() {
+            Some((inputs, targets)) => (inputs.len(), targets.len()),
+            None => return Err(Error::InvalidDataset("there are no samples".to_string())),
+        };
+        if let Some(idx) = samples
+            .iter()
+            .position(|(i, t)| i.len() != inputs || t.len() != targets)
+        {
+            return Err(Error::InvalidDataset(format!(
+                "the sample {} has {} inputs and {} targets, but the first one has {} and {}",
+                idx,
+                samples[idx].0.len(),
+                samples[idx].1.len(),
+                inputs,
+                targets
+            )));
+        }
+        Ok(Dataset {
+            samples,
+            inputs,
+            targets,
+            classes: Vec::new(),
+            input_scaling: InputScaling::None,
+        })
+    }
+
+    /// Reads a dataset from comma-separated values, where every row is a sample, see
+    /// `CsvOptions` for how the columns are turned into inputs and targets. Empty rows and rows
+    /// starting with `#` are skipped, values are trimmed and must not contain commas.
+    ///
+    /// Returns an `Error::InvalidDataset` if a value is not a number, if the rows have different
+    /// numbers of columns or if a selected column does not exist.
+    pub fn from_csv_reader<R: Read>(reader: R, options: &CsvOptions) -> Result<Self, Error> {
+        let mut rows = Vec::new();
+        for (idx, line) in BufReader::new(reader).lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if !line.is_empty() && !line.starts_with('#') {
+                let values: Vec<String> = line.split(',').map(|v| v.trim().to_string()).collect();
+                rows.push((idx + 1, values));
+            }
+        }
+        let width = match rows.first() {
+            Some((_, values)) => values.len(),
+            None => return Err(Error::InvalidDataset("there are no samples".to_string())),
+        };
+        let named = options
+            .inputs
+            .iter()
+            .chain(&options.targets)
+            .any(|column| matches!(column, Column::Name(_)));
+        let header = match options.header {
+            Some(header) => header,
+            None if named => true,
+            None => {
+                let (inputs, targets) = options.resolve(width, None)?;
+                let numeric = inputs
+                    .iter()
+                    .chain(targets.iter().filter(|_| !options.one_hot));
+                numeric
+                    .map(|&c| rows[0].1[c].parse::<f32>())
+                    .any(|v| v.is_err())
+            }
+        };
+        let names = match header {
+            true => Some(rows.remove(0).1),
+            false => None,
+        };
+        let (input_columns, target_columns) = options.resolve(width, names.as_deref())?;
+        if options.one_hot && target_columns.len() != 1 {
+            return Err(Error::InvalidDataset(format!(
+                "one-hot encoding needs one target column, but there are {}",
+                target_columns.len()
+            )));
+        }
+        let mut inputs = Vec::with_capacity(rows.len());
+        let mut labels = Vec::with_capacity(rows.len());
+        for (row, values) in rows.iter() {
+            if values.len() != width {
+                return Err(Error::InvalidDataset(format!(
+                    "the row {} has {} columns, but the first one has {}",
+                    row,
+                    values.len(),
+                    width
+                )));
+            }
+            let parse = |column: &usize| {
+                values[*column].parse::<f32>().map_err(|err| {
+                    Error::InvalidDataset(format!(
+                        "the value {:?} in the row {} is not a number: {}",
+                        values[*column], row, err
+                    ))
+                })
+            };
+            inputs.push(
+                input_columns
+                    .iter()
+                    .map(parse)
+                    .collect::<Result<Vec<_>, _>>()?,
+            );
+            match options.one_hot {
+                true => labels.push(vec![]),
+                false => labels.push(target_columns.iter().map(parse).collect::<Result<_, _>>()?),
+            }
+        }
+        let mut classes = Vec::new();
+        if options.one_hot {
+            let column = target_columns[0];
+            classes = rows
+                .iter()
+                .map(|(_, values)| values[column].clone())
+                .collect();
+            classes.sort_by(|a: &String, b| match (a.parse::<f32>(), b.parse::<f32>()) {
+                (Ok(a), Ok(b)) => a.total_cmp(&b),
+                _ => a.cmp(b),
+            });
+            classes.dedup();
+            for ((_, values), target) in rows.iter().zip(labels.iter_mut()) {
+                *target = vec![0.0; classes.len()];
+                target[classes.iter().position(|c| *c == values[column]).unwrap()] = 1.0;
+            }
+        }
+        let mut dataset = Dataset::new(inputs.into_iter().zip(labels).collect())?;
+        dataset.classes = classes;
+        if let Some(seed) = options.shuffle {
+            dataset.samples.shuffle(&mut Pcg64::seed_from_u64(seed));
+        }
+        if let Some(scaling) = options.scaling {
+            let inputs: Vec<Vec<f32>> = dataset.samples.iter().map(|(i, _)| i.clone()).collect();
+            dataset.input_scaling = scaling(&inputs);
+            for (inputs, _) in dataset.samples.iter_mut() {
+                *inputs = dataset.input_scaling.scale(inputs);
+            }
+        }
+        Ok(dataset)
+    }
+
+    /// Reads a dataset from a csv file, see `from_csv_reader`.
+    ///
+    /// # Example:
+    /// ```no_run
+    /// use neaters::problems::supervised::{CsvOptions, Dataset};
+    /// use neaters::InputScaling;
+    /// let options = CsvOptions {
+    ///     targets: vec!["species".into()],
+    ///     one_hot: true,
+    ///     scaling: Some(InputScaling::z_score),
+    ///     shuffle: Some(42),
+    ///     ..CsvOptions::default()
+    /// };
+    /// let dataset = Dataset::from_csv("iris.csv", &options)?;
+    /// println!("{} samples of {} classes", dataset.len(), dataset.classes().len());
+    /// # Ok::<(), neaters::Error>(())
+    /// ```
+    #[cfg(feature = "std-fs")]
+    pub fn from_csv<P: AsRef<Path>>(at: P, options: &CsvOptions) -> Result<Self, Error> {
+        Dataset::from_csv_reader(std::fs::File::open(at)?, options)
+    }
+
+    /// Returns the samples of inputs and their targets.
+    pub fn samples(&self) -> &[(Vec<f32>, Vec<f32>)] {
+        &self.samples
+    }
+
+    /// Returns the number of samples.
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Returns whether the dataset has no samples, which is never the case for a dataset which
+    /// has been constructed with `new`.
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Returns the number of inputs of every sample.
+    pub fn inputs(&self) -> usize {
+        self.inputs
+    }
+
+    /// Returns the number of targets of every sample.
+    pub fn targets(&self) -> usize {
+        self.targets
+    }
+
+    /// Returns the classes of a csv dataset whose targets are one-hot encoded in the order of
+    /// their target, empty for every other dataset.
+    pub fn classes(&self) -> &[String] {
+        &self.classes
+    }
+
+    /// Returns the class of the biggest output of a network, see `classes`. `None` if the
+    /// targets are not one-hot encoded.
+    pub fn class(&self, outputs: &[f32]) -> Option<&str> {
+        let (idx, _) = outputs
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))?;
+        self.classes.get(idx).map(String::as_str)
+    }
+
+    /// Returns the scaling which has been applied to the inputs of a csv dataset, see
+    /// `CsvOptions::scaling`. `InputScaling::None` if the inputs are not scaled.
+    ///
+    /// The samples hold the scaled inputs, so the networks are trained on them. Setting the scaling
+    /// on a trained network with `nn.set_input_scaling(dataset.input_scaling().clone())` lets it
+    /// compute the unscaled inputs of new data without keeping the dataset around.
+    pub fn input_scaling(&self) -> &InputScaling {
+        &self.input_scaling
+    }
+
+    /// Shuffles the samples and splits them into a training set and a validation set, which gets
+    /// the given fraction of the samples. Both sets keep at least one sample.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the dataset has less than 2 samples.
+    pub fn split<R: Rng + ?Sized>(&self, validation_fraction: f32, rng: &mut R) -> (Self, Self) {
+        assert!(
+            self.len() >= 2,
+            "a dataset needs at least 2 samples to be split"
+        );
+        let mut samples = self.samples.clone();
+        samples.shuffle(rng);
+        let validation = (samples.len() as f32 * validation_fraction.clamp(0.0, 1.0)).round();
+        let validation = (validation as usize).clamp(1, samples.len() - 1);
+        let training = samples.split_off(validation);
+        (self.with_samples(training), self.with_samples(samples))
+    }
+
+    /// Partitions the samples in their order into `k` folds of almost equal size and returns a
+    /// training set and a validation set for every fold, where the fold is the validation set and
+    /// the other folds are the training set. The samples should be shuffled before, e.g. by
+    /// `split`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k` is less than 2 or bigger than the number of samples.
+    pub fn folds(&self, k: usize) -> Vec<(Self, Self)> {
+        assert!(
+            (2..=self.len()).contains(&k),
+            "the number of folds has to be between 2 and the number of samples"
+        );
+        let bounds: Vec<usize> = (0..=k).map(|fold| fold * self.len() / k).collect();
+        bounds
+            .windows(2)
+            .map(|fold| {
+                let training = self.samples[..fold[0]]
+                    .iter()
+                    .chain(&self.samples[fold[1]..])
+                    .cloned()
+                    .collect();
+                let validation = self.samples[fold[0]..fold[1]].to_vec();
+                (self.with_samples(training), self.with_samples(validation))
+            })
+            .collect()
+    }
+
+    /// Returns a dataset with the same numbers of inputs and targets, classes and scaling and the
+    /// given samples.
+    fn with_samples(&self, samples: Vec<(Vec<f32>, Vec<f32>)>) -> Self {
+        Dataset {
+            samples,
+            inputs: self.inputs,
+            targets: self.targets,
+            classes: self.classes.clone(),
+            input_scaling: self.input_scaling.clone(),
+        }
+    }
+}
+
+/// A column of a csv file, see `CsvOptions`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Column {
+    /// The column at the index, starting with **0**.
+    Index(usize),
+    /// The column counted from the last one, `Last(0)` is the last column.
+    Last(usize),
+    /// The column with the name in the header.
+    Name(String),
+}
+
+impl From<usize> for Column {
+    fn from(idx: usize) -> Self {
+        Column::Index(idx)
+    }
+}
+
+impl From<&str> for Column {
+    fn from(name: &str) -> Self {
+        Column::Name(name.to_string())
+    }
+}
+
+/// Creates the scaling of the inputs from the inputs of all samples, see `CsvOptions::scaling`.
+pub type ScalingFn = fn(&[Vec<f32>]) -> InputScaling;
+
+/// How the columns of a csv file are read into a dataset, see `Dataset::from_csv`.
+///
+/// # Example
+/// ```rust
+/// use neaters::problems::supervised::{Column, CsvOptions, Dataset};
+/// use neaters::InputScaling;
+/// let csv = "size,weight,colour,fruit\n3,10,red,cherry\n8,110,green,apple\n7,150,red,apple\n";
+/// let options = CsvOptions {
+///     inputs: vec![Column::Index(0), "weight".into()],
+///     targets: vec![Column::Last(0)],
+///     one_hot: true,
+///     scaling: Some(InputScaling::min_max),
+///     ..CsvOptions::default()
+/// };
+/// let dataset = Dataset::from_csv_reader(csv.as_bytes(), &options)?;
+/// assert_eq!(dataset.classes(), ["apple", "cherry"]);
+/// assert_eq!(dataset.samples()[0], (vec![0.0, 0.0], vec![0.0, 1.0]));
+/// assert_eq!(dataset.class(&[0.8, 0.2]), Some("apple"));
+/// // a network trained on the dataset scales new inputs itself
+/// let mut nn = neaters::NeuralNetwork::with_size(2, 2);
+/// let expected = nn.compute([0.0, 0.0]);
+/// nn.set_input_scaling(dataset.input_scaling().clone());
+/// assert_eq!(nn.compute([3.0, 10.0]), expected);
+/// # Ok::<(), neaters::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct CsvOptions {
+    /// The columns of the inputs in their order, every column which is not a target if it is
+    /// empty.
+    pub inputs: Vec<Column>,
+    /// The columns of the targets in their order.
+    pub targets: Vec<Column>,
+    /// Whether the first row is a header with the names of the columns. If it is not set, the
+    /// first row is a header if columns are selected by their name or if it contains a value
+    /// which is not a number in a column which has to be one.
+    pub header: Option<bool>,
+    /// Whether the single target column contains categories, e.g. the names of classes, which
+    /// are encoded as one target per category which is **1.0** for the category of the sample
+    /// and **0.0** otherwise, see `Dataset::classes`.
+    pub one_hot: bool,
+    /// Creates the scaling of the inputs from the inputs of all samples, e.g.
+    /// `InputScaling::min_max` or `InputScaling::z_score`. The inputs are not scaled if it is not
+    /// set, see `Dataset::input_scaling`.
+    pub scaling: Option<ScalingFn>,
+    /// The samples are shuffled with this seed, otherwise they keep the order of the rows.
+    pub shuffle: Option<u64>,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions {
+            inputs: Vec::new(),
+            targets: vec![Column::Last(0)],
+            header: None,
+            one_hot: false,
+            scaling: None,
+            shuffle: None,
+        }
+    }
+}
+
+impl CsvOptions {
+    /// Constructor for options where the last `targets` columns are the targets and all other
+    /// columns are the inputs.
+    pub fn with_targets(targets: usize) -> Self {
+        CsvOptions {
+            targets: (0..targets).rev().map(Column::Last).collect(),
+            ..CsvOptions::default()
+        }
+    }
+
+    /// Returns the indices of the input and target columns of rows with `width` columns.
+    fn resolve(
+        &self,
+        width: usize,
+        names: Option<&[String]>,
+    ) -> Result<(Vec<usize>, Vec<usize>), Error> {
+        let index = |column: &Column| match column {
+            Column::Index(idx) if *idx < width => Ok(*idx),
+            Column::Last(idx) if *idx < width => Ok(width - 1 - idx),
+            Column::Name(name) => names
+                .and_then(|names| names.iter().position(|n| n == name))
+                .ok_or_else(|| {
+                    Error::InvalidDataset(format!("there is no column named {:?}", name))
+                }),
+            _ => Err(Error::InvalidDataset(format!(
+                "there is no column {:?} in rows of {} columns",
+                column, width
+            ))),
+        };
+        let targets = self
+            .targets
+            .iter()
+            .map(index)
+            .collect::<Result<Vec<_>, _>>()?;
+        let inputs: Vec<usize> = match self.inputs.is_empty() {
+            true => (0..width).filter(|c| !targets.contains(c)).collect(),
+            false => self.inputs.iter().map(index).collect::<Result<_, _>>()?,
+        };
+        if inputs.is_empty() || targets.is_empty() {
+            return Err(Error::InvalidDataset(
+                "there has to be at least one input and one target column".to_string(),
+            ));
+        }
+        Ok((inputs, targets))
+    }
+}
+
+/// The loss of the outputs of a network for the targets of a sample.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SupervisedLoss {
+    /// The mean squared difference between the outputs and the targets, for regression.
+    #[default]
+    MeanSquaredError,
+    /// The cross-entropy between the targets and the outputs, which are treated as probabilities,
+    /// for classification. A single output is the probability of the target **1.0**, multiple
+    /// outputs should be normalized by `OutputActivation::Softmax` and compared to one-hot
+    /// targets.
+    CrossEntropy,
+}
+
+impl DifferentiableLoss for SupervisedLoss {
+    fn loss(&self, outputs: &[f32], target: &[f32], gradient: &mut [f32]) -> f32 {
+        match self {
+            SupervisedLoss::MeanSquaredError => MeanSquaredError.loss(outputs, target, gradient),
+            SupervisedLoss::CrossEntropy if outputs.len() == 1 => {
+                let p = outputs[0].clamp(MIN_PROBABILITY, 1.0 - MIN_PROBABILITY);
+                gradient[0] = (1.0 - target[0]) / (1.0 - p) - target[0] / p;
+                -(target[0] * p.ln() + (1.0 - target[0]) * (1.0 - p).ln())
+            }
+            SupervisedLoss::CrossEntropy => {
+                let mut loss = 0.0;
+                for ((output, target), gradient) in outputs.iter().zip(target).zip(gradient) {
+                    let p = output.max(MIN_PROBABILITY);
+                    loss -= target * p.ln();
+                    *gradient = -target / p;
+                }
+                loss
+            }
+        }
+    }
+}
+
+/// A regression or classification problem on a dataset. The fitness of a network is
+/// `1 / (1 + loss)`, where the loss is the mean loss over the training samples, so the fitness
+/// is positive and a perfect network reaches the fitness **1.0**.
+///
+/// A problem with a validation set, see `with_validation`, measures how well the networks
+/// generalize with `validation_loss` without using the validation samples for the fitness.
+///
+/// # Example:
+/// ```
+/// use neaters::problems::supervised::{Dataset, SupervisedLoss, SupervisedProblem};
+/// use neaters::{FitnessEvaluator, NeuralNetwork};
+/// use rand::SeedableRng;
+/// let samples = (0..10).map(|i| (vec![i as f32 / 10.0], vec![0.5])).collect();
+/// let dataset = Dataset::new(samples)?;
+/// let mut rng = rand_pcg::Pcg64::seed_from_u64(0);
+/// let loss = SupervisedLoss::MeanSquaredError;
+/// let problem = SupervisedProblem::with_validation(dataset, loss, 0.2, &mut rng);
+/// assert_eq!(problem.training().len(), 8);
+/// let mut nn = NeuralNetwork::with_size(1, 1);
+/// assert!(problem.evaluate(&mut nn) <= 1.0);
+/// assert!(problem.validation_loss(&mut nn).is_some());
+/// # Ok::<(), neaters::Error>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SupervisedProblem {
+    training: Dataset,
+    validation: Option<Dataset>,
+    loss: SupervisedLoss,
+}
+
+impl SupervisedProblem {
+    /// Constructor for a problem whose fitness uses every sample of the dataset.
+    pub fn new(dataset: Dataset, loss: SupervisedLoss) -> Self {
+        SupervisedProblem {
+            training: dataset,
+            validation: None,
+            loss,
+        }
+    }
+
+    /// Constructor for a problem where the given fraction of the shuffled samples is held back
+    /// as a validation set, see `Dataset::split`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the dataset has less than 2 samples.
+    pub fn with_validation<R: Rng + ?Sized>(
+        dataset: Dataset,
+        loss: SupervisedLoss,
+        validation_fraction: f32,
+        rng: &mut R,
+    ) -> Self {
+        let (training, validation) = dataset.split(validation_fraction, rng);
+        SupervisedProblem {
+            training,
+            validation: Some(validation),
+            loss,
+        }
+    }
+
+    /// Returns one problem for every fold of the k-fold cross-validation of the dataset, see
+    /// `Dataset::folds`. Training a solver on every problem and averaging the validation losses
+    /// of the champions estimates how well the configuration generalizes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k` is less than 2 or bigger than the number of samples.
+    pub fn folds(dataset: &Dataset, loss: SupervisedLoss, k: usize) -> Vec<Self> {
+        dataset
+            .folds(k)
+            .into_iter()
+            .map(|(training, validation)| SupervisedProblem {
+                training,
+                validation: Some(validation),
+                loss,
+            })
+            .collect()
+    }
+
+    /// Returns the samples the fitness is computed on.
+    pub fn training(&self) -> &Dataset {
+        &self.training
+    }
+
+    /// Returns the held back samples, `None` if every sample is used for the fitness.
+    pub fn validation(&self) -> Option<&Dataset> {
+        self.validation.as_ref()
+    }
+
+    /// Returns the mean loss of the network over the training samples.
+    pub fn loss(&self, nn: &mut NeuralNetwork) -> f32 {
+        self.mean_loss(nn, &self.training)
+    }
+
+    /// Returns the mean loss of the network over the validation samples, `None` without a
+    /// validation set.
+    pub fn validation_loss(&self, nn: &mut NeuralNetwork) -> Option<f32> {
+        self.validation
+            .as_ref()
+            .map(|validation| self.mean_loss(nn, validation))
+    }
+
+    /// Returns the fitness of the network on the validation samples, which is
+    /// `1 / (1 + validation_loss)`, `None` without a validation set.
+    pub fn validation_fitness(&self, nn: &mut NeuralNetwork) -> Option<f32> {
+        self.validation_loss(nn).map(|loss| 1.0 / (1.0 + loss))
+    }
+
+    /// Trains the solver on the problem like `Solver::train` and stops early with
+    /// `StopReason::Observer` once the validation fitness of the best network of a generation
+    /// has not improved for `patience` generations, see `EarlyStopping`. The champion of the
+    /// report is the network with the best validation fitness instead of the one with the best
+    /// training fitness, which limits the overfitting of the evolved topologies.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the problem has no validation set or if `patience` is 0.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::problems::supervised::{Dataset, SupervisedLoss, SupervisedProblem};
+    /// use neaters::{Solver, TrainOptions};
+    /// use rand::SeedableRng;
+    /// let samples = (0..20).map(|i| (vec![i as f32 / 20.0], vec![(i % 2) as f32])).collect();
+    /// let dataset = Dataset::new(samples)?;
+    /// let mut rng = rand_pcg::Pcg64::seed_from_u64(0);
+    /// let loss = SupervisedLoss::MeanSquaredError;
+    /// let problem = SupervisedProblem::with_validation(dataset, loss, 0.25, &mut rng);
+    /// let mut solver = Solver::with_size(1, 1, 50);
+    /// let report = problem.train_with_early_stopping(&mut solver, &TrainOptions::default(), 5);
+    /// assert!(report.history.len() <= 100);
+    /// # Ok::<(), neaters::Error>(())
+    /// ```
+    pub fn train_with_early_stopping(
+        &self,
+        solver: &mut Solver,
+        options: &TrainOptions,
+        patience: usize,
+    ) -> TrainReport {
+        let mut stopping = EarlyStopping::new(self, patience);
+        let mut report = solver.train_with_observer(self, options, &mut stopping);
+        if let Some((champion, id, _)) = stopping.best {
+            report.champion = champion;
+            report.champion_id = id;
+        }
+        report
+    }
+
+    /// Returns the mean loss of the network over the samples, the state of recurrent networks is
+    /// reset before every sample.
+    fn mean_loss(&self, nn: &mut NeuralNetwork, dataset: &Dataset) -> f32 {
+        let mut gradient = vec![0.0; dataset.targets];
+        let total: f32 = dataset
+            .samples
+            .iter()
+            .map(|(inputs, target)| {
+                nn.reset_state();
+                let outputs = nn.compute(inputs);
+                self.loss.loss(&outputs, target, &mut gradient)
+            })
+            .sum();
+        total / dataset.len().max(1) as f32
+    }
+}
+
+impl FitnessEvaluator for SupervisedProblem {
+    fn evaluate(&self, nn: &mut NeuralNetwork) -> f32 {
+        1.0 / (1.0 + self.loss(nn))
+    }
+}
+
+/// An observer which tracks the validation fitness of the best network of every generation of a
+/// supervised problem and stops the training once it has not improved for a number of
+/// generations, see `SupervisedProblem::train_with_early_stopping`. It remembers the network
+/// with the best validation fitness, which generalizes better than the final champion if the
+/// networks started to overfit the training samples.
+///
+/// # Example:
+/// ```
+/// use neaters::problems::supervised::{Dataset, EarlyStopping, SupervisedLoss, SupervisedProblem};
+/// use neaters::{Solver, TrainOptions};
+/// use rand::SeedableRng;
+/// let samples = (0..10).map(|i| (vec![i as f32 / 10.0], vec![0.5])).collect();
+/// let dataset = Dataset::new(samples)?;
+/// let mut rng = rand_pcg::Pcg64::seed_from_u64(0);
+/// let loss = SupervisedLoss::MeanSquaredError;
+/// let problem = SupervisedProblem::with_validation(dataset, loss, 0.2, &mut rng);
+/// let mut stopping = EarlyStopping::new(&problem, 3);
+/// let options = TrainOptions {
+///     max_generations: Some(10),
+///     ..TrainOptions::default()
+/// };
+/// let report = Solver::with_size(1, 1, 20).train_with_observer(&problem, &options, &mut stopping);
+/// assert_eq!(stopping.validation_history().len(), report.history.len());
+/// let best = stopping.into_best().unwrap();
+/// # Ok::<(), neaters::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct EarlyStopping<'a> {
+    problem: &'a SupervisedProblem,
+    patience: usize,
+    best: Option<(NeuralNetwork, GenomeId, f32)>,
+    stale: usize,
+    history: Vec<f32>,
+}
+
+impl<'a> EarlyStopping<'a> {
+    /// Constructor for an observer which stops the training once the validation fitness of the
+    /// problem has not improved for `patience` generations. Whether a validation fitness is an
+    /// improvement follows the `Direction` in the config of the solver.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the problem has no validation set or if `patience` is 0.
+    pub fn new(problem: &'a SupervisedProblem, patience: usize) -> Self {
+        assert!(
+            problem.validation.is_some(),
+            "early stopping needs a problem with a validation set"
+        );
+        assert!(
+            patience > 0,
+            "early stopping needs a patience of at least 1"
+        );
+        EarlyStopping {
+            problem,
+            patience,
+            best: None,
+            stale: 0,
+            history: Vec::new(),
+        }
+    }
+
+    /// Returns the validation fitness of the best network of every observed generation.
+    pub fn validation_history(&self) -> &[f32] {
+        &self.history
+    }
+
+    /// Returns the network with the best validation fitness so far.
+    pub fn best(&self) -> Option<&NeuralNetwork> {
+        self.best.as_ref().map(|(nn, _, _)| nn)
+    }
+
+    /// Returns the generation and the id of the network with the best validation fitness.
+    pub fn best_id(&self) -> Option<GenomeId> {
+        self.best.as_ref().map(|(_, id, _)| *id)
+    }
+
+    /// Returns the best validation fitness so far.
+    pub fn best_validation_fitness(&self) -> Option<f32> {
+        self.best.as_ref().map(|(_, _, fitness)| *fitness)
+    }
+
+    /// Returns whether the validation fitness has not improved for `patience` generations.
+    pub fn is_stopped(&self) -> bool {
+        self.stale >= self.patience
+    }
+
+    /// Returns the network with the best validation fitness.
+    pub fn into_best(self) -> Option<NeuralNetwork> {
+        self.best.map(|(nn, _, _)| nn)
+    }
+}
+
+impl TrainingObserver for EarlyStopping<'_> {
+    fn on_generation_end(&mut self, solver: &Solver, stats: &GenerationStats) -> ControlFlow<()> {
+        let direction = solver.config().direction;
+        let Some(best) = solver.networks().iter().max_by(|a, b| {
+            direction
+                .score(a.fitness())
+                .total_cmp(&direction.score(b.fitness()))
+        }) else {
+            return ControlFlow::Continue(());
+        };
+        let mut nn = best.clone();
+        let fitness = self.problem.validation_fitness(&mut nn).unwrap_or(f32::MIN);
+        self.history.push(fitness);
+        if self
+            .best
+            .as_ref()
+            .is_none_or(|(_, _, best)| direction.is_better(fitness, *best))
+        {
+            let id = GenomeId {
+                generation: stats.generation,
+                id: nn.id(),
+            };
+            self.best = Some((nn, id, fitness));
+            self.stale = 0;
+        } else {
+            self.stale += 1;
+        }
+        match self.is_stopped() {
+            true => ControlFlow::Break(()),
+            false => ControlFlow::Continue(()),
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use crate::evaluator::FitnessEvaluator;
+use crate::neuralnetwork::NeuralNetwork;
+
+/// The exclusive or of two inputs, the classic problem of the NEAT paper. It cannot be solved
+/// without a hidden node, so solving it shows that the topology is evolved correctly.
+///
+/// The network gets the inputs **0.0** and **1.0** and should output a value above **0.5** for
+/// true and below **0.5** for false. The fitness is `(4 - error)^2`, where the error is the sum
+/// of the absolute differences to the expected outputs, so the maximum fitness is **16.0**.
+///
+/// # Example:
+/// ```
+/// use neaters::problems::XorProblem;
+/// use neaters::{Solver, TrainOptions};
+/// let mut solver = Solver::with_size(2, 1, 150);
+/// let options = TrainOptions {
+///     max_generations: Some(5),
+///     target_fitness: Some(XorProblem::TARGET_FITNESS),
+///     ..TrainOptions::default()
+/// };
+/// let mut report = solver.train(&XorProblem, &options);
+/// println!("solved: {}", XorProblem::solves(&mut report.champion));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct XorProblem;
+
+impl XorProblem {
+    /// The four inputs of the problem together with the expected output.
+    pub const CASES: [([f32; 2], f32); 4] = [
+        ([0.0, 0.0], 0.0),
+        ([0.0, 1.0], 1.0),
+        ([1.0, 0.0], 1.0),
+        ([1.0, 1.0], 0.0),
+    ];
+
+    /// The fitness of a network whose outputs are **0.4** away from the expected outputs in total.
+    /// Every network reaching it solves the problem, so it is a good choice for
+    /// `TrainOptions::target_fitness`.
+    pub const TARGET_FITNESS: f32 = 12.96;
+
+    /// Returns whether the network outputs the right side of **0.5** for every input.
+    pub fn solves(nn: &mut NeuralNetwork) -> bool {
+        XorProblem::CASES
+            .iter()
+            .all(|(input, expected)| (nn.compute(input)[0] > 0.5) == (*expected > 0.5))
+    }
+}
+
+impl FitnessEvaluator for XorProblem {
+    fn evaluate(&self, nn: &mut NeuralNetwork) -> f32 {
+        let error: f32 = XorProblem::CASES
+            .iter()
+            .map(|(input, expected)| (nn.compute(input)[0] - expected).abs())
+            .sum();
+        (4.0 - error).powi(2)
+    }
+}
@@ -0,0 +1,14 @@
+// Random choices which give the same results on every platform for the same seed.
+use rand::Rng;
+
+/// Returns a random index below `len`, every index is equally likely.
+///
+/// Sampling a `usize` directly uses a different amount of random bits on 32 bit and 64 bit
+/// platforms, so the index is sampled as a `u64` instead.
+///
+/// # Panics
+///
+/// Panics if `len` is **0**.
+pub(crate) fn index<R: Rng + ?Sized>(rng: &mut R, len: usize) -> usize {
+    rng.gen_range(0..len as u64) as usize
+}
@@ -0,0 +1,210 @@
+use crate::float::Float;
+use crate::math;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+/// A pre-processing step which scales every input of a network before it is computed, see
+/// `NeuralNetwork::set_input_scaling`.
+///
+/// The statistics of the inputs are taken from the training data and stored with the genome, so
+/// the code deploying the network computes it with the same scaling without having to remember
+/// them. Inputs which are absent in `compute_masked` stay **0.0** and are not scaled.
+///
+/// # Example
+/// ```rust
+/// use neaters::{InputScaling, NeuralNetwork};
+/// let samples = vec![vec![10.0, -1.0], vec![20.0, 1.0], vec![30.0, 0.0]];
+/// let scaling = InputScaling::min_max(&samples);
+/// assert_eq!(scaling.scale(&[15.0, 0.0]), vec![0.25, 0.5]);
+/// let mut nn = NeuralNetwork::with_size(2, 1);
+/// let expected = nn.compute([0.25, 0.5]);
+/// nn.set_input_scaling(scaling);
+/// assert_eq!(nn.compute([15.0, 0.0]), expected);
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, Default)]
+pub enum InputScaling<F = f32> {
+    /// The inputs are computed unchanged.
+    #[default]
+    None,
+    /// Every input is scaled from the range of its values to **0.0** to **1.0** by
+    /// `(x - min) / (max - min)`. An input whose minimum equals its maximum is only shifted.
+    MinMax {
+        /// The smallest value of every input.
+        min: Vec<F>,
+        /// The largest value of every input.
+        max: Vec<F>,
+    },
+    /// Every input is standardized by `(x - mean) / std_dev`, so it has the mean **0.0** and the
+    /// standard deviation **1.0**. An input whose standard deviation is **0.0** is only shifted.
+    ZScore {
+        /// The mean of every input.
+        mean: Vec<F>,
+        /// The standard deviation of every input.
+        std_dev: Vec<F>,
+    },
+}
+
+impl<F: Float> InputScaling<F> {
+    /// Creates a min-max scaling with the smallest and the largest value of every input in the
+    /// samples.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no sample or if the samples do not have the same length.
+    pub fn min_max(samples: &[Vec<F>]) -> Self {
+        let first = Self::first_sample(samples);
+        let mut min = first.clone();
+        let mut max = first.clone();
+        for sample in samples.iter() {
+            for (i, &x) in sample.iter().enumerate() {
+                if x < min[i] {
+                    min[i] = x;
+                }
+                if x > max[i] {
+                    max[i] = x;
+                }
+            }
+        }
+        InputScaling::MinMax { min, max }
+    }
+
+    /// Creates a z-score scaling with the mean and the standard deviation of every input in the
+    /// samples.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no sample or if the samples do not have the same length.
+    pub fn z_score(samples: &[Vec<F>]) -> Self {
+        let inputs = Self::first_sample(samples).len();
+        let count = samples.len() as f64;
+        let mut mean = alloc::vec![0.0; inputs];
+        for sample in samples.iter() {
+            for (sum, &x) in mean.iter_mut().zip(sample.iter()) {
+                *sum += x.to_f64() / count;
+            }
+        }
+        let mut variance = alloc::vec![0.0; inputs];
+        for sample in samples.iter() {
+            for (i, &x) in sample.iter().enumerate() {
+                let delta = x.to_f64() - mean[i];
+                variance[i] += delta * delta / count;
+            }
+        }
+        InputScaling::ZScore {
+            mean: mean.into_iter().map(F::from_f64).collect(),
+            std_dev: variance
+                .into_iter()
+                .map(|variance| F::from_f64(math::sqrt_f64(variance)))
+                .collect(),
+        }
+    }
+
+    /// Returns the number of inputs the scaling has statistics for, `None` if the inputs are not
+    /// scaled.
+    pub fn inputs(&self) -> Option<usize> {
+        match self {
+            InputScaling::None => None,
+            InputScaling::MinMax { min, .. } => Some(min.len()),
+            InputScaling::ZScore { mean, .. } => Some(mean.len()),
+        }
+    }
+
+    /// Returns the scaled inputs, the inputs are returned unchanged by `InputScaling::None`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of inputs does not match the statistics of the scaling.
+    pub fn scale(&self, inputs: &[F]) -> Vec<F> {
+        let coefficients = self.coefficients();
+        if coefficients.is_empty() {
+            return inputs.to_vec();
+        }
+        assert_eq!(
+            inputs.len(),
+            coefficients.len(),
+            "the scaling needs exactly one entry for every input"
+        );
+        inputs
+            .iter()
+            .zip(coefficients.iter())
+            .map(|(&x, &(factor, offset))| x * factor + offset)
+            .collect()
+    }
+
+    /// Returns the factor and the offset of every input, so the scaled input is
+    /// `x * factor + offset`. The list is empty if the inputs are not scaled.
+    pub(crate) fn coefficients(&self) -> Vec<(F, F)> {
+        let (shifts, ranges) = match self {
+            InputScaling::None => return Vec::new(),
+            InputScaling::MinMax { min, max } => (
+                min,
+                min.iter()
+                    .zip(max.iter())
+                    .map(|(&min, &max)| max - min)
+                    .collect(),
+            ),
+            InputScaling::ZScore { mean, std_dev } => (mean, std_dev.clone()),
+        };
+        shifts
+            .iter()
+            .zip(ranges)
+            .map(|(&shift, range)| {
+                let factor = if range > F::ZERO {
+                    F::ONE / range
+                } else {
+                    F::ONE
+                };
+                (factor, -(shift * factor))
+            })
+            .collect()
+    }
+
+    /// Returns the scaling for a changed input layout with the same mapping as
+    /// `NeuralNetwork::remap_inputs`. The statistics of the new inputs leave them unchanged.
+    #[cfg(feature = "std")]
+    pub(crate) fn remap(&self, mapping: &[Option<usize>]) -> Self {
+        let remap = |values: &[F], identity: F| {
+            mapping
+                .iter()
+                .map(|target| target.map_or(identity, |i| values[i]))
+                .collect()
+        };
+        match self {
+            InputScaling::None => InputScaling::None,
+            InputScaling::MinMax { min, max } => InputScaling::MinMax {
+                min: remap(min, F::ZERO),
+                max: remap(max, F::ONE),
+            },
+            InputScaling::ZScore { mean, std_dev } => InputScaling::ZScore {
+                mean: remap(mean, F::ZERO),
+                std_dev: remap(std_dev, F::ONE),
+            },
+        }
+    }
+
+    /// Converts the statistics to another floating point type, see `NeuralNetwork::cast`.
+    pub(crate) fn cast<G: Float>(&self) -> InputScaling<G> {
+        let cast = |values: &[F]| values.iter().map(|x| G::from_f64(x.to_f64())).collect();
+        match self {
+            InputScaling::None => InputScaling::None,
+            InputScaling::MinMax { min, max } => InputScaling::MinMax {
+                min: cast(min),
+                max: cast(max),
+            },
+            InputScaling::ZScore { mean, std_dev } => InputScaling::ZScore {
+                mean: cast(mean),
+                std_dev: cast(std_dev),
+            },
+        }
+    }
+
+    /// Returns the first of the samples after checking that they all have the same length.
+    fn first_sample(samples: &[Vec<F>]) -> &Vec<F> {
+        let first = samples.first().expect("at least one sample is needed");
+        assert!(
+            samples.iter().all(|sample| sample.len() == first.len()),
+            "every sample needs the same number of inputs"
+        );
+        first
+    }
+}
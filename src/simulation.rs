@@ -0,0 +1,190 @@
+use crate::environment::Environment;
+use crate::solver::Solver;
+use crate::train::GenerationStats;
+
+/// Evaluates the networks of a solver as a population of agents inside a running simulation, like
+/// the game loop of an engine, where every agent acts once per frame instead of running its
+/// episodes to the end.
+///
+/// Every network of the current generation is one agent. In every frame the simulation passes
+/// the observation of every active agent to `act` and applies the returned action, rewards are
+/// added with `reward` and an agent stops with `finish` or after `max_steps` actions. Once every
+/// agent has stopped, `next_generation` assigns the total rewards as fitness and breeds the next
+/// generation of agents. The pool owns the solver, so it can be kept as a resource of the
+/// simulation, e.g. by a plugin system of a game engine which runs every frame.
+///
+/// Simulations which already implement `Environment` for every agent can be stepped with
+/// `step_environments`.
+///
+/// # Example
+/// ```rust
+/// use neaters::{AgentPool, Solver};
+/// let mut pool = AgentPool::new(Solver::with_size(1, 1, 10), 100);
+/// let mut positions = vec![0.0; pool.agents()];
+/// // every iteration is one frame of the simulation
+/// while !pool.is_generation_done() {
+///     for agent in 0..pool.agents() {
+///         if pool.is_active(agent) {
+///             let action = pool.act(agent, &[positions[agent]]);
+///             positions[agent] += action[0] * 0.1;
+///             pool.reward(agent, positions[agent]);
+///             if positions[agent] > 5.0 {
+///                 pool.finish(agent);
+///             }
+///         }
+///     }
+/// }
+/// let stats = pool.next_generation();
+/// assert_eq!(stats.generation, 0);
+/// ```
+#[derive(Debug)]
+pub struct AgentPool {
+    solver: Solver,
+    /// The maximum number of actions of an agent in one generation.
+    max_steps: usize,
+    /// The total reward of every agent in the current generation.
+    rewards: Vec<f32>,
+    /// The number of actions of every agent in the current generation.
+    steps: Vec<usize>,
+    /// Whether every agent is still active in the current generation.
+    active: Vec<bool>,
+    /// The next observation of every agent stepped with `step_environments`, `None` before the
+    /// first step.
+    observations: Vec<Option<Vec<f32>>>,
+}
+
+impl AgentPool {
+    /// Constructor for a pool whose agents are the networks of the current generation of the
+    /// solver, every agent acts at most `max_steps` times per generation.
+    pub fn new(solver: Solver, max_steps: usize) -> Self {
+        let mut pool = AgentPool {
+            solver,
+            max_steps,
+            rewards: Vec::new(),
+            steps: Vec::new(),
+            active: Vec::new(),
+            observations: Vec::new(),
+        };
+        pool.start_generation();
+        pool
+    }
+
+    /// Returns the number of agents, which is the number of networks of the current generation.
+    pub fn agents(&self) -> usize {
+        self.rewards.len()
+    }
+
+    /// Returns whether the agent can still act in the current generation.
+    pub fn is_active(&self, agent: usize) -> bool {
+        self.active[agent]
+    }
+
+    /// Returns whether every agent has stopped, so `next_generation` can be called.
+    pub fn is_generation_done(&self) -> bool {
+        !self.active.contains(&true)
+    }
+
+    /// Computes the action of the agent for its observation. The agent stops after its
+    /// `max_steps`-th action.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the agent has already stopped or if the observation does not have the size of
+    /// the inputs of the networks.
+    pub fn act(&mut self, agent: usize, observation: &[f32]) -> Vec<f32> {
+        assert!(self.active[agent], "agent {} has already stopped", agent);
+        self.steps[agent] += 1;
+        if self.steps[agent] >= self.max_steps {
+            self.active[agent] = false;
+        }
+        self.solver
+            .network_mut(agent)
+            .expect("every agent has a network")
+            .compute(observation)
+    }
+
+    /// Adds the reward to the total reward of the agent in the current generation.
+    pub fn reward(&mut self, agent: usize, reward: f32) {
+        self.rewards[agent] += reward;
+    }
+
+    /// Stops the agent for the current generation, e.g. because it has reached its goal or
+    /// crashed.
+    pub fn finish(&mut self, agent: usize) {
+        self.active[agent] = false;
+    }
+
+    /// Returns the total reward of the agent in the current generation.
+    pub fn total_reward(&self, agent: usize) -> f32 {
+        self.rewards[agent]
+    }
+
+    /// Steps every active agent once in its own environment, the environment of the agent `i` is
+    /// `envs[i]`. An environment is reset before the first step of its agent and the agent stops
+    /// when its episode is done, so every agent acts in one episode per generation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are fewer environments than agents.
+    pub fn step_environments<E: Environment>(&mut self, envs: &mut [E]) {
+        assert!(
+            envs.len() >= self.agents(),
+            "every agent needs an environment"
+        );
+        for (agent, env) in envs.iter_mut().enumerate().take(self.agents()) {
+            if !self.active[agent] {
+                continue;
+            }
+            let observation = match self.observations[agent].take() {
+                Some(observation) => observation,
+                None => env.reset().as_ref().to_vec(),
+            };
+            let action = self.act(agent, &observation);
+            let (next, reward, done) = env.step(&action);
+            self.reward(agent, reward);
+            if done {
+                self.finish(agent);
+            } else {
+                self.observations[agent] = Some(next.as_ref().to_vec());
+            }
+        }
+    }
+
+    /// Assigns the total reward of every agent as the fitness of its network, creates the next
+    /// generation of the solver and starts it with new agents. Agents which are still active are
+    /// stopped.
+    ///
+    /// Returns the statistics of the finished generation, see `Solver::new_generation`.
+    pub fn next_generation(&mut self) -> GenerationStats {
+        for (agent, &reward) in self.rewards.iter().enumerate() {
+            if let Some(nn) = self.solver.network_mut(agent) {
+                nn.set_fitness(reward);
+            }
+        }
+        let stats = self.solver.new_generation();
+        self.start_generation();
+        stats
+    }
+
+    /// Returns the solver of the pool.
+    pub fn solver(&self) -> &Solver {
+        &self.solver
+    }
+
+    /// Returns the solver of the pool, e.g. to save its progress.
+    pub fn into_solver(self) -> Solver {
+        self.solver
+    }
+
+    /// Resets the agents for the networks of the current generation.
+    fn start_generation(&mut self) {
+        let agents = self.solver.networks().len();
+        for nn in self.solver.neural_nets() {
+            nn.reset_state();
+        }
+        self.rewards = vec![0.0; agents];
+        self.steps = vec![0; agents];
+        self.active = vec![self.max_steps > 0; agents];
+        self.observations = vec![None; agents];
+    }
+}
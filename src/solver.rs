@@ -1,10 +1,46 @@
-use crate::config::Config;
-use crate::neuralnetwork::NeuralNetwork;
-use crate::species::Species;
+use crate::config::{Config, RepresentativeSelection, SpeciationMetric};
+#[cfg(feature = "distributed")]
+use crate::distributed::Coordinator;
+use crate::environment::{self, Environment};
+use crate::error::Error;
+#[cfg(feature = "async")]
+use crate::evaluator::AsyncFitnessEvaluator;
+use crate::evaluator::{FitnessEvaluator, MultiObjectiveEvaluator, NoveltyEvaluator};
+#[cfg(feature = "std-fs")]
+use crate::file;
+use crate::format;
+use crate::genome::GeneIndex;
+use crate::hall_of_fame::HallOfFame;
+use crate::innovation::{InnovationMapping, InnovationTracker};
+use crate::lineage::{GenomeId, Lineage, LineageRecord, Origin};
+use crate::local_search::{Inheritance, LocalSearch, LocalSearchSlot};
+use crate::neuralnetwork::{NeuralNetwork, NodeType};
+use crate::novelty;
+use crate::observer::TrainingObserver;
+use crate::offspring::{OffspringValidator, OffspringVerdict, ValidatorSlot};
+use crate::pareto;
+use crate::phenotype::PhenotypeArena;
+use crate::random;
+use crate::species::{Species, SpeciesInfo};
+use crate::train::{
+    CancellationHandle, DiversityMetrics, GenerationStats, StopReason, TrainOptions, TrainReport,
+};
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
+use rustc_hash::FxHashMap;
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "std-fs")]
 use std::fs::OpenOptions;
-use std::io::{BufReader, Read, Write};
+#[cfg(feature = "std-fs")]
+use std::io::BufReader;
+use std::io::{Read, Write};
+#[cfg(feature = "std-fs")]
+use std::path::{Path, PathBuf};
 use std::slice::IterMut;
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use std::time::Instant;
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+use web_time::Instant;
 
 /// This is the main struct being used to train a network for a specific problem.
 ///
@@ -19,8 +55,41 @@ pub struct Solver {
     generation_size: usize,
     generation: usize,
     species: Vec<Species>,
+    next_species_id: usize,
     distance_threshold: f32,
     config: Config,
+    innovations: InnovationTracker,
+    hall_of_fame: HallOfFame,
+    history: Vec<GenerationStats>,
+    /// The behavior descriptors of novel networks of previous generations, see
+    /// `assign_novelty_fitness`.
+    novelty_archive: Vec<Vec<f32>>,
+    /// The inputs on which the outputs of the networks are compared by the behavioral distance of
+    /// the speciation, see `set_probe_inputs`.
+    #[serde(default)]
+    probe_inputs: Vec<Vec<f32>>,
+    /// The species which went extinct in the current generation, they are passed to the
+    /// observer of the training.
+    #[serde(skip)]
+    extinct: Vec<SpeciesInfo>,
+    /// The buffers of the phenotypes of the previous generation, which are reused for the
+    /// phenotypes of the next one.
+    #[serde(skip)]
+    arena: PhenotypeArena,
+    /// The ids of the elites of the current generation together with the fitness they had in the
+    /// previous generation, see `TrainOptions::reevaluate_elites`.
+    #[serde(default)]
+    elite_fitness: Vec<(usize, f32)>,
+    /// The parents and mutations of every network, if the config enables `track_lineage`.
+    #[serde(default)]
+    lineage: Lineage,
+    /// The validator of the offspring, see `set_offspring_validator`.
+    #[serde(skip)]
+    offspring_validator: ValidatorSlot,
+    /// The local search which fine-tunes the best networks, see `set_local_search`.
+    #[serde(skip)]
+    local_search: LocalSearchSlot,
+    rng: Pcg64,
 }
 
 impl Solver {
@@ -40,44 +109,135 @@ impl Solver {
     /// ```
     // TODO: sanitize input (no generation size of 0, ...)
     pub fn with_size(input_nodes: usize, output_nodes: usize, generation_size: usize) -> Self {
+        let config = Config {
+            generation_size,
+            ..Config::default()
+        };
+        Solver::with_config(input_nodes, output_nodes, config)
+    }
+
+    /// Constructor for the given problem with a specific input size, output size and a
+    /// configuration containing all training parameters, including the number of networks in one
+    /// generation.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::{Config, Solver};
+    /// let config = Config::builder()
+    ///     .generation_size(100)
+    ///     .survival_threshold(0.3)
+    ///     .build();
+    /// let solver = Solver::with_config(4, 3, config);
+    /// ```
+    pub fn with_config(input_nodes: usize, output_nodes: usize, config: Config) -> Self {
+        let generation_size = config.generation_size;
+        let mut rng = match config.seed {
+            Some(seed) => Pcg64::seed_from_u64(seed),
+            None => Pcg64::from_entropy(),
+        };
         let mut networks: Vec<NeuralNetwork> = Vec::with_capacity(generation_size);
         for i in 0..generation_size {
-            networks.push(NeuralNetwork::with_size_and_id(
-                input_nodes,
-                output_nodes,
-                i,
-            ));
+            networks.push(
+                NeuralNetwork::with_size_and_id(input_nodes, output_nodes, i)
+                    .with_recurrent(config.recurrent)
+                    .with_node_biases(config.node_biases)
+                    .with_output_activation(config.output_activation)
+                    .with_initial_topology(config.initial_topology, &mut rng)
+                    .with_weight_init(config.weight_init, &mut rng),
+            );
+        }
+        let template = NeuralNetwork::with_size(input_nodes, output_nodes)
+            .with_recurrent(config.recurrent)
+            .with_node_biases(config.node_biases)
+            .with_output_activation(config.output_activation);
+        // the tracker knows every initial connection, even if it has been removed from all networks
+        let innovations = InnovationTracker::for_network(&template);
+        let species = vec![Species::new_with_network(
+            template
+                .with_initial_topology(config.initial_topology, &mut rng)
+                .with_weight_init(config.weight_init, &mut rng),
+            0,
+            0,
+        )];
+        let distance_threshold = config.distance_threshold;
+        let mut lineage = Lineage::default();
+        if config.track_lineage {
+            lineage.insert_generation(0, &networks, Origin::Initial);
         }
-        let species = vec![Species::new_with_network(NeuralNetwork::with_size(
-            input_nodes,
-            output_nodes,
-        ))];
-
-        // TODO: make this variable configurable
-        let distance_threshold = 1.0;
-        let config = Config::default();
         Solver {
             networks,
             network_size: (input_nodes, output_nodes),
             generation_size,
             generation: 0,
             species,
+            next_species_id: 1,
             distance_threshold,
+            hall_of_fame: HallOfFame::with_capacity(config.hall_of_fame_size),
             config,
+            innovations,
+            history: Vec::new(),
+            novelty_archive: Vec::new(),
+            probe_inputs: Vec::new(),
+            extinct: Vec::new(),
+            arena: PhenotypeArena::new(),
+            elite_fitness: Vec::new(),
+            lineage,
+            offspring_validator: ValidatorSlot::default(),
+            local_search: LocalSearchSlot::default(),
+            rng,
         }
     }
 
+    /// Constructor for a solver whose whole population consists of variants of an existing
+    /// network, e.g. a trained network loaded from a file, so the training continues from there
+    /// instead of starting with minimal networks.
+    ///
+    /// The solver gets the size of the network. The first network of the population is an
+    /// unchanged copy of the champion, all others are mutated copies, see `reseed_population`.
+    /// The given generation size replaces the one of the configuration.
+    ///
+    /// Returns `Error::InvalidGenome` if the network is invalid, has presence indicators or has
+    /// recurrent edges while the configuration is not recurrent.
+    ///
+    /// # Example
+    /// ```rust
+    /// use neaters::{Config, NeuralNetwork, Solver};
+    /// let champion = NeuralNetwork::with_size(3, 2);
+    /// # let bytes = champion.as_byte_representation()?;
+    /// # let champion = NeuralNetwork::load_from_reader(&bytes[..])?;
+    /// let mut solver = Solver::from_champion(&champion, 50, Config::default())?;
+    /// assert_eq!(solver.networks().len(), 50);
+    /// assert_eq!(solver.best_network().size(), (3, 2));
+    /// # Ok::<(), neaters::Error>(())
+    /// ```
+    pub fn from_champion(
+        champion: &NeuralNetwork,
+        generation_size: usize,
+        config: Config,
+    ) -> Result<Self, Error> {
+        let config = Config {
+            generation_size,
+            ..config
+        };
+        let (inputs, outputs) = champion.size();
+        let mut solver = Solver::with_config(inputs, outputs, config);
+        solver.reseed_population(champion, 1.0)?;
+        // the first species forms around the champion instead of a minimal network
+        solver.species[0].representative = solver.networks[0].clone();
+        Ok(solver)
+    }
+
     /// Returning the encoded byte representation of the solver. This function is needed in
     /// order to store the solver on a disk, but it should not be used by a client.
+    ///
+    /// The bytes start with a header containing magic bytes and the format version, so that files
+    /// written by other versions of this crate can be detected when loading them. The header also
+    /// contains a checksum, so a truncated or damaged checkpoint is rejected with
+    /// `Error::Corrupted` instead of being loaded.
     // NOTE: should this be public?
-    pub fn as_byte_representation(&self) -> Vec<u8> {
-        // encode neural network as binary
-        let encoded: Vec<u8> = match bincode::serialize(&self) {
-            Ok(bytes) => bytes,
-            // TODO: add clean error handling
-            Err(_) => vec![],
-        };
-        encoded
+    pub fn as_byte_representation(&self) -> Result<Vec<u8>, Error> {
+        let encoded: Vec<u8> = format::encode(self)?;
+        Ok(format::with_header(format::SOLVER_MAGIC, encoded))
     }
 
     /// Saving the solver at the specified file location.
@@ -97,21 +257,42 @@ impl Solver {
     /// # let file_location = dir.path().join("example-solver.sv");
     /// # let path = file_location.as_path().to_str().unwrap();
     /// // This function should be called when the program gets terminated.
-    /// solver.save_as(path);
+    /// solver.save_as(path)?;
     /// # dir.close().unwrap();
+    /// # Ok::<(), neaters::Error>(())
     /// ```
     ///
     /// It is also possible to supply an absolute path instead of a relative path. Everything which
-    /// is being understood by rust's `File::open("path...")` will be fine.
-    pub fn save_as(&self, at: &str) {
-        let encoded = self.as_byte_representation();
-        // TODO: handle errors
-        let mut file = OpenOptions::new()
-            .write(true)
-            .create(true)
-            .open(at)
-            .unwrap();
-        file.write_all(&encoded).unwrap();
+    /// is being understood by rust's `File::open("path...")` will be fine. An error is returned if
+    /// the file cannot be written.
+    ///
+    /// The data is written to a temporary file in the same directory first, which then replaces an
+    /// existing file at once. So a crash while saving never corrupts a previously saved file.
+    ///
+    /// With the `gzip` feature the file is compressed if its name ends with `.gz`, e.g.
+    /// `checkpoint.sv.gz`. Compressed files are detected when they are loaded.
+    #[cfg(feature = "std-fs")]
+    pub fn save_as<P: AsRef<Path>>(&self, at: P) -> Result<(), Error> {
+        let encoded = self.as_byte_representation()?;
+        file::save(at.as_ref(), &encoded)
+    }
+
+    /// Writes the solver into the given writer, e.g. an in-memory buffer or a network stream, in the
+    /// same format as `save_as`.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::Solver;
+    /// let solver = Solver::with_size(2, 1, 5);
+    /// let mut buffer: Vec<u8> = Vec::new();
+    /// solver.save_to_writer(&mut buffer)?;
+    /// assert_eq!(Solver::load_from_reader(&buffer[..])?, solver);
+    /// # Ok::<(), neaters::Error>(())
+    /// ```
+    pub fn save_to_writer<W: Write>(&self, mut writer: W) -> Result<(), Error> {
+        let encoded = self.as_byte_representation()?;
+        writer.write_all(&encoded)?;
+        Ok(())
     }
 
     /// Loading a solver from a file.
@@ -120,6 +301,11 @@ impl Solver {
     /// needed to terminate your program. You can just store the solvers state when terminating and
     /// the load it back into the program when restarting.
     ///
+    /// Returns an error if the file cannot be read, if it cannot be decoded or if it contains an
+    /// invalid network.
+    ///
+    /// Files compressed with the `gzip` feature are decompressed, see `save_as`.
+    ///
     /// # Example:
     /// ```
     /// use neaters::Solver;
@@ -131,28 +317,66 @@ impl Solver {
     /// # }
     /// # let file_location = dir.path().join("example-solver.sv");
     /// # let path = file_location.as_path().to_str().unwrap();
-    /// # nn2.save_as(path);
-    /// let solver = Solver::load_from(path);
+    /// # nn2.save_as(path)?;
+    /// let solver = Solver::load_from(path)?;
     /// // Now you can use the solver to continue training its networks.
     /// # dir.close().unwrap();
+    /// # Ok::<(), neaters::Error>(())
     /// ```
+    #[cfg(feature = "std-fs")]
     // TODO: add compute usage example after compute functionality has been added.
-    pub fn load_from(at: &str) -> Self {
-        let bytes = Solver::load_bytes_from(at);
+    pub fn load_from<P: AsRef<Path>>(at: P) -> Result<Self, Error> {
+        let bytes = Solver::load_bytes_from(at)?;
         Solver::create_from_bytes(bytes)
     }
 
+    /// Reads a solver from the given reader, e.g. an in-memory buffer or a network stream, which
+    /// contains the data written by `save_to_writer` or `save_as`.
+    ///
+    /// Returns an error if the data cannot be read, if it cannot be decoded or if it does not
+    /// contain a valid solver.
+    pub fn load_from_reader<R: Read>(mut reader: R) -> Result<Self, Error> {
+        let mut buffer: Vec<u8> = Vec::new();
+        reader.read_to_end(&mut buffer)?;
+        Solver::create_from_bytes(buffer)
+    }
+
     /// This function loads the raw bytes from a file at the speficied location. It should not be
     /// used directly by the user. Use `Solver::load_from(path)` instead.
+    #[cfg(feature = "std-fs")]
     // NOTE: should this be public?
     // NOTE: should we use BufReader or just a normal read from a File?
-    pub fn load_bytes_from(at: &str) -> Vec<u8> {
-        // TODO: handle io errors
-        let file = OpenOptions::new().read(true).open(at).unwrap();
+    pub fn load_bytes_from<P: AsRef<Path>>(at: P) -> Result<Vec<u8>, Error> {
+        let file = OpenOptions::new().read(true).open(at)?;
         let mut buf_reader = BufReader::new(file);
         let mut buffer: Vec<u8> = Vec::new();
-        buf_reader.read_to_end(&mut buffer).unwrap();
-        buffer
+        buf_reader.read_to_end(&mut buffer)?;
+        Ok(buffer)
+    }
+
+    /// Returns a human-readable json representation of the solver.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::Solver;
+    /// let solver = Solver::with_size(2, 1, 5);
+    /// let json = solver.to_json()?;
+    /// assert_eq!(Solver::from_json(&json)?, solver);
+    /// # Ok::<(), neaters::Error>(())
+    /// ```
+    #[cfg(feature = "serde_json")]
+    pub fn to_json(&self) -> Result<String, Error> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Creates a solver from its json representation, see `to_json`.
+    ///
+    /// Returns an error if the json cannot be decoded or if it contains an invalid network.
+    #[cfg(feature = "serde_json")]
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        let mut decoded: Self = serde_json::from_str(json)?;
+        decoded.prepare_networks()?;
+        Ok(decoded)
     }
 
     /// This function computes the average fitness of a generation and returns it.
@@ -160,7 +384,7 @@ impl Solver {
     /// To have a useful result the fitness of each network must have been computed before.
     pub fn average_fitness(&mut self) -> f32 {
         let sum: f32 = self.networks.iter().fold(0.0, |a, x| a + x.fitness);
-        sum / self.generation_size as f32
+        sum / self.networks.len() as f32
     }
 
     /// This function gives an iterator over all neural networks in one generation. It can be used
@@ -177,108 +401,2092 @@ impl Solver {
     ///     let fitness = 1.5;
     /// }
     /// ```
-    pub fn neural_nets(&mut self) -> IterMut<NeuralNetwork> {
+    pub fn neural_nets(&mut self) -> IterMut<'_, NeuralNetwork> {
         self.networks.iter_mut()
     }
 
-    /// Returns the best network of one generation to use.
+    /// Returns the neural networks of the current generation without borrowing the solver
+    /// mutably, e.g. to evaluate them concurrently on a thread pool without cloning them.
+    ///
+    /// Every thread computes the outputs with its own phenotype of a network, see
+    /// `NeuralNetwork::phenotype`. The fitness values are assigned afterwards with `set_fitness`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use neaters::Solver;
+    /// let mut solver = Solver::with_size(1, 1, 8);
+    /// let results: Vec<(usize, f32)> = std::thread::scope(|s| {
+    ///     let handles: Vec<_> = solver
+    ///         .networks()
+    ///         .chunks(2)
+    ///         .map(|chunk| {
+    ///             s.spawn(move || {
+    ///                 chunk
+    ///                     .iter()
+    ///                     .map(|nn| (nn.id(), nn.phenotype().compute(&[0.5])[0]))
+    ///                     .collect::<Vec<_>>()
+    ///             })
+    ///         })
+    ///         .collect();
+    ///     handles.into_iter().flat_map(|h| h.join().unwrap()).collect()
+    /// });
+    /// for (id, fitness) in results {
+    ///     solver.set_fitness(id, fitness);
+    /// }
+    /// solver.new_generation();
+    /// ```
+    pub fn networks(&self) -> &[NeuralNetwork] {
+        &self.networks
+    }
+
+    /// Returns an iterator over the ids and the neural networks of the current generation. The id
+    /// of a network is its position in the generation, so it can be used as an index into
+    /// external state of the evaluation even if the `id` field of a network has been replaced,
+    /// e.g. by assigning a clone of another network.
+    ///
+    /// # Example
+    /// ```rust
+    /// use neaters::Solver;
+    /// let mut solver = Solver::with_size(1, 1, 4);
+    /// let scores = [0.5, 2.0, 1.0, 1.5];
+    /// for (id, nn) in solver.neural_nets_with_ids() {
+    ///     nn.set_fitness(scores[id]);
+    /// }
+    /// assert_eq!(solver.best_network().fitness(), 2.0);
+    /// ```
+    pub fn neural_nets_with_ids(
+        &mut self,
+    ) -> impl ExactSizeIterator<Item = (usize, &mut NeuralNetwork)> + '_ {
+        self.networks.iter_mut().enumerate()
+    }
+
+    /// Returns a parallel iterator over the ids and the neural networks of the current
+    /// generation like `neural_nets_with_ids`, e.g. to evaluate the networks on multiple threads
+    /// with an evaluation which needs more than a `FitnessEvaluator`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use neaters::Solver;
+    /// use rayon::prelude::*;
+    /// let mut solver = Solver::with_size(1, 1, 100);
+    /// solver.par_neural_nets().for_each(|(id, nn)| {
+    ///     let output = nn.compute([id as f32 / 100.0])[0];
+    ///     nn.set_fitness(output);
+    /// });
+    /// solver.new_generation();
+    /// ```
+    #[cfg(feature = "rayon")]
+    pub fn par_neural_nets(
+        &mut self,
+    ) -> impl rayon::iter::IndexedParallelIterator<Item = (usize, &mut NeuralNetwork)> + '_ {
+        use rayon::prelude::*;
+        self.networks.par_iter_mut().enumerate()
+    }
+
+    /// Returns the network with the given id, which is its position in the current generation,
+    /// or `None` if the generation has fewer networks.
+    pub fn network(&self, id: usize) -> Option<&NeuralNetwork> {
+        self.networks.get(id)
+    }
+
+    /// Returns the network with the given id mutably, see `network`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use neaters::Solver;
+    /// let mut solver = Solver::with_size(1, 1, 3);
+    /// solver.network_mut(1).unwrap().set_fitness(4.0);
+    /// assert_eq!(solver.network(1).unwrap().fitness(), 4.0);
+    /// assert!(solver.network_mut(3).is_none());
+    /// ```
+    pub fn network_mut(&mut self, id: usize) -> Option<&mut NeuralNetwork> {
+        self.networks.get_mut(id)
+    }
+
+    /// Evaluates every network of the current generation with the given evaluator and assigns the
+    /// resulting fitness to it.
+    ///
+    /// The state of recurrent networks is reset before they are evaluated, so every evaluation
+    /// starts without memory of the previous ones.
+    ///
+    /// # Example
+    /// ```rust
+    /// use neaters::{NeuralNetwork, Solver};
+    /// let mut solver = Solver::with_size(1, 1, 10);
+    /// solver.evaluate_generation(&|nn: &mut NeuralNetwork| nn.compute(vec![0.5])[0]);
+    /// solver.new_generation();
+    /// ```
+    pub fn evaluate_generation<E: FitnessEvaluator + ?Sized>(&mut self, evaluator: &E) {
+        for nn in self.networks.iter_mut() {
+            nn.reset_state();
+            let fitness = evaluator.evaluate(nn);
+            nn.set_fitness(fitness);
+        }
+    }
+
+    /// Evaluates every network of the current generation like `evaluate_generation`, but with
+    /// the repeated trials of the options: every network is evaluated `trials_per_network` times
+    /// and its fitness is the `trial_aggregation` of the results. The state of recurrent networks
+    /// is reset before every trial. Without `reevaluate_elites`, the elites which have been copied
+    /// from the previous generation keep their fitness and are not evaluated.
+    ///
+    /// This is how `train` and `train_with_observer` evaluate the generations.
+    ///
+    /// # Example
+    /// ```rust
+    /// use neaters::{NeuralNetwork, Solver, TrainOptions, TrialAggregation};
+    /// use rand::Rng;
+    /// let mut solver = Solver::with_size(1, 1, 10);
+    /// let options = TrainOptions {
+    ///     trials_per_network: 5,
+    ///     trial_aggregation: TrialAggregation::Median,
+    ///     ..TrainOptions::default()
+    /// };
+    /// // a noisy evaluator
+    /// let evaluator = |nn: &mut NeuralNetwork| {
+    ///     nn.compute([0.5])[0] + rand::thread_rng().gen_range(-0.1..0.1)
+    /// };
+    /// solver.evaluate_generation_with_trials(&evaluator, &options);
+    /// solver.new_generation();
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `trials_per_network` is **0**.
+    pub fn evaluate_generation_with_trials<E: FitnessEvaluator + ?Sized>(
+        &mut self,
+        evaluator: &E,
+        options: &TrainOptions,
+    ) {
+        assert!(
+            options.trials_per_network > 0,
+            "every network needs at least one trial"
+        );
+        let kept: FxHashMap<usize, f32> = if options.reevaluate_elites {
+            FxHashMap::default()
+        } else {
+            self.elite_fitness.iter().copied().collect()
+        };
+        let mut fitness: Vec<f32> = Vec::with_capacity(options.trials_per_network);
+        for nn in self.networks.iter_mut() {
+            let aggregated = match kept.get(&nn.id) {
+                Some(&previous) => previous,
+                None => Self::evaluate_trials(nn, evaluator, options, &mut fitness),
+            };
+            nn.set_fitness(aggregated);
+        }
+    }
+
+    /// Evaluates the network `trials_per_network` times, resetting its state before every trial,
+    /// and returns the `trial_aggregation` of the results. The buffer holds the results.
+    fn evaluate_trials<E: FitnessEvaluator + ?Sized>(
+        nn: &mut NeuralNetwork,
+        evaluator: &E,
+        options: &TrainOptions,
+        fitness: &mut Vec<f32>,
+    ) -> f32 {
+        fitness.clear();
+        for _ in 0..options.trials_per_network {
+            nn.reset_state();
+            fitness.push(evaluator.evaluate(nn));
+        }
+        options.trial_aggregation.aggregate(fitness)
+    }
+
+    /// Evaluates every network of the current generation like `evaluate_generation`, but the
+    /// networks are evaluated in parallel on multiple threads.
+    ///
+    /// This is useful if evaluating a single network is expensive, e.g. because it needs to run a
+    /// simulation. The evaluator is shared between the threads, so it has to be `Sync`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use neaters::{NeuralNetwork, Solver};
+    /// let mut solver = Solver::with_size(1, 1, 500);
+    /// solver.evaluate_generation_parallel(&|nn: &mut NeuralNetwork| nn.compute(vec![0.5])[0]);
+    /// solver.new_generation();
+    /// ```
+    #[cfg(feature = "rayon")]
+    pub fn evaluate_generation_parallel<E: FitnessEvaluator + Sync + ?Sized>(
+        &mut self,
+        evaluator: &E,
+    ) {
+        use rayon::prelude::*;
+        self.networks.par_iter_mut().for_each(|nn| {
+            nn.reset_state();
+            let fitness = evaluator.evaluate(nn);
+            nn.set_fitness(fitness);
+        });
+    }
+
+    /// Evaluates every network of the current generation like `evaluate_generation`, but with an
+    /// asynchronous evaluator whose evaluations of all networks run concurrently, e.g. requests
+    /// to a remote simulator or an HTTP service. No thread is blocked while the evaluations wait.
+    ///
+    /// The returned future does not depend on a specific async runtime and completes when every
+    /// network has been evaluated.
+    ///
+    /// # Example
+    /// ```rust
+    /// use neaters::{NeuralNetwork, Solver};
+    /// # fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    /// #     let mut future = std::pin::pin!(future);
+    /// #     let mut context = std::task::Context::from_waker(std::task::Waker::noop());
+    /// #     loop {
+    /// #         if let std::task::Poll::Ready(output) = future.as_mut().poll(&mut context) {
+    /// #             return output;
+    /// #         }
+    /// #     }
+    /// # }
+    /// let mut solver = Solver::with_size(1, 1, 10);
+    /// // the outputs are computed first, the returned future could send them to a server
+    /// let evaluator = |nn: &mut NeuralNetwork| {
+    ///     let output = nn.compute([0.5])[0];
+    ///     async move { output }
+    /// };
+    /// // any executor can run the evaluation, e.g. `tokio` or `futures::executor::block_on`
+    /// block_on(solver.evaluate_generation_async(&evaluator));
+    /// solver.new_generation();
+    /// ```
+    #[cfg(feature = "async")]
+    pub async fn evaluate_generation_async<E: AsyncFitnessEvaluator + ?Sized>(
+        &mut self,
+        evaluator: &E,
+    ) {
+        let evaluations = self.networks.iter_mut().map(|nn| async move {
+            nn.reset_state();
+            let fitness = evaluator.evaluate(nn).await;
+            nn.set_fitness(fitness);
+        });
+        futures_util::future::join_all(evaluations).await;
+    }
+
+    /// Evaluates every network of the current generation like `evaluate_generation`, but on the
+    /// remote workers connected to the coordinator, see the `distributed` module.
+    ///
+    /// Returns an error if a network could not be sent or has been lost by too many workers, see
+    /// `Coordinator::evaluate`.
+    #[cfg(feature = "distributed")]
+    pub fn evaluate_generation_distributed(
+        &mut self,
+        coordinator: &mut Coordinator,
+    ) -> Result<(), Error> {
+        coordinator.evaluate(&mut self.networks)
+    }
+
+    /// Evaluates every network of the current generation by letting it act in the given number
+    /// of episodes of the environment, the fitness is the average total reward of an episode.
+    ///
+    /// In every step the network computes its outputs for the observation and passes them to the
+    /// environment as the action. An episode ends when the environment reports that it is done or
+    /// after `max_steps` steps, the state of the network is reset before every episode.
+    ///
+    /// # Example
+    /// ```rust
+    /// use neaters::{Environment, Solver};
+    /// /// Rewards a high output in the single step of every episode.
+    /// struct HighOutput;
+    ///
+    /// impl Environment for HighOutput {
+    ///     type Observation = Vec<f32>;
+    ///
+    ///     fn reset(&mut self) -> Vec<f32> {
+    ///         vec![0.5]
+    ///     }
+    ///
+    ///     fn step(&mut self, action: &[f32]) -> (Vec<f32>, f32, bool) {
+    ///         (vec![0.5], action[0], true)
+    ///     }
+    /// }
+    ///
+    /// let mut solver = Solver::with_size(1, 1, 10);
+    /// solver.evaluate_in_env(&mut HighOutput, 5, 1);
+    /// solver.new_generation();
+    /// ```
+    pub fn evaluate_in_env<E: Environment + ?Sized>(
+        &mut self,
+        env: &mut E,
+        episodes: usize,
+        max_steps: usize,
+    ) {
+        for nn in self.networks.iter_mut() {
+            let fitness = environment::average_episode_reward(nn, env, episodes, max_steps);
+            nn.set_fitness(fitness);
+        }
+    }
+
+    /// Evaluates every network of the current generation with an evaluator computing multiple
+    /// objectives, then assigns a fitness based on the Pareto ranking of all networks, see
+    /// `assign_pareto_fitness`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use neaters::{NeuralNetwork, Solver};
+    /// let mut solver = Solver::with_size(1, 1, 10);
+    /// // a high output and few edges are both preferred
+    /// solver.evaluate_generation_objectives(&|nn: &mut NeuralNetwork| {
+    ///     vec![nn.compute([0.5])[0], -(nn.edges().len() as f32)]
+    /// });
+    /// assert!(solver.best_network().fitness() >= 1.0);
+    /// solver.new_generation();
+    /// ```
+    pub fn evaluate_generation_objectives<E: MultiObjectiveEvaluator + ?Sized>(
+        &mut self,
+        evaluator: &E,
+    ) {
+        for nn in self.networks.iter_mut() {
+            nn.reset_state();
+            let objectives = evaluator.evaluate(nn);
+            nn.set_objectives(objectives);
+        }
+        self.assign_pareto_fitness();
+    }
+
+    /// Evaluates every network of the current generation like `evaluate_generation_objectives`,
+    /// but the networks are evaluated in parallel on multiple threads.
+    #[cfg(feature = "rayon")]
+    pub fn evaluate_generation_objectives_parallel<E: MultiObjectiveEvaluator + Sync + ?Sized>(
+        &mut self,
+        evaluator: &E,
+    ) {
+        use rayon::prelude::*;
+        self.networks.par_iter_mut().for_each(|nn| {
+            nn.reset_state();
+            let objectives = evaluator.evaluate(nn);
+            nn.set_objectives(objectives);
+        });
+        self.assign_pareto_fitness();
+    }
+
+    /// Assigns a fitness to every network of the current generation which is computed from the
+    /// objectives of all networks, like in NSGA-II.
+    ///
+    /// The networks are sorted into Pareto fronts with non-dominated sorting: a network in the
+    /// first front is not worse than any other network in every objective. Networks in a better
+    /// front always get a higher fitness. Within the same front the networks in less crowded
+    /// regions get a higher fitness, which keeps the front diverse. This way competing objectives
+    /// can be optimized without combining them into a single value by hand.
+    ///
+    /// All networks need to have the same number of objectives, which are set with
+    /// `NeuralNetwork::set_objectives`. Networks without objectives get the fitness `f32::MIN`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use neaters::Solver;
+    /// let mut solver = Solver::with_size(1, 1, 3);
+    /// let objectives = [vec![1.0, 0.0], vec![0.0, 1.0], vec![0.0, 0.0]];
+    /// for (nn, objectives) in solver.neural_nets().zip(objectives) {
+    ///     nn.set_objectives(objectives);
+    /// }
+    /// solver.assign_pareto_fitness();
+    /// let fitness: Vec<f32> = solver.neural_nets().map(|nn| nn.fitness()).collect();
+    /// // the first two networks are not dominated, the last one is dominated by both
+    /// assert_eq!(fitness[0], fitness[1]);
+    /// assert!(fitness[2] < fitness[0]);
+    /// ```
+    pub fn assign_pareto_fitness(&mut self) {
+        let evaluated: Vec<usize> = (0..self.networks.len())
+            .filter(|&idx| !self.networks[idx].objectives.is_empty())
+            .collect();
+        let objectives: Vec<&[f32]> = evaluated
+            .iter()
+            .map(|&idx| self.networks[idx].objectives())
+            .collect();
+        let fitness = pareto::pareto_fitness(&objectives);
+        for nn in self.networks.iter_mut() {
+            nn.set_fitness(f32::MIN);
+        }
+        for (idx, fitness) in evaluated.into_iter().zip(fitness) {
+            self.networks[idx].set_fitness(self.config.direction.score(fitness));
+        }
+    }
+
+    /// Evaluates every network of the current generation with an evaluator computing its fitness
+    /// and its behavior descriptor, then assigns a fitness based on the novelty of the behavior,
+    /// see `assign_novelty_fitness`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use neaters::{NeuralNetwork, Solver};
+    /// let mut solver = Solver::with_size(1, 2, 10);
+    /// solver.evaluate_generation_novelty(&|nn: &mut NeuralNetwork| {
+    ///     let outputs = nn.compute([0.5]);
+    ///     (outputs[0], outputs)
+    /// });
+    /// assert_eq!(solver.novelty_archive().len(), 1);
+    /// solver.new_generation();
+    /// ```
+    pub fn evaluate_generation_novelty<E: NoveltyEvaluator + ?Sized>(&mut self, evaluator: &E) {
+        for nn in self.networks.iter_mut() {
+            nn.reset_state();
+            let (fitness, behavior) = evaluator.evaluate(nn);
+            nn.set_fitness(fitness);
+            nn.set_behavior(behavior);
+        }
+        self.assign_novelty_fitness();
+    }
+
+    /// Evaluates every network of the current generation like `evaluate_generation_novelty`, but
+    /// the networks are evaluated in parallel on multiple threads.
+    #[cfg(feature = "rayon")]
+    pub fn evaluate_generation_novelty_parallel<E: NoveltyEvaluator + Sync + ?Sized>(
+        &mut self,
+        evaluator: &E,
+    ) {
+        use rayon::prelude::*;
+        self.networks.par_iter_mut().for_each(|nn| {
+            nn.reset_state();
+            let (fitness, behavior) = evaluator.evaluate(nn);
+            nn.set_fitness(fitness);
+            nn.set_behavior(behavior);
+        });
+        self.assign_novelty_fitness();
+    }
+
+    /// Replaces the fitness of every network of the current generation by a blend of its fitness
+    /// and the novelty of its behavior.
+    ///
+    /// On deceptive problems following the fitness leads into local optima, rewarding networks
+    /// which behave differently than all networks before explores the problem instead. The
+    /// novelty of a network is the average distance of its behavior descriptor to the
+    /// `novelty_neighbors` nearest descriptors of the other networks and of the novelty archive.
+    /// Afterwards the `novelty_archive_additions` most novel descriptors are added to the archive,
+    /// so revisiting old behaviors is not rewarded in later generations.
+    ///
+    /// Both the fitness and the novelty are scaled into the range `[0, 1]` within the generation
+    /// and then blended using `novelty_weight`. The fitness which has been assigned before is kept
+    /// as the only entry of `NeuralNetwork::objectives`. Networks without a behavior descriptor
+    /// get the fitness `f32::MIN`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use neaters::Solver;
+    /// let mut solver = Solver::with_size(1, 1, 3);
+    /// for (i, nn) in solver.neural_nets().enumerate() {
+    ///     nn.set_fitness(1.0);
+    ///     nn.set_behavior(vec![[0.0, 0.1, 5.0][i]]);
+    /// }
+    /// solver.assign_novelty_fitness();
+    /// // the last network behaves differently than the others
+    /// assert_eq!(solver.best_network().behavior(), [5.0]);
+    /// assert_eq!(solver.novelty_archive(), [vec![5.0]]);
+    /// ```
+    pub fn assign_novelty_fitness(&mut self) {
+        let evaluated: Vec<usize> = (0..self.networks.len())
+            .filter(|&idx| !self.networks[idx].behavior.is_empty())
+            .collect();
+        let behaviors: Vec<&[f32]> = evaluated
+            .iter()
+            .map(|&idx| self.networks[idx].behavior())
+            .collect();
+        let scores = novelty::novelty_scores(
+            &behaviors,
+            &self.novelty_archive,
+            self.config.novelty_neighbors,
+        );
+        let fitness: Vec<f32> = evaluated
+            .iter()
+            .map(|&idx| self.config.direction.score(self.networks[idx].fitness))
+            .collect();
+        let novelty = novelty::normalize(&scores);
+        let fitness = novelty::normalize(&fitness);
+        let weight = self.config.novelty_weight;
+        // the most novel behaviors are added to the archive
+        let mut order: Vec<usize> = (0..evaluated.len()).collect();
+        order.sort_by(|&a, &b| scores[b].total_cmp(&scores[a]));
+        for &i in order.iter().take(self.config.novelty_archive_additions) {
+            self.novelty_archive.push(behaviors[i].to_vec());
+        }
+        for nn in self.networks.iter_mut() {
+            nn.objectives = vec![nn.fitness];
+            nn.set_fitness(f32::MIN);
+        }
+        for (i, idx) in evaluated.into_iter().enumerate() {
+            let blended = (1.0 - weight) * fitness[i] + weight * novelty[i];
+            self.networks[idx].set_fitness(self.config.direction.score(blended));
+        }
+    }
+
+    /// Returns the behavior descriptors in the novelty archive, in the order they have been added.
+    pub fn novelty_archive(&self) -> &[Vec<f32>] {
+        &self.novelty_archive
+    }
+
+    /// Trains the networks until one of the stopping criteria of the options is met.
+    ///
+    /// Every generation is evaluated with the evaluator, then its statistics are recorded and the
+    /// stopping criteria are checked. If the training continues, a new generation is created. When
+    /// the training stops, the last generation stays evaluated, so `best_network()` can be used,
+    /// and the solver is saved at the `checkpoint` of the options. A training running in a server
+    /// or a scheduler can be stopped cleanly with the `cancellation` handle of the options.
+    ///
+    /// # Example
+    /// ```rust
+    /// use neaters::{NeuralNetwork, Solver, StopReason, TrainOptions};
+    /// let mut solver = Solver::with_size(1, 1, 20);
+    /// let options = TrainOptions {
+    ///     max_generations: Some(10),
+    ///     ..TrainOptions::default()
+    /// };
+    /// let report = solver.train(&|nn: &mut NeuralNetwork| nn.compute(vec![0.5])[0], &options);
+    /// assert_eq!(report.stop_reason, StopReason::MaxGenerations);
+    /// assert_eq!(report.history.len(), 10);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `trials_per_network` of the options is **0**.
+    pub fn train<E: FitnessEvaluator + ?Sized>(
+        &mut self,
+        evaluator: &E,
+        options: &TrainOptions,
+    ) -> TrainReport {
+        self.train_with_observer(evaluator, options, &mut ())
+    }
+
+    /// Trains the networks like `train`, additionally invoking the callbacks of the observer.
+    ///
+    /// The observer is informed about every new best network, every extinct species and the end
+    /// of every generation, where it can also stop the training. See `TrainingObserver` for an
+    /// example.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `trials_per_network` of the options is **0**.
+    pub fn train_with_observer<E, O>(
+        &mut self,
+        evaluator: &E,
+        options: &TrainOptions,
+        observer: &mut O,
+    ) -> TrainReport
+    where
+        E: FitnessEvaluator + ?Sized,
+        O: TrainingObserver + ?Sized,
+    {
+        self.train_loop(
+            |solver| {
+                solver.evaluate_generation_with_trials(evaluator, options);
+                solver.fine_tune_generation(evaluator, options);
+            },
+            options,
+            observer,
+        )
+    }
+
+    /// Trains the networks like `train_with_observer`, but every generation is evaluated with
+    /// multiple objectives using `evaluate_generation_objectives`.
+    ///
+    /// The fitness of the networks is derived from their Pareto ranking, so a `target_fitness` of
+    /// the options is not meaningful. The champion of the report is a network of the first Pareto
+    /// front, the whole front can be found by comparing the `objectives()` of the networks.
+    ///
+    /// # Example
+    /// ```rust
+    /// use neaters::{NeuralNetwork, Solver, TrainOptions};
+    /// let mut solver = Solver::with_size(1, 1, 20);
+    /// let options = TrainOptions {
+    ///     max_generations: Some(5),
+    ///     ..TrainOptions::default()
+    /// };
+    /// let evaluator = |nn: &mut NeuralNetwork| vec![nn.compute([0.5])[0], -(nn.nodes().len() as f32)];
+    /// let report = solver.train_multi_objective(&evaluator, &options, &mut ());
+    /// assert_eq!(report.champion.objectives().len(), 2);
+    /// ```
+    pub fn train_multi_objective<E, O>(
+        &mut self,
+        evaluator: &E,
+        options: &TrainOptions,
+        observer: &mut O,
+    ) -> TrainReport
+    where
+        E: MultiObjectiveEvaluator + ?Sized,
+        O: TrainingObserver + ?Sized,
+    {
+        self.train_loop(
+            |solver| solver.evaluate_generation_objectives(evaluator),
+            options,
+            observer,
+        )
+    }
+
+    /// Trains the networks like `train_with_observer`, but every generation is evaluated with
+    /// novelty search using `evaluate_generation_novelty`.
+    ///
+    /// The fitness of the networks is blended with their novelty, so the `target_fitness` of the
+    /// options refers to the blended fitness. The fitness of the evaluator is kept as the only
+    /// entry of `objectives()`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use neaters::{NeuralNetwork, Solver, TrainOptions};
+    /// let mut solver = Solver::with_size(1, 2, 20);
+    /// let options = TrainOptions {
+    ///     max_generations: Some(5),
+    ///     ..TrainOptions::default()
+    /// };
+    /// let evaluator = |nn: &mut NeuralNetwork| {
+    ///     let outputs = nn.compute([0.5]);
+    ///     (outputs[0], outputs)
+    /// };
+    /// solver.train_novelty(&evaluator, &options, &mut ());
+    /// assert_eq!(solver.novelty_archive().len(), 5);
+    /// ```
+    pub fn train_novelty<E, O>(
+        &mut self,
+        evaluator: &E,
+        options: &TrainOptions,
+        observer: &mut O,
+    ) -> TrainReport
+    where
+        E: NoveltyEvaluator + ?Sized,
+        O: TrainingObserver + ?Sized,
+    {
+        self.train_loop(
+            |solver| solver.evaluate_generation_novelty(evaluator),
+            options,
+            observer,
+        )
+    }
+
+    /// The training loop shared by the training functions, which only differ in how a generation
+    /// is evaluated.
+    fn train_loop<F, O>(
+        &mut self,
+        mut evaluate: F,
+        options: &TrainOptions,
+        observer: &mut O,
+    ) -> TrainReport
+    where
+        F: FnMut(&mut Self),
+        O: TrainingObserver + ?Sized,
+    {
+        let start = Instant::now();
+        let mut history: Vec<GenerationStats> = Vec::new();
+        let mut champion: Option<NeuralNetwork> = None;
+        let mut champion_id = GenomeId {
+            generation: self.generation,
+            id: 0,
+        };
+        let mut stagnation = 0;
+        let stop_reason = loop {
+            #[cfg(feature = "tracing")]
+            let _span = tracing::info_span!("generation", generation = self.generation).entered();
+            evaluate(self);
+            let best = self.best_network();
+            let direction = self.config.direction;
+            if champion
+                .as_ref()
+                .is_none_or(|champion| direction.is_better(best.fitness, champion.fitness))
+            {
+                #[cfg(feature = "tracing")]
+                tracing::info!(fitness = best.fitness, "new best network");
+                observer.on_new_best(&best);
+                champion_id = GenomeId {
+                    generation: self.generation,
+                    id: best.id,
+                };
+                champion = Some(best);
+                stagnation = 0;
+            } else {
+                stagnation += 1;
+            }
+            let stats = self.record_generation();
+            self.report_extinct_species(observer);
+            let flow = observer.on_generation_end(self, &stats);
+            history.push(stats);
+            let best_fitness = champion.as_ref().map_or(f32::MIN, |nn| nn.fitness);
+            if options
+                .target_fitness
+                .is_some_and(|target| !direction.is_better(target, best_fitness))
+            {
+                break StopReason::TargetFitness;
+            }
+            if options
+                .max_generations
+                .is_some_and(|max| history.len() >= max)
+            {
+                break StopReason::MaxGenerations;
+            }
+            if options
+                .time_budget
+                .is_some_and(|budget| start.elapsed() >= budget)
+            {
+                break StopReason::TimeBudget;
+            }
+            if options
+                .stagnation_limit
+                .is_some_and(|limit| stagnation >= limit)
+            {
+                break StopReason::Stagnation;
+            }
+            if flow.is_break() {
+                break StopReason::Observer;
+            }
+            if options
+                .cancellation
+                .as_ref()
+                .is_some_and(CancellationHandle::is_cancelled)
+            {
+                break StopReason::Cancelled;
+            }
+            self.advance_generation();
+            self.report_extinct_species(observer);
+        };
+        #[cfg(feature = "std-fs")]
+        let checkpoint_error = options
+            .checkpoint
+            .as_ref()
+            .and_then(|path| self.save_as(path).err());
+        #[cfg(not(feature = "std-fs"))]
+        let checkpoint_error = None;
+        TrainReport {
+            history,
+            stop_reason,
+            champion: champion.expect("at least one generation has been evaluated"),
+            champion_id,
+            elapsed: start.elapsed(),
+            checkpoint_error,
+        }
+    }
+
+    /// Passes the species which went extinct since the last call to the observer.
+    fn report_extinct_species<O: TrainingObserver + ?Sized>(&mut self, observer: &mut O) {
+        for species in self.extinct.drain(..) {
+            observer.on_species_extinct(&species);
+        }
+    }
+
+    /// Sets the fitness of the network with the given id in the current generation.
+    ///
+    /// This is useful if the networks are being evaluated somewhere else and only their ids and
+    /// results are known. Like in `NeuralNetwork::set_fitness` a fitness of NaN is being replaced by
+    /// `f32::MIN`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use neaters::Solver;
+    /// let mut solver = Solver::with_size(1, 1, 3);
+    /// let ids: Vec<usize> = solver.neural_nets().map(|nn| nn.id()).collect();
+    /// for id in ids {
+    ///     solver.set_fitness(id, id as f32);
+    /// }
+    /// assert_eq!(solver.best_network().fitness(), 2.0);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no network with the given id in the current generation.
+    pub fn set_fitness(&mut self, id: usize, fitness: f32) {
+        self.networks
+            .iter_mut()
+            .find(|nn| nn.id == id)
+            .expect("there is no network with this id in the current generation")
+            .set_fitness(fitness);
+    }
+
+    /// Returns the best network of one generation to use, which is the one with the lowest
+    /// fitness if the config minimizes the fitness.
     ///
     /// Note that this function should not be used multiple times as it creates a new NeuralNetwork and could hurt
     /// performance.
     pub fn best_network(&mut self) -> NeuralNetwork {
+        let direction = self.config.direction;
         self.networks
             .iter()
-            .reduce(|a, b| if a > b { a } else { b })
+            .reduce(|a, b| {
+                if direction.is_better(a.fitness, b.fitness) {
+                    a
+                } else {
+                    b
+                }
+            })
             .unwrap()
             .clone()
     }
 
-    /// Create a new generation through speciation, mutation and ?
+    /// Create a new generation through speciation, crossover and mutation.
     ///
     /// 1. group networks by distance threshold (need distance function)
     /// 2. adjust distance threshold for next generation
     /// 3. compute adjusted fitness values
     /// 4. eliminate lower part of each group (proportional to sum of adjusted fitness of one group)
-    /// 5. crossover between two networks
-    /// 6. mutate them (disable Connection, change connection weight, add connection, ... Node ...)
-    // TODO: move steps in to their own functions
-    pub fn new_generation(&mut self) {
+    /// 5. copy the best networks unchanged (elitism), then crossover between two networks
+    /// 6. mutate them (change connection weight, add connection, add node)
+    ///
+    /// The fitness of every network should have been assigned before calling this function. The
+    /// best networks are recorded in the hall of fame before they are replaced. The new generation
+    /// consists of `generation_size` networks whose fitness is reset.
+    ///
+    /// Returns the statistics of the replaced generation, they are also added to the history of
+    /// the solver, see `history()`.
+    pub fn new_generation(&mut self) -> GenerationStats {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("generation", generation = self.generation).entered();
         // 1. group networks by distance threshold
-        self.clear_species();
-        self.group_networks();
-        self.remove_unused_species();
+        let stats = self.record_generation();
+        self.advance_generation();
+        // only the training reports the extinct species
+        self.extinct.clear();
+        stats
+    }
 
-        // 2. TODO: adjust distance threshold for next generation
+    /// Creates the next generation from the networks which have already been grouped into their
+    /// species by `record_generation`.
+    fn advance_generation(&mut self) {
+        self.remove_stagnant_species();
+
+        // 2. adjust distance threshold for next generation
+        self.adjust_distance_threshold();
 
         // 3. compute adjusted fitness values
-        // TODO: could be more efficient using binary search
-        for species in self.species.iter() {
-            let n = species.members.len() as f32;
-            for &network_id in species.members.iter() {
-                self.networks
-                    .iter_mut()
-                    .find(|x| x.id == network_id)
-                    .unwrap()
-                    .fitness /= n;
-            }
-        }
+        let adjusted = self.adjusted_fitness();
 
         // 4. eliminate lower part of each group
-    }
+        let survivors = self.surviving_members(&adjusted);
 
-    fn create_from_bytes(bytes: Vec<u8>) -> Self {
-        // TODO: handle serialization errors
-        let decoded: Self = bincode::deserialize(&bytes).unwrap();
-        decoded
+        if self.config.track_lineage {
+            self.lineage.set_fitness(self.generation, &self.networks);
+        }
+
+        // 5. + 6. copy the elites, then crossover and mutation
+        let elites = self.elites();
+        let offspring =
+            self.offspring_counts(&adjusted, self.generation_size.saturating_sub(elites.len()));
+        self.reproduce(&elites, &survivors, &adjusted, &offspring);
+        self.generation += 1;
     }
 
-    /// Group networks into their species
-    fn group_networks(&mut self) {
-        'outer: for network in self.networks.iter() {
-            for species in self.species.iter_mut() {
-                let dist = Solver::distance(&species.representative, network, &self.config);
-                if dist <= self.distance_threshold {
-                    species.members.push(network.id);
-                    continue 'outer;
+    /// Groups the evaluated networks into their species and records the generation in the hall
+    /// of fame and in the history.
+    fn record_generation(&mut self) -> GenerationStats {
+        self.clear_species();
+        self.group_networks();
+        self.remove_unused_species();
+        self.update_representatives();
+        self.update_species_fitness();
+        self.hall_of_fame
+            .update(&self.networks, self.config.direction);
+        let species_sizes = self.species.iter().map(|s| s.members.len()).collect();
+        let stats = GenerationStats::compute(
+            self.generation,
+            &self.networks,
+            species_sizes,
+            self.diversity_metrics(),
+            self.config.direction,
+        );
+        #[cfg(feature = "tracing")]
+        tracing::info!(
+            best_fitness = stats.best_fitness,
+            average_fitness = stats.average_fitness,
+            species = stats.species_count,
+            "generation evaluated"
+        );
+        // the last generation of `train` is recorded again if the training is continued
+        match self.history.last_mut() {
+            Some(last) if last.generation == self.generation => *last = stats.clone(),
+            _ => self.history.push(stats.clone()),
+        }
+        stats
+    }
+
+    /// Adds `n` output nodes to every network of the solver.
+    ///
+    /// This is useful when the problem gains new actions during training. The hidden structure of
+    /// the networks is being preserved and the new outputs get the same ids and innovation numbers
+    /// in every network, so the networks can still be compared and crossed over.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::Solver;
+    /// let mut solver = Solver::with_size(2, 1, 10);
+    /// solver.add_outputs(1);
+    /// for nn in solver.neural_nets() {
+    ///     assert_eq!(nn.compute(vec![0.1, 0.2]).len(), 2);
+    /// }
+    /// ```
+    pub fn add_outputs(&mut self, n: usize) {
+        let ids: Vec<usize> = (0..n).map(|_| self.innovations.new_node_id()).collect();
+        for nn in self
+            .networks
+            .iter_mut()
+            .chain(self.species.iter_mut().map(|s| &mut s.representative))
+        {
+            nn.add_outputs(&ids, &mut self.innovations, &mut self.rng);
+        }
+        self.network_size.1 += n;
+    }
+
+    /// Removes the output node with the given id from every network of the solver.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the networks have no output node with the given id.
+    pub fn remove_output(&mut self, id: usize) {
+        for nn in self
+            .networks
+            .iter_mut()
+            .chain(self.species.iter_mut().map(|s| &mut s.representative))
+        {
+            nn.remove_output(id);
+        }
+        self.network_size.1 -= 1;
+    }
+
+    /// Inserts a network which has been created outside of the solver into the current
+    /// generation, e.g. a network trained in a previous run. It replaces the network with the
+    /// lowest fitness and takes over its id, which is returned. The elites copied from the
+    /// previous generation are only replaced if there is no other network.
+    ///
+    /// The nodes and edges of the network get the node ids and innovation numbers of this solver,
+    /// so it can be compared and crossed over with the other networks. Its fitness is reset, so
+    /// it needs to be evaluated like every other network of the generation.
+    ///
+    /// Returns `Error::InvalidGenome` and leaves the generation unchanged if the network is
+    /// invalid or does not fit the solver: it needs the same numbers of inputs and outputs, and
+    /// recurrent edges are only allowed if the solver is recurrent. Networks with different
+    /// inputs can be adjusted with `NeuralNetwork::remap_inputs` first.
+    ///
+    /// # Example
+    /// ```rust
+    /// use neaters::{NeuralNetwork, Solver};
+    /// let mut solver = Solver::with_size(2, 1, 10);
+    /// let id = solver.insert_network(NeuralNetwork::with_size(2, 1))?;
+    /// assert!(solver.networks().iter().any(|nn| nn.id() == id));
+    /// assert!(solver.insert_network(NeuralNetwork::with_size(3, 1)).is_err());
+    /// # Ok::<(), neaters::Error>(())
+    /// ```
+    pub fn insert_network(&mut self, nn: NeuralNetwork) -> Result<usize, Error> {
+        let nn = self.adopt(&nn)?;
+        Ok(self.replace_worst(nn))
+    }
+
+    /// Merges the innovation numbers and node ids of another solver into this one, e.g. of a
+    /// separate run or of another island, so its networks can be inserted with
+    /// `insert_network_from` and crossed over with the networks of this solver later.
+    ///
+    /// The input and output nodes are matched by their position. A hidden node which the other
+    /// solver created by splitting a connection is matched with the node this solver created by
+    /// splitting the same connection, or it gets a new id which is given to this split from now
+    /// on. So networks which evolved the same structure independently share their genes after
+    /// they have been inserted.
+    ///
+    /// Returns `Error::InvalidGenome` if the networks of the solvers do not have the same numbers
+    /// of inputs and outputs or differ in their presence indicators.
+    ///
+    /// # Example
+    /// ```rust
+    /// use neaters::{Config, NeuralNetwork, Solver};
+    /// let config = Config::builder().add_node_rate(0.5).seed(1).build();
+    /// let mut island = Solver::with_config(1, 1, config);
+    /// island.evaluate_generation(&|nn: &mut NeuralNetwork| nn.compute([0.5])[0]);
+    /// island.new_generation();
+    ///
+    /// let mut solver = Solver::with_size(1, 1, 10);
+    /// let mapping = solver.merge_innovations(&island)?;
+    /// solver.insert_network_from(&island.best_network(), &mapping)?;
+    /// # Ok::<(), neaters::Error>(())
+    /// ```
+    pub fn merge_innovations(&mut self, other: &Solver) -> Result<InnovationMapping, Error> {
+        let (own, theirs) = (&self.networks[0], &other.networks[0]);
+        if other.network_size != self.network_size
+            || own.presence_indicators != theirs.presence_indicators
+        {
+            return Err(Error::InvalidGenome(
+                "the networks of the solvers do not have the same inputs and outputs".to_string(),
+            ));
+        }
+        let base = |nn: &NeuralNetwork| {
+            nn.nodes
+                .iter()
+                .filter(|node| node.node_type != NodeType::Hidden)
+                .map(|node| node.id)
+                .collect::<Vec<usize>>()
+        };
+        let shared: FxHashMap<usize, usize> = base(theirs).into_iter().zip(base(own)).collect();
+        let mut nodes = self.innovations.merge(&other.innovations, shared.clone());
+        // hidden nodes which have not been created by a split, e.g. of inserted networks
+        let hidden = other
+            .networks
+            .iter()
+            .chain(other.species.iter().map(|species| &species.representative))
+            .flat_map(|nn| nn.nodes.iter())
+            .filter(|node| node.node_type == NodeType::Hidden);
+        for node in hidden {
+            nodes
+                .entry(node.id)
+                .or_insert_with(|| self.innovations.new_node_id());
+        }
+        nodes.retain(|id, _| !shared.contains_key(id));
+        Ok(InnovationMapping { nodes })
+    }
+
+    /// Inserts a network of another solver into the current generation like `insert_network`,
+    /// but its hidden nodes are translated with the mapping returned by `merge_innovations`
+    /// instead of getting new ids, so it stays aligned with the networks of this solver.
+    ///
+    /// Returns `Error::InvalidGenome` like `insert_network`.
+    pub fn insert_network_from(
+        &mut self,
+        nn: &NeuralNetwork,
+        mapping: &InnovationMapping,
+    ) -> Result<usize, Error> {
+        let nn = self.adopt_with(nn, Some(mapping))?;
+        Ok(self.replace_worst(nn))
+    }
+
+    /// Replaces the network with the lowest fitness by the adopted network, which takes over its
+    /// id.
+    fn replace_worst(&mut self, mut nn: NeuralNetwork) -> usize {
+        let idx = self.worst_networks(1)[0];
+        nn.id = self.networks[idx].id;
+        self.record_inserted(nn.id);
+        self.forget_elite(nn.id);
+        self.networks[idx] = nn;
+        self.networks[idx].id
+    }
+
+    /// Removes the kept fitness of the elite with the given id, whose slot has been taken over by
+    /// another network which needs to be evaluated.
+    fn forget_elite(&mut self, id: usize) {
+        self.elite_fitness.retain(|&(elite, _)| elite != id);
+    }
+
+    /// Replaces the given fraction of the current generation, the networks with the lowest
+    /// fitness, with variants of a champion network, e.g. the best network of a previous run.
+    ///
+    /// The elites copied from the previous generation are kept, unless the fraction covers more
+    /// networks than the rest of the generation. The first replaced network is an unchanged copy
+    /// of the champion, the others are mutated copies. The champion is inserted like in
+    /// `insert_network`, which also describes the returned errors. The fraction is clamped to
+    /// [0, 1].
+    ///
+    /// # Example
+    /// ```rust
+    /// use neaters::{NeuralNetwork, Solver};
+    /// let mut solver = Solver::with_size(2, 1, 10);
+    /// let champion = NeuralNetwork::with_size(2, 1).with_added_outputs(1);
+    /// // the champion has one output too many
+    /// assert!(solver.reseed_population(&champion, 0.5).is_err());
+    /// solver.reseed_population(&NeuralNetwork::with_size(2, 1), 0.5)?;
+    /// # Ok::<(), neaters::Error>(())
+    /// ```
+    pub fn reseed_population(
+        &mut self,
+        champion: &NeuralNetwork,
+        fraction: f32,
+    ) -> Result<(), Error> {
+        let champion = self.adopt(champion)?;
+        let count = (fraction.clamp(0.0, 1.0) * self.networks.len() as f32).round() as usize;
+        for (i, idx) in self.worst_networks(count).into_iter().enumerate() {
+            let mut nn = champion.clone();
+            if i > 0 {
+                nn.mutate_recorded(
+                    &self.config,
+                    self.generation,
+                    &mut self.innovations,
+                    &mut self.rng,
+                );
+            }
+            nn.id = self.networks[idx].id;
+            self.record_inserted(nn.id);
+            self.forget_elite(nn.id);
+            self.networks[idx] = nn;
+        }
+        Ok(())
+    }
+
+    /// Writes every network of the current generation into its own file in the given directory,
+    /// so single genomes can be inspected, replaced or removed by hand or by external tools and
+    /// read back with `import_population`.
+    ///
+    /// The files are named `genome-<id>.nn` with the id padded by zeros, so sorting the names
+    /// keeps the order of the generation, and they are saved like `NeuralNetwork::save_as`. The
+    /// directory is created if it does not exist and the genome files of a previous export are
+    /// removed, other files are kept. Returns the paths of the written files.
+    ///
+    /// # Example
+    /// ```rust
+    /// use neaters::Solver;
+    /// # use tempfile::tempdir;
+    /// # let dir = tempdir().unwrap();
+    /// # let dir = dir.path();
+    /// let mut solver = Solver::with_size(2, 1, 10);
+    /// let files = solver.export_population(dir)?;
+    /// assert_eq!(files.len(), 10);
+    /// // remove the last genome, the next one is bred from the imported networks
+    /// std::fs::remove_file(&files[9])?;
+    /// assert_eq!(solver.import_population(dir)?, 9);
+    /// assert_eq!(solver.networks().len(), 9);
+    /// # Ok::<(), neaters::Error>(())
+    /// ```
+    #[cfg(feature = "std-fs")]
+    pub fn export_population<P: AsRef<Path>>(&self, dir: P) -> Result<Vec<PathBuf>, Error> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if is_genome_file(&path) {
+                std::fs::remove_file(&path)?;
+            }
+        }
+        let width = self.networks.len().saturating_sub(1).to_string().len();
+        let mut files = Vec::with_capacity(self.networks.len());
+        for nn in &self.networks {
+            let path = dir.join(format!("genome-{:0width$}.nn", nn.id, width = width));
+            nn.save_as(&path)?;
+            files.push(path);
+        }
+        Ok(files)
+    }
+
+    /// Replaces the current generation with the networks in the files of the given directory,
+    /// e.g. a population written by `export_population` which has been curated by hand. Returns
+    /// the number of imported networks.
+    ///
+    /// Every file whose name does not start with a dot is loaded in the order of the file names,
+    /// with the `serde_json` feature files ending with `.json` are read as json. The networks are
+    /// inserted like in `insert_network`, so they get the ids of their position in the generation
+    /// and their fitness is reset. The number of networks may differ from the generation size,
+    /// the next generation is bred with the usual size again.
+    ///
+    /// Returns an error and leaves the generation unchanged if a file cannot be loaded, if a
+    /// network does not fit the solver or if the directory contains no networks.
+    #[cfg(feature = "std-fs")]
+    pub fn import_population<P: AsRef<Path>>(&mut self, dir: P) -> Result<usize, Error> {
+        let mut paths = Vec::new();
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?;
+            let hidden = entry.file_name().to_string_lossy().starts_with('.');
+            if entry.file_type()?.is_file() && !hidden {
+                paths.push(entry.path());
+            }
+        }
+        paths.sort();
+        if paths.is_empty() {
+            return Err(Error::InvalidGenome(
+                "the directory does not contain any networks".to_string(),
+            ));
+        }
+        let mut networks = Vec::with_capacity(paths.len());
+        for path in &paths {
+            let in_file = |err| match err {
+                Error::InvalidGenome(problem) => {
+                    Error::InvalidGenome(format!("{}: {}", path.display(), problem))
+                }
+                err => err,
+            };
+            let nn = load_genome(path).map_err(in_file)?;
+            let mut nn = self.adopt(&nn).map_err(in_file)?;
+            nn.id = networks.len();
+            networks.push(nn);
+        }
+        // the lineage is only changed once every network has been imported
+        for id in 0..networks.len() {
+            self.record_inserted(id);
+        }
+        self.elite_fitness.clear();
+        self.networks = networks;
+        Ok(self.networks.len())
+    }
+
+    /// Returns the indexes of the `n` networks with the lowest fitness, the worst one first.
+    /// Networks with the same fitness are returned in their order in the generation. The elites
+    /// which have been copied from the previous generation come after every other network, so
+    /// they are only returned if `n` exceeds the number of the other networks.
+    fn worst_networks(&self, n: usize) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.networks.len()).collect();
+        let elite = |idx: usize| {
+            let id = self.networks[idx].id;
+            self.elite_fitness.iter().any(|&(elite, _)| elite == id)
+        };
+        let score = |idx: usize| self.config.direction.score(self.networks[idx].fitness);
+        order.sort_by(|&a, &b| elite(a).cmp(&elite(b)).then(score(a).total_cmp(&score(b))));
+        order.truncate(n);
+        order
+    }
+
+    /// Returns a copy of a network created outside of the solver which uses the node ids and
+    /// innovation numbers of this solver, see `insert_network`.
+    ///
+    /// Input and output nodes are matched by their position, every hidden node gets a new id and
+    /// the innovation numbers of the edges are taken from the innovation tracker.
+    fn adopt(&mut self, nn: &NeuralNetwork) -> Result<NeuralNetwork, Error> {
+        self.adopt_with(nn, None)
+    }
+
+    /// Adopts the network like `adopt`, but the hidden nodes which are contained in the mapping
+    /// get their translated ids instead of new ones.
+    fn adopt_with(
+        &mut self,
+        nn: &NeuralNetwork,
+        mapping: Option<&InnovationMapping>,
+    ) -> Result<NeuralNetwork, Error> {
+        let mut nn = nn.clone();
+        nn.mark_bias_node();
+        nn.check_structure()?;
+        let template = &self.networks[0];
+        if nn.size != self.network_size {
+            return Err(Error::InvalidGenome(format!(
+                "the network has {} inputs and {} outputs, but the solver expects {} and {}",
+                nn.size.0, nn.size.1, self.network_size.0, self.network_size.1
+            )));
+        }
+        if nn.presence_indicators != template.presence_indicators {
+            return Err(Error::InvalidGenome(
+                "the presence indicators of the network do not match the solver".to_string(),
+            ));
+        }
+        if !self.config.recurrent && nn.edges.iter().any(|edge| edge.recurrent) {
+            return Err(Error::InvalidGenome(
+                "the network has recurrent edges, but the solver is not recurrent".to_string(),
+            ));
+        }
+        let solver_ids = |node_type: NodeType| {
+            template
+                .nodes
+                .iter()
+                .filter(move |node| node.node_type == node_type)
+                .map(|node| node.id)
+        };
+        let mut ids: FxHashMap<usize, usize> = FxHashMap::default();
+        for node_type in [NodeType::Bias, NodeType::Input, NodeType::Output] {
+            let own = nn.nodes.iter().filter(|node| node.node_type == node_type);
+            ids.extend(own.map(|node| node.id).zip(solver_ids(node_type)));
+        }
+        let mut adopted = nn;
+        adopted.retain_frozen();
+        adopted.retain_modules();
+        for node in adopted.nodes.iter_mut() {
+            node.id = match node.node_type {
+                NodeType::Hidden => {
+                    let id = match mapping.and_then(|mapping| mapping.node(node.id)) {
+                        Some(id) => id,
+                        None => self.innovations.new_node_id(),
+                    };
+                    ids.insert(node.id, id);
+                    id
                 }
+                _ => ids[&node.id],
+            };
+        }
+        adopted.remap_frozen(|id| ids[&id]);
+        adopted.remap_modules(|id| ids[&id]);
+        for edge in adopted.edges.iter_mut() {
+            edge.from = ids[&edge.from];
+            edge.to = ids[&edge.to];
+            edge.innovation = self.innovations.connection(edge.from, edge.to);
+        }
+        adopted.edges.sort_by_key(|edge| edge.innovation);
+        adopted.recurrent = self.config.recurrent;
+        adopted.node_biases = self.config.node_biases;
+        adopted.fitness = f32::MIN;
+        adopted.objectives.clear();
+        adopted.behavior.clear();
+        adopted.genome_changed();
+        Ok(adopted)
+    }
+
+    /// Computes the adjusted fitness of every network through explicit fitness sharing, the
+    /// penalized fitness of a network is divided by the size of its species.
+    ///
+    /// The penalized fitness values are normalized with the `fitness_transform` at first, by
+    /// default they are shifted so that the worst network has an adjusted fitness of **0.0**,
+    /// because the proportional selection cannot handle negative values. The adjusted fitness of
+    /// young species is boosted and the one of old species is penalized, see `age_factor`. The
+    /// result is indexed by the position of the network in `self.networks`.
+    fn adjusted_fitness(&self) -> Vec<f32> {
+        let penalized: Vec<f32> = self
+            .networks
+            .iter()
+            .map(|nn| self.penalized_fitness(nn))
+            .collect();
+        let transformed = self.config.fitness_transform.apply(&penalized);
+        let index_of = self.network_index_mapping();
+        let mut adjusted = vec![0.0; self.networks.len()];
+        for species in self.species.iter() {
+            let n = species.members.len() as f32;
+            let factor = self.age_factor(species);
+            for network_id in species.members.iter() {
+                let idx = index_of[network_id];
+                adjusted[idx] = transformed[idx] / n * factor;
             }
-            self.species
-                .push(Species::new_with_network(network.clone()));
-        }
-    }
-
-    /// Compute distance between two networks
-    /// Needs the edge lists to be sorted by innovation number
-    fn distance(a: &NeuralNetwork, b: &NeuralNetwork, config: &Config) -> f32 {
-        // iterate through both edge lists and stop when one is finished
-        let mut disjoint = 0;
-        let mut a_pointer = 0;
-        let mut b_pointer = 0;
-        let mut weight_diff = 0.0;
-        while a_pointer < a.edges.len() && b_pointer < b.edges.len() {
-            let a_inno = a.edges[a_pointer].innovation;
-            let b_inno = b.edges[b_pointer].innovation;
-            match a_inno.cmp(&b_inno) {
-                std::cmp::Ordering::Less => {
-                    a_pointer += 1;
-                    disjoint += 1;
+        }
+        adjusted
+    }
+
+    /// Returns the factor of the adjusted fitness of the members of the species: the
+    /// `young_species_boost` for species younger than `young_species_age` generations, the
+    /// `old_species_penalty` for species which are at least `old_species_age` generations old
+    /// and **1.0** otherwise.
+    fn age_factor(&self, species: &Species) -> f32 {
+        let age = self.generation.saturating_sub(species.created);
+        if age < self.config.young_species_age {
+            self.config.young_species_boost
+        } else if age >= self.config.old_species_age {
+            self.config.old_species_penalty
+        } else {
+            1.0
+        }
+    }
+
+    /// Returns the fitness of the network as a score where higher is better (see
+    /// `Direction::score`), reduced by the `node_penalty` for every hidden node and by the
+    /// `edge_penalty` for every enabled edge. Without this pressure the networks of long
+    /// trainings tend to accumulate nodes which do not improve their fitness.
+    fn penalized_fitness(&self, nn: &NeuralNetwork) -> f32 {
+        self.config.direction.score(nn.fitness)
+            - self.config.node_penalty * nn.hidden_node_count() as f32
+            - self.config.edge_penalty * nn.enabled_edge_count() as f32
+    }
+
+    /// Compares the networks at the given indexes by their size if `prefer_smaller_networks` is
+    /// set, the smaller network first. Used to break ties between networks with the same fitness.
+    fn compare_size(&self, a: usize, b: usize) -> std::cmp::Ordering {
+        if !self.config.prefer_smaller_networks {
+            return std::cmp::Ordering::Equal;
+        }
+        let size = |nn: &NeuralNetwork| nn.hidden_node_count() + nn.enabled_edge_count();
+        size(&self.networks[a]).cmp(&size(&self.networks[b]))
+    }
+
+    /// Returns for every species the indexes of the networks which are allowed to reproduce,
+    /// sorted from the best to the worst network. At least one network of each species survives.
+    fn surviving_members(&self, adjusted: &[f32]) -> Vec<Vec<usize>> {
+        let index_of = self.network_index_mapping();
+        self.species
+            .iter()
+            .map(|species| {
+                let mut members: Vec<usize> =
+                    species.members.iter().map(|id| index_of[id]).collect();
+                members.sort_by(|&a, &b| {
+                    adjusted[b]
+                        .total_cmp(&adjusted[a])
+                        .then_with(|| self.compare_size(a, b))
+                });
+                let survivors =
+                    (members.len() as f32 * self.config.survival_threshold).ceil() as usize;
+                members.truncate(usize::max(survivors, 1));
+                members
+            })
+            .collect()
+    }
+
+    /// Computes the number of offspring of every species proportional to the sum of the adjusted
+    /// fitness of its members. The counts always add up to `total_offspring`.
+    ///
+    /// Every species gets at least `min_species_offspring` offspring if there are enough of them,
+    /// the missing offspring are taken from the species with the most offspring.
+    fn offspring_counts(&self, adjusted: &[f32], total_offspring: usize) -> Vec<usize> {
+        let index_of = self.network_index_mapping();
+        let sums: Vec<f32> = self
+            .species
+            .iter()
+            .map(|species| {
+                species
+                    .members
+                    .iter()
+                    .map(|id| adjusted[index_of[id]])
+                    .sum()
+            })
+            .collect();
+        let total: f32 = sums.iter().sum();
+        let shares: Vec<f32> = if total > 0.0 {
+            sums.iter()
+                .map(|sum| sum / total * total_offspring as f32)
+                .collect()
+        } else {
+            // every network performed equally, so every species gets the same amount of offspring
+            vec![total_offspring as f32 / self.species.len() as f32; self.species.len()]
+        };
+        let mut counts: Vec<usize> = shares.iter().map(|share| share.floor() as usize).collect();
+        // distribute the remaining offspring to the species with the largest remainders
+        let mut order: Vec<usize> = (0..shares.len()).collect();
+        order.sort_by(|&a, &b| {
+            (shares[b] - shares[b].floor()).total_cmp(&(shares[a] - shares[a].floor()))
+        });
+        let assigned: usize = counts.iter().sum();
+        for &i in order
+            .iter()
+            .cycle()
+            .take(total_offspring.saturating_sub(assigned))
+        {
+            counts[i] += 1;
+        }
+        let minimum = usize::min(
+            self.config.min_species_offspring,
+            total_offspring / counts.len().max(1),
+        );
+        for i in 0..counts.len() {
+            while counts[i] < minimum {
+                // the largest count is above the minimum, because the counts add up to at least
+                // `minimum` times the number of species
+                let largest = (0..counts.len())
+                    .max_by(|&a, &b| counts[a].cmp(&counts[b]).then(b.cmp(&a)))
+                    .unwrap_or(i);
+                counts[largest] -= 1;
+                counts[i] += 1;
+            }
+        }
+        counts
+    }
+
+    /// Returns the indexes of the networks which are copied into the next generation unchanged.
+    ///
+    /// These are the `elitism` best networks of the whole generation and the `species_elitism`
+    /// best networks of every species with more than `species_elitism_min_size` members, ranked
+    /// by their penalized fitness. Every network is only included once and there are never more
+    /// elites than `generation_size`.
+    fn elites(&self) -> Vec<usize> {
+        let by_fitness = |members: &mut Vec<usize>| {
+            members.sort_by(|&a, &b| {
+                self.penalized_fitness(&self.networks[b])
+                    .total_cmp(&self.penalized_fitness(&self.networks[a]))
+                    .then_with(|| self.compare_size(a, b))
+            });
+        };
+        let mut all: Vec<usize> = (0..self.networks.len()).collect();
+        by_fitness(&mut all);
+        let mut elites: Vec<usize> = all.into_iter().take(self.config.elitism).collect();
+        let index_of = self.network_index_mapping();
+        for species in self
+            .species
+            .iter()
+            .filter(|species| species.members.len() > self.config.species_elitism_min_size)
+        {
+            let mut members: Vec<usize> = species.members.iter().map(|id| index_of[id]).collect();
+            by_fitness(&mut members);
+            for idx in members.into_iter().take(self.config.species_elitism) {
+                if !elites.contains(&idx) {
+                    elites.push(idx);
                 }
-                std::cmp::Ordering::Equal => {
-                    a_pointer += 1;
-                    b_pointer += 1;
-                    weight_diff += f32::abs(a.edges[a_pointer].weight - b.edges[b_pointer].weight);
+            }
+        }
+        elites.truncate(self.generation_size);
+        elites
+    }
+
+    /// Replaces the networks with the elites followed by the offspring of the surviving networks
+    /// of each species.
+    ///
+    /// Parents are selected by their adjusted fitness with the strategy `selection`. With the probability
+    /// `crossover_rate` an offspring is created by crossing over two parents, otherwise it is a
+    /// copy of one parent. The second parent of a crossover belongs to the same species, except
+    /// with the probability `interspecies_mating_rate`, where it is selected from a random other
+    /// species. Every offspring gets mutated afterwards.
+    fn reproduce(
+        &mut self,
+        elites: &[usize],
+        survivors: &[Vec<usize>],
+        adjusted: &[f32],
+        offspring: &[usize],
+    ) {
+        let mut next_generation: Vec<NeuralNetwork> = Vec::with_capacity(self.generation_size);
+        let mut records: Vec<LineageRecord> = Vec::new();
+        let track = self.config.track_lineage;
+        let generation = self.generation;
+        let genome = |id| GenomeId { generation, id };
+        self.elite_fitness.clear();
+        for &idx in elites.iter() {
+            let mut elite = self.networks[idx].clone_in(&mut self.arena);
+            if track {
+                records.push(LineageRecord {
+                    genome: GenomeId {
+                        generation: generation + 1,
+                        id: next_generation.len(),
+                    },
+                    parents: vec![genome(elite.id)],
+                    origin: Origin::Elite,
+                    mutations: Vec::new(),
+                    fitness: f32::MIN,
+                });
+            }
+            elite.id = next_generation.len();
+            self.elite_fitness.push((elite.id, elite.fitness));
+            elite.fitness = f32::MIN;
+            next_generation.push(elite);
+        }
+        let selection = self.config.selection;
+        let weights: Vec<Vec<f32>> = survivors
+            .iter()
+            .map(|members| members.iter().map(|&idx| adjusted[idx]).collect())
+            .collect();
+        for (species, (members, &count)) in survivors.iter().zip(offspring.iter()).enumerate() {
+            for _ in 0..count {
+                let mut attempts = 0;
+                let (mut child, parents, mutations) = loop {
+                    let parent = members[selection.select(&weights[species], &mut self.rng)];
+                    let a = &self.networks[parent];
+                    let crossover = self.rng.gen::<f32>() < self.config.crossover_rate;
+                    let interspecies = crossover
+                        && survivors.len() > 1
+                        && self.rng.gen::<f32>() < self.config.interspecies_mating_rate;
+                    let mut parents = vec![genome(a.id)];
+                    let mut child = if interspecies {
+                        // any species except the one of the first parent
+                        let mut other = random::index(&mut self.rng, survivors.len() - 1);
+                        if other >= species {
+                            other += 1;
+                        }
+                        let idx =
+                            survivors[other][selection.select(&weights[other], &mut self.rng)];
+                        parents.push(genome(self.networks[idx].id));
+                        a.crossover_with_rate(
+                            &self.networks[idx],
+                            self.config.reenable_rate,
+                            self.config.direction,
+                            &mut self.rng,
+                        )
+                    } else if crossover && members.len() > 1 {
+                        let b = &self.networks
+                            [members[selection.select(&weights[species], &mut self.rng)]];
+                        parents.push(genome(b.id));
+                        a.crossover_with_rate(
+                            b,
+                            self.config.reenable_rate,
+                            self.config.direction,
+                            &mut self.rng,
+                        )
+                    } else {
+                        // the phenotype of the parent is updated by the mutation of the weights
+                        self.networks[parent].clone_in(&mut self.arena)
+                    };
+                    let mutations = child.mutate_recorded(
+                        &self.config,
+                        generation + 1,
+                        &mut self.innovations,
+                        &mut self.rng,
+                    );
+                    attempts += 1;
+                    match self.offspring_validator.check(&mut child) {
+                        OffspringVerdict::Accept => break (child, parents, mutations),
+                        // the unchanged first parent replaces an offspring which is never accepted
+                        OffspringVerdict::Reject if attempts >= self.config.offspring_attempts => {
+                            break (
+                                self.networks[parent].clone_in(&mut self.arena),
+                                vec![genome(self.networks[parent].id)],
+                                Vec::new(),
+                            );
+                        }
+                        OffspringVerdict::Reject => {}
+                    }
+                };
+                if track {
+                    records.push(LineageRecord {
+                        genome: GenomeId {
+                            generation: generation + 1,
+                            id: next_generation.len(),
+                        },
+                        origin: match parents.len() {
+                            1 => Origin::Mutation,
+                            _ => Origin::Crossover,
+                        },
+                        parents,
+                        mutations,
+                        fitness: f32::MIN,
+                    });
                 }
+                child.id = next_generation.len();
+                child.fitness = f32::MIN;
+                next_generation.push(child);
+            }
+        }
+        for record in records {
+            self.lineage.insert(record);
+        }
+        // the phenotypes of this generation are rebuilt in place for the next one
+        for nn in self.networks.iter_mut() {
+            nn.recycle_phenotype(&mut self.arena);
+        }
+        for nn in next_generation.iter_mut() {
+            nn.cache_phenotype(&mut self.arena);
+        }
+        self.networks = next_generation;
+    }
+
+    /// Records the network with the id in the current generation as inserted from outside of the
+    /// evolution, if the lineage is tracked.
+    fn record_inserted(&mut self, id: usize) {
+        if self.config.track_lineage {
+            self.lineage.insert(LineageRecord {
+                genome: GenomeId {
+                    generation: self.generation,
+                    id,
+                },
+                parents: Vec::new(),
+                origin: Origin::Inserted,
+                mutations: Vec::new(),
+                fitness: f32::MIN,
+            });
+        }
+    }
+
+    /// Creates the mapping from the id of a network to its index in `self.networks`.
+    fn network_index_mapping(&self) -> FxHashMap<usize, usize> {
+        self.networks
+            .iter()
+            .enumerate()
+            .map(|(idx, nn)| (nn.id, idx))
+            .collect()
+    }
+
+    pub(crate) fn create_from_bytes(bytes: Vec<u8>) -> Result<Self, Error> {
+        let bytes = format::decompress(&bytes)?;
+        let (version, payload) = format::split_header(format::SOLVER_MAGIC, &bytes)?;
+        let mut decoded = Solver::migrate(version, payload)?;
+        decoded.prepare_networks()?;
+        Ok(decoded)
+    }
+
+    /// Decodes a solver which has been stored with the given format version.
+    ///
+    /// When the format changes, the decoding of the old versions is added here, converting them
+    /// to the current representation.
+    fn migrate(version: u32, payload: &[u8]) -> Result<Self, Error> {
+        match version {
+            // the layout of solvers stored with bincode is not known anymore, see
+            // `format::BINCODE_VERSION`
+            format::UNCHECKED_VERSION | format::FORMAT_VERSION => format::decode(payload),
+            _ => Err(Error::UnsupportedVersion(version)),
+        }
+    }
+
+    /// Marks the bias nodes of networks stored by earlier versions, see `NodeType::Bias`, and
+    /// checks the structure of every network of the solver, including the representatives and
+    /// the hall of fame.
+    fn prepare_networks(&mut self) -> Result<(), Error> {
+        for nn in self
+            .networks
+            .iter_mut()
+            .chain(self.species.iter_mut().map(|s| &mut s.representative))
+            .chain(self.hall_of_fame.networks_mut().iter_mut())
+        {
+            nn.mark_bias_node();
+            nn.check_structure()?;
+        }
+        Ok(())
+    }
+
+    /// Groups the networks into their species again without recording the generation, used by
+    /// the speciation benchmark and the tests.
+    #[cfg(any(test, feature = "bench_support"))]
+    pub(crate) fn speciate(&mut self) {
+        self.clear_species();
+        self.group_networks();
+    }
+
+    /// Group networks into their species
+    ///
+    /// The networks are visited in their order and every network joins the first species in the
+    /// order of the species whose representative is close enough. Both orders only depend on the
+    /// previous generations, so the species are the same in every run with the same seed.
+    ///
+    /// The representatives of the species of the previous generation do not change while the
+    /// networks are grouped, so the first close enough one of them is searched for every network
+    /// independently, in parallel with the `rayon` feature. Only the networks which are too far
+    /// from all of them are compared to the species created during the grouping one after another.
+    fn group_networks(&mut self) {
+        let keys: Vec<SpeciationKey> = map_slice(&self.networks, |nn| self.speciation_key(nn));
+        let mut representatives: Vec<SpeciationKey> = self
+            .species
+            .iter()
+            .map(|species| self.speciation_key(&species.representative))
+            .collect();
+        let closest = |key: &SpeciationKey, representatives: &[SpeciationKey]| {
+            representatives.iter().position(|representative| {
+                Solver::speciation_distance(representative, key, &self.config)
+                    <= self.distance_threshold
+            })
+        };
+        let previous = self.species.len();
+        let existing: Vec<Option<usize>> =
+            map_slice(&keys, |key| closest(key, &representatives[..]));
+        let mut created: Vec<Species> = Vec::new();
+        for ((network, key), existing) in self.networks.iter().zip(keys).zip(existing) {
+            let species = existing
+                .or_else(|| closest(&key, &representatives[previous..]).map(|idx| previous + idx));
+            match species {
+                Some(idx) if idx < previous => self.species[idx].members.push(network.id),
+                Some(idx) => created[idx - previous].members.push(network.id),
+                None => {
+                    representatives.push(key);
+                    created.push(Species::new_with_network(
+                        network.clone(),
+                        self.next_species_id,
+                        self.generation,
+                    ));
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(species = self.next_species_id, "species created");
+                    self.next_species_id += 1;
+                }
+            }
+        }
+        self.species.append(&mut created);
+    }
+
+    /// Returns the distance of the speciation metric between two networks.
+    fn speciation_distance(a: &SpeciationKey, b: &SpeciationKey, config: &Config) -> f32 {
+        match config.speciation_metric {
+            SpeciationMetric::Genetic => a.genes.distance(&b.genes, config),
+            SpeciationMetric::Behavioral => probe_distance(&a.response, &b.response),
+            SpeciationMetric::Blend(weight) => {
+                (1.0 - weight) * a.genes.distance(&b.genes, config)
+                    + weight * probe_distance(&a.response, &b.response)
+            }
+        }
+    }
+
+    /// Returns what the speciation metric compares of the network: the index of its genes unless
+    /// the metric is behavioral and its outputs on the probe inputs unless the metric is genetic.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the speciation metric is behavioral but there are no probe inputs.
+    fn speciation_key(&self, nn: &NeuralNetwork) -> SpeciationKey {
+        let genes = match self.config.speciation_metric {
+            SpeciationMetric::Behavioral => GeneIndex::default(),
+            _ => GeneIndex::new(nn),
+        };
+        SpeciationKey {
+            genes,
+            response: self.probe_response(nn),
+        }
+    }
+
+    /// Returns the outputs of the network on every probe input one after another, which are
+    /// compared by the behavioral distance. Nothing is computed if the speciation metric does
+    /// not use them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the speciation metric is behavioral but there are no probe inputs.
+    fn probe_response(&self, nn: &NeuralNetwork) -> Vec<f32> {
+        if self.config.speciation_metric == SpeciationMetric::Genetic {
+            return Vec::new();
+        }
+        assert!(
+            !self.probe_inputs.is_empty(),
+            "the behavioral speciation metric needs probe inputs, see `Solver::set_probe_inputs`"
+        );
+        let mut pt = nn.phenotype();
+        self.probe_inputs
+            .iter()
+            .flat_map(|input| pt.compute(input))
+            .collect()
+    }
+
+    /// Adjusts the distance threshold towards the target number of species: if there are too many
+    /// species the threshold is increased, if there are too few it is decreased.
+    fn adjust_distance_threshold(&mut self) {
+        if let Some(target) = self.config.target_species {
+            match self.species.len().cmp(&target) {
+                std::cmp::Ordering::Less => {
+                    self.distance_threshold = f32::max(
+                        self.distance_threshold - self.config.threshold_adjustment,
+                        self.config.min_distance_threshold,
+                    );
+                }
+                std::cmp::Ordering::Equal => (),
                 std::cmp::Ordering::Greater => {
-                    b_pointer += 1;
-                    disjoint += 1;
+                    self.distance_threshold += self.config.threshold_adjustment;
                 }
             }
         }
-        let excess = a_pointer.abs_diff(b_pointer);
-        let n = usize::max(a.edges.len(), b.edges.len()) as f32;
-        config.c1 * (excess as f32) / n
-            + config.c2 * (disjoint as f32) / n
-            + config.c3 * weight_diff
+    }
+
+    /// Returns the configuration used for training.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Returns the number of the current generation, starting at **0** and increased by every
+    /// new generation.
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// Returns the current distance threshold which determines whether two networks belong to
+    /// the same species.
+    pub fn distance_threshold(&self) -> f32 {
+        self.distance_threshold
+    }
+
+    /// Sets the inputs on which the outputs of the networks are compared by the behavioral
+    /// distance of the speciation, see `SpeciationMetric`. The probes should cover the situations
+    /// in which different behavior matters, e.g. a few typical observations of the problem.
+    ///
+    /// # Example
+    /// ```rust
+    /// use neaters::{Config, NeuralNetwork, Solver, SpeciationMetric};
+    /// let config = Config::builder()
+    ///     .generation_size(20)
+    ///     .speciation_metric(SpeciationMetric::Blend(0.5))
+    ///     .build();
+    /// let mut solver = Solver::with_config(2, 1, config);
+    /// solver.set_probe_inputs(vec![vec![0.0, 0.0], vec![0.0, 1.0], vec![1.0, 1.0]]);
+    /// solver.evaluate_generation(&|nn: &mut NeuralNetwork| nn.compute([1.0, 0.0])[0]);
+    /// solver.new_generation();
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if a probe does not have one value for every input of the networks.
+    pub fn set_probe_inputs(&mut self, probes: Vec<Vec<f32>>) {
+        assert!(
+            probes
+                .iter()
+                .all(|probe| probe.len() == self.network_size.0),
+            "every probe input needs one value for each of the {} inputs",
+            self.network_size.0
+        );
+        self.probe_inputs = probes;
+    }
+
+    /// Sets the validator which checks and possibly repairs every offspring after its crossover
+    /// and mutation, see `OffspringValidator`. A rejected offspring is created again from newly
+    /// selected parents, at most `offspring_attempts` times in total, afterwards an unchanged copy
+    /// of its first parent is used instead.
+    ///
+    /// The validator is not saved together with the solver, so it needs to be set again after
+    /// loading a solver.
+    ///
+    /// # Example
+    /// ```rust
+    /// use neaters::{Config, NeuralNetwork, OffspringVerdict, Solver};
+    /// let config = Config::builder().generation_size(20).add_node_rate(0.5).build();
+    /// let mut solver = Solver::with_config(1, 1, config);
+    /// solver.set_offspring_validator(|nn: &mut NeuralNetwork| match nn.hidden_node_count() {
+    ///     0..=2 => OffspringVerdict::Accept,
+    ///     _ => OffspringVerdict::Reject,
+    /// });
+    /// for _ in 0..10 {
+    ///     solver.evaluate_generation(&|nn: &mut NeuralNetwork| nn.compute([0.5])[0]);
+    ///     solver.new_generation();
+    /// }
+    /// assert!(solver.networks().iter().all(|nn| nn.hidden_node_count() <= 2));
+    /// ```
+    pub fn set_offspring_validator<V: OffspringValidator + Send + Sync + 'static>(
+        &mut self,
+        validator: V,
+    ) {
+        self.offspring_validator = ValidatorSlot::new(Some(Box::new(validator)));
+    }
+
+    /// Removes the validator of the offspring, so every offspring is accepted again.
+    pub fn clear_offspring_validator(&mut self) {
+        self.offspring_validator = ValidatorSlot::default();
+    }
+
+    /// Sets the local search which fine-tunes the weights of the best networks of every
+    /// generation, see `fine_tune_generation`. `train` and `train_with_observer` apply it after
+    /// every evaluation.
+    ///
+    /// The local search is not saved together with the solver, so it needs to be set again after
+    /// loading a solver.
+    pub fn set_local_search(&mut self, search: LocalSearch) {
+        self.local_search = LocalSearchSlot(Some(search));
+    }
+
+    /// Removes the local search, so the weights are only changed by the evolution again.
+    pub fn clear_local_search(&mut self) {
+        self.local_search = LocalSearchSlot::default();
+    }
+
+    /// Fine-tunes the weights of the best networks of the evaluated generation with the local
+    /// search set by `set_local_search` and evaluates them again with the repeated trials of the
+    /// options, like `evaluate_generation_with_trials`. Does nothing without a local search.
+    ///
+    /// The Lamarckian inheritance keeps the fine-tuned weights in the networks, the Baldwinian
+    /// inheritance only assigns them the fitness of their fine-tuned copies, see `Inheritance`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use neaters::{LocalSearch, MeanSquaredError, NeuralNetwork, Solver, TrainOptions};
+    /// let samples: Vec<(Vec<f32>, Vec<f32>)> =
+    ///     (0..5).map(|i| (vec![i as f32 / 4.0], vec![i as f32 / 8.0])).collect();
+    /// let fitness = |nn: &mut NeuralNetwork| {
+    ///     let error: f32 = (0..5)
+    ///         .map(|i| (nn.compute([i as f32 / 4.0])[0] - i as f32 / 8.0).powi(2))
+    ///         .sum();
+    ///     -error
+    /// };
+    /// let mut solver = Solver::with_size(1, 1, 20);
+    /// solver.set_local_search(LocalSearch::new(samples, MeanSquaredError).with_steps(20));
+    /// let options = TrainOptions::default();
+    /// solver.evaluate_generation_with_trials(&fitness, &options);
+    /// let best = solver.best_network().fitness();
+    /// solver.fine_tune_generation(&fitness, &options);
+    /// assert!(solver.best_network().fitness() >= best);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if a local search is set and `trials_per_network` of the options is **0**.
+    pub fn fine_tune_generation<E: FitnessEvaluator + ?Sized>(
+        &mut self,
+        evaluator: &E,
+        options: &TrainOptions,
+    ) {
+        let Some(search) = &self.local_search.0 else {
+            return;
+        };
+        assert!(
+            options.trials_per_network > 0,
+            "every network needs at least one trial"
+        );
+        let direction = self.config.direction;
+        let mut order: Vec<usize> = (0..self.networks.len()).collect();
+        order.sort_by(|&a, &b| {
+            direction
+                .score(self.networks[b].fitness)
+                .total_cmp(&direction.score(self.networks[a].fitness))
+        });
+        let mut trials: Vec<f32> = Vec::with_capacity(options.trials_per_network);
+        for idx in order.into_iter().take(search.champions()) {
+            let nn = &mut self.networks[idx];
+            let fitness = match search.inheritance() {
+                Inheritance::Lamarckian => {
+                    nn.fine_tune(search);
+                    Self::evaluate_trials(nn, evaluator, options, &mut trials)
+                }
+                Inheritance::Baldwinian => {
+                    let mut tuned = nn.clone();
+                    tuned.fine_tune(search);
+                    Self::evaluate_trials(&mut tuned, evaluator, options, &mut trials)
+                }
+            };
+            nn.set_fitness(fitness);
+        }
+    }
+
+    /// Returns the inputs compared by the behavioral distance, see `set_probe_inputs`.
+    pub fn probe_inputs(&self) -> &[Vec<f32>] {
+        &self.probe_inputs
+    }
+
+    /// Returns the statistics of the last generation which has been replaced by `new_generation`
+    /// or evaluated by `train`, `None` if there has been no such generation yet.
+    pub fn stats(&self) -> Option<&GenerationStats> {
+        self.history.last()
+    }
+
+    /// Returns the statistics of every generation which has been replaced by `new_generation` or
+    /// evaluated by `train`, the oldest one first.
+    ///
+    /// # Example
+    /// ```rust
+    /// use neaters::{NeuralNetwork, Solver};
+    /// let mut solver = Solver::with_size(1, 1, 10);
+    /// for _ in 0..3 {
+    ///     solver.evaluate_generation(&|nn: &mut NeuralNetwork| nn.compute([0.5])[0]);
+    ///     solver.new_generation();
+    /// }
+    /// let best: Vec<f32> = solver.history().iter().map(|s| s.best_fitness).collect();
+    /// assert_eq!(best.len(), 3);
+    /// ```
+    pub fn history(&self) -> &[GenerationStats] {
+        &self.history
+    }
+
+    /// Computes metrics of the genetic diversity of the current networks, which help to detect a
+    /// premature convergence of the population. They are included in the statistics of every
+    /// generation.
+    ///
+    /// The mean distance is computed from all pairs of networks in small populations and from
+    /// **500** random pairs otherwise. The pairs are sampled with a random generator seeded with
+    /// the generation number, so the metrics do not change the random state of the solver.
+    ///
+    /// # Example
+    /// ```rust
+    /// use neaters::Solver;
+    /// let solver = Solver::with_size(2, 1, 20);
+    /// let diversity = solver.diversity_metrics();
+    /// // the initial networks only differ in their weights
+    /// assert_eq!(diversity.unique_topologies, 1);
+    /// assert_eq!(diversity.genotypic_entropy, 0.0);
+    /// assert!(diversity.mean_distance > 0.0);
+    /// ```
+    pub fn diversity_metrics(&self) -> DiversityMetrics {
+        const SAMPLED_PAIRS: usize = 500;
+        let n = self.networks.len();
+        let pairs: Vec<(usize, usize)> = if n * n.saturating_sub(1) / 2 <= SAMPLED_PAIRS {
+            (0..n)
+                .flat_map(|a| (a + 1..n).map(move |b| (a, b)))
+                .collect()
+        } else {
+            let mut rng = Pcg64::seed_from_u64(self.generation as u64);
+            (0..SAMPLED_PAIRS)
+                .map(|_| {
+                    let a = random::index(&mut rng, n);
+                    // any network except the first one of the pair
+                    let b = (a + 1 + random::index(&mut rng, n - 1)) % n;
+                    (a, b)
+                })
+                .collect()
+        };
+        let mean_distance = pairs
+            .iter()
+            .map(|&(a, b)| {
+                GeneIndex::new(&self.networks[a])
+                    .distance(&GeneIndex::new(&self.networks[b]), &self.config)
+            })
+            .sum::<f32>()
+            / pairs.len().max(1) as f32;
+        let mut topologies: FxHashMap<Vec<usize>, usize> = FxHashMap::default();
+        for nn in self.networks.iter() {
+            let enabled = nn.edges.iter().filter(|edge| edge.enabled);
+            *topologies
+                .entry(enabled.map(|edge| edge.innovation).collect())
+                .or_insert(0) += 1;
+        }
+        let mut counts: Vec<usize> = topologies.into_values().collect();
+        // the order of the summands must not depend on the order of the hash map
+        counts.sort_unstable();
+        let genotypic_entropy = counts
+            .iter()
+            .map(|&count| {
+                let p = count as f32 / n as f32;
+                p * (1.0 / p).log2()
+            })
+            .sum::<f32>();
+        DiversityMetrics {
+            mean_distance,
+            genotypic_entropy,
+            unique_topologies: counts.len(),
+        }
+    }
+
+    /// Returns information about every species of the current generation.
+    ///
+    /// The networks are grouped into species when a generation is recorded by `new_generation`
+    /// or `train`, so the sizes refer to the last recorded generation.
+    ///
+    /// # Example
+    /// ```rust
+    /// use neaters::{NeuralNetwork, Solver};
+    /// let mut solver = Solver::with_size(2, 1, 20);
+    /// solver.evaluate_generation(&|nn: &mut NeuralNetwork| nn.compute([0.5, 0.5])[0]);
+    /// solver.new_generation();
+    /// let sizes: usize = solver.species().map(|species| species.size).sum();
+    /// assert!(sizes <= 20);
+    /// ```
+    pub fn species(&self) -> impl Iterator<Item = SpeciesInfo> + '_ {
+        self.species
+            .iter()
+            .map(|species| species.info(self.generation))
+    }
+
+    /// Returns the hall of fame containing the best networks of all generations which have been
+    /// replaced by `new_generation` or evaluated by `train`.
+    pub fn hall_of_fame(&self) -> &HallOfFame {
+        &self.hall_of_fame
+    }
+
+    /// Returns the lineage of the networks, which is only recorded if the config enables
+    /// `track_lineage`. The id of a network of the current generation is
+    /// `GenomeId { generation: solver.generation(), id: nn.id() }`, the one of the champion of a
+    /// training is `TrainReport::champion_id`.
+    pub fn lineage(&self) -> &Lineage {
+        &self.lineage
     }
 
     /// Resetting the species
@@ -288,14 +2496,157 @@ impl Solver {
         }
     }
 
+    /// Removes every species whose best fitness has not improved for `stagnation_limit`
+    /// generations. Their offspring is redistributed to the remaining species. The species with
+    /// the best network is never removed, so at least one species survives.
+    fn remove_stagnant_species(&mut self) {
+        let score = |idx: usize| self.config.direction.score(self.species[idx].best_fitness);
+        let best_species = (0..self.species.len()).max_by(|&a, &b| score(a).total_cmp(&score(b)));
+        for (idx, species) in std::mem::take(&mut self.species).into_iter().enumerate() {
+            if Some(idx) == best_species
+                || !species.is_stagnant(self.generation, self.config.stagnation_limit)
+            {
+                self.species.push(species);
+            } else {
+                self.record_extinction(species.info(self.generation));
+            }
+        }
+    }
+
+    /// Chooses a member of every species as its new representative according to the config, so
+    /// the species follow the changes of their members instead of being compared to the network
+    /// they have been created with. Ties are broken by the order of the members.
+    fn update_representatives(&mut self) {
+        let index_of = self.network_index_mapping();
+        let medoid = self.config.representative == RepresentativeSelection::Medoid;
+        let keys: Vec<Option<SpeciationKey>> =
+            map_slice(&self.networks, |nn| medoid.then(|| self.speciation_key(nn)));
+        for species in self.species.iter_mut() {
+            let members: Vec<(&NeuralNetwork, Option<&SpeciationKey>)> = species
+                .members
+                .iter()
+                .map(|id| (&self.networks[index_of[id]], keys[index_of[id]].as_ref()))
+                .collect();
+            let chosen = match self.config.representative {
+                RepresentativeSelection::Random => random::index(&mut self.rng, members.len()),
+                RepresentativeSelection::Best => first_highest(
+                    members
+                        .iter()
+                        .map(|(nn, _)| self.config.direction.score(nn.fitness)),
+                ),
+                RepresentativeSelection::Medoid => {
+                    first_highest(members.iter().map(|&(_, key)| {
+                        let key = key.expect("the keys are computed for the medoid");
+                        -members
+                            .iter()
+                            .filter_map(|&(_, other)| other)
+                            .map(|other| Solver::speciation_distance(key, other, &self.config))
+                            .sum::<f32>()
+                    }))
+                }
+            };
+            species.representative = members[chosen].0.clone();
+        }
+    }
+
+    /// Updates the best fitness of every species with the fitness of its current members.
+    fn update_species_fitness(&mut self) {
+        let index_of = self.network_index_mapping();
+        for species in self.species.iter_mut() {
+            let direction = self.config.direction;
+            let best = species
+                .members
+                .iter()
+                .map(|id| self.networks[index_of[id]].fitness)
+                .fold(f32::MIN, |best, f| {
+                    if direction.is_better(f, best) {
+                        f
+                    } else {
+                        best
+                    }
+                });
+            species.update_best_fitness(best, self.generation, self.config.direction);
+        }
+    }
+
+    /// Remembers a species which went extinct, so the training can report it to the observer.
+    fn record_extinction(&mut self, species: SpeciesInfo) {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            species = species.id,
+            age = species.age,
+            best_fitness = species.best_fitness,
+            "species went extinct"
+        );
+        self.extinct.push(species);
+    }
+
     fn remove_unused_species(&mut self) {
         let mut i = 0;
         while i < self.species.len() {
             if self.species[i].is_unused() {
-                self.species.swap_remove(i);
+                let species = self.species.swap_remove(i);
+                self.record_extinction(species.info(self.generation));
             } else {
                 i += 1;
             }
         }
     }
 }
+
+/// Returns the position of the first highest score, **0** if there is no score.
+fn first_highest(scores: impl Iterator<Item = f32>) -> usize {
+    let mut best = (0, f32::NEG_INFINITY);
+    for (idx, score) in scores.enumerate() {
+        if score > best.1 {
+            best = (idx, score);
+        }
+    }
+    best.0
+}
+
+/// What the speciation metric compares of a network, computed once for every network which is
+/// grouped, see `Solver::speciation_key`.
+struct SpeciationKey {
+    genes: GeneIndex,
+    response: Vec<f32>,
+}
+
+/// Maps every item of the slice, in parallel with the `rayon` feature.
+fn map_slice<T: Sync, U: Send>(items: &[T], f: impl Fn(&T) -> U + Sync) -> Vec<U> {
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+        items.par_iter().map(&f).collect()
+    }
+    #[cfg(not(feature = "rayon"))]
+    {
+        items.iter().map(f).collect()
+    }
+}
+
+/// Returns the mean absolute difference between the outputs of two networks on the probe inputs.
+fn probe_distance(a: &[f32], b: &[f32]) -> f32 {
+    let sum: f32 = a.iter().zip(b).map(|(a, b)| (a - b).abs()).sum();
+    sum / a.len().max(1) as f32
+}
+
+/// Returns whether the file has been written by `Solver::export_population`.
+#[cfg(feature = "std-fs")]
+fn is_genome_file(path: &Path) -> bool {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    name.starts_with("genome-") && name.ends_with(".nn")
+}
+
+/// Loads a network of a population directory, see `Solver::import_population`.
+#[cfg(feature = "std-fs")]
+fn load_genome(path: &Path) -> Result<NeuralNetwork, Error> {
+    #[cfg(feature = "serde_json")]
+    if path
+        .extension()
+        .is_some_and(|extension| extension == "json")
+    {
+        return NeuralNetwork::from_json(&std::fs::read_to_string(path)?);
+    }
+    NeuralNetwork::load_from(path)
+}
@@ -1,18 +1,31 @@
 use serde::{Deserialize, Serialize};
 
+use crate::config::Direction;
 use crate::neuralnetwork::NeuralNetwork;
 
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub struct Species {
+    /// The id of the species, it is unique within the solver.
+    pub id: usize,
+    /// The generation in which the species has been created.
+    pub created: usize,
     pub representative: NeuralNetwork,
     pub members: Vec<usize>,
+    /// The best fitness any member of this species has ever reached.
+    pub best_fitness: f32,
+    /// The generation in which the best fitness of this species improved the last time.
+    pub last_improvement: usize,
 }
 
 impl Species {
-    pub fn new_with_network(nn: NeuralNetwork) -> Self {
+    pub fn new_with_network(nn: NeuralNetwork, id: usize, generation: usize) -> Self {
         Species {
+            id,
+            created: generation,
             members: vec![nn.id],
             representative: nn,
+            best_fitness: f32::MIN,
+            last_improvement: generation,
         }
     }
     pub fn clear(&mut self) {
@@ -22,4 +35,50 @@ impl Species {
     pub fn is_unused(&self) -> bool {
         self.members.is_empty()
     }
+
+    /// Updates the best fitness of the species with the best fitness of its current members.
+    pub fn update_best_fitness(&mut self, fitness: f32, generation: usize, direction: Direction) {
+        if direction.is_better(fitness, self.best_fitness) {
+            self.best_fitness = fitness;
+            self.last_improvement = generation;
+        }
+    }
+
+    /// Checks whether the species has not improved for at least `limit` generations.
+    pub fn is_stagnant(&self, generation: usize, limit: usize) -> bool {
+        generation.saturating_sub(self.last_improvement) >= limit
+    }
+
+    /// Returns the read-only view of the species in the given generation.
+    pub fn info(&self, generation: usize) -> SpeciesInfo {
+        let representative = &self.representative;
+        SpeciesInfo {
+            id: self.id,
+            size: self.members.len(),
+            best_fitness: self.best_fitness,
+            age: generation.saturating_sub(self.created),
+            representative_complexity: representative.nodes.len()
+                + representative
+                    .edges
+                    .iter()
+                    .filter(|edge| edge.enabled)
+                    .count(),
+        }
+    }
+}
+
+/// Read-only information about a species of the solver, see `Solver::species`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpeciesInfo {
+    /// The id of the species, it is unique within the solver and never reused.
+    pub id: usize,
+    /// The number of networks of the current generation which belong to the species.
+    pub size: usize,
+    /// The best fitness any member of the species has ever reached.
+    pub best_fitness: f32,
+    /// The number of generations since the species has been created.
+    pub age: usize,
+    /// The number of nodes plus the number of enabled edges of the representative of the
+    /// species.
+    pub representative_complexity: usize,
 }
@@ -0,0 +1,204 @@
+use crate::float::Float;
+use crate::math;
+use crate::neuralnetwork::{NeuralNetwork, NodeType};
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// The morphology and complexity of a network, returned by `NeuralNetwork::summary`, e.g. to log
+/// the size of the champions or to analyze how a parsimony pressure changes the networks.
+///
+/// The `Display` implementation prints the summary in a few lines.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NetworkSummary {
+    /// The number of input nodes, including the presence indicators.
+    pub input_nodes: usize,
+    /// The number of bias nodes, **1** for every network created by this crate.
+    pub bias_nodes: usize,
+    /// The number of hidden nodes.
+    pub hidden_nodes: usize,
+    /// The number of output nodes.
+    pub output_nodes: usize,
+    /// The number of enabled edges, including the recurrent ones.
+    pub enabled_edges: usize,
+    /// The number of disabled edges.
+    pub disabled_edges: usize,
+    /// The number of enabled recurrent edges.
+    pub recurrent_edges: usize,
+    /// The number of enabled, non-recurrent edges on the longest path through the network, **0**
+    /// for a network without edges.
+    pub depth: usize,
+    /// The average number of enabled incoming edges of the hidden and output nodes.
+    pub mean_fan_in: f32,
+    /// The average number of enabled outgoing edges of the bias, input and hidden nodes.
+    pub mean_fan_out: f32,
+    /// The statistics of the weights of the enabled edges.
+    pub weights: WeightSummary,
+}
+
+/// Statistics of the weights of the enabled edges of a network, all of them are **0.0** if there
+/// is no enabled edge.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WeightSummary {
+    /// The average weight.
+    pub mean: f32,
+    /// The standard deviation of the weights.
+    pub std_dev: f32,
+    /// The average absolute weight.
+    pub mean_abs: f32,
+    /// The smallest weight.
+    pub min: f32,
+    /// The largest weight.
+    pub max: f32,
+}
+
+impl<F: Float> NeuralNetwork<F> {
+    /// Returns the morphology summary of the network: the numbers of nodes by type and of edges,
+    /// its depth, the average fan-in and fan-out and the statistics of the weights.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::NeuralNetwork;
+    /// let nn = NeuralNetwork::with_size(2, 1);
+    /// let summary = nn.summary();
+    /// assert_eq!(summary.input_nodes, 2);
+    /// assert_eq!(summary.output_nodes, 1);
+    /// // every input and the bias node are connected to the output
+    /// assert_eq!(summary.enabled_edges, 3);
+    /// assert_eq!(summary.depth, 1);
+    /// println!("{}", summary);
+    /// ```
+    pub fn summary(&self) -> NetworkSummary {
+        let count = |node_type: NodeType| {
+            self.nodes
+                .iter()
+                .filter(|node| node.node_type == node_type)
+                .count()
+        };
+        let enabled: Vec<_> = self.edges.iter().filter(|edge| edge.enabled).collect();
+        let receiving = self.nodes.len() - count(NodeType::Input) - count(NodeType::Bias);
+        let sending = self.nodes.len() - count(NodeType::Output);
+        NetworkSummary {
+            input_nodes: count(NodeType::Input),
+            bias_nodes: count(NodeType::Bias),
+            hidden_nodes: count(NodeType::Hidden),
+            output_nodes: count(NodeType::Output),
+            enabled_edges: enabled.len(),
+            disabled_edges: self.edges.len() - enabled.len(),
+            recurrent_edges: enabled.iter().filter(|edge| edge.recurrent).count(),
+            depth: self.depth(),
+            mean_fan_in: average(enabled.len(), receiving),
+            mean_fan_out: average(enabled.len(), sending),
+            weights: WeightSummary::compute(enabled.iter().map(|edge| edge.weight.to_f64())),
+        }
+    }
+
+    /// Returns the number of edges on the longest path along the enabled, non-recurrent edges.
+    /// Nodes on a cycle of such edges, which only legacy recurrent networks can contain, are not
+    /// part of any path.
+    fn depth(&self) -> usize {
+        let index: BTreeMap<usize, usize> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| (node.id, index))
+            .collect();
+        let mut successors: Vec<Vec<usize>> = vec![Vec::new(); self.nodes.len()];
+        let mut in_degree: Vec<usize> = vec![0; self.nodes.len()];
+        for edge in self
+            .edges
+            .iter()
+            .filter(|edge| edge.enabled && !edge.recurrent)
+        {
+            if let (Some(&from), Some(&to)) = (index.get(&edge.from), index.get(&edge.to)) {
+                successors[from].push(to);
+                in_degree[to] += 1;
+            }
+        }
+        // Kahn's algorithm, every node is placed after all of its predecessors
+        let mut queue: VecDeque<usize> = (0..self.nodes.len())
+            .filter(|&node| in_degree[node] == 0)
+            .collect();
+        let mut depth: Vec<usize> = vec![0; self.nodes.len()];
+        let mut longest = 0;
+        while let Some(node) = queue.pop_front() {
+            longest = longest.max(depth[node]);
+            for &next in successors[node].iter() {
+                depth[next] = depth[next].max(depth[node] + 1);
+                in_degree[next] -= 1;
+                if in_degree[next] == 0 {
+                    queue.push_back(next);
+                }
+            }
+        }
+        longest
+    }
+}
+
+impl WeightSummary {
+    /// Computes the statistics of the weights.
+    fn compute<I: Iterator<Item = f64> + Clone>(weights: I) -> Self {
+        let count = weights.clone().count();
+        if count == 0 {
+            return WeightSummary {
+                mean: 0.0,
+                std_dev: 0.0,
+                mean_abs: 0.0,
+                min: 0.0,
+                max: 0.0,
+            };
+        }
+        let n = count as f64;
+        let mean = weights.clone().sum::<f64>() / n;
+        let variance = weights
+            .clone()
+            .map(|w| (w - mean) * (w - mean))
+            .sum::<f64>()
+            / n;
+        WeightSummary {
+            mean: mean as f32,
+            std_dev: math::sqrt_f64(variance) as f32,
+            mean_abs: (weights.clone().map(math::abs_f64).sum::<f64>() / n) as f32,
+            min: weights.clone().fold(f64::INFINITY, f64::min) as f32,
+            max: weights.fold(f64::NEG_INFINITY, f64::max) as f32,
+        }
+    }
+}
+
+/// Returns the number of edges per node, **0.0** if there are no nodes.
+fn average(edges: usize, nodes: usize) -> f32 {
+    match nodes {
+        0 => 0.0,
+        nodes => edges as f32 / nodes as f32,
+    }
+}
+
+impl fmt::Display for NetworkSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "nodes: {} input, {} bias, {} hidden, {} output",
+            self.input_nodes, self.bias_nodes, self.hidden_nodes, self.output_nodes
+        )?;
+        writeln!(
+            f,
+            "edges: {} enabled ({} recurrent), {} disabled",
+            self.enabled_edges, self.recurrent_edges, self.disabled_edges
+        )?;
+        writeln!(
+            f,
+            "depth: {}, mean fan-in: {:.2}, mean fan-out: {:.2}",
+            self.depth, self.mean_fan_in, self.mean_fan_out
+        )?;
+        write!(
+            f,
+            "weights: mean {:.3}, std dev {:.3}, mean abs {:.3}, min {:.3}, max {:.3}",
+            self.weights.mean,
+            self.weights.std_dev,
+            self.weights.mean_abs,
+            self.weights.min,
+            self.weights.max
+        )
+    }
+}
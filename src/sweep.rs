@@ -0,0 +1,231 @@
+use crate::config::Config;
+use crate::evaluator::FitnessEvaluator;
+use crate::solver::Solver;
+use crate::train::{StopReason, TrainOptions, TrainReport};
+
+/// Compares multiple configs by training every one of them several times on the same problem, so
+/// parameters like the compatibility coefficients or the mutation rates can be tuned
+/// systematically instead of by hand.
+///
+/// The configs to compare are either given directly or created as a grid with `vary`, which
+/// combines every config of the sweep with every given value of one parameter. Every config is
+/// trained `repetitions` times with the stopping criteria of the options. A config with a `seed`
+/// uses the seeds `seed`, `seed + 1`, ... for its repetitions, so configs with the same seed are
+/// compared on the same random initial populations and the results are reproducible.
+///
+/// # Example
+/// ```rust
+/// use neaters::{Config, NeuralNetwork, Sweep, TrainOptions};
+/// let base = Config::builder().generation_size(20).seed(1).build();
+/// let sweep = Sweep::new(1, 1, vec![base])
+///     .vary(&[0.5, 1.0, 2.0], |config, c1| config.c1 = c1)
+///     .vary(&[0.1, 0.8], |config, rate| config.weight_mutation_rate = rate)
+///     .with_repetitions(2)
+///     .with_options(TrainOptions {
+///         max_generations: Some(5),
+///         ..TrainOptions::default()
+///     });
+/// assert_eq!(sweep.configs().len(), 6);
+/// let results = sweep.run(&|nn: &mut NeuralNetwork| nn.compute([0.5])[0]);
+/// assert_eq!(results.len(), 6);
+/// // the results are ranked by their mean fitness, the best config first
+/// assert!(results[0].mean_fitness >= results[5].mean_fitness);
+/// println!("best c1: {}", results[0].config.c1);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sweep {
+    /// The number of inputs of the trained networks.
+    inputs: usize,
+    /// The number of outputs of the trained networks.
+    outputs: usize,
+    /// The configs which are compared.
+    configs: Vec<Config>,
+    /// The number of trainings with every config.
+    repetitions: usize,
+    /// The stopping criteria of every training.
+    options: TrainOptions,
+}
+
+/// The results of training one config of a `Sweep` multiple times.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SweepResult {
+    /// The config which has been trained.
+    pub config: Config,
+    /// The fitness of the champion of every repetition in order.
+    pub fitness: Vec<f32>,
+    /// The average fitness of the champions.
+    pub mean_fitness: f32,
+    /// The standard deviation of the fitness of the champions.
+    pub fitness_std_dev: f32,
+    /// The best fitness of a champion, the lowest one if the config minimizes the fitness.
+    pub best_fitness: f32,
+    /// The worst fitness of a champion.
+    pub worst_fitness: f32,
+    /// The average number of evaluated generations of a training.
+    pub mean_generations: f32,
+    /// The number of repetitions which have reached the target fitness of the options.
+    pub solved: usize,
+}
+
+impl Sweep {
+    /// Constructor for a sweep comparing the given configs with networks of the given size. Every
+    /// config is trained once with the default `TrainOptions`.
+    pub fn new(inputs: usize, outputs: usize, configs: Vec<Config>) -> Self {
+        Sweep {
+            inputs,
+            outputs,
+            configs,
+            repetitions: 1,
+            options: TrainOptions::default(),
+        }
+    }
+
+    /// Replaces every config of the sweep by one copy for each of the values, where the parameter
+    /// is set to the value by the given function. Calling it multiple times creates a grid of all
+    /// combinations of the values.
+    pub fn vary<T: Copy>(mut self, values: &[T], set: impl Fn(&mut Config, T)) -> Self {
+        let set = &set;
+        self.configs = self
+            .configs
+            .iter()
+            .flat_map(|&config| {
+                values.iter().map(move |&value| {
+                    let mut config = config;
+                    set(&mut config, value);
+                    config
+                })
+            })
+            .collect();
+        self
+    }
+
+    /// Sets the number of trainings with every config.
+    pub fn with_repetitions(mut self, repetitions: usize) -> Self {
+        self.repetitions = repetitions;
+        self
+    }
+
+    /// Sets the stopping criteria of every training.
+    pub fn with_options(mut self, options: TrainOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Returns the configs which are compared.
+    pub fn configs(&self) -> &[Config] {
+        &self.configs
+    }
+
+    /// Trains every config of the sweep `repetitions` times one after another and returns the
+    /// results of the configs, ranked by their mean fitness with the best config first.
+    pub fn run<E: FitnessEvaluator + ?Sized>(&self, evaluator: &E) -> Vec<SweepResult> {
+        let reports: Vec<TrainReport> = self
+            .runs()
+            .map(|(config, repetition)| self.train(config, repetition, evaluator))
+            .collect();
+        self.summarize(reports)
+    }
+
+    /// Trains the configs like `run`, but the trainings run in parallel on multiple threads. The
+    /// evaluator is shared between the threads, so it has to be `Sync`.
+    #[cfg(feature = "rayon")]
+    pub fn run_parallel<E: FitnessEvaluator + Sync + ?Sized>(
+        &self,
+        evaluator: &E,
+    ) -> Vec<SweepResult> {
+        use rayon::prelude::*;
+        let runs: Vec<(usize, usize)> = self.runs().collect();
+        let reports: Vec<TrainReport> = runs
+            .into_par_iter()
+            .map(|(config, repetition)| self.train(config, repetition, evaluator))
+            .collect();
+        self.summarize(reports)
+    }
+
+    /// Returns the index of the config and the number of the repetition of every training, the
+    /// repetitions of a config follow each other.
+    fn runs(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (0..self.configs.len()).flat_map(move |config| {
+            (0..self.repetitions).map(move |repetition| (config, repetition))
+        })
+    }
+
+    /// Trains a new solver with the config, the seed is offset by the number of the repetition.
+    fn train<E: FitnessEvaluator + ?Sized>(
+        &self,
+        config: usize,
+        repetition: usize,
+        evaluator: &E,
+    ) -> TrainReport {
+        let mut config = self.configs[config];
+        config.seed = config.seed.map(|seed| seed.wrapping_add(repetition as u64));
+        let mut solver = Solver::with_config(self.inputs, self.outputs, config);
+        solver.train(evaluator, &self.options)
+    }
+
+    /// Computes the results of every config from the reports returned by `runs` and ranks them.
+    fn summarize(&self, reports: Vec<TrainReport>) -> Vec<SweepResult> {
+        let mut results: Vec<SweepResult> = if self.repetitions == 0 {
+            self.configs
+                .iter()
+                .map(|&config| SweepResult::compute(config, &[]))
+                .collect()
+        } else {
+            self.configs
+                .iter()
+                .zip(reports.chunks(self.repetitions))
+                .map(|(&config, reports)| SweepResult::compute(config, reports))
+                .collect()
+        };
+        // a stable sort keeps the order of the configs with the same mean fitness
+        let score = |result: &SweepResult| result.config.direction.score(result.mean_fitness);
+        results.sort_by(|a, b| score(b).total_cmp(&score(a)));
+        results
+    }
+}
+
+impl SweepResult {
+    /// Computes the statistics of the reports of all repetitions of the config.
+    fn compute(config: Config, reports: &[TrainReport]) -> Self {
+        let fitness: Vec<f32> = reports
+            .iter()
+            .map(|report| report.champion.fitness())
+            .collect();
+        let count = reports.len().max(1) as f32;
+        let mean_fitness = if reports.is_empty() {
+            f32::MIN
+        } else {
+            fitness.iter().sum::<f32>() / count
+        };
+        let variance = fitness
+            .iter()
+            .map(|f| (f - mean_fitness) * (f - mean_fitness))
+            .sum::<f32>()
+            / count;
+        let direction = config.direction;
+        let generations: usize = reports.iter().map(|report| report.history.len()).sum();
+        SweepResult {
+            config,
+            mean_fitness,
+            fitness_std_dev: variance.sqrt(),
+            best_fitness: select(&fitness, |a, b| direction.is_better(a, b)),
+            worst_fitness: select(&fitness, |a, b| direction.is_better(b, a)),
+            mean_generations: generations as f32 / count,
+            solved: reports
+                .iter()
+                .filter(|report| report.stop_reason == StopReason::TargetFitness)
+                .count(),
+            fitness,
+        }
+    }
+}
+
+/// Returns the value which is preferred over all others, the first one on ties and `f32::MIN` if
+/// there are no values.
+fn select(values: &[f32], prefer: impl Fn(f32, f32) -> bool) -> f32 {
+    values
+        .iter()
+        .copied()
+        .reduce(|chosen, value| if prefer(value, chosen) { value } else { chosen })
+        .unwrap_or(f32::MIN)
+}
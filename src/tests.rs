@@ -10,6 +10,7 @@ pub fn create_neural_network() {
 }
 
 #[test]
+#[cfg(feature = "std-fs")]
 pub fn save_and_load_neural_network() {
     use crate::NeuralNetwork;
     use tempfile::tempdir;
@@ -22,23 +23,25 @@ pub fn save_and_load_neural_network() {
     // saving both networks as a binary
     let file_location = dir.path().join("test-load.nn");
     let file_location2 = dir.path().join("test-load2.nn");
-    nn.save_as(file_location.as_path().to_str().unwrap());
-    nn2.save_as(file_location2.as_path().to_str().unwrap());
+    nn.save_as(file_location.as_path().to_str().unwrap())
+        .unwrap();
+    nn2.save_as(file_location2.as_path().to_str().unwrap())
+        .unwrap();
 
     // binary of first network should be equal to binary data in first file
     assert_eq!(
-        nn.as_byte_representation(),
-        NeuralNetwork::load_bytes_from(file_location.as_path().to_str().unwrap())
+        nn.as_byte_representation().unwrap(),
+        NeuralNetwork::load_bytes_from(file_location.as_path().to_str().unwrap()).unwrap()
     );
     // both files should contain different data
     assert_ne!(
-        NeuralNetwork::load_bytes_from(file_location.as_path().to_str().unwrap()),
-        NeuralNetwork::load_bytes_from(file_location2.as_path().to_str().unwrap())
+        NeuralNetwork::load_bytes_from(file_location.as_path().to_str().unwrap()).unwrap(),
+        NeuralNetwork::load_bytes_from(file_location2.as_path().to_str().unwrap()).unwrap()
     );
 
     // load both networks from their respective files
-    let new_nn = NeuralNetwork::load_from(file_location.as_path().to_str().unwrap());
-    let new_nn2 = NeuralNetwork::load_from(file_location2.as_path().to_str().unwrap());
+    let new_nn = NeuralNetwork::load_from(file_location.as_path().to_str().unwrap()).unwrap();
+    let new_nn2 = NeuralNetwork::load_from(file_location2.as_path().to_str().unwrap()).unwrap();
 
     // they should equal themselves, but not the other network
     assert_eq!(nn, new_nn);
@@ -58,6 +61,7 @@ pub fn create_solver() {
 }
 
 #[test]
+#[cfg(feature = "std-fs")]
 pub fn save_and_load_solver() {
     use crate::Solver;
     use tempfile::tempdir;
@@ -70,23 +74,25 @@ pub fn save_and_load_solver() {
     // saving both solvers as a binary
     let file_location = dir.path().join("test-load.nn");
     let file_location2 = dir.path().join("test-load2.nn");
-    sv.save_as(file_location.as_path().to_str().unwrap());
-    sv2.save_as(file_location2.as_path().to_str().unwrap());
+    sv.save_as(file_location.as_path().to_str().unwrap())
+        .unwrap();
+    sv2.save_as(file_location2.as_path().to_str().unwrap())
+        .unwrap();
 
     // binary of first solver should be equal to binary data in first file
     assert_eq!(
-        sv.as_byte_representation(),
-        Solver::load_bytes_from(file_location.as_path().to_str().unwrap())
+        sv.as_byte_representation().unwrap(),
+        Solver::load_bytes_from(file_location.as_path().to_str().unwrap()).unwrap()
     );
     // both files should contain different data
     assert_ne!(
-        Solver::load_bytes_from(file_location.as_path().to_str().unwrap()),
-        Solver::load_bytes_from(file_location2.as_path().to_str().unwrap())
+        Solver::load_bytes_from(file_location.as_path().to_str().unwrap()).unwrap(),
+        Solver::load_bytes_from(file_location2.as_path().to_str().unwrap()).unwrap()
     );
 
     // load both solvers from their respective files
-    let new_sv = Solver::load_from(file_location.as_path().to_str().unwrap());
-    let new_sv2 = Solver::load_from(file_location2.as_path().to_str().unwrap());
+    let new_sv = Solver::load_from(file_location.as_path().to_str().unwrap()).unwrap();
+    let new_sv2 = Solver::load_from(file_location2.as_path().to_str().unwrap()).unwrap();
 
     // they should equal themselves, but not the other solver
     assert_eq!(sv, new_sv);
@@ -120,7 +126,7 @@ pub fn compute_with_phenotype() {
     let nn = NeuralNetwork::with_size(1, 1);
     // creating phenotype from network with some placeholder inputs
     let mut pt = Phenotype::from_nn(&nn);
-    let res = pt.compute(vec![0.5]);
+    let res = pt.compute(&[0.5]);
     assert_eq!(pt.node_value_array, vec![0.5, 0.33333334, 0.45454547]);
     assert_eq!(res, vec![0.45454547]);
 }
@@ -137,3 +143,4168 @@ pub fn compute() {
     let res3 = nn2.compute(vec![0.5, 1.5]);
     assert_eq!(res3, vec![0.5890411, 0.5890411, 0.5890411]);
 }
+
+#[test]
+pub fn compute_masked() {
+    use crate::NeuralNetwork;
+    let mut nn = NeuralNetwork::with_size(2, 1);
+    // masked inputs should be treated as if they were 0.0
+    let res = nn.compute_masked(vec![0.5, 1.5], &[true, false]);
+    assert_eq!(res, nn.compute(vec![0.5, 0.0]));
+    let res = nn.compute_masked(vec![0.5, 1.5], &[true, true]);
+    assert_eq!(res, nn.compute(vec![0.5, 1.5]));
+}
+
+#[test]
+pub fn compute_with_presence_indicators() {
+    use crate::NeuralNetwork;
+    let mut nn = NeuralNetwork::with_presence_indicators(2, 1, 0);
+    // constant node + 2 inputs + 2 indicators + 1 output
+    assert_eq!(nn.nodes.len(), 6);
+    assert_eq!(nn.edges.len(), 5);
+    let present = nn.compute(vec![0.0, 0.0]);
+    let absent = nn.compute_masked(vec![0.0, 0.0], &[false, false]);
+    // the indicator nodes make a difference between an input of 0.0 and an absent input
+    assert_ne!(present, absent);
+    assert_eq!(absent, nn.compute_masked(vec![3.0, -2.0], &[false, false]));
+}
+
+#[test]
+pub fn change_output_count() {
+    use crate::NeuralNetwork;
+    let mut nn = NeuralNetwork::with_size(2, 1);
+    let before = nn.compute(vec![0.5, 1.5]);
+    let mut nn = nn.with_added_outputs(2);
+    assert_eq!(nn.size, (2, 3));
+    // every new output only gets a single connection
+    assert_eq!(nn.edges.len(), 5);
+    let after = nn.compute(vec![0.5, 1.5]);
+    assert_eq!(after.len(), 3);
+    // the already existing output is not affected
+    assert_eq!(after[0], before[0]);
+
+    nn.remove_output(3);
+    assert_eq!(nn.size, (2, 2));
+    assert_eq!(nn.edges.len(), 2);
+    assert_eq!(nn.compute(vec![0.5, 1.5]).len(), 2);
+}
+
+#[test]
+pub fn change_output_count_of_solver() {
+    use crate::Solver;
+    let mut sv = Solver::with_size(2, 1, 5);
+    sv.add_outputs(2);
+    let networks: Vec<_> = sv.neural_nets().map(|nn| nn.clone()).collect();
+    for nn in networks.iter() {
+        assert_eq!(nn.size, (2, 3));
+        // new outputs have the same ids in every network
+        assert_eq!(nn.nodes, networks[0].nodes);
+    }
+    sv.remove_output(4);
+    for nn in sv.neural_nets() {
+        assert_eq!(nn.compute(vec![0.5, 1.5]).len(), 2);
+    }
+}
+
+#[test]
+pub fn remap_inputs() {
+    use crate::NeuralNetwork;
+    let mut nn = NeuralNetwork::with_size(3, 2);
+    // give every edge an individual weight so that the inputs are distinguishable
+    for (i, edge) in nn.edges.iter_mut().enumerate() {
+        edge.weight = i as f32 * 0.1;
+    }
+    let before = nn.compute(vec![0.1, 0.2, 0.3]);
+    // reverse the inputs and add a new one in the middle
+    nn.remap_inputs(&[Some(2), None, Some(1), Some(0)]);
+    assert_eq!(nn.size, (4, 2));
+    assert_eq!(nn.nodes.len(), 7);
+    assert_eq!(nn.edges.len(), 10);
+    // with an input of 0.0 the new input node does not change the result
+    let after = nn.compute(vec![0.3, 0.0, 0.2, 0.1]);
+    for (a, b) in after.iter().zip(before.iter()) {
+        assert!((a - b).abs() < 1e-6);
+    }
+
+    // removing inputs also removes their edges
+    nn.remap_inputs(&[Some(3)]);
+    assert_eq!(nn.size, (1, 2));
+    assert_eq!(nn.edges.len(), 4);
+}
+
+#[test]
+pub fn extend_inputs_with_presence_indicators() {
+    use crate::NeuralNetwork;
+    let nn = NeuralNetwork::with_presence_indicators(1, 1, 0);
+    let mut nn = nn.with_extended_inputs(2);
+    assert_eq!(nn.size, (3, 1));
+    // constant node + 3 inputs + 3 indicators + 1 output
+    assert_eq!(nn.nodes.len(), 8);
+    let res = nn.compute_masked(vec![0.5, 0.5, 0.5], &[true, false, true]);
+    assert_eq!(res.len(), 1);
+}
+
+#[test]
+pub fn new_generation() {
+    use crate::Solver;
+    let mut sv = Solver::with_size(2, 1, 20);
+    for generation in 0..5 {
+        for (i, nn) in sv.neural_nets().enumerate() {
+            nn.assign_fitness((i + generation) as f32);
+        }
+        sv.new_generation();
+        // the new generation always has the configured size and consecutive ids
+        let ids: Vec<usize> = sv.neural_nets().map(|nn| nn.id).collect();
+        assert_eq!(ids, (0..20).collect::<Vec<usize>>());
+        for nn in sv.neural_nets() {
+            assert_eq!(nn.fitness, f32::MIN);
+            assert_eq!(nn.compute(vec![0.5, 0.5]).len(), 1);
+        }
+    }
+}
+
+#[test]
+pub fn structural_mutations() {
+    use crate::{InnovationTracker, NeuralNetwork};
+    use rand::SeedableRng;
+    let mut rng = rand_pcg::Pcg64::seed_from_u64(42);
+    let mut nn = NeuralNetwork::with_size(2, 2);
+    let mut innovations = InnovationTracker::for_network(&nn);
+    let before = nn.compute(vec![0.5, 0.5]);
+    for _ in 0..20 {
+        assert!(nn.mutate_add_node(&mut innovations, &mut rng));
+        nn.mutate_add_connection(&mut innovations, &mut rng);
+    }
+    assert_eq!(nn.nodes.len(), 25);
+    // every node id is unique
+    for (i, a) in nn.nodes.iter().enumerate() {
+        assert!(nn.nodes[i + 1..].iter().all(|b| a.id != b.id));
+    }
+    for (i, a) in nn.edges.iter().enumerate() {
+        assert!(nn.edges[i + 1..]
+            .iter()
+            .all(|b| a.innovation != b.innovation));
+    }
+    // the phenotype processes every node exactly once
+    let pt = crate::phenotype::Phenotype::from_nn(&nn);
+    let mut order = pt.topo_order.clone();
+    order.sort();
+    assert_eq!(order, (0..25).collect::<Vec<usize>>());
+    assert_ne!(nn.compute(vec![0.5, 0.5]), before);
+}
+
+#[test]
+pub fn same_split_gets_same_node_id() {
+    use crate::{InnovationTracker, NeuralNetwork};
+    use rand::SeedableRng;
+    let mut nn = NeuralNetwork::with_size(1, 1);
+    let mut nn2 = nn.clone();
+    let mut innovations = InnovationTracker::for_network(&nn);
+    // both networks split the same edge, so they should get the same structure
+    nn.edges.remove(0);
+    nn2.edges.remove(0);
+    let mut rng = rand_pcg::Pcg64::seed_from_u64(1);
+    nn.mutate_add_node(&mut innovations, &mut rng);
+    nn2.mutate_add_node(&mut innovations, &mut rng);
+    assert_eq!(nn.nodes, nn2.nodes);
+    assert_eq!(nn.edges, nn2.edges);
+}
+
+#[test]
+pub fn mutate_weights() {
+    use crate::config::{Config, Perturbation};
+    use crate::NeuralNetwork;
+    use rand::SeedableRng;
+    let mut rng = rand_pcg::Pcg64::seed_from_u64(7);
+    let nn = NeuralNetwork::with_size(3, 2);
+    let mut config = Config {
+        weight_mutation_rate: 1.0,
+        weight_replace_rate: 0.0,
+        weight_perturbation: Perturbation::Uniform(0.0),
+        ..Config::default()
+    };
+    // a perturbation of 0.0 does not change anything
+    let mut mutated = nn.clone();
+    mutated.mutate_weights(&config, &mut rng);
+    assert_eq!(mutated.edges, nn.edges);
+
+    // every weight is being perturbed
+    config.weight_perturbation = Perturbation::Gaussian(0.5);
+    let mut mutated = nn.clone();
+    mutated.mutate_weights(&config, &mut rng);
+    assert!(mutated.edges.iter().all(|edge| edge.weight != 1.0));
+
+    // every weight is being replaced by a value in [-1.0, 1.0]
+    config.weight_replace_rate = 1.0;
+    config.weight_perturbation = Perturbation::Uniform(10.0);
+    let mut mutated = nn.clone();
+    mutated.mutate_weights(&config, &mut rng);
+    assert!(mutated.edges.iter().all(|edge| edge.weight.abs() <= 1.0));
+}
+
+#[test]
+pub fn crossover() {
+    use crate::{InnovationTracker, NeuralNetwork};
+    use rand::SeedableRng;
+    let mut rng = rand_pcg::Pcg64::seed_from_u64(3);
+    let initial = NeuralNetwork::with_size(2, 1);
+    let mut innovations = InnovationTracker::for_network(&initial);
+    let mut a = initial.clone();
+    a.mutate_add_node(&mut innovations, &mut rng);
+    // split a different edge in the second genome
+    let mut b = initial.clone();
+    while b.edges().len() == 3 || b.edges()[3].innovation == a.edges()[3].innovation {
+        b = initial.clone();
+        b.mutate_add_node(&mut innovations, &mut rng);
+    }
+    for edge in b.edges.iter_mut() {
+        edge.weight = 2.0;
+    }
+    a.assign_fitness(1.0);
+    b.assign_fitness(2.0);
+    // b is fitter, so all genes (and nodes) are inherited from b
+    let child = a.crossover(&b, &mut rng);
+    assert_eq!(child.nodes, b.nodes);
+    let innovations_of = |nn: &NeuralNetwork| -> Vec<usize> {
+        nn.edges.iter().map(|edge| edge.innovation).collect()
+    };
+    assert_eq!(innovations_of(&child), innovations_of(&b));
+    assert_eq!(child.fitness, f32::MIN);
+    // matching genes come from either parent, the others only from b
+    for edge in child.edges.iter() {
+        if a.edges.iter().all(|e| e.innovation != edge.innovation) {
+            assert_eq!(edge.weight, 2.0);
+        }
+    }
+}
+
+#[test]
+pub fn set_fitness() {
+    use crate::Solver;
+    let mut sv = Solver::with_size(2, 1, 4);
+    sv.set_fitness(0, 1.0);
+    sv.set_fitness(1, f32::NAN);
+    sv.set_fitness(2, -3.0);
+    sv.set_fitness(3, 5.0);
+    let fitness: Vec<f32> = sv.neural_nets().map(|nn| nn.fitness()).collect();
+    assert_eq!(fitness, vec![1.0, f32::MIN, -3.0, 5.0]);
+    assert_eq!(sv.best_network().id, 3);
+}
+
+#[test]
+#[cfg(feature = "std-fs")]
+pub fn load_errors() {
+    use crate::{Error, NeuralNetwork, Solver};
+    use tempfile::tempdir;
+    let dir = tempdir().unwrap();
+    // missing files are reported as io errors
+    let missing = dir.path().join("missing.nn");
+    let missing = missing.as_path().to_str().unwrap();
+    assert!(matches!(
+        NeuralNetwork::load_from(missing),
+        Err(Error::Io(_))
+    ));
+    assert!(matches!(Solver::load_from(missing), Err(Error::Io(_))));
+
+    // data without the file header is reported as unknown format
+    let unknown = dir.path().join("unknown.nn");
+    std::fs::write(&unknown, [1, 2, 3]).unwrap();
+    let unknown = unknown.as_path().to_str().unwrap();
+    assert!(matches!(
+        NeuralNetwork::load_from(unknown),
+        Err(Error::UnknownFormat)
+    ));
+
+    // a solver is not a network
+    let solver = dir.path().join("solver.sv");
+    let solver = solver.as_path().to_str().unwrap();
+    Solver::with_size(1, 1, 2).save_as(solver).unwrap();
+    assert!(matches!(
+        NeuralNetwork::load_from(solver),
+        Err(Error::UnknownFormat)
+    ));
+
+    // files from newer versions are rejected
+    let mut bytes = NeuralNetwork::with_size(1, 1)
+        .as_byte_representation()
+        .unwrap();
+    bytes[4..8].copy_from_slice(&42u32.to_le_bytes());
+    let newer = dir.path().join("newer.nn");
+    std::fs::write(&newer, bytes).unwrap();
+    let newer = newer.as_path().to_str().unwrap();
+    assert!(matches!(
+        NeuralNetwork::load_from(newer),
+        Err(Error::UnsupportedVersion(42))
+    ));
+    // the layout of solvers stored with bincode is not known
+    let mut bytes = Solver::with_size(1, 1, 2).as_byte_representation().unwrap();
+    bytes[4..8].copy_from_slice(&1u32.to_le_bytes());
+    assert!(matches!(
+        Solver::load_from_reader(&bytes[..]),
+        Err(Error::UnsupportedVersion(1))
+    ));
+
+    // truncated or damaged data is detected by the checksum
+    let bytes = NeuralNetwork::with_size(1, 1)
+        .as_byte_representation()
+        .unwrap();
+    let corrupted = dir.path().join("corrupted.nn");
+    std::fs::write(&corrupted, &bytes[..bytes.len() - 1]).unwrap();
+    let corrupted = corrupted.as_path().to_str().unwrap();
+    assert!(matches!(
+        NeuralNetwork::load_from(corrupted),
+        Err(Error::Corrupted)
+    ));
+    let mut damaged = bytes.clone();
+    damaged[20] ^= 0x10;
+    assert!(matches!(
+        NeuralNetwork::<f32>::from_bytes(&damaged),
+        Err(Error::Corrupted)
+    ));
+    assert!(matches!(
+        NeuralNetwork::<f32>::from_bytes(&bytes[..10]),
+        Err(Error::Corrupted)
+    ));
+    let solver = Solver::with_size(1, 1, 5).as_byte_representation().unwrap();
+    assert!(matches!(
+        Solver::load_from_reader(&solver[..solver.len() / 2]),
+        Err(Error::Corrupted)
+    ));
+
+    // data with a valid checksum which cannot be decoded is reported as serialization error
+    let undecodable = crate::format::with_header(crate::format::NETWORK_MAGIC, vec![0xc1]);
+    assert!(matches!(
+        NeuralNetwork::<f32>::from_bytes(&undecodable),
+        Err(Error::Serialization(_))
+    ));
+
+    // a network with a dangling edge is reported as invalid genome
+    let mut nn = NeuralNetwork::with_size(1, 1);
+    nn.edges[0].to = 42;
+    let invalid = dir.path().join("invalid.nn");
+    let invalid = invalid.as_path().to_str().unwrap();
+    nn.save_as(invalid).unwrap();
+    assert!(matches!(
+        NeuralNetwork::load_from(invalid),
+        Err(Error::InvalidGenome(_))
+    ));
+    dir.close().unwrap();
+}
+
+#[test]
+pub fn activation_functions() {
+    use crate::{Activation, NeuralNetwork};
+    assert_eq!(Activation::Sigmoid.apply(1.0), 0.5);
+    assert_eq!(Activation::ReLU.apply(-2.0), 0.0);
+    assert_eq!(Activation::LeakyReLU.apply(-2.0), -0.02);
+    assert_eq!(Activation::Gaussian.apply(0.0), 1.0);
+    assert_eq!(Activation::Step.apply(0.3), 1.0);
+    assert_eq!(Activation::Identity.apply(-4.0), -4.0);
+    assert_eq!(Activation::Abs.apply(-4.0), 4.0);
+    assert_eq!(Activation::Cosine.apply(0.0), 1.0);
+    assert_eq!(Activation::Square.apply(-3.0), 9.0);
+
+    // the activation function is applied per node
+    let mut nn = NeuralNetwork::with_size(1, 1).with_activation(Activation::Identity);
+    nn.nodes[2].activation = Activation::ReLU;
+    assert_eq!(nn.compute(vec![-3.0]), vec![0.0]);
+    // and it is stored together with the network
+    let bytes = nn.as_byte_representation().unwrap();
+    let loaded = NeuralNetwork::create_from_bytes(bytes).unwrap();
+    assert_eq!(loaded.nodes[2].activation, Activation::ReLU);
+}
+
+#[test]
+#[cfg(feature = "rayon")]
+pub fn evaluate_generation() {
+    use crate::{Config, NeuralNetwork, Solver, WeightInit};
+    let evaluator = |nn: &mut NeuralNetwork| nn.compute(vec![0.5, 0.5])[0] + nn.id as f32;
+    // both solvers start with the same weights
+    let config = Config::builder()
+        .generation_size(50)
+        .weight_init(WeightInit::Constant(1.0))
+        .build();
+    let mut sv = Solver::with_config(2, 1, config);
+    sv.evaluate_generation(&evaluator);
+    let sequential: Vec<f32> = sv.neural_nets().map(|nn| nn.fitness()).collect();
+    let mut sv2 = Solver::with_config(2, 1, config);
+    sv2.evaluate_generation_parallel(&evaluator);
+    let parallel: Vec<f32> = sv2.neural_nets().map(|nn| nn.fitness()).collect();
+    // the order of the networks is not changed by the parallel evaluation
+    assert_eq!(sequential, parallel);
+    assert_eq!(sv.best_network().id, 49);
+}
+
+#[test]
+pub fn train_until_target_fitness() {
+    use crate::{NeuralNetwork, Solver, StopReason, TrainOptions};
+    // the output should be as big as possible
+    let evaluator = |nn: &mut NeuralNetwork| nn.compute(vec![0.5])[0];
+    let mut sv = Solver::with_size(1, 1, 50);
+    let options = TrainOptions {
+        max_generations: Some(500),
+        target_fitness: Some(0.6),
+        ..TrainOptions::default()
+    };
+    let report = sv.train(&evaluator, &options);
+    assert_eq!(report.stop_reason, StopReason::TargetFitness);
+    assert!(report.champion.fitness() >= 0.6);
+    assert_eq!(
+        report.history.last().unwrap().best_fitness,
+        report.champion.fitness()
+    );
+
+    // the fitness cannot improve for a constant evaluator
+    let mut sv = Solver::with_size(1, 1, 10);
+    let options = TrainOptions {
+        stagnation_limit: Some(3),
+        ..TrainOptions::default()
+    };
+    let report = sv.train(&|_: &mut NeuralNetwork| 1.0, &options);
+    assert_eq!(report.stop_reason, StopReason::Stagnation);
+    assert_eq!(report.history.len(), 4);
+}
+
+#[test]
+pub fn species_stagnation() {
+    use crate::species::Species;
+    use crate::{Direction, NeuralNetwork, Solver};
+    let mut species = Species::new_with_network(NeuralNetwork::with_size(1, 1), 0, 0);
+    species.update_best_fitness(1.0, 0, Direction::Maximize);
+    species.update_best_fitness(2.0, 3, Direction::Maximize);
+    // no improvement
+    species.update_best_fitness(2.0, 5, Direction::Maximize);
+    assert_eq!(species.last_improvement, 3);
+    assert!(!species.is_stagnant(10, 15));
+    assert!(species.is_stagnant(18, 15));
+
+    // even if every species stagnates the population does not die out
+    let mut sv = Solver::with_size(2, 1, 20);
+    for _ in 0..40 {
+        sv.evaluate_generation(&|_: &mut NeuralNetwork| 1.0);
+        sv.new_generation();
+        assert_eq!(sv.neural_nets().count(), 20);
+    }
+}
+
+#[test]
+pub fn elitism_keeps_best_network() {
+    use crate::{NeuralNetwork, Solver, TrainOptions};
+    // a deterministic evaluator preferring high outputs
+    let evaluator = |nn: &mut NeuralNetwork| nn.compute(vec![0.3, -0.7])[0];
+    let mut sv = Solver::with_size(2, 1, 30);
+    let options = TrainOptions {
+        max_generations: Some(30),
+        ..TrainOptions::default()
+    };
+    let report = sv.train(&evaluator, &options);
+    // the best network is copied unchanged, so the best fitness never decreases
+    for pair in report.history.windows(2) {
+        assert!(pair[1].best_fitness >= pair[0].best_fitness);
+    }
+}
+
+#[test]
+pub fn adjust_distance_threshold() {
+    use crate::{Config, NeuralNetwork, Solver, WeightInit};
+    let config = Config::builder()
+        .generation_size(20)
+        .weight_init(WeightInit::Constant(1.0))
+        .build();
+    let mut sv = Solver::with_config(2, 1, config);
+    let evaluator = |nn: &mut NeuralNetwork| nn.compute(vec![0.3, 0.7])[0];
+    // all initial networks are identical, so there are too few species and the threshold sinks
+    sv.evaluate_generation(&evaluator);
+    sv.new_generation();
+    assert!(sv.distance_threshold() < 1.0);
+    for _ in 0..50 {
+        sv.evaluate_generation(&evaluator);
+        sv.new_generation();
+        assert!(sv.distance_threshold() >= 0.1);
+    }
+}
+
+#[test]
+pub fn config_builder() {
+    use crate::{Config, Perturbation, Solver};
+    let config = Config::builder()
+        .generation_size(12)
+        .distance_threshold(2.5)
+        .weight_perturbation(Perturbation::Gaussian(0.1))
+        .target_species(None)
+        .build();
+    assert_eq!(config.generation_size, 12);
+    assert_eq!(config.weight_perturbation, Perturbation::Gaussian(0.1));
+    // the other parameters keep their default values
+    assert_eq!(config.c1, Config::default().c1);
+    let mut sv = Solver::with_config(3, 2, config);
+    assert_eq!(sv.neural_nets().count(), 12);
+    assert_eq!(sv.distance_threshold(), 2.5);
+    assert_eq!(sv.config(), &config);
+}
+
+#[test]
+#[cfg(feature = "serde_json")]
+pub fn json_representation() {
+    use crate::{Error, NeuralNetwork, Solver};
+    let mut sv = Solver::with_size(2, 1, 10);
+    for _ in 0..5 {
+        sv.evaluate_generation(&|nn: &mut NeuralNetwork| nn.compute(vec![0.1, 0.9])[0]);
+        sv.new_generation();
+    }
+    let json = sv.to_json().unwrap();
+    assert_eq!(Solver::from_json(&json).unwrap(), sv);
+
+    let nn = sv.best_network();
+    let json = nn.to_json().unwrap();
+    assert!(json.contains("\"innovation\""));
+    assert_eq!(NeuralNetwork::from_json(&json).unwrap(), nn);
+    assert!(matches!(
+        NeuralNetwork::from_json("{}"),
+        Err(Error::Json(_))
+    ));
+}
+
+#[test]
+pub fn dot_representation() {
+    use crate::{InnovationTracker, NeuralNetwork};
+    use rand::SeedableRng;
+    let mut rng = rand_pcg::Pcg64::seed_from_u64(3);
+    let mut nn = NeuralNetwork::with_size(2, 1);
+    let mut innovations = InnovationTracker::for_network(&nn);
+    assert!(nn.mutate_add_node(&mut innovations, &mut rng));
+    let dot = nn.to_dot();
+    assert!(dot.starts_with("digraph {") && dot.ends_with("}\n"));
+    assert_eq!(dot.matches("fillcolor=lightblue").count(), 2);
+    assert_eq!(dot.matches("fillcolor=lightyellow").count(), 1);
+    assert_eq!(dot.matches("fillcolor=lightgrey").count(), 1);
+    assert_eq!(dot.matches("fillcolor=lightgreen").count(), 1);
+    assert_eq!(dot.matches(" -> ").count(), nn.edges.len());
+    // the split edge is disabled
+    assert_eq!(dot.matches("style=dashed").count(), 1);
+}
+
+#[test]
+pub fn recurrent_network() {
+    use crate::neuralnetwork::Edge;
+    use crate::{Activation, InnovationTracker, NeuralNetwork};
+    use rand::SeedableRng;
+    // the output remembers its previous value through a recurrent edge to itself
+    let mut nn = NeuralNetwork::with_size(1, 1)
+        .with_activation(Activation::Identity)
+        .with_recurrent(true);
+    nn.edges[0].weight = 0.0;
+    nn.edges.push(Edge {
+        from: 2,
+        to: 2,
+        weight: 1.0,
+        enabled: true,
+        innovation: 2,
+        recurrent: true,
+    });
+    assert_eq!(nn.compute(vec![1.0]), vec![1.0]);
+    assert_eq!(nn.compute(vec![1.0]), vec![2.0]);
+    assert_eq!(nn.compute(vec![0.0]), vec![2.0]);
+    nn.reset_state();
+    assert_eq!(nn.compute(vec![0.5]), vec![0.5]);
+
+    // connections which would form a cycle are added as recurrent edges
+    let mut rng = rand_pcg::Pcg64::seed_from_u64(7);
+    let mut nn = NeuralNetwork::with_size(2, 2).with_recurrent(true);
+    let mut innovations = InnovationTracker::for_network(&nn);
+    for _ in 0..10 {
+        nn.mutate_add_node(&mut innovations, &mut rng);
+    }
+    while nn.mutate_add_connection(&mut innovations, &mut rng) {}
+    assert!(nn.edges.iter().any(|edge| edge.recurrent));
+    // every pair of nodes without an input as destination is connected
+    assert_eq!(nn.edges.len(), nn.nodes.len() * (nn.nodes.len() - 3));
+    let pt = crate::phenotype::Phenotype::from_nn(&nn);
+    let mut order = pt.topo_order.clone();
+    order.sort();
+    assert_eq!(order, (0..nn.nodes.len()).collect::<Vec<usize>>());
+    let first = nn.compute(vec![0.5, 0.5]);
+    assert_ne!(nn.compute(vec![0.5, 0.5]), first);
+
+    // feed-forward networks never get recurrent edges
+    let mut nn = NeuralNetwork::with_size(2, 2);
+    let mut innovations = InnovationTracker::for_network(&nn);
+    for _ in 0..10 {
+        nn.mutate_add_node(&mut innovations, &mut rng);
+    }
+    while nn.mutate_add_connection(&mut innovations, &mut rng) {}
+    assert!(nn.edges.iter().all(|edge| !edge.recurrent));
+}
+
+#[test]
+pub fn compute_batch() {
+    use crate::{InnovationTracker, NeuralNetwork};
+    use rand::SeedableRng;
+    let mut rng = rand_pcg::Pcg64::seed_from_u64(11);
+    let mut nn = NeuralNetwork::with_presence_indicators(2, 2, 0);
+    let mut innovations = InnovationTracker::for_network(&nn);
+    for _ in 0..5 {
+        nn.mutate_add_node(&mut innovations, &mut rng);
+        nn.mutate_add_connection(&mut innovations, &mut rng);
+    }
+    let inputs: Vec<Vec<f32>> = (0..20)
+        .map(|i| vec![i as f32 / 10.0, 1.0 - i as f32 / 10.0])
+        .collect();
+    let outputs = nn.compute_batch(&inputs);
+    assert_eq!(outputs.len(), inputs.len());
+    for (input, output) in inputs.iter().zip(outputs.iter()) {
+        assert_eq!(&nn.compute(input.clone()), output);
+    }
+    assert!(nn.compute_batch(&[]).is_empty());
+}
+
+#[test]
+pub fn compute_into() {
+    use crate::NeuralNetwork;
+    let mut nn = NeuralNetwork::with_presence_indicators(3, 2, 0);
+    let mut output = [0.0; 2];
+    let input = [0.3, -0.2, 0.9];
+    nn.compute_into(&input, &mut output);
+    assert_eq!(output.to_vec(), nn.compute(input));
+    assert_eq!(output.to_vec(), nn.compute(&input[..]));
+}
+
+#[test]
+#[should_panic]
+pub fn compute_into_wrong_buffer() {
+    use crate::NeuralNetwork;
+    let mut nn = NeuralNetwork::with_size(3, 2);
+    let mut output = [0.0; 3];
+    nn.compute_into(&[0.3, -0.2, 0.9], &mut output);
+}
+
+#[test]
+#[should_panic]
+pub fn compute_wrong_input() {
+    use crate::NeuralNetwork;
+    // the extra input would otherwise be read as a presence indicator
+    let mut nn = NeuralNetwork::with_presence_indicators(2, 1, 0);
+    nn.compute([0.5, 0.5, 9.0]);
+}
+
+#[test]
+pub fn phenotype_handle() {
+    use crate::{InnovationTracker, NeuralNetwork};
+    use rand::SeedableRng;
+    let mut rng = rand_pcg::Pcg64::seed_from_u64(5);
+    let mut nn = NeuralNetwork::with_size(2, 1);
+    let mut pt = nn.phenotype();
+    let before = nn.compute([0.2, 0.4]);
+    assert_eq!(pt.compute(&[0.2, 0.4]), before);
+    // the phenotype keeps computing the network it has been created from
+    let mut innovations = InnovationTracker::for_network(&nn);
+    nn.mutate_add_node(&mut innovations, &mut rng);
+    nn.edges[0].weight = 3.0;
+    assert_ne!(nn.compute([0.2, 0.4]), before);
+    assert_eq!(pt.compute(&[0.2, 0.4]), before);
+}
+
+#[test]
+pub fn hall_of_fame() {
+    use crate::{Config, NeuralNetwork, Solver};
+    let config = Config::builder()
+        .generation_size(20)
+        .hall_of_fame_size(5)
+        .build();
+    let mut solver = Solver::with_config(2, 1, config);
+    assert!(solver.hall_of_fame().is_empty());
+    let mut best = f32::MIN;
+    for _ in 0..10 {
+        solver.evaluate_generation(&|nn: &mut NeuralNetwork| nn.compute([0.3, 0.7])[0]);
+        best = best.max(solver.best_network().fitness());
+        solver.new_generation();
+    }
+    let hall_of_fame = solver.hall_of_fame();
+    assert_eq!(hall_of_fame.len(), 5);
+    assert_eq!(hall_of_fame.best().unwrap().fitness(), best);
+    let networks = hall_of_fame.networks();
+    for (i, a) in networks.iter().enumerate() {
+        // sorted by fitness and every genome is unique
+        assert!(networks[i + 1..]
+            .iter()
+            .all(|b| a >= b && !a.same_genome(b)));
+    }
+    // the hall of fame is stored together with the solver
+    let bytes = solver.as_byte_representation().unwrap();
+    let loaded = Solver::create_from_bytes(bytes).unwrap();
+    assert_eq!(loaded.hall_of_fame(), solver.hall_of_fame());
+}
+
+#[test]
+pub fn generation_stats() {
+    use crate::{
+        Direction, DiversityMetrics, GenerationStats, NeuralNetwork, Solver, TrainOptions,
+    };
+    let mut networks: Vec<NeuralNetwork> = (0..4).map(|_| NeuralNetwork::with_size(1, 1)).collect();
+    for (i, nn) in networks.iter_mut().enumerate() {
+        nn.set_fitness([4.0, 1.0, 2.0, 5.0][i]);
+    }
+    networks[0].edges[0].enabled = false;
+    let stats = GenerationStats::compute(
+        7,
+        &networks,
+        vec![3, 1],
+        DiversityMetrics::default(),
+        Direction::Maximize,
+    );
+    assert_eq!(stats.generation, 7);
+    assert_eq!(stats.best_fitness, 5.0);
+    assert_eq!(stats.average_fitness, 3.0);
+    assert_eq!(stats.median_fitness, 3.0);
+    assert_eq!(stats.fitness_std_dev, 2.5f32.sqrt());
+    assert_eq!(stats.species_count, 2);
+    assert_eq!(stats.average_nodes, 3.0);
+    assert_eq!(stats.average_edges, 1.75);
+
+    let mut sv = Solver::with_size(2, 1, 20);
+    assert!(sv.stats().is_none());
+    let options = TrainOptions {
+        max_generations: Some(5),
+        ..TrainOptions::default()
+    };
+    let report = sv.train(
+        &|nn: &mut NeuralNetwork| nn.compute([0.2, 0.1])[0],
+        &options,
+    );
+    assert_eq!(sv.history(), &report.history[..]);
+    for (i, stats) in sv.history().iter().enumerate() {
+        assert_eq!(stats.generation, i);
+        assert_eq!(stats.species_sizes.iter().sum::<usize>(), 20);
+    }
+    // continuing the training replaces the statistics of the last generation
+    sv.new_generation();
+    assert_eq!(sv.history().len(), 5);
+    assert_eq!(sv.stats().unwrap().generation, 4);
+}
+
+#[test]
+pub fn training_observer() {
+    use crate::{
+        GenerationStats, NeuralNetwork, Solver, StopReason, TrainOptions, TrainingObserver,
+    };
+    use std::ops::ControlFlow;
+    #[derive(Default)]
+    struct Recorder {
+        generations: Vec<usize>,
+        best: Vec<f32>,
+        extinct: usize,
+    }
+    impl TrainingObserver for Recorder {
+        fn on_generation_end(
+            &mut self,
+            solver: &Solver,
+            stats: &GenerationStats,
+        ) -> ControlFlow<()> {
+            assert_eq!(solver.stats(), Some(stats));
+            self.generations.push(stats.generation);
+            ControlFlow::Continue(())
+        }
+        fn on_new_best(&mut self, nn: &NeuralNetwork) {
+            self.best.push(nn.fitness());
+        }
+        fn on_species_extinct(&mut self, _: &crate::SpeciesInfo) {
+            self.extinct += 1;
+        }
+    }
+    let mut sv = Solver::with_size(2, 1, 30);
+    let mut recorder = Recorder::default();
+    let options = TrainOptions {
+        max_generations: Some(20),
+        ..TrainOptions::default()
+    };
+    let evaluator = |nn: &mut NeuralNetwork| nn.compute([0.4, 0.9])[0];
+    let report = sv.train_with_observer(&evaluator, &options, &mut recorder);
+    assert_eq!(report.stop_reason, StopReason::MaxGenerations);
+    assert_eq!(recorder.generations, (0..20).collect::<Vec<usize>>());
+    // every new best network is better than the previous one
+    assert!(recorder.best.windows(2).all(|pair| pair[0] < pair[1]));
+    assert_eq!(*recorder.best.last().unwrap(), report.champion.fitness());
+    assert!(recorder.extinct > 0);
+}
+
+#[test]
+#[cfg(feature = "std-fs")]
+pub fn save_replaces_existing_file() {
+    use crate::{InnovationTracker, NeuralNetwork};
+    use rand::SeedableRng;
+    use tempfile::tempdir;
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("network.nn");
+    let path = path.as_path().to_str().unwrap();
+    // a bigger network is overwritten by a smaller one without leaving trailing bytes
+    let mut big = NeuralNetwork::with_size(5, 5);
+    let mut innovations = InnovationTracker::for_network(&big);
+    let mut rng = rand_pcg::Pcg64::seed_from_u64(2);
+    for _ in 0..10 {
+        big.mutate_add_node(&mut innovations, &mut rng);
+    }
+    big.save_as(path).unwrap();
+    let small = NeuralNetwork::with_size(1, 1);
+    small.save_as(path).unwrap();
+    assert_eq!(
+        std::fs::read(path).unwrap(),
+        small.as_byte_representation().unwrap()
+    );
+    assert_eq!(NeuralNetwork::load_from(path).unwrap(), small);
+    // no temporary file is left behind
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+
+    // saving into a missing directory fails without creating anything
+    let missing = dir.path().join("missing").join("network.nn");
+    assert!(small.save_as(missing.to_str().unwrap()).is_err());
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+
+    // concurrent saves to the same path use their own temporary files
+    std::thread::scope(|scope| {
+        for inputs in 1..=8 {
+            scope.spawn(move || NeuralNetwork::with_size(inputs, 1).save_as(path).unwrap());
+        }
+    });
+    let saved = NeuralNetwork::load_from(path).unwrap();
+    assert_eq!(saved, NeuralNetwork::with_size(saved.size().0, 1));
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    dir.close().unwrap();
+}
+
+#[test]
+#[cfg(feature = "std-fs")]
+pub fn save_with_paths_and_streams() {
+    use crate::{NeuralNetwork, Solver};
+    use tempfile::tempdir;
+    let dir = tempdir().unwrap();
+    let nn = NeuralNetwork::with_size(3, 2);
+    // paths can be used directly without converting them into strings
+    let path = dir.path().join("network.nn");
+    nn.save_as(&path).unwrap();
+    assert_eq!(NeuralNetwork::load_from(&path).unwrap(), nn);
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        let non_utf8 = dir
+            .path()
+            .join(std::ffi::OsStr::from_bytes(b"net\xffwork.nn"));
+        nn.save_as(&non_utf8).unwrap();
+        assert_eq!(NeuralNetwork::load_from(non_utf8).unwrap(), nn);
+    }
+
+    // writers and readers work for in-memory data
+    let sv = Solver::with_size(2, 2, 4);
+    let mut buffer: Vec<u8> = Vec::new();
+    sv.save_to_writer(&mut buffer).unwrap();
+    assert_eq!(buffer, sv.as_byte_representation().unwrap());
+    assert_eq!(
+        Solver::load_from_reader(std::io::Cursor::new(buffer)).unwrap(),
+        sv
+    );
+    assert!(NeuralNetwork::load_from_reader(&[0u8; 3][..]).is_err());
+    dir.close().unwrap();
+}
+
+#[test]
+pub fn species_info() {
+    use crate::{Config, NeuralNetwork, Solver};
+    let config = Config::builder()
+        .generation_size(30)
+        .target_species(Some(4))
+        .build();
+    let mut sv = Solver::with_config(2, 1, config);
+    assert_eq!(sv.species().count(), 1);
+    let evaluator = |nn: &mut NeuralNetwork| nn.compute([0.1, 0.8])[0];
+    for _ in 0..15 {
+        sv.evaluate_generation(&evaluator);
+        sv.new_generation();
+        let species: Vec<crate::SpeciesInfo> = sv.species().collect();
+        // every network of the replaced generation belongs to exactly one species
+        assert_eq!(species.iter().map(|s| s.size).sum::<usize>(), 30);
+        for (i, a) in species.iter().enumerate() {
+            assert!(species[i + 1..].iter().all(|b| a.id != b.id));
+            assert!(a.representative_complexity >= 5);
+        }
+    }
+    assert!(sv.species().any(|s| s.age > 0));
+}
+
+#[test]
+pub fn toggle_connections() {
+    use crate::{Config, InnovationTracker, NeuralNetwork};
+    use rand::SeedableRng;
+    let mut rng = rand_pcg::Pcg64::seed_from_u64(9);
+    let mut nn = NeuralNetwork::with_size(3, 2);
+    let mut innovations = InnovationTracker::for_network(&nn);
+    let before = nn.compute([0.1, 0.2, 0.3]);
+    while nn.edges.iter().any(|edge| edge.enabled) {
+        assert!(nn.mutate_toggle_connection(&mut rng));
+    }
+    // without any enabled edge the inputs do not matter
+    assert_ne!(nn.compute([0.1, 0.2, 0.3]), before);
+    assert_eq!(nn.compute([0.1, 0.2, 0.3]), nn.compute([0.9, 0.8, 0.7]));
+    while nn.mutate_reenable_connection(&mut rng) {}
+    assert_eq!(nn.compute([0.1, 0.2, 0.3]), before);
+
+    // the mutation rates of the config are used by mutate
+    let config = Config::builder()
+        .weight_mutation_rate(0.0)
+        .weight_replace_rate(0.0)
+        .add_node_rate(0.0)
+        .add_connection_rate(0.0)
+        .toggle_connection_rate(1.0)
+        .build();
+    nn.mutate(&config, &mut innovations, &mut rng);
+    assert_eq!(nn.edges.iter().filter(|edge| !edge.enabled).count(), 1);
+    let config = Config::builder()
+        .weight_mutation_rate(0.0)
+        .weight_replace_rate(0.0)
+        .add_node_rate(0.0)
+        .add_connection_rate(0.0)
+        .reenable_connection_rate(1.0)
+        .build();
+    nn.mutate(&config, &mut innovations, &mut rng);
+    assert!(nn.edges.iter().all(|edge| edge.enabled));
+}
+
+#[test]
+pub fn weight_init() {
+    use crate::{Config, InnovationTracker, NeuralNetwork, Solver, WeightInit};
+    let mut rng = rand::thread_rng();
+    let nn = NeuralNetwork::with_size(3, 2).with_weight_init(WeightInit::Constant(0.5), &mut rng);
+    assert!(nn.edges.iter().all(|edge| edge.weight == 0.5));
+    // an invalid standard deviation falls back to the mean
+    let nn = NeuralNetwork::with_size(3, 2).with_weight_init(
+        WeightInit::Gaussian {
+            mean: 0.3,
+            std_dev: -1.0,
+        },
+        &mut rng,
+    );
+    assert!(nn.edges.iter().all(|edge| edge.weight == 0.3));
+    // the bound of xavier depends on the number of edges of each node
+    let nn = NeuralNetwork::with_size(3, 2).with_weight_init(WeightInit::Xavier, &mut rng);
+    let bound = (6.0f32 / 6.0).sqrt();
+    assert!(nn.edges.iter().all(|edge| edge.weight.abs() <= bound));
+    // replaced weights are sampled from the same distribution, in modules as well
+    let config = Config::builder().weight_replace_rate(1.0).build();
+    let mut nn =
+        NeuralNetwork::with_size(3, 2).with_weight_init(WeightInit::Constant(0.5), &mut rng);
+    let mut innovations = InnovationTracker::for_network(&nn);
+    assert!(nn.mutate_add_module(2, &mut innovations, &mut rng));
+    nn.weight_init = WeightInit::Constant(0.25);
+    nn.mutate_weights(&config, &mut rng);
+    assert!(nn.edges.iter().all(|edge| edge.weight == 0.25));
+    let module = &nn.modules()[0];
+    assert!(module.edges().iter().all(|edge| edge.weight == 0.25));
+    // the initial networks of the solver are different by default
+    let mut sv = Solver::with_size(3, 2, 10);
+    let edges: Vec<_> = sv.neural_nets().map(|nn| nn.edges.clone()).collect();
+    assert!(edges.iter().any(|other| *other != edges[0]));
+}
+
+#[test]
+pub fn initial_topology() {
+    use crate::{Config, InitialTopology, InnovationTracker, NeuralNetwork, Solver};
+    let mut rng = rand::thread_rng();
+    let nn =
+        NeuralNetwork::with_size(5, 3).with_initial_topology(InitialTopology::Sparse(2), &mut rng);
+    for to in 6..9 {
+        assert_eq!(nn.edges.iter().filter(|edge| edge.to == to).count(), 2);
+    }
+    // asking for more inputs than there are gives a fully connected network
+    let nn =
+        NeuralNetwork::with_size(5, 3).with_initial_topology(InitialTopology::Sparse(10), &mut rng);
+    assert_eq!(nn.edges, NeuralNetwork::with_size(5, 3).edges);
+    // an unconnected network can still be computed and grow new connections
+    let mut nn = NeuralNetwork::with_size(2, 1)
+        .with_initial_topology(InitialTopology::Unconnected, &mut rng);
+    let mut innovations = InnovationTracker::for_network(&NeuralNetwork::with_size(2, 1));
+    assert_eq!(nn.compute([0.5, 0.5]).len(), 1);
+    assert!(nn.mutate_add_connection(&mut innovations, &mut rng));
+    // the initial connections keep their innovation numbers
+    let edge = nn.edges[0];
+    assert_eq!(edge.innovation, edge.from);
+    // a solver with unconnected networks can be trained
+    let config = Config::builder()
+        .generation_size(20)
+        .initial_topology(InitialTopology::Unconnected)
+        .build();
+    let mut sv = Solver::with_config(2, 1, config);
+    assert!(sv.neural_nets().all(|nn| nn.edges.is_empty()));
+    for _ in 0..5 {
+        sv.evaluate_generation(&|nn: &mut NeuralNetwork| nn.compute([0.5, 0.5])[0]);
+        sv.new_generation();
+    }
+    assert!(sv.stats().unwrap().species_count >= 1);
+}
+
+#[test]
+pub fn pareto_ranking() {
+    use crate::pareto::{crowding_distances, non_dominated_fronts, pareto_fitness};
+    let objectives: Vec<&[f32]> = vec![
+        &[1.0, 0.0],
+        &[0.0, 1.0],
+        &[0.5, 0.5],
+        &[0.4, 0.4],
+        &[0.0, 0.0],
+    ];
+    let fronts = non_dominated_fronts(&objectives);
+    assert_eq!(fronts, vec![vec![0, 1, 2], vec![3], vec![4]]);
+    // the boundary members are preferred over the member in the middle
+    let distances = crowding_distances(&objectives, &fronts[0]);
+    assert!(distances[0].is_infinite() && distances[1].is_infinite());
+    assert_eq!(distances[2], 2.0);
+    let fitness = pareto_fitness(&objectives);
+    assert!(fitness[0] > fitness[2] && fitness[2] > fitness[3] && fitness[3] > fitness[4]);
+    assert!(fitness[4] > 0.0);
+}
+
+#[test]
+pub fn multi_objective_training() {
+    use crate::{NeuralNetwork, Solver, TrainOptions};
+    let mut sv = Solver::with_size(2, 1, 30);
+    // a high output with as few connections as possible
+    let evaluator = |nn: &mut NeuralNetwork| {
+        let enabled = nn.edges.iter().filter(|edge| edge.enabled).count();
+        vec![nn.compute([0.3, 0.6])[0], -(enabled as f32)]
+    };
+    let options = TrainOptions {
+        max_generations: Some(10),
+        ..TrainOptions::default()
+    };
+    let report = sv.train_multi_objective(&evaluator, &options, &mut ());
+    assert_eq!(report.history.len(), 10);
+    // every network has been evaluated with both objectives and got a pareto fitness
+    assert!(sv
+        .neural_nets()
+        .all(|nn| nn.objectives().len() == 2 && nn.fitness() >= 1.0));
+}
+
+#[test]
+pub fn novelty_search() {
+    use crate::novelty::{normalize, novelty_scores};
+    use crate::{Config, NeuralNetwork, Solver, TrainOptions};
+    let behaviors: Vec<&[f32]> = vec![&[0.0, 0.0], &[0.0, 1.0], &[3.0, 4.0]];
+    let scores = novelty_scores(&behaviors, &[], 1);
+    assert_eq!(scores, vec![1.0, 1.0, f32::sqrt(18.0)]);
+    // the archive counts as neighbours as well
+    let scores = novelty_scores(&behaviors, &[vec![3.0, 4.0]], 1);
+    assert_eq!(scores[2], 0.0);
+    assert_eq!(normalize(&[1.0, 3.0, 2.0]), vec![0.0, 1.0, 0.5]);
+    assert_eq!(normalize(&[2.0, 2.0]), vec![0.0, 0.0]);
+    // without novelty only the fitness of the evaluator decides
+    let config = Config::builder()
+        .generation_size(3)
+        .novelty_weight(0.0)
+        .build();
+    let mut sv = Solver::with_config(1, 1, config);
+    for (i, nn) in sv.neural_nets().enumerate() {
+        nn.set_fitness(i as f32);
+        nn.set_behavior(vec![-(i as f32)]);
+    }
+    sv.assign_novelty_fitness();
+    let fitness: Vec<f32> = sv.neural_nets().map(|nn| nn.fitness()).collect();
+    assert_eq!(fitness, vec![0.0, 0.5, 1.0]);
+    assert_eq!(sv.best_network().objectives(), [2.0]);
+    // training grows the archive and the solver can still be stored
+    let mut sv = Solver::with_size(1, 2, 20);
+    let options = TrainOptions {
+        max_generations: Some(5),
+        ..TrainOptions::default()
+    };
+    let evaluator = |nn: &mut NeuralNetwork| {
+        let outputs = nn.compute([0.5]);
+        (outputs[0], outputs)
+    };
+    sv.train_novelty(&evaluator, &options, &mut ());
+    assert_eq!(sv.novelty_archive().len(), 5);
+    let bytes = sv.as_byte_representation().unwrap();
+    let loaded = Solver::create_from_bytes(bytes).unwrap();
+    assert_eq!(loaded.novelty_archive(), sv.novelty_archive());
+}
+
+#[test]
+pub fn complexity_penalty() {
+    use crate::{Config, InnovationTracker, Solver};
+    let mut rng = rand::thread_rng();
+    // the first network is slightly better, but it has an additional hidden node
+    let prepare = |sv: &mut Solver, fitness: f32, rng: &mut rand::rngs::ThreadRng| {
+        for nn in sv.neural_nets() {
+            if nn.id == 0 {
+                let mut innovations = InnovationTracker::for_network(nn);
+                assert!(nn.mutate_add_node(&mut innovations, rng));
+                nn.set_fitness(fitness);
+            } else {
+                nn.set_fitness(1.0);
+            }
+        }
+    };
+    let config = Config::builder()
+        .generation_size(10)
+        .elitism(1)
+        .node_penalty(0.1)
+        .build();
+    let mut sv = Solver::with_config(2, 1, config);
+    prepare(&mut sv, 1.05, &mut rng);
+    sv.new_generation();
+    assert_eq!(sv.neural_nets().next().unwrap().hidden_node_count(), 0);
+    // without a penalty the better network is kept
+    let config = Config::builder().generation_size(10).elitism(1).build();
+    let mut sv = Solver::with_config(2, 1, config);
+    prepare(&mut sv, 1.05, &mut rng);
+    sv.new_generation();
+    assert_eq!(sv.neural_nets().next().unwrap().hidden_node_count(), 1);
+    // with the same fitness the smaller network is preferred
+    let config = Config::builder()
+        .generation_size(10)
+        .elitism(1)
+        .prefer_smaller_networks(true)
+        .build();
+    let mut sv = Solver::with_config(2, 1, config);
+    prepare(&mut sv, 1.0, &mut rng);
+    sv.new_generation();
+    let elite = sv.neural_nets().next().unwrap();
+    assert_eq!(elite.hidden_node_count(), 0);
+    assert_eq!(elite.enabled_edge_count(), 3);
+}
+
+#[test]
+pub fn prune() {
+    use crate::{InnovationTracker, NeuralNetwork};
+    use rand::SeedableRng;
+    let mut rng = rand_pcg::Pcg64::seed_from_u64(3);
+    let mut nn = NeuralNetwork::with_size(2, 1);
+    let mut innovations = InnovationTracker::for_network(&nn);
+    for _ in 0..4 {
+        assert!(nn.mutate_add_node(&mut innovations, &mut rng));
+    }
+    let hidden: Vec<usize> = nn
+        .nodes
+        .iter()
+        .filter(|node| node.node_type == crate::neuralnetwork::NodeType::Hidden)
+        .map(|node| node.id)
+        .collect();
+    // one hidden node cannot be reached anymore and another one has no path to the output
+    for edge in nn.edges.iter_mut() {
+        if edge.to == hidden[0] || edge.from == hidden[1] {
+            edge.enabled = false;
+        }
+    }
+    let before = nn.compute([0.3, 0.6]);
+    let report = nn.prune();
+    assert!(report.removed_nodes.contains(&hidden[0]));
+    assert!(report.removed_nodes.contains(&hidden[1]));
+    assert!(nn.edges.iter().all(|edge| edge.enabled));
+    assert!((nn.compute([0.3, 0.6])[0] - before[0]).abs() < 1e-6);
+    // the pruned network can still be stored and pruning it again changes nothing
+    let bytes = nn.as_byte_representation().unwrap();
+    assert!(NeuralNetwork::create_from_bytes(bytes).is_ok());
+    assert!(nn.prune().is_empty());
+}
+
+#[test]
+pub fn phenotype_invalidation() {
+    use crate::{Activation, NeuralNetwork};
+    let mut nn = NeuralNetwork::with_size(2, 1);
+    let before = nn.compute([0.5, 0.5]);
+    // changes which keep the numbers of nodes and edges are detected as well
+    nn.edit().set_enabled(1, 3, false).unwrap();
+    let without = nn.compute([0.5, 0.5]);
+    assert_ne!(without, before);
+    nn.edit().set_enabled(1, 3, true).unwrap();
+    assert_eq!(nn.compute([0.5, 0.5]), before);
+    nn.edit().set_activation(3, Activation::Identity).unwrap();
+    assert_ne!(nn.compute([0.5, 0.5]), before);
+    let mut copy = nn.clone();
+    copy.edit().set_activation(3, Activation::Sigmoid).unwrap();
+    assert_eq!(copy.compute([0.5, 0.5]), before);
+    // changing the genome directly needs to mark it as changed
+    nn.edges[1].weight = -2.0;
+    nn.genome_changed();
+    let changed = nn.compute([0.5, 0.5]);
+    nn.invalidate_phenotype();
+    assert_eq!(nn.compute([0.5, 0.5]), changed);
+}
+
+#[test]
+pub fn genome_editor() {
+    use crate::{Activation, Error, NeuralNetwork};
+    let mut nn = NeuralNetwork::with_size(2, 1);
+    let before = nn.compute([0.5, 0.5]);
+    let mut editor = nn.edit();
+    let hidden = editor.add_node(Activation::Identity);
+    assert_eq!(hidden, 4);
+    // invalid changes are rejected
+    assert!(matches!(
+        editor.add_connection(9, hidden, 1.0),
+        Err(Error::InvalidGenome(_))
+    ));
+    assert!(editor.add_connection(hidden, 1, 1.0).is_err());
+    assert!(editor.add_connection(0, 3, 1.0).is_err());
+    assert!(editor.set_weight(3, 0, 1.0).is_err());
+    assert!(editor.set_activation(0, Activation::ReLU).is_err());
+    assert_eq!(editor.add_connection(1, hidden, 1.0).unwrap(), 3);
+    editor.add_connection(hidden, 3, 1.0).unwrap();
+    assert!(editor.add_connection(3, hidden, 1.0).is_err());
+    editor.set_activation(hidden, Activation::ReLU).unwrap();
+    let removed = editor.remove_connection(1, hidden).unwrap();
+    assert_eq!(removed.innovation, 3);
+    assert!(nn.check_structure().is_ok());
+    // the cached phenotype has been dropped, the hidden node adds relu(0) = 0
+    assert_eq!(nn.compute([0.5, 0.5]), before);
+    nn.edit().set_weight(0, 3, -1.0).unwrap();
+    assert_ne!(nn.compute([0.5, 0.5]), before);
+    // recurrent networks accept cycles as recurrent connections
+    let mut nn = NeuralNetwork::with_size(1, 1).with_recurrent(true);
+    nn.edit().add_connection(2, 2, 0.5).unwrap();
+    assert!(nn.edges()[2].recurrent);
+}
+
+#[test]
+pub fn validate_and_repair() {
+    use crate::{GenomeProblem, NeuralNetwork};
+    let mut nn = NeuralNetwork::with_size(2, 1);
+    assert!(nn.validate().is_empty());
+    // a cycle between two hidden nodes, an edge into an input and a duplicated connection
+    let mut editor = nn.edit();
+    let a = editor.add_node(crate::Activation::Identity);
+    let b = editor.add_node(crate::Activation::Identity);
+    editor.add_connection(1, a, 1.0).unwrap();
+    editor.add_connection(a, b, 1.0).unwrap();
+    editor.add_connection(b, 3, 1.0).unwrap();
+    let mut back = nn.edges[4];
+    back.from = b;
+    back.to = a;
+    back.innovation = 10;
+    nn.edges.push(back);
+    let mut into_input = nn.edges[0];
+    into_input.to = 1;
+    into_input.innovation = 11;
+    nn.edges.push(into_input);
+    let duplicate = nn.edges[1];
+    nn.edges.push(duplicate);
+    let problems = nn.validate();
+    assert!(problems.contains(&GenomeProblem::Cycle(10)));
+    assert!(problems.contains(&GenomeProblem::ConnectionIntoInput(11)));
+    assert!(problems.contains(&GenomeProblem::DuplicateConnection { from: 1, to: 3 }));
+    assert!(problems.contains(&GenomeProblem::UnsortedEdges));
+    // these problems do not prevent loading the network
+    assert!(nn.check_structure().is_ok());
+    let mut recurrent = nn.clone().with_recurrent(true);
+    assert!(nn.repair().is_empty());
+    assert!(nn.edges.iter().all(|edge| edge.innovation != 10));
+    // in a recurrent network the edge closing the cycle becomes recurrent
+    assert!(recurrent.repair().is_empty());
+    assert!(recurrent
+        .edges
+        .iter()
+        .any(|edge| edge.innovation == 10 && edge.recurrent));
+    // a wrong number of outputs cannot be repaired
+    nn.size.1 = 2;
+    assert_eq!(
+        nn.repair(),
+        vec![GenomeProblem::OutputCount {
+            expected: 2,
+            found: 1
+        }]
+    );
+    assert!(nn.check_structure().is_err());
+}
+
+#[test]
+pub fn codegen_rust() {
+    use crate::{Activation, NeuralNetwork};
+    let mut nn = NeuralNetwork::with_size(2, 1).with_activation(Activation::Identity);
+    nn.edit().set_weight(1, 3, 0.25).unwrap();
+    let code = nn.codegen_rust();
+    assert!(code.starts_with("pub fn forward(inputs: [f32; 2]) -> [f32; 1] {\n"));
+    assert!(code.contains("    v[1] = inputs[0];\n"));
+    assert!(code.contains("    v[3] += 0.25 * v[1];\n"));
+    // identity activations are left out
+    assert!(!code.contains("v[3] = v[3]"));
+    assert!(code.ends_with("    [v[3]]\n}\n"));
+    // recurrent networks keep their state between calls
+    let mut nn = NeuralNetwork::with_size(1, 1).with_recurrent(true);
+    nn.edit().add_connection(2, 2, 0.5).unwrap();
+    let code = nn.codegen_rust();
+    assert!(code.contains("state: &mut [f32; 3]"));
+    assert!(code.contains("    v[2] += 0.5 * state[2];\n"));
+    assert!(code.contains("    *state = v;\n"));
+}
+
+#[test]
+pub fn shared_between_threads() {
+    use crate::{NeuralNetwork, Phenotype, Solver};
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<NeuralNetwork>();
+    assert_send_sync::<Phenotype>();
+    assert_send_sync::<Solver>();
+    // a copy of the cached phenotype of a recurrent network starts without previous values
+    let mut nn = NeuralNetwork::with_size(1, 1).with_recurrent(true);
+    nn.edit().add_connection(2, 2, 0.5).unwrap();
+    let first = nn.compute([0.5]);
+    assert_ne!(nn.compute([0.5]), first);
+    let nn = &nn;
+    std::thread::scope(|s| {
+        for _ in 0..4 {
+            s.spawn(|| assert_eq!(nn.phenotype().compute(&[0.5]), first));
+        }
+    });
+}
+
+#[test]
+pub fn compiled_network() {
+    use crate::{Config, InnovationTracker, NeuralNetwork};
+    use rand::SeedableRng;
+    let mut rng = rand_pcg::Pcg64::seed_from_u64(17);
+    let config = Config::builder()
+        .add_node_rate(0.5)
+        .add_connection_rate(0.8)
+        .activation_mutation_rate(0.5)
+        .toggle_connection_rate(0.2)
+        .build();
+    for recurrent in [false, true] {
+        let mut nn = NeuralNetwork::with_presence_indicators(3, 2, 0).with_recurrent(recurrent);
+        let mut innovations = InnovationTracker::for_network(&nn);
+        for _ in 0..40 {
+            nn.mutate(&config, &mut innovations, &mut rng);
+        }
+        let compiled = nn.compile();
+        assert_eq!(compiled.size(), (3, 2));
+        assert_eq!(
+            compiled.is_recurrent(),
+            nn.edges.iter().any(|e| e.enabled && e.recurrent)
+        );
+        let mut state = compiled.state();
+        let mut output = [0.0; 2];
+        for input in [[0.1, -0.5, 0.9], [1.0, 0.0, -1.0], [0.3, 0.3, 0.3]] {
+            compiled.compute_with(&mut state, &input, &mut output);
+            // the results are exactly the same, including the passed on values of the recurrent edges
+            assert_eq!(output.to_vec(), nn.compute(input));
+        }
+        state.reset();
+        nn.reset_state();
+        compiled.compute_with(&mut state, &[0.1, -0.5, 0.9], &mut output);
+        assert_eq!(output.to_vec(), nn.compute([0.1, -0.5, 0.9]));
+        assert_eq!(compiled.compute(&[0.1, -0.5, 0.9]), output.to_vec());
+    }
+}
+
+#[test]
+pub fn cartpole_problem() {
+    use crate::evaluator::FitnessEvaluator;
+    use crate::problems::{CartPole, CartPoleProblem};
+    use crate::NeuralNetwork;
+    let mut cart = CartPole::new([0.0; 4]);
+    assert!(!cart.has_failed());
+    assert!(cart.step(true));
+    // pushing to the right accelerates the cart to the right and tilts the pole to the left
+    let [x, x_dot, theta, theta_dot] = cart.state();
+    assert!(x == 0.0 && x_dot > 0.0 && theta == 0.0 && theta_dot < 0.0);
+    assert!(CartPole::new([2.5, 0.0, 0.0, 0.0]).has_failed());
+    assert!(CartPole::new([0.0, 0.0, -0.25, 0.0]).has_failed());
+
+    let problem = CartPoleProblem::default();
+    // a network which always pushes to the same side fails quickly
+    let mut nn = NeuralNetwork::with_size(4, 1);
+    for edge in nn.edges.iter_mut() {
+        edge.weight = 0.0;
+    }
+    nn.edges[0].weight = 1.0;
+    nn.genome_changed();
+    let fitness = problem.evaluate(&mut nn);
+    assert!(fitness > 0.0 && fitness < 0.2);
+    // pushing towards the side the pole is falling to balances it
+    for (edge, weight) in nn.edges.iter_mut().zip([0.0, 0.5, 1.0, 5.0, 2.0]) {
+        edge.weight = weight;
+    }
+    nn.genome_changed();
+    assert_eq!(problem.evaluate(&mut nn), 1.0);
+    let empty = CartPoleProblem {
+        initial_states: Vec::new(),
+        ..CartPoleProblem::default()
+    };
+    assert_eq!(empty.evaluate(&mut nn), 0.0);
+}
+
+#[test]
+pub fn evaluate_in_env() {
+    use crate::environment::average_episode_reward;
+    use crate::{Environment, NeuralNetwork, Solver};
+    /// Counts the calls and ends every episode after three steps with a reward of 1.0 each.
+    #[derive(Default)]
+    struct Counting {
+        resets: usize,
+        steps: usize,
+        episode_steps: usize,
+    }
+    impl Environment for Counting {
+        type Observation = [f32; 2];
+        fn reset(&mut self) -> [f32; 2] {
+            self.resets += 1;
+            self.episode_steps = 0;
+            [0.0, 1.0]
+        }
+        fn step(&mut self, action: &[f32]) -> ([f32; 2], f32, bool) {
+            assert_eq!(action.len(), 1);
+            self.steps += 1;
+            self.episode_steps += 1;
+            ([1.0, 0.0], 1.0, self.episode_steps == 3)
+        }
+    }
+    let mut nn = NeuralNetwork::with_size(2, 1);
+    let mut env = Counting::default();
+    assert_eq!(average_episode_reward(&mut nn, &mut env, 4, 10), 3.0);
+    assert_eq!((env.resets, env.steps), (4, 12));
+    // the episodes are cut off after the maximum number of steps
+    assert_eq!(average_episode_reward(&mut nn, &mut env, 2, 2), 2.0);
+    assert_eq!(average_episode_reward(&mut nn, &mut env, 0, 2), 0.0);
+
+    let mut solver = Solver::with_size(2, 1, 10);
+    let mut env = Counting::default();
+    solver.evaluate_in_env(&mut env, 2, 10);
+    assert_eq!(env.resets, 20);
+    assert!(solver.networks().iter().all(|nn| nn.fitness() == 3.0));
+}
+
+#[test]
+pub fn f64_networks() {
+    use crate::{Config, Error, InnovationTracker, NeuralNetwork};
+    use rand::SeedableRng;
+    let mut rng = rand_pcg::Pcg64::seed_from_u64(3);
+    let config = Config::builder()
+        .add_node_rate(0.5)
+        .add_connection_rate(0.8)
+        .activation_mutation_rate(0.5)
+        .build();
+    let mut nn = NeuralNetwork::with_size(3, 2).with_recurrent(true);
+    let mut innovations = InnovationTracker::for_network(&nn);
+    for _ in 0..30 {
+        nn.mutate(&config, &mut innovations, &mut rng);
+    }
+    let mut precise = nn.cast::<f64>();
+    assert_eq!(precise.cast::<f32>(), nn);
+    let compiled = precise.compile();
+    let mut state = compiled.state();
+    let mut output = [0.0f64; 2];
+    for input in [[0.1, -0.5, 0.9], [1.0, 0.0, -1.0]] {
+        let expected = nn.compute(input);
+        let result = precise.compute(input.map(f64::from));
+        compiled.compute_with(&mut state, &input.map(f64::from), &mut output);
+        assert_eq!(result, output.to_vec());
+        for (a, b) in expected.iter().zip(result.iter()) {
+            assert!((*a as f64 - b).abs() < 1e-4);
+        }
+    }
+    assert!(precise
+        .codegen_rust()
+        .starts_with("pub fn forward(inputs: [f64; 3], state: &mut [f64;"));
+    // networks with f64 weights are stored with other magic bytes
+    let bytes = precise.as_byte_representation().unwrap();
+    assert!(matches!(
+        NeuralNetwork::create_from_bytes(bytes.clone()),
+        Err(Error::UnknownFormat)
+    ));
+    assert_eq!(NeuralNetwork::<f64>::from_bytes(&bytes).unwrap(), precise);
+}
+
+#[test]
+pub fn deterministic_with_seed() {
+    use crate::{Config, NeuralNetwork, Solver};
+    let run = |seed: u64| {
+        let config = Config::builder()
+            .generation_size(60)
+            .add_node_rate(0.2)
+            .add_connection_rate(0.3)
+            .activation_mutation_rate(0.1)
+            .seed(seed)
+            .build();
+        let mut solver = Solver::with_config(2, 1, config);
+        let evaluator = |nn: &mut NeuralNetwork| nn.compute([0.3, 0.7])[0];
+        for _ in 0..15 {
+            solver.evaluate_generation(&evaluator);
+            solver.new_generation();
+        }
+        solver
+    };
+    let a = run(42);
+    let b = run(42);
+    assert_eq!(a, b);
+    let species = |solver: &Solver| -> Vec<(usize, usize)> {
+        solver.species().map(|s| (s.id, s.size)).collect()
+    };
+    assert_eq!(species(&a), species(&b));
+    assert_eq!(
+        a.as_byte_representation().unwrap(),
+        b.as_byte_representation().unwrap()
+    );
+    assert_ne!(a, run(43));
+}
+
+#[test]
+pub fn insert_and_reseed_networks() {
+    use crate::{Config, Error, InnovationTracker, NeuralNetwork, Solver};
+    use rand::SeedableRng;
+    let mut rng = rand_pcg::Pcg64::seed_from_u64(5);
+    // a champion of another run, whose hidden node ids are unknown to the solver
+    let mut champion = NeuralNetwork::with_size(2, 1);
+    let mut innovations = InnovationTracker::for_network(&champion);
+    innovations.new_node_id();
+    for _ in 0..3 {
+        champion.mutate_add_node(&mut innovations, &mut rng);
+    }
+    champion.mutate_add_connection(&mut innovations, &mut rng);
+    champion.set_fitness(10.0);
+    let expected = champion.compute([0.2, 0.9]);
+
+    let config = Config::builder().generation_size(10).seed(1).build();
+    let mut solver = Solver::with_config(2, 1, config);
+    for (i, nn) in solver.neural_nets().enumerate() {
+        nn.set_fitness(i as f32);
+    }
+    // the network with the lowest fitness is replaced
+    assert_eq!(solver.insert_network(champion.clone()).unwrap(), 0);
+    let inserted = solver.networks()[0].clone();
+    assert_eq!(inserted.fitness(), f32::MIN);
+    assert_eq!(inserted.hidden_node_count(), 3);
+    assert_eq!(inserted.clone().compute([0.2, 0.9]), expected);
+    assert!(inserted.validate().is_empty());
+    // the initial edges keep their innovation numbers
+    assert_eq!(inserted.edges()[0], champion.edges()[0]);
+
+    solver.reseed_population(&champion, 0.5).unwrap();
+    let copies = solver
+        .networks()
+        .iter()
+        .filter(|nn| nn.hidden_node_count() >= 3)
+        .count();
+    // the inserted network has the lowest fitness, so it is replaced again
+    assert_eq!(copies, 5);
+    // the copies share their genes, only the first copy is unchanged
+    assert_eq!(solver.networks()[0].clone().compute([0.2, 0.9]), expected);
+    let ids: std::collections::BTreeSet<usize> =
+        solver.networks().iter().map(|nn| nn.id()).collect();
+    assert_eq!(ids.len(), 10);
+
+    let wrong_size = NeuralNetwork::with_size(2, 2);
+    assert!(matches!(
+        solver.insert_network(wrong_size),
+        Err(Error::InvalidGenome(_))
+    ));
+    let mut recurrent = NeuralNetwork::with_size(2, 1).with_recurrent(true);
+    let mut innovations = InnovationTracker::for_network(&recurrent);
+    while !recurrent.edges().iter().any(|e| e.recurrent) {
+        recurrent.mutate_add_connection(&mut innovations, &mut rng);
+    }
+    assert!(solver.insert_network(recurrent).is_err());
+    solver.evaluate_generation(&|nn: &mut NeuralNetwork| nn.compute([0.2, 0.9])[0]);
+    solver.new_generation();
+}
+
+#[test]
+pub fn solver_from_champion() {
+    use crate::{Config, InnovationTracker, NeuralNetwork, Solver};
+    use rand::SeedableRng;
+    let mut rng = rand_pcg::Pcg64::seed_from_u64(8);
+    let mut champion = NeuralNetwork::with_size(3, 2);
+    let mut innovations = InnovationTracker::for_network(&champion);
+    for _ in 0..4 {
+        champion.mutate_add_node(&mut innovations, &mut rng);
+    }
+    let expected = champion.compute([0.1, 0.5, 0.9]);
+    let config = Config::builder().generation_size(5).seed(2).build();
+    let mut solver = Solver::from_champion(&champion, 30, config).unwrap();
+    assert_eq!(solver.networks().len(), 30);
+    assert_eq!(solver.config().generation_size, 30);
+    assert!(solver
+        .networks()
+        .iter()
+        .all(|nn| nn.size() == (3, 2) && nn.hidden_node_count() >= 4));
+    assert_eq!(
+        solver
+            .neural_nets()
+            .next()
+            .unwrap()
+            .compute([0.1, 0.5, 0.9]),
+        expected
+    );
+    solver.evaluate_generation(&|nn: &mut NeuralNetwork| nn.compute([0.1, 0.5, 0.9])[0]);
+    let stats = solver.new_generation();
+    // all networks are close to the champion
+    assert!(stats.species_sizes[0] > 1);
+    assert_eq!(solver.networks().len(), 30);
+
+    let mut recurrent = NeuralNetwork::with_size(1, 1).with_recurrent(true);
+    let mut innovations = InnovationTracker::for_network(&recurrent);
+    recurrent.mutate_add_connection(&mut innovations, &mut rng);
+    assert!(Solver::from_champion(&recurrent, 10, Config::default()).is_err());
+    let config = Config::builder().recurrent(true).build();
+    assert!(Solver::from_champion(&recurrent, 10, config).is_ok());
+}
+
+#[test]
+pub fn interspecies_crossover() {
+    use crate::{Config, NeuralNetwork, Solver, WeightInit};
+    use rand::SeedableRng;
+    let mut rng = rand_pcg::Pcg64::seed_from_u64(3);
+    let positive =
+        NeuralNetwork::with_size(2, 1).with_weight_init(WeightInit::Constant(5.0), &mut rng);
+    let negative =
+        NeuralNetwork::with_size(2, 1).with_weight_init(WeightInit::Constant(-5.0), &mut rng);
+    // counts the children inheriting weights of both species
+    let mixed_children = |rate: f32| {
+        let config = Config::builder()
+            .generation_size(20)
+            .seed(4)
+            .target_species(None)
+            .crossover_rate(1.0)
+            .interspecies_mating_rate(rate)
+            .weight_mutation_rate(0.0)
+            .weight_replace_rate(0.0)
+            .add_node_rate(0.0)
+            .add_connection_rate(0.0)
+            .activation_mutation_rate(0.0)
+            .toggle_connection_rate(0.0)
+            .build();
+        let mut solver = Solver::with_config(2, 1, config);
+        solver.reseed_population(&positive, 1.0).unwrap();
+        for (i, nn) in solver.neural_nets().enumerate() {
+            nn.set_fitness(i as f32);
+        }
+        solver.reseed_population(&negative, 0.5).unwrap();
+        solver.speciate();
+        solver.evaluate_generation(&|_: &mut NeuralNetwork| 1.0);
+        solver.new_generation();
+        solver
+            .networks()
+            .iter()
+            .filter(|nn| {
+                nn.edges().iter().any(|e| e.weight > 0.0)
+                    && nn.edges().iter().any(|e| e.weight < 0.0)
+            })
+            .count()
+    };
+    // both kinds of networks are in different species, so they are only mixed across species
+    assert_eq!(mixed_children(0.0), 0);
+    assert!(mixed_children(1.0) > 0);
+}
+
+#[test]
+pub fn selection_strategies() {
+    use crate::{Config, NeuralNetwork, SelectionStrategy, Solver};
+    use rand::SeedableRng;
+    let mut rng = rand_pcg::Pcg64::seed_from_u64(6);
+    let fitness = [1.0, 4.0, 0.0, 3.0];
+    let counts = |selection: SelectionStrategy, rng: &mut rand_pcg::Pcg64| {
+        let mut counts = [0; 4];
+        for _ in 0..1000 {
+            counts[selection.select(&fitness, rng)] += 1;
+        }
+        counts
+    };
+    let roulette = counts(SelectionStrategy::Roulette, &mut rng);
+    assert_eq!(roulette[2], 0);
+    assert!(roulette[1] > roulette[3] && roulette[3] > roulette[0]);
+    // ranks are proportional to 2, 4, 1, 3, so the network without fitness is selected as well
+    let rank = counts(SelectionStrategy::Rank, &mut rng);
+    assert!(rank[2] > 0 && rank[2] < rank[0] && rank[0] < rank[3] && rank[3] < rank[1]);
+    let truncation = counts(SelectionStrategy::Truncation(0.5), &mut rng);
+    assert_eq!(truncation[0] + truncation[2], 0);
+    assert_eq!(
+        counts(SelectionStrategy::Truncation(0.0), &mut rng)[1],
+        1000
+    );
+    let tournament = counts(SelectionStrategy::Tournament(1), &mut rng);
+    assert!(tournament.iter().all(|&count| count > 150));
+    assert_eq!(
+        counts(SelectionStrategy::Tournament(100), &mut rng)[1],
+        1000
+    );
+
+    let config = Config::builder()
+        .generation_size(30)
+        .selection(SelectionStrategy::Tournament(3))
+        .seed(1)
+        .build();
+    let mut solver = Solver::with_config(2, 1, config);
+    for _ in 0..3 {
+        solver.evaluate_generation(&|nn: &mut NeuralNetwork| nn.compute([0.5, 1.0])[0]);
+        solver.new_generation();
+    }
+    assert_eq!(solver.networks().len(), 30);
+}
+
+#[test]
+pub fn minimum_species_offspring_and_species_elitism() {
+    use crate::{ConfigBuilder, NeuralNetwork, Solver, WeightInit};
+    use rand::SeedableRng;
+    let mut rng = rand_pcg::Pcg64::seed_from_u64(9);
+    let fit = NeuralNetwork::with_size(2, 1).with_weight_init(WeightInit::Constant(5.0), &mut rng);
+    let unfit =
+        NeuralNetwork::with_size(2, 1).with_weight_init(WeightInit::Constant(-5.0), &mut rng);
+    // returns the number of offspring of the species of unfit networks, which has 6 members
+    let unfit_offspring = |builder: ConfigBuilder| {
+        let config = builder
+            .generation_size(20)
+            .seed(2)
+            .elitism(0)
+            .crossover_rate(0.0)
+            .weight_mutation_rate(0.0)
+            .weight_replace_rate(0.0)
+            .add_node_rate(0.0)
+            .add_connection_rate(0.0)
+            .build();
+        let mut solver = Solver::with_config(2, 1, config);
+        solver.reseed_population(&fit, 1.0).unwrap();
+        for (i, nn) in solver.neural_nets().enumerate() {
+            nn.set_fitness(i as f32);
+        }
+        solver.reseed_population(&unfit, 0.3).unwrap();
+        let evaluator = |nn: &mut NeuralNetwork| {
+            if nn.edges()[0].weight > 0.0 {
+                10.0
+            } else {
+                0.0
+            }
+        };
+        solver.evaluate_generation(&evaluator);
+        let stats = solver.new_generation();
+        assert_eq!(stats.species_sizes.len(), 2);
+        assert_eq!(solver.networks().len(), 20);
+        solver
+            .networks()
+            .iter()
+            .filter(|nn| nn.edges()[0].weight < 0.0)
+            .count()
+    };
+    assert_eq!(unfit_offspring(ConfigBuilder::default()), 0);
+    assert_eq!(
+        unfit_offspring(ConfigBuilder::default().min_species_offspring(3)),
+        3
+    );
+    // there are not enough offspring to give every species 15 of them
+    assert_eq!(
+        unfit_offspring(ConfigBuilder::default().min_species_offspring(15)),
+        10
+    );
+    let elitism = ConfigBuilder::default().species_elitism(1);
+    assert_eq!(unfit_offspring(elitism.species_elitism_min_size(5)), 1);
+    assert_eq!(unfit_offspring(elitism.species_elitism_min_size(6)), 0);
+}
+
+#[test]
+pub fn species_age_adjustment() {
+    use crate::{ConfigBuilder, NeuralNetwork, Solver, WeightInit};
+    use rand::SeedableRng;
+    let mut rng = rand_pcg::Pcg64::seed_from_u64(10);
+    let old = NeuralNetwork::with_size(2, 1).with_weight_init(WeightInit::Constant(5.0), &mut rng);
+    let new = NeuralNetwork::with_size(2, 1).with_weight_init(WeightInit::Constant(-5.0), &mut rng);
+    // returns the number of offspring of a species which appears after three generations and is
+    // less fit than the old species
+    let new_offspring = |builder: ConfigBuilder| {
+        let config = builder
+            .generation_size(20)
+            .seed(3)
+            .crossover_rate(0.0)
+            .weight_mutation_rate(0.0)
+            .weight_replace_rate(0.0)
+            .add_node_rate(0.0)
+            .add_connection_rate(0.0)
+            .build();
+        let mut solver = Solver::with_config(2, 1, config);
+        solver.reseed_population(&old, 1.0).unwrap();
+        for _ in 0..3 {
+            solver.evaluate_generation(&|_: &mut NeuralNetwork| 1.0);
+            solver.new_generation();
+        }
+        solver.reseed_population(&new, 0.3).unwrap();
+        for (i, nn) in solver.neural_nets().enumerate() {
+            let fitness = if nn.edges()[0].weight < 0.0 {
+                5.0
+            } else {
+                10.0
+            };
+            nn.set_fitness(if i == 19 { 0.0 } else { fitness });
+        }
+        let stats = solver.new_generation();
+        assert_eq!(stats.species_sizes, vec![14, 6]);
+        solver
+            .networks()
+            .iter()
+            .filter(|nn| nn.edges()[0].weight < 0.0)
+            .count()
+    };
+    let young = ConfigBuilder::default().young_species_age(2);
+    let unchanged = new_offspring(young);
+    assert_eq!(unchanged, 7);
+    assert!(new_offspring(young.young_species_boost(3.0)) > unchanged);
+    assert!(new_offspring(young.old_species_age(3).old_species_penalty(0.1)) > unchanged);
+    // both species are young by default
+    assert_eq!(
+        new_offspring(ConfigBuilder::default().young_species_boost(3.0)),
+        unchanged
+    );
+}
+
+#[test]
+pub fn align_genomes() {
+    use crate::genome::{align, GeneMatch};
+    use crate::{InnovationTracker, NeuralNetwork};
+    use rand::SeedableRng;
+    let mut rng = rand_pcg::Pcg64::seed_from_u64(11);
+    let initial = NeuralNetwork::with_size(2, 1);
+    let mut innovations = InnovationTracker::for_network(&initial);
+    let mut a = initial.clone();
+    a.mutate_add_node(&mut innovations, &mut rng);
+    // split a different edge in the second genome
+    let mut b = initial.clone();
+    while b.edges().len() == 3 || b.edges()[3].innovation == a.edges()[3].innovation {
+        b = initial.clone();
+        b.mutate_add_node(&mut innovations, &mut rng);
+    }
+    let kinds: Vec<&str> = align(&a, &b)
+        .map(|gene| match gene {
+            GeneMatch::Matching(x, y) => {
+                assert_eq!(x.innovation, y.innovation);
+                "matching"
+            }
+            GeneMatch::DisjointFirst(_) => "disjoint first",
+            GeneMatch::DisjointSecond(_) => "disjoint second",
+            GeneMatch::ExcessFirst(_) => "excess first",
+            GeneMatch::ExcessSecond(_) => "excess second",
+        })
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            "matching",
+            "matching",
+            "matching",
+            "disjoint first",
+            "disjoint first",
+            "excess second",
+            "excess second",
+        ]
+    );
+    // every gene of both genomes appears once
+    assert_eq!(kinds.len() + 3, a.edges().len() + b.edges().len());
+    let swapped = align(&b, &a).filter(|gene| matches!(gene, GeneMatch::ExcessFirst(_)));
+    assert_eq!(swapped.count(), 2);
+    assert_eq!(align(&a, &a).count(), a.edges().len());
+}
+
+#[test]
+pub fn representative_selection() {
+    use crate::{ConfigBuilder, InnovationTracker, NeuralNetwork, RepresentativeSelection, Solver};
+    use rand::SeedableRng;
+    let mut rng = rand_pcg::Pcg64::seed_from_u64(12);
+    let small = NeuralNetwork::with_size(2, 1);
+    let mut big = small.clone();
+    big.mutate_add_node(&mut InnovationTracker::for_network(&big), &mut rng);
+    // returns the complexity of the representative of a species with one big and nine small
+    // networks, where the big network is the fittest
+    let representative_complexity = |representative: RepresentativeSelection| {
+        let config = ConfigBuilder::default()
+            .generation_size(10)
+            .seed(5)
+            .distance_threshold(100.0)
+            .target_species(None)
+            .representative(representative)
+            .build();
+        let mut solver = Solver::with_config(2, 1, config);
+        solver.reseed_population(&small, 1.0).unwrap();
+        solver.insert_network(big.clone()).unwrap();
+        solver.evaluate_generation(&|nn: &mut NeuralNetwork| nn.hidden_node_count() as f32);
+        solver.new_generation();
+        let species: Vec<_> = solver.species().collect();
+        assert_eq!(species.len(), 1);
+        species[0].representative_complexity
+    };
+    assert_eq!(representative_complexity(RepresentativeSelection::Best), 9);
+    assert_eq!(
+        representative_complexity(RepresentativeSelection::Medoid),
+        7
+    );
+    assert!([7, 9].contains(&representative_complexity(RepresentativeSelection::Random)));
+}
+
+#[test]
+pub fn diversity_metrics() {
+    use crate::{ConfigBuilder, InnovationTracker, NeuralNetwork, Solver};
+    use rand::SeedableRng;
+    let mut rng = rand_pcg::Pcg64::seed_from_u64(13);
+    let small = NeuralNetwork::with_size(2, 1);
+    let mut big = small.clone();
+    big.mutate_add_node(&mut InnovationTracker::for_network(&big), &mut rng);
+    let config = ConfigBuilder::default()
+        .generation_size(8)
+        .seed(6)
+        .weight_mutation_rate(0.0)
+        .weight_replace_rate(0.0)
+        .add_node_rate(0.0)
+        .add_connection_rate(0.0)
+        .build();
+    let mut solver = Solver::with_config(2, 1, config);
+    solver.reseed_population(&small, 1.0).unwrap();
+    let converged = solver.diversity_metrics();
+    assert_eq!(converged.mean_distance, 0.0);
+    assert_eq!(converged.genotypic_entropy, 0.0);
+    assert_eq!(converged.unique_topologies, 1);
+    for (i, nn) in solver.neural_nets().enumerate() {
+        nn.set_fitness(i as f32);
+    }
+    // half of the networks have a different topology
+    solver.reseed_population(&big, 0.5).unwrap();
+    let diverse = solver.diversity_metrics();
+    assert!(diverse.mean_distance > 0.0);
+    assert_eq!(diverse.genotypic_entropy, 1.0);
+    assert_eq!(diverse.unique_topologies, 2);
+    solver.evaluate_generation(&|_: &mut NeuralNetwork| 1.0);
+    let stats = solver.new_generation();
+    assert_eq!(stats.diversity, diverse);
+
+    // big populations are sampled without changing the random state of the solver
+    let mut a = Solver::with_config(2, 1, ConfigBuilder::default().seed(7).build());
+    let mut b = Solver::with_config(2, 1, ConfigBuilder::default().seed(7).build());
+    assert_eq!(a.diversity_metrics(), b.diversity_metrics());
+    a.diversity_metrics();
+    for solver in [&mut a, &mut b] {
+        solver.evaluate_generation(&|nn: &mut NeuralNetwork| nn.compute([0.5, 1.0])[0]);
+        solver.new_generation();
+    }
+    assert_eq!(a.networks(), b.networks());
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+pub fn tracing_spans_and_events() {
+    use crate::{NeuralNetwork, Solver, TrainOptions};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata};
+    /// Counts the spans and events of the training.
+    #[derive(Default)]
+    struct Counter {
+        spans: AtomicUsize,
+        events: AtomicUsize,
+    }
+    impl tracing::Subscriber for Counter {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+        fn new_span(&self, _: &Attributes<'_>) -> Id {
+            Id::from_u64(self.spans.fetch_add(1, Ordering::Relaxed) as u64 + 1)
+        }
+        fn record(&self, _: &Id, _: &Record<'_>) {}
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+        fn event(&self, _: &Event<'_>) {
+            self.events.fetch_add(1, Ordering::Relaxed);
+        }
+        fn enter(&self, _: &Id) {}
+        fn exit(&self, _: &Id) {}
+    }
+    let counter = std::sync::Arc::new(Counter::default());
+    tracing::subscriber::with_default(counter.clone(), || {
+        let mut solver = Solver::with_size(2, 1, 20);
+        let options = TrainOptions {
+            max_generations: Some(3),
+            ..TrainOptions::default()
+        };
+        solver.train(
+            &|nn: &mut NeuralNetwork| nn.compute([0.5, 0.5])[0],
+            &options,
+        );
+    });
+    assert_eq!(counter.spans.load(Ordering::Relaxed), 3);
+    // at least one new best network and the end of every generation
+    assert!(counter.events.load(Ordering::Relaxed) >= 4);
+}
+
+#[test]
+pub fn stats_logger() {
+    use crate::{LogFormat, NeuralNetwork, Solver, StatsLogger, StopReason, TrainOptions};
+    let options = TrainOptions {
+        max_generations: Some(4),
+        ..TrainOptions::default()
+    };
+    let evaluator = |nn: &mut NeuralNetwork| nn.compute([0.3, 0.6])[0];
+    let mut solver = Solver::with_size(2, 1, 20);
+    let mut logger = StatsLogger::new(Vec::new(), LogFormat::Csv);
+    let report = solver.train_with_observer(&evaluator, &options, &mut logger);
+    let csv = String::from_utf8(logger.finish().unwrap()).unwrap();
+    let rows: Vec<Vec<&str>> = csv.lines().map(|line| line.split(',').collect()).collect();
+    assert_eq!(rows.len(), 5);
+    assert_eq!(rows[0][0], "generation");
+    assert_eq!(rows[0].len(), 12);
+    for (row, stats) in rows[1..].iter().zip(report.history.iter()) {
+        assert_eq!(row.len(), 12);
+        assert_eq!(row[0], stats.generation.to_string());
+        assert_eq!(row[1].parse::<f32>().unwrap(), stats.best_fitness);
+        let sizes: usize = row[6]
+            .split(';')
+            .map(|size| size.parse::<usize>().unwrap())
+            .sum();
+        assert_eq!(sizes, 20);
+    }
+
+    let mut logger = StatsLogger::new(Vec::new(), LogFormat::JsonLines);
+    solver.train_with_observer(&evaluator, &options, &mut logger);
+    let json = String::from_utf8(logger.finish().unwrap()).unwrap();
+    assert_eq!(json.lines().count(), 4);
+    for line in json.lines() {
+        assert!(line.starts_with("{\"generation\":") && line.ends_with('}'));
+        assert!(line.contains("\"species_sizes\":["));
+    }
+
+    // a failing writer stops the training
+    struct Broken;
+    impl std::io::Write for Broken {
+        fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::other("broken"))
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+    let mut logger = StatsLogger::new(Broken, LogFormat::Csv);
+    let report = solver.train_with_observer(&evaluator, &options, &mut logger);
+    assert_eq!(report.stop_reason, StopReason::Observer);
+    assert_eq!(report.history.len(), 1);
+    assert!(logger.finish().is_err());
+}
+
+#[cfg(feature = "std-fs")]
+#[test]
+pub fn stats_logger_checkpoints() {
+    use crate::{LogFormat, NeuralNetwork, Solver, StatsLogger, TrainOptions};
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("log.csv");
+    let mut logger = StatsLogger::create(&path, LogFormat::Csv)
+        .unwrap()
+        .with_checkpoints(dir.path());
+    let options = TrainOptions {
+        max_generations: Some(5),
+        ..TrainOptions::default()
+    };
+    let mut solver = Solver::with_size(2, 1, 20);
+    let report = solver.train_with_observer(
+        &|nn: &mut NeuralNetwork| nn.compute([0.3, 0.6])[0],
+        &options,
+        &mut logger,
+    );
+    logger.finish().unwrap();
+    let csv = std::fs::read_to_string(&path).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert!(lines[0].ends_with(",champion"));
+    // the first generation always has a new champion
+    assert!(lines[1].ends_with("champion-0.bin"));
+    let latest = lines[5].rsplit(',').next().unwrap();
+    assert_eq!(NeuralNetwork::load_from(latest).unwrap(), report.champion);
+}
+
+#[test]
+#[cfg(feature = "std")]
+pub fn config_validation() {
+    use crate::{Config, Error, SelectionStrategy};
+    assert!(Config::default().validate().is_ok());
+    let config = Config::builder()
+        .add_node_rate(-0.5)
+        .c2(-1.0)
+        .generation_size(0)
+        .selection(SelectionStrategy::Tournament(0))
+        .build();
+    let Err(Error::InvalidConfig(problems)) = config.validate() else {
+        panic!("the config has invalid parameters");
+    };
+    // every invalid parameter is reported
+    assert_eq!(problems.split("; ").count(), 4);
+    assert!(problems.contains("add_node_rate is -0.5"));
+    assert!(problems.contains("c2 is -1"));
+    assert!(problems.contains("generation_size"));
+    assert!(problems.contains("tournament"));
+    let config = Config::builder().reenable_rate(f32::NAN).build();
+    assert!(config.validate().is_err());
+}
+
+#[test]
+#[cfg(all(feature = "toml", feature = "std-fs"))]
+pub fn config_from_toml() {
+    use crate::{Config, Error, Perturbation};
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    std::fs::write(
+        &path,
+        "generation_size = 30\nc1 = 2.0\nweight_perturbation = { Gaussian = 0.2 }\n",
+    )
+    .unwrap();
+    let config = Config::from_toml_file(&path).unwrap();
+    assert_eq!(config.generation_size, 30);
+    assert_eq!(config.c1, 2.0);
+    assert_eq!(config.weight_perturbation, Perturbation::Gaussian(0.2));
+    assert_eq!(config.c2, Config::default().c2);
+    assert!(matches!(
+        Config::from_toml_str("generation_size = \"many\""),
+        Err(Error::Toml(_))
+    ));
+    assert!(matches!(
+        Config::from_toml_str("crossover_rate = 2.0"),
+        Err(Error::InvalidConfig(_))
+    ));
+    assert!(matches!(
+        Config::from_toml_file(dir.path().join("missing.toml")),
+        Err(Error::Io(_))
+    ));
+}
+
+#[test]
+pub fn config_sweep() {
+    use crate::{Config, NeuralNetwork, Sweep, TrainOptions};
+    let evaluator = |nn: &mut NeuralNetwork| -(nn.compute([0.2, 0.7])[0] - 0.9).abs();
+    let base = Config::builder().generation_size(15).seed(5).build();
+    let sweep = Sweep::new(2, 1, vec![base])
+        .vary(&[0.0, 0.8], |config, rate| {
+            config.weight_mutation_rate = rate
+        })
+        .vary(&[0.5, 3.0], |config, c3| config.c3 = c3)
+        .with_repetitions(3)
+        .with_options(TrainOptions {
+            max_generations: Some(4),
+            target_fitness: Some(-0.05),
+            ..TrainOptions::default()
+        });
+    let configs = sweep.configs();
+    assert_eq!(configs.len(), 4);
+    assert_eq!((configs[1].weight_mutation_rate, configs[1].c3), (0.0, 3.0));
+    assert_eq!((configs[2].weight_mutation_rate, configs[2].c3), (0.8, 0.5));
+    let results = sweep.run(&evaluator);
+    assert_eq!(results.len(), 4);
+    for result in results.iter() {
+        assert_eq!(result.fitness.len(), 3);
+        assert!(result.worst_fitness <= result.mean_fitness);
+        assert!(result.mean_fitness <= result.best_fitness);
+        assert!(result.mean_generations >= 1.0 && result.mean_generations <= 4.0);
+        assert!(result.solved <= 3);
+    }
+    assert!(results
+        .windows(2)
+        .all(|pair| pair[0].mean_fitness >= pair[1].mean_fitness));
+    // every repetition uses its own seed, so the results are reproducible
+    assert_eq!(sweep.run(&evaluator), results);
+    #[cfg(feature = "rayon")]
+    assert_eq!(sweep.run_parallel(&evaluator), results);
+}
+
+#[test]
+pub fn hyperneat_substrate() {
+    use crate::hyperneat::{self, HyperNeat, Substrate};
+    use crate::{Activation, ActivationSet, Config, FitnessEvaluator, InnovationTracker};
+    use crate::{NeuralNetwork, Solver};
+    // the weight output is x1 and the bias output is 0.5
+    let mut cppn = NeuralNetwork::with_size(4, 2).with_activation(Activation::Identity);
+    for edge in cppn.edges.iter_mut() {
+        edge.weight = match (edge.from, edge.to) {
+            (1, 5) => 1.0,
+            (0, 6) => 0.5,
+            _ => 0.0,
+        };
+    }
+    let substrate = Substrate::builder()
+        .inputs(hyperneat::row(3, -1.0))
+        .outputs(vec![[0.0, 1.0]])
+        .hidden_activation(Activation::Identity)
+        .output_activation(Activation::Identity)
+        .build();
+    let mut nn = substrate.network(&mut cppn);
+    assert_eq!(nn.size(), (3, 1));
+    assert!(nn.validate().is_empty());
+    // the input at x = 0 is below the threshold, the others get the maximum weight
+    let weights: Vec<(usize, usize, f32)> = nn
+        .edges()
+        .iter()
+        .map(|edge| (edge.from, edge.to, edge.weight))
+        .collect();
+    assert_eq!(weights, vec![(0, 4, 1.125), (1, 4, -3.0), (3, 4, 3.0)]);
+    assert_eq!(nn.compute([1.0, 5.0, 2.0]), vec![4.125]);
+
+    // every layer is connected to the next one
+    let substrate = Substrate::builder()
+        .inputs(hyperneat::grid(3, 3))
+        .hidden_layer(hyperneat::row(4, 0.0))
+        .hidden_layer(hyperneat::row(2, 0.5))
+        .outputs(hyperneat::row(2, 1.0))
+        .weight_threshold(0.0)
+        .build();
+    // a constant CPPN creates every possible connection
+    let mut cppn = NeuralNetwork::with_size(4, 2);
+    for edge in cppn.edges.iter_mut().filter(|edge| edge.from != 0) {
+        edge.weight = 0.0;
+    }
+    let mut nn = substrate.network(&mut cppn);
+    assert_eq!(nn.hidden_node_count(), 6);
+    assert_eq!(nn.edges().len(), 9 * 4 + 4 * 2 + 2 * 2 + 8);
+    assert!(nn.validate().is_empty());
+    let evaluator = HyperNeat::new(substrate, |nn: &mut NeuralNetwork| {
+        nn.compute(vec![0.5; 9]).iter().sum::<f32>()
+    });
+    assert_eq!(
+        evaluator.evaluate(&mut cppn),
+        nn.compute(vec![0.5; 9]).iter().sum::<f32>()
+    );
+
+    // mutations only choose activation functions of the set
+    let config = Config::builder()
+        .activation_mutation_rate(1.0)
+        .activation_set(ActivationSet::Cppn)
+        .build();
+    let mut rng = rand::thread_rng();
+    let mut innovations = InnovationTracker::for_network(&cppn);
+    for _ in 0..100 {
+        cppn.mutate(&config, &mut innovations, &mut rng);
+    }
+    assert!(cppn
+        .nodes()
+        .iter()
+        .all(|node| Activation::CPPN.contains(&node.activation)));
+    let mut solver: Solver = evaluator.substrate().solver(config);
+    assert_eq!(solver.best_network().size(), (4, 2));
+}
+
+#[test]
+pub fn cppn_patterns() {
+    use crate::{Activation, NeuralNetwork};
+    // a gaussian of x is symmetric around the center of the pattern
+    let mut cppn = NeuralNetwork::with_size(2, 1).with_activation(Activation::Identity);
+    for edge in cppn.edges.iter_mut() {
+        edge.weight = if edge.from == 1 { 2.0 } else { 0.0 };
+    }
+    cppn.edit().set_activation(3, Activation::Gaussian).unwrap();
+    let pattern = cppn.sample_pattern(5, 2);
+    assert_eq!(pattern.len(), 10);
+    assert_eq!(pattern[2], 1.0);
+    assert_eq!(pattern[0], pattern[4]);
+    assert_eq!(pattern[1], pattern[3]);
+    assert_eq!(pattern[..5], pattern[5..]);
+    let image = cppn.render_pgm(5, 2);
+    let (header, pixels) = image.split_at(11);
+    assert_eq!(header, b"P5\n5 2\n255\n");
+    assert_eq!(pixels[2], 255);
+    assert!(pixels[0] < pixels[1] && pixels[1] < pixels[2]);
+
+    // the third input of a CPPN of points is the distance to the center
+    let mut cppn = NeuralNetwork::with_size(3, 1).with_activation(Activation::Identity);
+    for edge in cppn.edges.iter_mut() {
+        edge.weight = if edge.from == 3 { 1.0 } else { 0.0 };
+    }
+    assert_eq!(cppn.query_point(-0.6, 0.8), vec![1.0]);
+    assert_eq!(cppn.sample_pattern(3, 3)[4], 0.0);
+
+    let mut rng = rand::thread_rng();
+    let nn = NeuralNetwork::with_size(2, 3)
+        .with_random_activations(&[Activation::Sine, Activation::Abs], &mut rng);
+    for node in nn.nodes() {
+        match node.id {
+            0..=2 => assert_eq!(node.activation, Activation::Sigmoid),
+            _ => assert!([Activation::Sine, Activation::Abs].contains(&node.activation)),
+        }
+    }
+    let mut cppn = NeuralNetwork::cppn(4, 2, &mut rng);
+    assert_eq!(
+        cppn.query_connection([0.1, 0.2], [0.3, 0.4]),
+        cppn.compute([0.1, 0.2, 0.3, 0.4])
+    );
+}
+
+#[test]
+pub fn fitness_transforms() {
+    use crate::{Config, FitnessTransform, NeuralNetwork, Solver};
+    let fitness = [1.0, 2.0, 3.0, 6.0];
+    // mean 3 and standard deviation sqrt(3.5)
+    let sigma = FitnessTransform::SigmaScaling.apply(&fitness);
+    let std_dev = 3.5f32.sqrt();
+    assert_eq!(sigma[2], 1.0);
+    assert!((sigma[0] - (1.0 - 1.0 / std_dev)).abs() < 1e-6);
+    assert!((sigma[3] - (1.0 + 1.5 / std_dev)).abs() < 1e-6);
+    // values below the mean by more than twice the standard deviation are raised to 0
+    let mut outlier = vec![0.0; 10];
+    outlier[0] = -1000.0;
+    let sigma = FitnessTransform::SigmaScaling.apply(&outlier);
+    assert_eq!(sigma[0], 0.0);
+    assert!((sigma[1] - 7.0 / 6.0).abs() < 1e-6);
+    for transform in [FitnessTransform::SigmaScaling, FitnessTransform::MinMax] {
+        assert_eq!(transform.apply(&[-5.0, -5.0]), vec![1.0, 1.0]);
+    }
+    assert_eq!(FitnessTransform::Rank.apply(&[7.0, 7.0]), vec![0.75, 0.75]);
+    assert!(FitnessTransform::Rank.apply(&[]).is_empty());
+
+    // ranks only depend on the order, so scaling and shifting the fitness changes nothing
+    let config = Config::builder()
+        .generation_size(30)
+        .seed(11)
+        .fitness_transform(FitnessTransform::Rank)
+        .build();
+    let mut original = Solver::with_config(2, 1, config);
+    let mut scaled = Solver::with_config(2, 1, config);
+    for _ in 0..5 {
+        original.evaluate_generation(&|nn: &mut NeuralNetwork| nn.compute([0.3, 0.8])[0]);
+        scaled.evaluate_generation(&|nn: &mut NeuralNetwork| 4.0 * nn.compute([0.3, 0.8])[0] - 2.0);
+        original.new_generation();
+        scaled.new_generation();
+    }
+    let genomes = |solver: &mut Solver| -> Vec<_> {
+        solver.neural_nets().map(|nn| nn.edges().to_vec()).collect()
+    };
+    assert_eq!(genomes(&mut original), genomes(&mut scaled));
+}
+
+#[test]
+pub fn minimize_fitness() {
+    use crate::{Config, Direction, NeuralNetwork, Solver, TrainOptions};
+    let cost = |nn: &mut NeuralNetwork| (nn.compute([0.4, 0.9])[0] - 0.25).abs();
+    let config = Config::builder()
+        .generation_size(30)
+        .seed(3)
+        .hall_of_fame_size(5)
+        .build();
+    let options = TrainOptions {
+        max_generations: Some(8),
+        ..TrainOptions::default()
+    };
+    // minimizing the cost behaves exactly like maximizing the negated cost
+    let mut minimizing = Solver::with_config(
+        2,
+        1,
+        Config {
+            direction: Direction::Minimize,
+            ..config
+        },
+    );
+    let mut maximizing = Solver::with_config(2, 1, config);
+    let min_report = minimizing.train(&cost, &options);
+    let max_report = maximizing.train(&|nn: &mut NeuralNetwork| -cost(nn), &options);
+    assert_eq!(
+        min_report.champion.fitness(),
+        -max_report.champion.fitness()
+    );
+    assert_eq!(min_report.champion.edges(), max_report.champion.edges());
+    for (min, max) in min_report.history.iter().zip(max_report.history.iter()) {
+        assert_eq!(min.best_fitness, -max.best_fitness);
+        assert_eq!(min.species_sizes, max.species_sizes);
+    }
+    let genomes = |solver: &mut Solver| -> Vec<_> {
+        solver.neural_nets().map(|nn| nn.edges().to_vec()).collect()
+    };
+    assert_eq!(genomes(&mut minimizing), genomes(&mut maximizing));
+    // the best network is the one with the lowest cost
+    let best = minimizing.best_network();
+    assert!(minimizing
+        .neural_nets()
+        .all(|nn| nn.fitness() >= best.fitness()));
+    let hall_of_fame: Vec<f32> = minimizing
+        .hall_of_fame()
+        .networks()
+        .iter()
+        .map(|nn| nn.fitness())
+        .collect();
+    assert!(hall_of_fame.windows(2).all(|pair| pair[0] <= pair[1]));
+    assert_eq!(hall_of_fame[0], min_report.champion.fitness());
+
+    // the target is reached once the cost is low enough
+    let options = TrainOptions {
+        max_generations: Some(100),
+        target_fitness: Some(0.2),
+        ..TrainOptions::default()
+    };
+    let report = minimizing.train(&cost, &options);
+    assert!(report.champion.fitness() <= 0.2);
+}
+
+#[test]
+pub fn indexed_network_access() {
+    use crate::{Config, NeuralNetwork, Solver};
+    let config = Config::builder().generation_size(10).seed(3).build();
+    let mut solver = Solver::with_config(2, 1, config);
+    for _ in 0..3 {
+        solver.evaluate_generation(&|nn: &mut NeuralNetwork| nn.compute([0.5, 0.5])[0]);
+        solver.new_generation();
+    }
+    // the ids are the positions in the generation
+    let ids: Vec<usize> = solver.neural_nets_with_ids().map(|(id, _)| id).collect();
+    assert_eq!(ids, (0..10).collect::<Vec<_>>());
+    assert!(solver
+        .networks()
+        .iter()
+        .enumerate()
+        .all(|(id, nn)| nn.id() == id));
+    assert!(solver.network(10).is_none());
+
+    // the position stays the id even if a network is replaced by a clone of another one
+    let first = solver.network(0).unwrap().clone();
+    *solver.network_mut(5).unwrap() = first;
+    let scores: Vec<f32> = (0..10).map(|id| id as f32).collect();
+    for (id, nn) in solver.neural_nets_with_ids() {
+        nn.set_fitness(scores[id]);
+    }
+    assert_eq!(solver.network(5).unwrap().fitness(), 5.0);
+    assert_eq!(solver.network(0).unwrap().fitness(), 0.0);
+
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+        solver
+            .par_neural_nets()
+            .for_each(|(id, nn)| nn.set_fitness(2.0 * scores[id]));
+        assert_eq!(solver.network(5).unwrap().fitness(), 10.0);
+        assert_eq!(solver.best_network().fitness(), 18.0);
+    }
+}
+
+#[test]
+#[cfg(all(feature = "gzip", feature = "std-fs"))]
+pub fn compressed_files() {
+    use crate::{NeuralNetwork, Solver};
+    use tempfile::tempdir;
+    let dir = tempdir().unwrap();
+    let mut solver = Solver::with_size(3, 2, 100);
+    for _ in 0..3 {
+        solver.evaluate_generation(&|nn: &mut NeuralNetwork| nn.compute([0.1, 0.2, 0.3])[0]);
+        solver.new_generation();
+    }
+    let plain = dir.path().join("solver.sv");
+    let compressed = dir.path().join("solver.sv.gz");
+    solver.save_as(&plain).unwrap();
+    solver.save_as(&compressed).unwrap();
+    let plain_size = std::fs::metadata(&plain).unwrap().len();
+    let bytes = std::fs::read(&compressed).unwrap();
+    assert!(bytes.starts_with(&[0x1f, 0x8b]));
+    assert!((bytes.len() as u64) < plain_size / 2);
+    // compressed data is detected by its header, not by the file name
+    assert_eq!(Solver::load_from(&compressed).unwrap(), solver);
+    assert_eq!(Solver::load_from_reader(&bytes[..]).unwrap(), solver);
+    assert_eq!(Solver::load_from(&plain).unwrap(), solver);
+
+    let nn = solver.best_network();
+    let path = dir.path().join("champion.nn.gz");
+    nn.save_as(&path).unwrap();
+    assert_eq!(NeuralNetwork::load_from(&path).unwrap(), nn);
+    let bytes = std::fs::read(&path).unwrap();
+    assert_eq!(NeuralNetwork::<f32>::from_bytes(&bytes).unwrap(), nn);
+    dir.close().unwrap();
+}
+
+#[test]
+#[cfg(feature = "std-fs")]
+pub fn export_and_import_population() {
+    use crate::{Config, Error, NeuralNetwork, Solver};
+    use tempfile::tempdir;
+    let dir = tempdir().unwrap();
+    let config = Config::builder()
+        .generation_size(12)
+        .seed(5)
+        .track_lineage(true)
+        .build();
+    let mut solver = Solver::with_config(2, 1, config);
+    for _ in 0..3 {
+        solver.evaluate_generation(&|nn: &mut NeuralNetwork| nn.compute([0.3, 0.6])[0]);
+        solver.new_generation();
+    }
+    let files = solver.export_population(dir.path()).unwrap();
+    assert_eq!(files.len(), 12);
+    assert_eq!(files[3].file_name().unwrap(), "genome-03.nn");
+    assert_eq!(
+        NeuralNetwork::load_from(&files[3]).unwrap(),
+        solver.networks()[3]
+    );
+    // curating: remove two genomes, add one by hand and keep a note file
+    std::fs::remove_file(&files[0]).unwrap();
+    std::fs::remove_file(&files[1]).unwrap();
+    let added = NeuralNetwork::with_size(2, 1);
+    added.save_as(dir.path().join("manual.nn")).unwrap();
+    std::fs::write(dir.path().join(".notes"), "hidden files are ignored").unwrap();
+    let original: Vec<_> = solver.networks()[2..].to_vec();
+    assert_eq!(solver.import_population(dir.path()).unwrap(), 11);
+    let networks = solver.networks();
+    assert!(networks.iter().enumerate().all(|(id, nn)| nn.id() == id));
+    assert!(networks.iter().all(|nn| nn.fitness() == f32::MIN));
+    for (imported, original) in networks.iter().zip(&original) {
+        assert_eq!(imported.nodes().len(), original.nodes().len());
+        assert_eq!(imported.edges().len(), original.edges().len());
+    }
+    assert_eq!(networks[10].edges().len(), added.edges().len());
+
+    // the imported generation is trained like any other one
+    solver.evaluate_generation(&|nn: &mut NeuralNetwork| nn.compute([0.3, 0.6])[0]);
+    solver.new_generation();
+    assert_eq!(solver.networks().len(), 12);
+
+    // exporting again replaces the old genome files
+    let files = solver.export_population(dir.path()).unwrap();
+    let mut names: Vec<_> = std::fs::read_dir(dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    names.sort();
+    assert_eq!(names.len(), files.len() + 2);
+    assert!(names.contains(&"manual.nn".to_string()));
+
+    // a network which does not fit leaves the generation unchanged
+    NeuralNetwork::with_size(3, 1)
+        .save_as(dir.path().join("wrong.nn"))
+        .unwrap();
+    let before = solver.networks().to_vec();
+    let lineage = solver.lineage().clone();
+    let err = solver.import_population(dir.path()).unwrap_err();
+    assert!(matches!(&err, Error::InvalidGenome(problem) if problem.contains("wrong.nn")));
+    assert_eq!(solver.networks(), &before[..]);
+    assert_eq!(solver.lineage(), &lineage);
+    let empty = dir.path().join("empty");
+    std::fs::create_dir(&empty).unwrap();
+    assert!(solver.import_population(&empty).is_err());
+    dir.close().unwrap();
+}
+
+#[test]
+pub fn ensemble_aggregation() {
+    use crate::{Activation, Aggregation, Ensemble, NeuralNetwork};
+    // the outputs of a member are the weights of the constant input
+    let member = |first: f32, second: f32| {
+        let mut nn = NeuralNetwork::with_size(1, 2).with_activation(Activation::Identity);
+        let mut editor = nn.edit();
+        editor.set_weight(0, 2, first).unwrap();
+        editor.set_weight(0, 3, second).unwrap();
+        editor.set_weight(1, 2, 0.0).unwrap();
+        editor.set_weight(1, 3, 0.0).unwrap();
+        nn
+    };
+    let members = vec![member(1.0, 0.0), member(2.0, 5.0), member(9.0, 1.0)];
+    let compute = |aggregation| Ensemble::new(members.clone(), aggregation).compute([0.7]);
+    assert_eq!(compute(Aggregation::Mean), vec![4.0, 2.0]);
+    assert_eq!(compute(Aggregation::Median), vec![2.0, 1.0]);
+    assert_eq!(
+        compute(Aggregation::MajorityVote),
+        vec![2.0 / 3.0, 1.0 / 3.0]
+    );
+    let mut even = Ensemble::new(members[..2].to_vec(), Aggregation::Median);
+    assert_eq!(even.compute([0.7]), vec![1.5, 2.5]);
+    assert_eq!(even.size(), (1, 2));
+
+    #[cfg(feature = "std-fs")]
+    {
+        use tempfile::tempdir;
+        let dir = tempdir().unwrap();
+        let paths: Vec<_> = (0..3)
+            .map(|i| dir.path().join(format!("{}.nn", i)))
+            .collect();
+        for (nn, path) in members.iter().zip(&paths) {
+            nn.save_as(path).unwrap();
+        }
+        let mut loaded = Ensemble::load_from(&paths, Aggregation::Mean).unwrap();
+        assert_eq!(loaded.members(), &members[..]);
+        assert_eq!(loaded.compute([0.7]), vec![4.0, 2.0]);
+        NeuralNetwork::with_size(2, 2)
+            .save_as(dir.path().join("wrong.nn"))
+            .unwrap();
+        let wrong = [paths[0].clone(), dir.path().join("wrong.nn")];
+        assert!(Ensemble::load_from(&wrong, Aggregation::Mean).is_err());
+        assert!(Ensemble::load_from::<&str>(&[], Aggregation::Mean).is_err());
+        dir.close().unwrap();
+    }
+}
+
+#[test]
+pub fn behavioral_speciation() {
+    use crate::{Activation, Config, NeuralNetwork, Solver, SpeciationMetric};
+    // networks computing `bias + slope * x`
+    let linear = |bias: f32, slope: f32| {
+        let mut nn = NeuralNetwork::with_size(1, 1).with_activation(Activation::Identity);
+        let mut editor = nn.edit();
+        editor.set_weight(0, 2, bias).unwrap();
+        editor.set_weight(1, 2, slope).unwrap();
+        nn
+    };
+    // the same function as `linear(0.0, 1.0)` with a different genome
+    let mut unused_node = linear(0.0, 1.0);
+    let mut editor = unused_node.edit();
+    let hidden = editor.add_node(Activation::Identity);
+    editor.add_connection(1, hidden, 1.0).unwrap();
+    editor.add_connection(hidden, 2, 0.0).unwrap();
+    let networks = [
+        linear(0.0, 1.0),
+        unused_node,
+        linear(0.0, -1.0),
+        linear(0.1, 1.0),
+    ];
+    let species_count = |metric| {
+        let config = Config::builder()
+            .generation_size(4)
+            .distance_threshold(0.3)
+            .target_species(None)
+            .speciation_metric(metric)
+            .build();
+        // the first species forms around the first network
+        let mut solver = Solver::from_champion(&networks[0], 4, config).unwrap();
+        solver.set_probe_inputs(vec![vec![0.0], vec![1.0]]);
+        for (id, nn) in networks.iter().enumerate() {
+            let mut nn = nn.clone();
+            nn.id = id;
+            *solver.network_mut(id).unwrap() = nn;
+        }
+        solver.speciate();
+        solver.species().map(|s| s.size).collect::<Vec<_>>()
+    };
+    // the unused node makes the genomes different, the negated slope both the genome and the
+    // behavior
+    assert_eq!(species_count(SpeciationMetric::Genetic), vec![2, 1, 1]);
+    assert_eq!(species_count(SpeciationMetric::Behavioral), vec![3, 1]);
+    assert_eq!(species_count(SpeciationMetric::Blend(0.5)), vec![3, 1]);
+    assert!(Config::builder()
+        .speciation_metric(SpeciationMetric::Blend(1.5))
+        .build()
+        .validate()
+        .is_err());
+
+    // a whole training with the behavioral medoid as representative
+    let config = Config::builder()
+        .generation_size(30)
+        .seed(2)
+        .speciation_metric(SpeciationMetric::Behavioral)
+        .representative(crate::RepresentativeSelection::Medoid)
+        .build();
+    let mut solver = Solver::with_config(2, 1, config);
+    solver.set_probe_inputs(vec![vec![0.0, 1.0], vec![1.0, 0.0]]);
+    for _ in 0..5 {
+        solver.evaluate_generation(&|nn: &mut NeuralNetwork| nn.compute([1.0, 1.0])[0]);
+        solver.new_generation();
+    }
+    assert_eq!(solver.networks().len(), 30);
+}
+
+#[test]
+pub fn topological_order_matches_reference() {
+    use crate::phenotype::Phenotype;
+    use crate::{Activation, NeuralNetwork};
+    use rand::seq::SliceRandom;
+    use rand::{Rng, SeedableRng};
+    // places the first node whose predecessors have all been placed until every node is placed,
+    // there is no order if no such node is left
+    let reference = |edges: &[Vec<(usize, f32)>]| -> Option<Vec<usize>> {
+        let mut order: Vec<usize> = Vec::new();
+        while order.len() < edges.len() {
+            let next = (0..edges.len()).find(|node| {
+                !order.contains(node)
+                    && (0..edges.len()).all(|from| {
+                        order.contains(&from) || edges[from].iter().all(|&(to, _)| to != *node)
+                    })
+            })?;
+            order.push(next);
+        }
+        Some(order)
+    };
+    let mut rng = rand_pcg::Pcg64::seed_from_u64(5);
+    for _ in 0..500 {
+        let nodes = rng.gen_range(1..20);
+        let acyclic = rng.gen_bool(0.5);
+        let density = rng.gen_range(0.0..0.3);
+        let mut rank: Vec<usize> = (0..nodes).collect();
+        rank.shuffle(&mut rng);
+        let edges: Vec<Vec<(usize, f32)>> = (0..nodes)
+            .map(|from| {
+                (0..nodes)
+                    .filter(|&to| (!acyclic || rank[from] < rank[to]) && rng.gen_bool(density))
+                    .map(|to| (to, 1.0))
+                    .collect()
+            })
+            .collect();
+        let expected = reference(&edges);
+        match Phenotype::<f32>::create_topo_order(&edges) {
+            Ok(order) => {
+                assert!(expected.is_some(), "the reference found a cycle");
+                let mut sorted = order.clone();
+                sorted.sort_unstable();
+                assert_eq!(sorted, (0..nodes).collect::<Vec<usize>>());
+                let position = |node: usize| order.iter().position(|&n| n == node).unwrap();
+                for (from, node_edges) in edges.iter().enumerate() {
+                    assert!(node_edges
+                        .iter()
+                        .all(|&(to, _)| position(from) < position(to)));
+                }
+            }
+            Err(node) => {
+                assert!(!acyclic && expected.is_none());
+                // the node reaches itself
+                let mut stack: Vec<usize> = edges[node].iter().map(|&(to, _)| to).collect();
+                let mut visited = vec![false; nodes];
+                while let Some(next) = stack.pop() {
+                    if !visited[next] {
+                        visited[next] = true;
+                        stack.extend(edges[next].iter().map(|&(to, _)| to));
+                    }
+                }
+                assert!(visited[node]);
+            }
+        }
+    }
+
+    // a feed-forward network with a cycle is only rejected by `try_from_nn`
+    let mut nn = NeuralNetwork::with_size(1, 1);
+    let mut editor = nn.edit();
+    let a = editor.add_node(Activation::Identity);
+    let b = editor.add_node(Activation::Identity);
+    editor.add_connection(1, a, 1.0).unwrap();
+    editor.add_connection(a, b, 1.0).unwrap();
+    editor.add_connection(b, 2, 1.0).unwrap();
+    let mut back = nn.edges[4];
+    back.from = b;
+    back.to = a;
+    back.innovation = 10;
+    nn.edges.push(back);
+    assert!(Phenotype::try_from_nn(&nn).is_err());
+    assert_eq!(Phenotype::from_nn(&nn).topo_order.len(), 5);
+    assert!(Phenotype::try_from_nn(&nn.with_recurrent(true)).is_ok());
+}
+
+#[test]
+pub fn arbitrary_node_ids() {
+    use crate::phenotype::Phenotype;
+    use crate::{InnovationTracker, NeuralNetwork};
+    use rand::seq::SliceRandom;
+    use rand::SeedableRng;
+    let mut rng = rand_pcg::Pcg64::seed_from_u64(9);
+    let mut nn = NeuralNetwork::with_size(3, 2);
+    let mut innovations = InnovationTracker::for_network(&nn);
+    for _ in 0..8 {
+        nn.mutate_add_node(&mut innovations, &mut rng);
+        nn.mutate_add_connection(&mut innovations, &mut rng);
+    }
+    let inputs = [[0.5, -0.25, 1.0], [0.0, 0.75, -1.0]];
+    let expected: Vec<Vec<f32>> = inputs.iter().map(|input| nn.compute(input)).collect();
+    // sparse ids in a shuffled order and hidden nodes between the outputs
+    let mut ids: Vec<usize> = (0..nn.nodes.len()).map(|i| 1000 - 37 * i).collect();
+    ids.shuffle(&mut rng);
+    let new_id = |id: usize| ids[nn.nodes.iter().position(|node| node.id == id).unwrap()];
+    let mut relabeled = nn.clone();
+    for node in relabeled.nodes.iter_mut() {
+        node.id = new_id(node.id);
+    }
+    for edge in relabeled.edges.iter_mut() {
+        edge.from = new_id(edge.from);
+        edge.to = new_id(edge.to);
+    }
+    let hidden = relabeled.nodes.pop().unwrap();
+    relabeled.nodes.insert(5, hidden);
+    relabeled.genome_changed();
+    assert!(relabeled.validate().is_empty());
+    // the mapping goes from the id to the position in the node list
+    let mapping = Phenotype::<f32>::create_node_index_mapping(&relabeled.nodes);
+    for (idx, node) in relabeled.nodes.iter().enumerate() {
+        assert_eq!(mapping[&node.id], idx);
+    }
+    for (input, expected) in inputs.iter().zip(expected.iter()) {
+        assert_eq!(&relabeled.compute(input), expected);
+        assert_eq!(&relabeled.compile().compute(input), expected);
+    }
+    // new nodes get an id which is bigger than every existing one
+    let largest = relabeled.nodes.iter().map(|node| node.id).max().unwrap();
+    let mut innovations = InnovationTracker::for_network(&relabeled);
+    assert!(relabeled.mutate_add_node(&mut innovations, &mut rng));
+    assert!(relabeled.nodes.iter().any(|node| node.id > largest));
+    assert_eq!(relabeled.compute(inputs[0]).len(), 2);
+}
+
+#[test]
+pub fn phenotype_arena() {
+    use crate::phenotype::Phenotype;
+    use crate::{InnovationTracker, NeuralNetwork, PhenotypeArena, Solver};
+    use rand::SeedableRng;
+    let mut rng = rand_pcg::Pcg64::seed_from_u64(4);
+    let mut big = NeuralNetwork::with_size(3, 2).with_recurrent(true);
+    let mut innovations = InnovationTracker::for_network(&big);
+    for _ in 0..10 {
+        big.mutate_add_node(&mut innovations, &mut rng);
+        big.mutate_add_connection(&mut innovations, &mut rng);
+    }
+    let small = NeuralNetwork::with_presence_indicators(1, 1, 0);
+    let mut arena = PhenotypeArena::new();
+    let mut pt = arena.phenotype(&big);
+    assert_eq!(pt, Phenotype::from_nn(&big));
+    pt.compute(&[0.5, 0.5, 0.5]);
+    let buffer = pt.edges.as_ptr();
+    arena.recycle(pt);
+    // the buffers of the big network are used for the small one without the previous values
+    let pt = arena.phenotype(&small);
+    assert!(arena.is_empty());
+    assert_eq!(pt.edges.as_ptr(), buffer);
+    assert_eq!(pt, Phenotype::from_nn(&small));
+    arena.recycle(pt);
+    assert_eq!(arena.compile(&big), big.compile());
+    assert_eq!(arena.len(), 1);
+
+    // the solver passes the phenotypes on to the next generation
+    let mut solver = Solver::with_size(2, 1, 20);
+    for _ in 0..3 {
+        solver.evaluate_generation(&|nn: &mut NeuralNetwork| nn.compute([0.5, 1.0])[0]);
+        solver.new_generation();
+        for nn in solver.networks() {
+            assert_eq!(nn.pt.as_ref().unwrap(), &Phenotype::from_nn(nn));
+        }
+    }
+}
+
+#[test]
+pub fn incremental_phenotype_updates() {
+    use crate::phenotype::Phenotype;
+    use crate::{Activation, Config, InnovationTracker, NeuralNetwork};
+    use rand::SeedableRng;
+    let mut rng = rand_pcg::Pcg64::seed_from_u64(12);
+    let config = Config::builder().weight_mutation_rate(0.5).build();
+    // the cached phenotype matches a new one without the node values
+    let assert_current = |nn: &NeuralNetwork| {
+        let mut cached = nn.pt.clone().expect("the phenotype has been dropped");
+        cached.node_value_array.clear();
+        cached.reset_state();
+        assert_eq!(cached, Phenotype::from_nn(nn));
+    };
+    for recurrent in [false, true] {
+        let mut nn = NeuralNetwork::with_size(2, 2).with_recurrent(recurrent);
+        let mut innovations = InnovationTracker::for_network(&nn);
+        for _ in 0..4 {
+            nn.mutate_add_node(&mut innovations, &mut rng);
+        }
+        nn.compute([0.5, -0.5]);
+        for _ in 0..15 {
+            nn.mutate_weights(&config, &mut rng);
+            assert_current(&nn);
+            if nn.mutate_add_connection(&mut innovations, &mut rng) {
+                assert_current(&nn);
+            }
+            let expected = Phenotype::from_nn(&nn).compute(&[0.5, -0.5]);
+            nn.reset_state();
+            assert_eq!(nn.compute([0.5, -0.5]), expected);
+        }
+    }
+
+    // the editor updates the phenotype as well
+    let mut nn = NeuralNetwork::with_size(1, 1);
+    let hidden = nn.edit().add_node(Activation::ReLU);
+    nn.compute([1.0]);
+    nn.edit().add_connection(1, hidden, 0.5).unwrap();
+    nn.edit().add_connection(hidden, 2, 2.0).unwrap();
+    nn.edit().set_weight(0, 2, -1.0).unwrap();
+    assert_current(&nn);
+}
+
+#[test]
+pub fn output_activation() {
+    use crate::neuralnetwork::NodeType;
+    use crate::{Activation, Config, NeuralNetwork, OutputActivation, Solver};
+    use rand::SeedableRng;
+    let mut rng = rand_pcg::Pcg64::seed_from_u64(8);
+    let identity = OutputActivation::Fixed(Activation::Identity);
+    // the outputs keep their activation function, the hidden nodes get the new one
+    let mut nn = NeuralNetwork::with_size(1, 2)
+        .with_output_activation(identity)
+        .with_activation(Activation::Tanh);
+    let mut editor = nn.edit();
+    let hidden = editor.add_node(Activation::Tanh);
+    editor.add_connection(1, hidden, 1.0).unwrap();
+    editor.add_connection(hidden, 2, 1.0).unwrap();
+    for _ in 0..20 {
+        assert!(nn.mutate_activation(&mut rng));
+    }
+    for node in nn.nodes() {
+        assert_eq!(
+            node.activation == Activation::Identity,
+            node.node_type == NodeType::Output
+        );
+    }
+    assert!(nn.compute([10.0])[1] > 1.0);
+
+    // softmax over the outputs in every kind of computation
+    let mut classifier =
+        NeuralNetwork::with_size(2, 3).with_output_activation(OutputActivation::Softmax);
+    let outputs = classifier.compute([0.5, -2.0]);
+    assert!((outputs.iter().sum::<f32>() - 1.0).abs() < 1e-6);
+    assert!(outputs.iter().all(|&p| p > 0.0));
+    assert_eq!(classifier.compile().compute(&[0.5, -2.0]), outputs);
+    assert!(classifier.codegen_rust().contains("(*x - m).exp()"));
+    let bytes = classifier.as_byte_representation().unwrap();
+    let mut loaded: NeuralNetwork = NeuralNetwork::from_bytes(&bytes).unwrap();
+    assert_eq!(loaded.output_activation(), OutputActivation::Softmax);
+    assert_eq!(loaded.compute([0.5, -2.0]), outputs);
+
+    // the solver keeps the output activation of every network
+    let config = Config::builder()
+        .generation_size(20)
+        .seed(3)
+        .activation_mutation_rate(1.0)
+        .output_activation(identity)
+        .build();
+    let mut solver = Solver::with_config(1, 1, config);
+    for _ in 0..5 {
+        solver.evaluate_generation(&|nn: &mut NeuralNetwork| -(nn.compute([1.0])[0] - 4.0).abs());
+        solver.new_generation();
+    }
+    for nn in solver.networks() {
+        assert_eq!(nn.output_activation(), identity);
+        assert_eq!(nn.nodes()[2].activation, Activation::Identity);
+    }
+}
+
+#[test]
+pub fn softmax_and_class_outputs() {
+    use crate::{Activation, NeuralNetwork, OutputActivation};
+    let mut nn = NeuralNetwork::with_size(1, 3).with_activation(Activation::Identity);
+    // node 0 is the constant input, node 1 the input of the computation
+    nn.edit().set_weight(1, 2, 2.0).unwrap();
+    nn.edit().set_weight(1, 3, 0.0).unwrap();
+    nn.edit().set_weight(1, 4, -1.0).unwrap();
+    // the outputs of the input 1.0 are 3.0, 1.0 and 0.0
+    assert_eq!(nn.compute([1.0]), vec![3.0, 1.0, 0.0]);
+    let sum = 3f32.exp() + 1f32.exp() + 1.0;
+    let probabilities = nn.compute_softmax([1.0], 1.0);
+    let expected = [3f32.exp() / sum, 1f32.exp() / sum, 1.0 / sum];
+    for (p, e) in probabilities.iter().zip(expected) {
+        assert!((p - e).abs() < 1e-6);
+    }
+    // a low temperature makes the probabilities sharper, a high one more uniform
+    assert!(nn.compute_softmax([1.0], 0.5)[0] > probabilities[0]);
+    assert!(nn.compute_softmax([1.0], 10.0)[0] < probabilities[0]);
+    // large outputs do not overflow
+    let large = nn.compute_softmax([1000.0], 1.0);
+    assert!(large.iter().all(|p| p.is_finite()));
+    assert!((large[0] - 1.0).abs() < 1e-6);
+
+    assert_eq!(nn.compute_class([1.0]), 0);
+    assert_eq!(nn.compute_class([-1.0]), 2);
+
+    // a network normalizing its outputs is not normalized twice
+    let mut classifier = nn.clone().with_output_activation(OutputActivation::Softmax);
+    assert_eq!(classifier.compute_softmax([1.0], 1.0), probabilities);
+    assert_eq!(classifier.compute_class([-1.0]), 2);
+}
+
+#[test]
+pub fn bias_node() {
+    use crate::neuralnetwork::NodeType;
+    use crate::{Activation, Config, InnovationTracker, NeuralNetwork, Solver};
+    use rand::SeedableRng;
+    let mut nn = NeuralNetwork::with_size(2, 2);
+    assert_eq!(nn.bias_node(), Some(0));
+    let types: Vec<NodeType> = nn.nodes().iter().map(|node| node.node_type).collect();
+    assert_eq!(
+        types,
+        [
+            NodeType::Bias,
+            NodeType::Input,
+            NodeType::Input,
+            NodeType::Output,
+            NodeType::Output
+        ]
+    );
+    assert_eq!(nn.bias(3), Some(1.0));
+    assert!(nn.edit().add_connection(3, 0, 1.0).is_err());
+    let before = nn.compute([0.5, 0.25]);
+
+    // genomes of earlier versions store the bias node as their first input
+    let mut legacy = nn.clone();
+    legacy.nodes[0].node_type = NodeType::Input;
+    assert_eq!(legacy.bias_node(), None);
+    assert!(legacy.validate().is_empty());
+    assert_eq!(legacy.compute([0.5, 0.25]), before);
+    let bytes = legacy.as_byte_representation().unwrap();
+    assert_eq!(NeuralNetwork::from_bytes(&bytes).unwrap(), nn);
+    assert!(legacy.repair().is_empty());
+    assert_eq!(legacy, nn);
+
+    // a new hidden node gets its own bias without changing the outputs
+    let mut rng = rand_pcg::Pcg64::seed_from_u64(5);
+    let mut nn = NeuralNetwork::with_size(2, 1)
+        .with_activation(Activation::Identity)
+        .with_node_biases(true);
+    nn.edit().set_enabled(0, 3, false).unwrap();
+    let before = nn.compute([0.5, 0.25]);
+    let mut innovations = InnovationTracker::for_network(&nn);
+    assert!(nn.mutate_add_node(&mut innovations, &mut rng));
+    assert_eq!(nn.bias(4), Some(0.0));
+    assert_eq!(nn.edges().len(), 6);
+    assert_eq!(nn.compute([0.5, 0.25]), before);
+    // the bias is mutated like every other weight
+    let config = Config::builder().weight_mutation_rate(1.0).build();
+    nn.mutate_weights(&config, &mut rng);
+    assert_ne!(nn.bias(4), Some(0.0));
+
+    let config = Config::builder()
+        .generation_size(10)
+        .node_biases(true)
+        .build();
+    let solver = Solver::with_config(2, 1, config);
+    assert!(solver.networks().iter().all(|nn| nn.node_biases()));
+}
+
+#[test]
+pub fn input_scaling() {
+    use crate::{InputScaling, NeuralNetwork};
+    let samples = vec![
+        vec![2.0, 5.0, 1.0],
+        vec![4.0, 5.0, 3.0],
+        vec![6.0, 5.0, 2.0],
+    ];
+    let min_max = InputScaling::min_max(&samples);
+    assert_eq!(
+        min_max,
+        InputScaling::MinMax {
+            min: vec![2.0, 5.0, 1.0],
+            max: vec![6.0, 5.0, 3.0]
+        }
+    );
+    // the constant second input is only shifted
+    assert_eq!(min_max.scale(&[4.0, 7.0, 1.0]), vec![0.5, 2.0, 0.0]);
+    let z_score = InputScaling::z_score(&samples);
+    match &z_score {
+        InputScaling::ZScore { mean, std_dev } => {
+            assert_eq!(mean, &vec![4.0, 5.0, 2.0]);
+            assert!((std_dev[0] - (8.0f32 / 3.0).sqrt()).abs() < 1e-6);
+            assert_eq!(std_dev[1], 0.0);
+        }
+        _ => panic!("expected a z-score scaling"),
+    }
+    let scaled = z_score.scale(&[4.0, 5.0, 2.0]);
+    assert!(scaled.iter().all(|x| x.abs() < 1e-6));
+
+    let mut nn = NeuralNetwork::with_size(3, 2);
+    let mut unscaled = nn.clone();
+    nn.set_input_scaling(z_score.clone());
+    for input in samples.iter() {
+        let expected = unscaled.compute(z_score.scale(input));
+        assert_eq!(nn.compute(input), expected);
+        assert_eq!(nn.compile().compute(input), expected);
+        assert_eq!(nn.phenotype().compute(input), expected);
+    }
+    // absent inputs are not scaled
+    assert_eq!(
+        nn.compute_masked([100.0, 5.0, 2.0], &[false, true, true]),
+        unscaled.compute([0.0, 0.0, 0.0])
+    );
+    assert!(nn.codegen_rust().contains("inputs[0] * "));
+    let bytes = nn.as_byte_representation().unwrap();
+    let loaded: NeuralNetwork = NeuralNetwork::from_bytes(&bytes).unwrap();
+    assert_eq!(loaded.input_scaling(), &z_score);
+    assert_eq!(nn.cast::<f64>().cast::<f32>(), nn);
+    // the offspring keep the scaling
+    let mut rng = rand::thread_rng();
+    assert_eq!(
+        nn.crossover(&nn.clone(), &mut rng).input_scaling(),
+        &z_score
+    );
+
+    nn.set_input_scaling(InputScaling::None);
+    assert_eq!(
+        nn.compute([1.0, 2.0, 3.0]),
+        unscaled.compute([1.0, 2.0, 3.0])
+    );
+}
+
+#[test]
+pub fn remap_scaled_inputs() {
+    use crate::{GenomeProblem, InputScaling, NeuralNetwork};
+    let scaling = InputScaling::MinMax {
+        min: vec![0.0, 100.0],
+        max: vec![1.0, 200.0],
+    };
+    let mut nn = NeuralNetwork::with_size(2, 1);
+    nn.set_input_scaling(scaling);
+    let expected = nn.compute([0.5, 150.0]);
+    nn.remap_inputs(&[Some(1), Some(0)]);
+    assert_eq!(nn.compute([150.0, 0.5]), expected);
+    assert!(nn.validate().is_empty());
+
+    // new inputs are not scaled and removed inputs lose their statistics
+    let extended = nn.clone().with_extended_inputs(1);
+    assert_eq!(
+        extended.input_scaling(),
+        &InputScaling::MinMax {
+            min: vec![100.0, 0.0, 0.0],
+            max: vec![200.0, 1.0, 1.0]
+        }
+    );
+    assert!(extended.validate().is_empty());
+    nn.remap_inputs(&[Some(0)]);
+    assert_eq!(nn.input_scaling().inputs(), Some(1));
+
+    nn.input_scaling = InputScaling::ZScore {
+        mean: vec![0.0; 3],
+        std_dev: vec![1.0; 3],
+    };
+    assert_eq!(
+        nn.validate(),
+        vec![GenomeProblem::InputScalingLength {
+            expected: 1,
+            found: 3
+        }]
+    );
+}
+
+#[test]
+pub fn repeated_trials() {
+    use crate::{Config, NeuralNetwork, Solver, TrainOptions, TrialAggregation};
+    use std::cell::Cell;
+    let mut trials = [4.0, 1.0, 3.0, 2.0];
+    assert_eq!(TrialAggregation::Mean.aggregate(&mut trials), 2.5);
+    assert_eq!(TrialAggregation::Median.aggregate(&mut trials), 2.5);
+    assert_eq!(TrialAggregation::Median.aggregate(&mut trials[..3]), 2.0);
+    assert_eq!(TrialAggregation::Min.aggregate(&mut trials), 1.0);
+
+    // the results of the trials of a network are 0.0, 1.0 and 2.0
+    let calls = Cell::new(0);
+    let evaluator = |_: &mut NeuralNetwork| {
+        calls.set(calls.get() + 1);
+        (calls.get() % 3) as f32
+    };
+    let config = Config::builder()
+        .generation_size(10)
+        .elitism(2)
+        .seed(4)
+        .build();
+    for (aggregation, expected) in [
+        (TrialAggregation::Mean, 1.0),
+        (TrialAggregation::Median, 1.0),
+        (TrialAggregation::Min, 0.0),
+    ] {
+        let mut solver = Solver::with_config(1, 1, config);
+        let options = TrainOptions {
+            trials_per_network: 3,
+            trial_aggregation: aggregation,
+            ..TrainOptions::default()
+        };
+        calls.set(0);
+        solver.evaluate_generation_with_trials(&evaluator, &options);
+        assert_eq!(calls.get(), 30);
+        assert!(solver.networks().iter().all(|nn| nn.fitness() == expected));
+    }
+
+    // without reevaluation the elites keep their fitness
+    let evaluator = |_: &mut NeuralNetwork| {
+        calls.set(calls.get() + 1);
+        calls.get() as f32
+    };
+    let mut solver = Solver::with_config(1, 1, config);
+    let options = TrainOptions {
+        max_generations: Some(2),
+        reevaluate_elites: false,
+        ..TrainOptions::default()
+    };
+    calls.set(0);
+    let report = solver.train(&evaluator, &options);
+    assert_eq!(calls.get(), 18);
+    let elites: Vec<f32> = solver.networks()[..2]
+        .iter()
+        .map(|nn| nn.fitness())
+        .collect();
+    assert_eq!(elites, [10.0, 9.0]);
+    assert_eq!(report.history[1].best_fitness, 18.0);
+}
+
+#[test]
+pub fn reseed_keeps_elites() {
+    use crate::{Config, NeuralNetwork, Solver};
+    let config = Config::builder()
+        .generation_size(10)
+        .elitism(2)
+        .seed(3)
+        .build();
+    let mut solver = Solver::with_config(1, 1, config);
+    solver.evaluate_generation(&|nn: &mut NeuralNetwork| nn.compute([0.5])[0]);
+    solver.new_generation();
+    let elites = solver.networks()[..2].to_vec();
+    // every network is unevaluated, the elites are still not replaced
+    solver
+        .reseed_population(&NeuralNetwork::with_size(1, 1), 0.8)
+        .unwrap();
+    solver
+        .insert_network(NeuralNetwork::with_size(1, 1))
+        .unwrap();
+    assert_eq!(solver.networks()[..2], elites[..]);
+}
+
+#[test]
+pub fn inserted_network_replacing_elite() {
+    use crate::{Config, NeuralNetwork, Solver, TrainOptions};
+    let config = Config::builder()
+        .generation_size(10)
+        .elitism(1)
+        .seed(3)
+        .build();
+    let mut solver = Solver::with_config(1, 1, config);
+    solver.evaluate_generation(&|_: &mut NeuralNetwork| 100.0);
+    solver.new_generation();
+    let options = TrainOptions {
+        reevaluate_elites: false,
+        ..TrainOptions::default()
+    };
+    // the inserted network takes over a slot, it has to be evaluated instead of keeping the
+    // fitness of the elite
+    solver
+        .reseed_population(&NeuralNetwork::with_size(1, 1), 1.0)
+        .unwrap();
+    solver.evaluate_generation_with_trials(&|_: &mut NeuralNetwork| -1000.0, &options);
+    assert!(solver.networks().iter().all(|nn| nn.fitness() == -1000.0));
+}
+
+#[test]
+#[should_panic]
+pub fn zero_trials() {
+    use crate::{NeuralNetwork, Solver, TrainOptions};
+    let options = TrainOptions {
+        trials_per_network: 0,
+        ..TrainOptions::default()
+    };
+    let mut solver = Solver::with_size(1, 1, 10);
+    solver.train(&|nn: &mut NeuralNetwork| nn.compute([0.5])[0], &options);
+}
+
+#[test]
+#[cfg(feature = "std-fs")]
+pub fn cancelled_training() {
+    use crate::{CancellationHandle, Error, NeuralNetwork, Solver, StopReason, TrainOptions};
+    use std::cell::Cell;
+    use tempfile::tempdir;
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("checkpoint.sv");
+    let handle = CancellationHandle::new();
+    let evaluations = Cell::new(0);
+    // the handle is cancelled in the middle of the third generation
+    let evaluator = |nn: &mut NeuralNetwork| {
+        evaluations.set(evaluations.get() + 1);
+        if evaluations.get() == 25 {
+            handle.cancel();
+        }
+        nn.compute([0.5])[0]
+    };
+    let options = TrainOptions {
+        max_generations: None,
+        cancellation: Some(handle.clone()),
+        checkpoint: Some(path.clone()),
+        ..TrainOptions::default()
+    };
+    let mut solver = Solver::with_size(1, 1, 10);
+    let report = solver.train(&evaluator, &options);
+    assert_eq!(report.stop_reason, StopReason::Cancelled);
+    assert_eq!(report.history.len(), 3);
+    assert_eq!(evaluations.get(), 30);
+    assert!(report.checkpoint_error.is_none());
+    assert_eq!(Solver::load_from(&path).unwrap(), solver);
+
+    // a checkpoint which cannot be written is reported
+    let options = TrainOptions {
+        max_generations: Some(1),
+        checkpoint: Some(dir.path().join("missing").join("checkpoint.sv")),
+        ..TrainOptions::default()
+    };
+    let report = solver.train(&evaluator, &options);
+    assert!(matches!(report.checkpoint_error, Some(Error::Io(_))));
+}
+
+#[test]
+#[cfg(feature = "async")]
+pub fn async_evaluation() {
+    use crate::{AsyncFitnessEvaluator, NeuralNetwork, Solver};
+    use std::cell::Cell;
+    use std::future::{poll_fn, Future};
+    use std::pin::pin;
+    use std::task::{Context, Poll, Waker};
+    /// Answers every request after it has been polled once, like a slow remote service.
+    struct Remote {
+        pending: Cell<usize>,
+        max_pending: Cell<usize>,
+    }
+
+    impl AsyncFitnessEvaluator for Remote {
+        async fn evaluate(&self, nn: &mut NeuralNetwork) -> f32 {
+            let output = nn.compute([0.5])[0];
+            self.pending.set(self.pending.get() + 1);
+            self.max_pending
+                .set(self.max_pending.get().max(self.pending.get()));
+            let mut polled = false;
+            poll_fn(|_| match polled {
+                true => Poll::Ready(()),
+                false => {
+                    polled = true;
+                    Poll::Pending
+                }
+            })
+            .await;
+            self.pending.set(self.pending.get() - 1);
+            output * 2.0
+        }
+    }
+
+    let remote = Remote {
+        pending: Cell::new(0),
+        max_pending: Cell::new(0),
+    };
+    let mut solver = Solver::with_size(1, 1, 10);
+    {
+        let mut evaluation = pin!(solver.evaluate_generation_async(&remote));
+        let mut context = Context::from_waker(Waker::noop());
+        assert!(evaluation.as_mut().poll(&mut context).is_pending());
+        assert!(evaluation.as_mut().poll(&mut context).is_ready());
+    }
+    // every network waited for its answer at the same time
+    assert_eq!(remote.max_pending.get(), 10);
+    for nn in solver.neural_nets() {
+        assert_eq!(nn.fitness(), nn.compute([0.5])[0] * 2.0);
+    }
+}
+
+#[test]
+#[cfg(feature = "distributed")]
+pub fn distributed_evaluation() {
+    use crate::distributed::{Coordinator, Worker};
+    use crate::{NeuralNetwork, Solver};
+    use std::time::Duration;
+    let mut coordinator = Coordinator::bind("127.0.0.1:0")
+        .unwrap()
+        .with_timeout(Duration::from_millis(200));
+    let address = coordinator.local_addr().unwrap();
+    let fast = std::thread::spawn(move || {
+        Worker::new("fast")
+            .with_heartbeat_interval(Duration::from_millis(50))
+            .run(address, &|nn: &mut NeuralNetwork| nn.compute([0.5])[0])
+    });
+    // the slow worker does not send heartbeats while it evaluates, so it is lost
+    let slow = std::thread::spawn(move || {
+        Worker::new("slow")
+            .with_heartbeat_interval(Duration::from_secs(60))
+            .run(address, &|nn: &mut NeuralNetwork| {
+                std::thread::sleep(Duration::from_millis(600));
+                nn.compute([0.5])[0]
+            })
+    });
+    // a peer which announces a large message without sending it never registers and is lost
+    let mut peer = std::net::TcpStream::connect(address).unwrap();
+    std::io::Write::write_all(&mut peer, &(1u32 << 23).to_le_bytes()).unwrap();
+    assert_eq!(
+        coordinator
+            .wait_for_workers(2, Duration::from_secs(10))
+            .unwrap(),
+        2
+    );
+    let mut solver = Solver::with_size(1, 1, 10);
+    solver
+        .evaluate_generation_distributed(&mut coordinator)
+        .unwrap();
+    assert_eq!(coordinator.workers(), ["fast"]);
+    for nn in solver.neural_nets() {
+        assert_eq!(nn.fitness(), nn.compute([0.5])[0]);
+    }
+    drop(coordinator);
+    // the network of the slow worker has been evaluated again by the fast one
+    assert_eq!(fast.join().unwrap().unwrap(), 10);
+    slow.join().unwrap().ok();
+}
+
+#[test]
+pub fn agent_pool() {
+    use crate::{AgentPool, Environment, Solver};
+    /// Rewards the output of the network in three steps.
+    struct Steps(usize);
+
+    impl Environment for Steps {
+        type Observation = [f32; 1];
+
+        fn reset(&mut self) -> [f32; 1] {
+            self.0 = 0;
+            [0.5]
+        }
+
+        fn step(&mut self, action: &[f32]) -> ([f32; 1], f32, bool) {
+            self.0 += 1;
+            ([0.5], action[0], self.0 == 3)
+        }
+    }
+
+    let mut pool = AgentPool::new(Solver::with_size(1, 1, 10), 100);
+    let mut envs: Vec<Steps> = (0..pool.agents()).map(|_| Steps(0)).collect();
+    let mut frames = 0;
+    while !pool.is_generation_done() {
+        pool.step_environments(&mut envs);
+        frames += 1;
+    }
+    assert_eq!(frames, 3);
+    let expected: Vec<f32> = pool
+        .solver()
+        .networks()
+        .iter()
+        .map(|nn| 3.0 * nn.clone().compute([0.5])[0])
+        .collect();
+    for (agent, &reward) in expected.iter().enumerate() {
+        assert!((pool.total_reward(agent) - reward).abs() < 1e-5);
+    }
+    let best = expected.iter().copied().fold(f32::MIN, f32::max);
+    assert_eq!(pool.next_generation().best_fitness, best);
+    assert_eq!(pool.total_reward(0), 0.0);
+
+    // the agents stop after their maximum number of steps
+    let mut pool = AgentPool::new(pool.into_solver(), 2);
+    pool.step_environments(&mut envs);
+    assert!(!pool.is_generation_done());
+    pool.step_environments(&mut envs);
+    assert!(pool.is_generation_done());
+}
+
+#[test]
+pub fn network_summary() {
+    use crate::{Activation, NeuralNetwork};
+    let mut nn = NeuralNetwork::with_size(2, 1).with_recurrent(true);
+    let mut editor = nn.edit();
+    for from in 0..3 {
+        editor.set_weight(from, 3, 1.0).unwrap();
+    }
+    let a = editor.add_node(Activation::Identity);
+    let b = editor.add_node(Activation::Identity);
+    editor.add_connection(1, a, -1.0).unwrap();
+    editor.add_connection(a, b, 2.0).unwrap();
+    editor.add_connection(b, 3, 1.0).unwrap();
+    editor.add_connection(b, a, 0.5).unwrap();
+    editor.set_enabled(2, 3, false).unwrap();
+    let summary = nn.summary();
+    assert_eq!(
+        (
+            summary.input_nodes,
+            summary.bias_nodes,
+            summary.hidden_nodes,
+            summary.output_nodes
+        ),
+        (2, 1, 2, 1)
+    );
+    assert_eq!(summary.enabled_edges, 6);
+    assert_eq!(summary.disabled_edges, 1);
+    assert_eq!(summary.recurrent_edges, 1);
+    // the recurrent edge does not make the path longer
+    assert_eq!(summary.depth, 3);
+    assert_eq!(summary.mean_fan_in, 2.0);
+    assert_eq!(summary.mean_fan_out, 1.2);
+    assert_eq!(summary.weights.mean, 0.75);
+    assert_eq!(summary.weights.mean_abs, 6.5 / 6.0);
+    assert_eq!((summary.weights.min, summary.weights.max), (-1.0, 2.0));
+    assert!(summary.to_string().contains("2 hidden"));
+}
+
+#[test]
+pub fn lineage_tracking() {
+    use crate::{Config, GenomeId, NeuralNetwork, Origin, Solver, TrainOptions};
+    let config = Config::builder()
+        .generation_size(20)
+        .elitism(2)
+        .add_node_rate(0.5)
+        .track_lineage(true)
+        .seed(3)
+        .build();
+    let mut solver = Solver::with_config(1, 1, config);
+    let options = TrainOptions {
+        max_generations: Some(5),
+        ..TrainOptions::default()
+    };
+    let evaluator = |nn: &mut NeuralNetwork| nn.compute([0.5])[0];
+    let report = solver.train(&evaluator, &options);
+    let lineage = solver.lineage();
+    assert_eq!(lineage.records().len(), 100);
+    assert_eq!(lineage.generation(4).len(), 20);
+    assert!(lineage
+        .generation(0)
+        .iter()
+        .all(|record| record.origin == Origin::Initial && record.fitness > f32::MIN));
+    for record in lineage.records().iter().skip(20) {
+        let generation = record.genome.generation;
+        assert!(
+            record
+                .parents
+                .iter()
+                .all(|parent| parent.generation == generation - 1
+                    && lineage.record(*parent).is_some())
+        );
+        match record.origin {
+            Origin::Elite => assert!(record.parents.len() == 1 && record.mutations.is_empty()),
+            Origin::Mutation => assert_eq!(record.parents.len(), 1),
+            Origin::Crossover => assert_eq!(record.parents.len(), 2),
+            origin => panic!("unexpected origin {:?}", origin),
+        }
+    }
+    let elite = lineage.record(GenomeId {
+        generation: 1,
+        id: 0,
+    });
+    assert_eq!(elite.map(|record| record.origin), Some(Origin::Elite));
+    assert!(lineage
+        .records()
+        .iter()
+        .any(|record| !record.mutations.is_empty()));
+    let ancestry = lineage.ancestry_of(report.champion_id);
+    assert_eq!(ancestry[0].genome, report.champion_id);
+    assert_eq!(ancestry.last().unwrap().genome.generation, 0);
+
+    // the lineage is stored with the solver and inserted networks have no parents
+    let bytes = solver.as_byte_representation().unwrap();
+    let mut loaded = Solver::load_from_reader(&bytes[..]).unwrap();
+    assert_eq!(loaded.lineage(), solver.lineage());
+    let id = loaded
+        .insert_network(NeuralNetwork::with_size(1, 1))
+        .unwrap();
+    let genome = GenomeId {
+        generation: loaded.generation(),
+        id,
+    };
+    assert_eq!(loaded.lineage().ancestry_of(genome).len(), 1);
+    assert_eq!(
+        loaded.lineage().record(genome).unwrap().origin,
+        Origin::Inserted
+    );
+
+    // nothing is recorded by default
+    let mut solver = Solver::with_size(1, 1, 10);
+    solver.train(&evaluator, &options);
+    assert!(solver.lineage().is_empty());
+}
+
+#[test]
+pub fn merged_innovations() {
+    use crate::neuralnetwork::NodeType;
+    use crate::{Config, NeuralNetwork, Solver};
+    let config = Config::builder()
+        .generation_size(20)
+        .add_node_rate(1.0)
+        .seed(5)
+        .build();
+    let evaluator = |nn: &mut NeuralNetwork| nn.compute([0.5])[0];
+    let mut island = Solver::with_config(1, 1, config);
+    for _ in 0..3 {
+        island.evaluate_generation(&evaluator);
+        island.new_generation();
+    }
+    let hidden = |nn: &NeuralNetwork| -> Vec<usize> {
+        nn.nodes()
+            .iter()
+            .filter(|node| node.node_type == NodeType::Hidden)
+            .map(|node| node.id)
+            .collect()
+    };
+    // a copy of the island uses the same innovations, so nothing is translated
+    let bytes = island.as_byte_representation().unwrap();
+    let mut copy = Solver::load_from_reader(&bytes[..]).unwrap();
+    let mapping = copy.merge_innovations(&island).unwrap();
+    assert!(island
+        .networks()
+        .iter()
+        .all(|nn| hidden(nn).iter().all(|&id| mapping.node(id) == Some(id))));
+    let migrant = island.networks()[3].clone();
+    let id = copy.insert_network_from(&migrant, &mapping).unwrap();
+    assert_eq!(copy.network(id).unwrap().edges(), migrant.edges());
+
+    // networks sharing a node of the island share its translated node in another run
+    let mut solver = Solver::with_size(1, 1, 20);
+    let mapping = solver.merge_innovations(&island).unwrap();
+    let (a, b) = (0..20)
+        .flat_map(|a| (0..a).map(move |b| (a, b)))
+        .find(|&(a, b)| {
+            let shared = hidden(&island.networks()[b]);
+            hidden(&island.networks()[a])
+                .iter()
+                .any(|id| shared.contains(id))
+        })
+        .expect("two networks of the island share a hidden node");
+    let shared: Vec<usize> = hidden(&island.networks()[a])
+        .into_iter()
+        .filter(|id| hidden(&island.networks()[b]).contains(id))
+        .collect();
+    let a = solver
+        .insert_network_from(&island.networks()[a].clone(), &mapping)
+        .unwrap();
+    let b = solver
+        .insert_network_from(&island.networks()[b].clone(), &mapping)
+        .unwrap();
+    for id in shared {
+        let translated = mapping.node(id).unwrap();
+        assert!(hidden(solver.network(a).unwrap()).contains(&translated));
+        assert!(hidden(solver.network(b).unwrap()).contains(&translated));
+    }
+    assert!(solver
+        .merge_innovations(&Solver::with_size(2, 1, 5))
+        .is_err());
+}
+
+#[test]
+pub fn gene_index_distance() {
+    use crate::genome::{self, GeneIndex, GeneMatch};
+    use crate::{Config, NeuralNetwork, Solver};
+    let config = Config::builder()
+        .generation_size(20)
+        .add_node_rate(0.5)
+        .add_connection_rate(0.5)
+        .seed(11)
+        .build();
+    // the distance of the speciation, computed from the aligned genes
+    let aligned = |a: &NeuralNetwork, b: &NeuralNetwork| {
+        let (mut excess, mut disjoint, mut matching, mut weight_diff) = (0, 0, 0, 0.0);
+        for gene in genome::align(a, b) {
+            match gene {
+                GeneMatch::Matching(a, b) => {
+                    weight_diff += (a.weight - b.weight).abs();
+                    matching += 1;
+                }
+                GeneMatch::DisjointFirst(_) | GeneMatch::DisjointSecond(_) => disjoint += 1,
+                GeneMatch::ExcessFirst(_) | GeneMatch::ExcessSecond(_) => excess += 1,
+            }
+        }
+        let n = a.edges.len().max(b.edges.len()).max(1) as f32;
+        config.c1 * excess as f32 / n
+            + config.c2 * disjoint as f32 / n
+            + config.c3 * weight_diff / matching.max(1) as f32
+    };
+    let mut solver = Solver::with_config(3, 2, config);
+    for _ in 0..5 {
+        solver.evaluate_generation(&|nn: &mut NeuralNetwork| nn.compute([0.5, 0.1, 0.9])[0]);
+        solver.new_generation();
+    }
+    for a in solver.networks() {
+        for b in solver.networks() {
+            let distance = GeneIndex::new(a).distance(&GeneIndex::new(b), &config);
+            assert!((distance - aligned(a, b)).abs() < 1e-6);
+        }
+    }
+}
+
+#[test]
+pub fn mutation_rate_adaptation() {
+    use crate::{Config, NeuralNetwork, RateSchedule, Solver};
+    let evaluator = |nn: &mut NeuralNetwork| nn.compute([0.5])[0];
+    let hidden_nodes = |schedule| {
+        let config = Config::builder()
+            .generation_size(20)
+            .add_node_rate(1.0)
+            .mutation_schedule(schedule)
+            .seed(2)
+            .build();
+        let mut solver = Solver::with_config(1, 1, config);
+        for _ in 0..3 {
+            solver.evaluate_generation(&evaluator);
+            solver.new_generation();
+        }
+        solver
+            .networks()
+            .iter()
+            .map(|nn| nn.hidden_node_count())
+            .sum::<usize>()
+    };
+    assert!(hidden_nodes(RateSchedule::Constant) > 0);
+    // the rates drop to zero after the first generation, so no network is mutated
+    let decayed = RateSchedule::Exponential {
+        decay: 0.0,
+        min_factor: 0.0,
+    };
+    assert_eq!(hidden_nodes(decayed), 0);
+
+    let config = Config::builder()
+        .generation_size(20)
+        .self_adaptive_mutation(Some(0.5))
+        .seed(4)
+        .build();
+    let mut solver = Solver::with_config(1, 1, config);
+    for _ in 0..5 {
+        solver.evaluate_generation(&evaluator);
+        solver.new_generation();
+    }
+    let rates: Vec<f32> = solver
+        .networks()
+        .iter()
+        .filter_map(|nn| nn.mutation_rates())
+        .map(|rates| rates.add_node_rate)
+        .collect();
+    // only the elite has never been mutated
+    assert!(rates.len() >= 19);
+    assert!(rates.iter().all(|rate| (0.0..=1.0).contains(rate)));
+    assert!(rates.iter().any(|&rate| rate != config.add_node_rate));
+    let bytes = solver.as_byte_representation().unwrap();
+    let loaded = Solver::load_from_reader(&bytes[..]).unwrap();
+    assert_eq!(loaded.networks(), solver.networks());
+    assert!(Config::builder()
+        .self_adaptive_mutation(Some(-1.0))
+        .build()
+        .validate()
+        .is_err());
+}
+
+#[test]
+pub fn offspring_validation() {
+    use crate::{Config, NeuralNetwork, OffspringVerdict, Solver};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    let evaluator = |nn: &mut NeuralNetwork| nn.compute([0.5, 0.5])[0];
+    let config = Config::builder()
+        .generation_size(10)
+        .elitism(0)
+        .add_node_rate(1.0)
+        .add_connection_rate(1.0)
+        .offspring_attempts(3)
+        .seed(8)
+        .build();
+    // every offspring is repaired by disabling the connections of the second input
+    let mut solver = Solver::with_config(2, 1, config);
+    solver.set_offspring_validator(|nn: &mut NeuralNetwork| {
+        let forbidden: Vec<(usize, usize)> = nn
+            .edges()
+            .iter()
+            .filter(|edge| edge.from == 2 && edge.enabled)
+            .map(|edge| (edge.from, edge.to))
+            .collect();
+        let mut editor = nn.edit();
+        for (from, to) in forbidden {
+            editor.set_enabled(from, to, false).unwrap();
+        }
+        OffspringVerdict::Accept
+    });
+    for _ in 0..3 {
+        solver.evaluate_generation(&evaluator);
+        solver.new_generation();
+    }
+    assert!(solver.networks().iter().all(|nn| nn
+        .edges()
+        .iter()
+        .all(|edge| edge.from != 2 || !edge.enabled)));
+
+    // every offspring is rejected, so the unchanged parents are copied after three attempts
+    let calls = Arc::new(AtomicUsize::new(0));
+    let mut solver = Solver::with_config(2, 1, config);
+    let counter = calls.clone();
+    solver.set_offspring_validator(move |_: &mut NeuralNetwork| {
+        counter.fetch_add(1, Ordering::Relaxed);
+        OffspringVerdict::Reject
+    });
+    for _ in 0..2 {
+        solver.evaluate_generation(&evaluator);
+        solver.new_generation();
+    }
+    assert_eq!(calls.load(Ordering::Relaxed), 2 * 10 * 3);
+    assert!(solver
+        .networks()
+        .iter()
+        .all(|nn| nn.hidden_node_count() == 0));
+    solver.clear_offspring_validator();
+    solver.evaluate_generation(&evaluator);
+    solver.new_generation();
+    assert!(solver
+        .networks()
+        .iter()
+        .any(|nn| nn.hidden_node_count() > 0));
+}
+
+#[test]
+pub fn frozen_skeleton() {
+    use crate::{Activation, Config, NeuralNetwork, Solver};
+    // a hand-designed feature detector between the first input and the output
+    let mut skeleton = NeuralNetwork::with_size(2, 1);
+    let mut editor = skeleton.edit();
+    let detector = editor.add_node(Activation::Gaussian);
+    editor.add_connection(1, detector, 2.0).unwrap();
+    editor.add_connection(detector, 3, -1.5).unwrap();
+    skeleton.freeze_node(detector).unwrap();
+    skeleton.freeze_connection(1, detector).unwrap();
+    skeleton.freeze_connection(detector, 3).unwrap();
+    assert!(skeleton.edit().set_weight(1, detector, 0.0).is_err());
+    assert_eq!(skeleton.frozen_connections().count(), 2);
+
+    let config = Config::builder()
+        .generation_size(20)
+        .add_node_rate(0.5)
+        .activation_mutation_rate(1.0)
+        .toggle_connection_rate(1.0)
+        .weight_replace_rate(0.5)
+        .seed(6)
+        .build();
+    let mut solver = Solver::from_champion(&skeleton, 20, config).unwrap();
+    for _ in 0..5 {
+        solver.evaluate_generation(&|nn: &mut NeuralNetwork| nn.compute([0.2, 0.8])[0]);
+        solver.new_generation();
+    }
+    for nn in solver.networks() {
+        // the skeleton got new node ids in the solver
+        let (from, detector) = nn.frozen_connections().next().unwrap();
+        assert_eq!(from, 1);
+        let node = nn.nodes().iter().find(|node| node.id == detector).unwrap();
+        assert_eq!(node.activation, Activation::Gaussian);
+        let weights: Vec<(f32, bool)> = nn
+            .edges()
+            .iter()
+            .filter(|edge| nn.is_connection_frozen(edge.from, edge.to))
+            .map(|edge| (edge.weight, edge.enabled))
+            .collect();
+        assert_eq!(weights, vec![(2.0, true), (-1.5, true)]);
+        let mut pruned = nn.clone();
+        pruned.prune();
+        assert!(pruned.is_node_frozen(detector));
+        assert_eq!(pruned.frozen_connections().count(), 2);
+    }
+}
+
+#[test]
+pub fn modular_genome() {
+    use crate::{Config, Module, NeuralNetwork, Solver};
+    // every instance carries the weights of its module
+    let consistent = |nn: &NeuralNetwork| {
+        nn.module_instances().iter().all(|instance| {
+            let module = &nn.modules()[instance.module()];
+            let nodes = instance.nodes();
+            nodes.len() == module.node_count()
+                && module.edges().iter().all(|local| {
+                    nn.edges().iter().any(|edge| {
+                        edge.from == nodes[local.from]
+                            && edge.to == nodes[local.to]
+                            && edge.weight == local.weight
+                            && edge.enabled == local.enabled
+                    })
+                })
+        })
+    };
+    // two legs with two sensors and two motors each
+    let mut nn = NeuralNetwork::with_size(4, 4);
+    let leg = nn.define_module(Module::new(2, 2));
+    nn.instantiate_module(leg, &[1, 2], &[5, 6]).unwrap();
+    nn.instantiate_module(leg, &[3, 4], &[7, 8]).unwrap();
+    assert!(nn.instantiate_module(leg, &[1], &[5, 6]).is_err());
+    let motor = nn.module_instances()[0].nodes()[2];
+    assert!(nn.edit().set_weight(1, motor, 0.0).is_err());
+    assert!(nn.edit().add_connection(3, motor, 0.0).is_err());
+    let symmetric = nn.compute([0.1, 0.9, 0.1, 0.9]);
+    assert_eq!(symmetric[..2], symmetric[2..]);
+
+    let config = Config::builder()
+        .generation_size(20)
+        .add_node_rate(0.5)
+        .add_connection_rate(0.5)
+        .activation_mutation_rate(1.0)
+        .toggle_connection_rate(1.0)
+        .add_module_instance_rate(0.3)
+        .module_add_node_rate(0.3)
+        .seed(3)
+        .build();
+    let mut solver = Solver::from_champion(&nn, 20, config).unwrap();
+    for _ in 0..5 {
+        solver.evaluate_generation(&|nn: &mut NeuralNetwork| nn.compute([0.2, 0.8, 0.4, 0.6])[0]);
+        solver.new_generation();
+    }
+    assert!(solver
+        .networks()
+        .iter()
+        .any(|nn| nn.modules()[0].node_count() > 4));
+    for nn in solver.networks() {
+        assert!(consistent(nn));
+        let mut pruned = nn.clone();
+        pruned.prune();
+        assert_eq!(pruned.module_instances(), nn.module_instances());
+    }
+    let stored = Solver::load_from_reader(&solver.as_byte_representation().unwrap()[..]).unwrap();
+    assert_eq!(
+        stored.networks()[0].modules(),
+        solver.networks()[0].modules()
+    );
+}
+
+#[test]
+pub fn local_search() {
+    use crate::{
+        Activation, Config, Inheritance, InnovationTracker, LocalSearch, MeanSquaredError,
+        NeuralNetwork, OutputActivation, Solver, TrainOptions,
+    };
+    use rand::SeedableRng;
+    use rand_pcg::Pcg64;
+    let samples: Vec<(Vec<f32>, Vec<f32>)> = (0..8)
+        .map(|i| {
+            let x = i as f32 / 7.0;
+            (vec![x, 1.0 - x], vec![x * x, 0.5 - x])
+        })
+        .collect();
+    // one gradient step moves every weight by the learning rate times the numeric gradient
+    let mut rng = Pcg64::seed_from_u64(4);
+    let mut nn = NeuralNetwork::with_size(2, 2).with_output_activation(OutputActivation::Softmax);
+    let mut innovations = InnovationTracker::for_network(&nn);
+    for activation in [Activation::Tanh, Activation::Gaussian, Activation::Sine] {
+        nn.mutate_add_node(&mut innovations, &mut rng);
+        nn.mutate_add_connection(&mut innovations, &mut rng);
+        let last = nn.nodes().len() - 1;
+        let id = nn.nodes()[last].id;
+        nn.edit().set_activation(id, activation).unwrap();
+    }
+    let step = LocalSearch::new(samples.clone(), MeanSquaredError)
+        .with_steps(1)
+        .with_learning_rate(1e-3);
+    let mut tuned = nn.clone();
+    tuned.fine_tune(&step);
+    for (idx, edge) in nn.edges().iter().enumerate() {
+        let loss_at = |delta: f32| {
+            let mut shifted = nn.clone();
+            shifted
+                .edit()
+                .set_weight(edge.from, edge.to, edge.weight + delta)
+                .unwrap();
+            shifted.sample_loss(&step)
+        };
+        let numeric = if edge.enabled {
+            (loss_at(1e-2) - loss_at(-1e-2)) / 2e-2
+        } else {
+            0.0
+        };
+        let moved = (edge.weight - tuned.edges()[idx].weight) / 1e-3;
+        assert!((moved - numeric).abs() < 1e-2, "{} {}", moved, numeric);
+    }
+
+    let calls = std::cell::Cell::new(0);
+    let fitness = |nn: &mut NeuralNetwork| {
+        calls.set(calls.get() + 1);
+        -samples
+            .iter()
+            .map(|(inputs, target)| {
+                let outputs = nn.compute(inputs);
+                (outputs[0] - target[0]).powi(2) + (outputs[1] - target[1]).powi(2)
+            })
+            .sum::<f32>()
+    };
+    for inheritance in [Inheritance::Lamarckian, Inheritance::Baldwinian] {
+        let config = Config::builder().generation_size(10).seed(1).build();
+        let mut solver = Solver::with_config(2, 2, config);
+        let search = LocalSearch::new(samples.clone(), MeanSquaredError)
+            .with_steps(30)
+            .with_champions(3)
+            .with_inheritance(inheritance);
+        solver.set_local_search(search);
+        // the fine-tuned networks are scored with the same trials as the others
+        let options = TrainOptions {
+            trials_per_network: 2,
+            ..TrainOptions::default()
+        };
+        solver.evaluate_generation_with_trials(&fitness, &options);
+        let before: Vec<NeuralNetwork> = solver.networks().to_vec();
+        calls.set(0);
+        solver.fine_tune_generation(&fitness, &options);
+        assert_eq!(calls.get(), 6);
+        let changed: Vec<bool> = before
+            .iter()
+            .zip(solver.networks())
+            .map(|(old, new)| old.edges() != new.edges())
+            .collect();
+        let improved = before
+            .iter()
+            .zip(solver.networks())
+            .filter(|(old, new)| new.fitness > old.fitness)
+            .count();
+        assert_eq!(improved, 3);
+        match inheritance {
+            Inheritance::Lamarckian => assert_eq!(changed.iter().filter(|&&c| c).count(), 3),
+            Inheritance::Baldwinian => assert!(changed.iter().all(|&c| !c)),
+        }
+    }
+}
+
+#[test]
+pub fn supervised_problem() {
+    use crate::problems::supervised::{CsvOptions, Dataset, SupervisedLoss, SupervisedProblem};
+    use crate::{DifferentiableLoss, FitnessEvaluator, NeuralNetwork};
+    let csv = "# y = x\nx,y\n0.0,0.0\n\n0.25, 0.25\n0.5,0.5\n0.75,0.75\n1.0,1.0\n";
+    let options = CsvOptions::default();
+    let dataset = Dataset::from_csv_reader(csv.as_bytes(), &options).unwrap();
+    assert_eq!(dataset.len(), 5);
+    assert_eq!(dataset.samples()[1], (vec![0.25], vec![0.25]));
+    assert!(Dataset::from_csv_reader("1,2\n3\n".as_bytes(), &options).is_err());
+    assert!(Dataset::from_csv_reader("1,2\n3,x\n".as_bytes(), &options).is_err());
+    let options = CsvOptions::with_targets(2);
+    assert!(Dataset::from_csv_reader("1,2\n".as_bytes(), &options).is_err());
+
+    // a network reproducing the targets has no loss
+    let mut nn = NeuralNetwork::with_size(1, 1);
+    let targets: Vec<(Vec<f32>, Vec<f32>)> = dataset
+        .samples()
+        .iter()
+        .map(|(inputs, _)| (inputs.clone(), nn.compute(inputs)))
+        .collect();
+    let problem = SupervisedProblem::new(
+        Dataset::new(targets).unwrap(),
+        SupervisedLoss::MeanSquaredError,
+    );
+    assert_eq!(problem.loss(&mut nn), 0.0);
+    assert_eq!(problem.evaluate(&mut nn), 1.0);
+    assert_eq!(problem.validation_loss(&mut nn), None);
+    let problem = SupervisedProblem::new(dataset.clone(), SupervisedLoss::MeanSquaredError);
+    assert!(problem.evaluate(&mut nn) < 1.0);
+
+    // both sets of a split keep at least one sample
+    let mut rng = rand::thread_rng();
+    let (training, validation) = dataset.split(0.99, &mut rng);
+    assert_eq!((training.len(), validation.len()), (1, 4));
+
+    let folds = SupervisedProblem::folds(&dataset, SupervisedLoss::MeanSquaredError, 3);
+    assert_eq!(folds.len(), 3);
+    let mut validated: Vec<(Vec<f32>, Vec<f32>)> = folds
+        .iter()
+        .flat_map(|fold| {
+            assert_eq!(fold.training().len() + fold.validation().unwrap().len(), 5);
+            fold.validation().unwrap().samples().to_vec()
+        })
+        .collect();
+    validated.sort_by(|a, b| a.0[0].total_cmp(&b.0[0]));
+    assert_eq!(validated, dataset.samples());
+
+    // the binary cross-entropy prefers confident right answers
+    let mut gradient = [0.0];
+    let right = SupervisedLoss::CrossEntropy.loss(&[0.9], &[1.0], &mut gradient);
+    assert!(gradient[0] < 0.0);
+    let wrong = SupervisedLoss::CrossEntropy.loss(&[0.0], &[1.0], &mut gradient);
+    assert!(right < 0.2 && wrong > 10.0 && wrong.is_finite());
+}
+
+#[test]
+#[should_panic]
+pub fn split_single_sample() {
+    use crate::problems::supervised::Dataset;
+    let dataset = Dataset::new(vec![(vec![0.0], vec![0.0])]).unwrap();
+    dataset.split(0.5, &mut rand::thread_rng());
+}
+
+#[test]
+pub fn csv_options() {
+    use crate::problems::supervised::{Column, CsvOptions, Dataset};
+    use crate::{InputScaling, NeuralNetwork};
+    let csv = "id,a,b,label\n0,1,10,2\n1,2,10,1\n2,3,10,2\n3,5,10,10\n";
+    let options = CsvOptions {
+        inputs: vec!["a".into(), Column::Index(2)],
+        targets: vec![Column::Name("label".to_string())],
+        one_hot: true,
+        scaling: Some(InputScaling::z_score),
+        ..CsvOptions::default()
+    };
+    let dataset = Dataset::from_csv_reader(csv.as_bytes(), &options).unwrap();
+    // numeric classes are sorted by their value
+    assert_eq!(dataset.classes(), ["1", "2", "10"]);
+    assert_eq!(dataset.targets(), 3);
+    assert_eq!(dataset.samples()[3].1, vec![0.0, 0.0, 1.0]);
+    assert_eq!(dataset.class(&[0.1, 0.7, 0.2]), Some("2"));
+    // the inputs have the mean 0 and the standard deviation 1, a constant input is only shifted
+    let mean: f32 = dataset.samples().iter().map(|(i, _)| i[0]).sum::<f32>() / 4.0;
+    let variance: f32 = dataset
+        .samples()
+        .iter()
+        .map(|(i, _)| i[0] * i[0])
+        .sum::<f32>()
+        / 4.0;
+    assert!(mean.abs() < 1e-6 && (variance - 1.0).abs() < 1e-5);
+    assert!(dataset.samples().iter().all(|(i, _)| i[1] == 0.0));
+    let scaling = dataset.input_scaling();
+    assert_eq!(scaling.scale(&[2.75, 11.0]), vec![0.0, 1.0]);
+    // a network with the scaling of the dataset computes the unscaled inputs
+    let mut nn = NeuralNetwork::with_size(2, 1);
+    let expected = nn.compute(&dataset.samples()[1].0);
+    nn.set_input_scaling(scaling.clone());
+    assert_eq!(nn.compute([2.0, 10.0]), expected);
+
+    // shuffling with a seed is reproducible and keeps the samples
+    let options = CsvOptions {
+        targets: vec![Column::Last(0), Column::Index(0)],
+        scaling: Some(InputScaling::min_max),
+        shuffle: Some(3),
+        ..CsvOptions::default()
+    };
+    let shuffled = Dataset::from_csv_reader(csv.as_bytes(), &options).unwrap();
+    assert_eq!(
+        shuffled,
+        Dataset::from_csv_reader(csv.as_bytes(), &options).unwrap()
+    );
+    let mut samples = shuffled.samples().to_vec();
+    samples.sort_by(|a, b| a.1[1].total_cmp(&b.1[1]));
+    assert_eq!(samples[1], (vec![0.25, 0.0], vec![1.0, 1.0]));
+
+    let missing = CsvOptions {
+        targets: vec!["missing".into()],
+        ..CsvOptions::default()
+    };
+    assert!(Dataset::from_csv_reader(csv.as_bytes(), &missing).is_err());
+    let two_labels = CsvOptions {
+        one_hot: true,
+        ..CsvOptions::with_targets(2)
+    };
+    assert!(Dataset::from_csv_reader(csv.as_bytes(), &two_labels).is_err());
+    // a header of numbers has to be declared
+    let header = CsvOptions {
+        header: Some(true),
+        ..CsvOptions::default()
+    };
+    assert_eq!(
+        Dataset::from_csv_reader("0,1\n2,3\n".as_bytes(), &header)
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[test]
+pub fn early_stopping() {
+    use crate::problems::supervised::{Dataset, EarlyStopping, SupervisedLoss, SupervisedProblem};
+    use crate::{Config, Direction, Solver, StopReason, TrainOptions};
+    use rand::SeedableRng;
+    // noisy labels, so the training fitness keeps improving while the validation one does not
+    let samples = (0..16)
+        .map(|i| (vec![i as f32 / 16.0], vec![(i * 7 % 3) as f32 / 2.0]))
+        .collect();
+    let dataset = Dataset::new(samples).unwrap();
+    let mut rng = rand_pcg::Pcg64::seed_from_u64(5);
+    let loss = SupervisedLoss::MeanSquaredError;
+    let problem = SupervisedProblem::with_validation(dataset, loss, 0.25, &mut rng);
+    let options = TrainOptions {
+        max_generations: Some(200),
+        ..TrainOptions::default()
+    };
+    let config = Config::builder().generation_size(30).seed(3).build();
+
+    let mut solver = Solver::with_config(1, 1, config);
+    let mut stopping = EarlyStopping::new(&problem, 3);
+    let report = solver.train_with_observer(&problem, &options, &mut stopping);
+    assert_eq!(report.stop_reason, StopReason::Observer);
+    assert!(stopping.is_stopped());
+    let history = stopping.validation_history();
+    assert_eq!(history.len(), report.history.len());
+    let best = stopping.best_validation_fitness().unwrap();
+    assert!(history.iter().all(|&fitness| fitness <= best));
+    assert!(history[history.len() - 3..]
+        .iter()
+        .all(|&fitness| fitness < best));
+    let mut champion = stopping.best().unwrap().clone();
+    assert_eq!(problem.validation_fitness(&mut champion), Some(best));
+
+    // the report of the same training returns the network with the best validation fitness
+    let mut solver = Solver::with_config(1, 1, config);
+    let report = problem.train_with_early_stopping(&mut solver, &options, 3);
+    assert_eq!(report.champion, champion);
+    assert_eq!(Some(report.champion_id), stopping.best_id());
+
+    // when minimizing, a lower validation fitness is an improvement
+    let config = Config::builder()
+        .generation_size(30)
+        .seed(3)
+        .direction(Direction::Minimize)
+        .build();
+    let mut solver = Solver::with_config(1, 1, config);
+    let mut stopping = EarlyStopping::new(&problem, 3);
+    solver.train_with_observer(&problem, &options, &mut stopping);
+    let history = stopping.validation_history();
+    let best = stopping.best_validation_fitness().unwrap();
+    assert!(history.iter().all(|&fitness| fitness >= best));
+}
+
+#[test]
+#[should_panic(expected = "patience of at least 1")]
+pub fn early_stopping_without_patience() {
+    use crate::problems::supervised::{Dataset, EarlyStopping, SupervisedLoss, SupervisedProblem};
+    use rand::SeedableRng;
+    let samples = (0..8).map(|i| (vec![i as f32 / 8.0], vec![0.5])).collect();
+    let dataset = Dataset::new(samples).unwrap();
+    let mut rng = rand_pcg::Pcg64::seed_from_u64(0);
+    let loss = SupervisedLoss::MeanSquaredError;
+    let problem = SupervisedProblem::with_validation(dataset, loss, 0.25, &mut rng);
+    EarlyStopping::new(&problem, 0);
+}
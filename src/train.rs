@@ -0,0 +1,289 @@
+use crate::config::Direction;
+use crate::error::Error;
+use crate::lineage::GenomeId;
+use crate::neuralnetwork::NeuralNetwork;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "std-fs")]
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Options for `Solver::train`, they determine when the training stops.
+///
+/// The training stops as soon as one of the criteria is met, criteria which are `None` are
+/// ignored. If every criterion is `None` the training never stops.
+///
+/// # Example
+/// ```rust
+/// use neaters::TrainOptions;
+/// use std::time::Duration;
+/// let options = TrainOptions {
+///     target_fitness: Some(3.9),
+///     time_budget: Some(Duration::from_secs(60)),
+///     ..TrainOptions::default()
+/// };
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrainOptions {
+    /// The maximum number of generations which are evaluated.
+    pub max_generations: Option<usize>,
+    /// The training stops when a network reaches at least this fitness, or at most this fitness
+    /// if the config minimizes it (see `Direction`).
+    pub target_fitness: Option<f32>,
+    /// The training stops after the generation which exceeded this wall-clock time.
+    pub time_budget: Option<Duration>,
+    /// The training stops if the best fitness has not improved for this many generations.
+    pub stagnation_limit: Option<usize>,
+    /// The number of times every network is evaluated per generation, the fitness of the network
+    /// is the aggregation of the results of the trials. More than one trial is useful for
+    /// stochastic evaluators, where a single lucky result could make a bad network survive. It
+    /// has to be at least **1**.
+    pub trials_per_network: usize,
+    /// How the results of the trials of a network are combined into its fitness.
+    pub trial_aggregation: TrialAggregation,
+    /// Whether the elites, which are copied unchanged into the next generation, are evaluated
+    /// again. Otherwise they keep the fitness they had in the previous generation, which saves
+    /// evaluations if the evaluator is deterministic.
+    pub reevaluate_elites: bool,
+    /// The training stops after the current generation once the handle has been cancelled, e.g.
+    /// by another thread when a server shuts down.
+    pub cancellation: Option<CancellationHandle>,
+    /// The solver is saved at this path when the training stops, so an interrupted training can
+    /// be resumed with `Solver::load_from`.
+    #[cfg(feature = "std-fs")]
+    pub checkpoint: Option<PathBuf>,
+}
+
+impl Default for TrainOptions {
+    fn default() -> Self {
+        TrainOptions {
+            max_generations: Some(100),
+            target_fitness: None,
+            time_budget: None,
+            stagnation_limit: None,
+            trials_per_network: 1,
+            trial_aggregation: TrialAggregation::Mean,
+            reevaluate_elites: true,
+            cancellation: None,
+            #[cfg(feature = "std-fs")]
+            checkpoint: None,
+        }
+    }
+}
+
+/// How the fitness of the repeated trials of a network is combined, see
+/// `TrainOptions::trials_per_network`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TrialAggregation {
+    /// The average fitness of the trials.
+    #[default]
+    Mean,
+    /// The median fitness of the trials, so a single very lucky or unlucky trial does not change
+    /// the fitness. For an even number of trials it is the average of the two middle values.
+    Median,
+    /// The lowest fitness of the trials, which only rewards networks which are good in every
+    /// trial if the fitness is maximized.
+    Min,
+}
+
+impl TrialAggregation {
+    /// Combines the fitness of every trial, the fitness is sorted in place. There has to be at
+    /// least one trial.
+    pub(crate) fn aggregate(&self, fitness: &mut [f32]) -> f32 {
+        debug_assert!(
+            !fitness.is_empty(),
+            "every network needs at least one trial"
+        );
+        fitness.sort_by(|a, b| a.total_cmp(b));
+        match (self, fitness.len()) {
+            (TrialAggregation::Mean, n) => fitness.iter().sum::<f32>() / n as f32,
+            (TrialAggregation::Median, n) if n % 2 == 0 => {
+                (fitness[n / 2 - 1] + fitness[n / 2]) / 2.0
+            }
+            (TrialAggregation::Median, n) => fitness[n / 2],
+            (TrialAggregation::Min, _) => fitness[0],
+        }
+    }
+}
+
+/// A handle which stops a running training from another thread, see
+/// `TrainOptions::cancellation`. Clones of a handle share their state, so cancelling one of them
+/// cancels all of them.
+///
+/// # Example
+/// ```rust
+/// use neaters::{CancellationHandle, NeuralNetwork, Solver, StopReason, TrainOptions};
+/// let handle = CancellationHandle::new();
+/// let options = TrainOptions {
+///     max_generations: None,
+///     cancellation: Some(handle.clone()),
+///     ..TrainOptions::default()
+/// };
+/// let worker = std::thread::spawn(move || {
+///     let mut solver = Solver::with_size(1, 1, 10);
+///     solver.train(&|nn: &mut NeuralNetwork| nn.compute([0.5])[0], &options)
+/// });
+/// handle.cancel();
+/// let report = worker.join().unwrap();
+/// assert_eq!(report.stop_reason, StopReason::Cancelled);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancellationHandle {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationHandle {
+    /// Constructor for a handle which has not been cancelled yet.
+    pub fn new() -> Self {
+        CancellationHandle::default()
+    }
+
+    /// Cancels the training, it stops after the generation which is currently evaluated.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns whether the handle has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// Handles are equal if they are clones of each other.
+impl PartialEq for CancellationHandle {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.cancelled, &other.cancelled)
+    }
+}
+
+/// The reason why the training has been stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StopReason {
+    /// The maximum number of generations has been evaluated.
+    MaxGenerations,
+    /// A network reached the target fitness.
+    TargetFitness,
+    /// The time budget has been used up.
+    TimeBudget,
+    /// The best fitness did not improve for too many generations.
+    Stagnation,
+    /// The observer stopped the training, see `TrainingObserver::on_generation_end`.
+    Observer,
+    /// The cancellation handle of the options has been cancelled.
+    Cancelled,
+}
+
+/// Statistics about one evaluated generation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GenerationStats {
+    /// The number of the generation, starting at **0**.
+    pub generation: usize,
+    /// The fitness of the best network of the generation, the lowest one if the fitness is
+    /// minimized.
+    pub best_fitness: f32,
+    /// The average fitness of all networks of the generation.
+    pub average_fitness: f32,
+    /// The median fitness of all networks of the generation.
+    pub median_fitness: f32,
+    /// The standard deviation of the fitness of all networks of the generation.
+    pub fitness_std_dev: f32,
+    /// The number of species the networks are grouped into.
+    pub species_count: usize,
+    /// The number of networks in every species.
+    pub species_sizes: Vec<usize>,
+    /// The average number of nodes of the networks, including the input and output nodes.
+    pub average_nodes: f32,
+    /// The average number of enabled edges of the networks.
+    pub average_edges: f32,
+    /// The genetic diversity of the networks, see `Solver::diversity_metrics`.
+    pub diversity: DiversityMetrics,
+}
+
+/// Metrics of the genetic diversity of a population, created with `Solver::diversity_metrics`.
+///
+/// A population which converged prematurely has a small mean distance, an entropy close to
+/// **0.0** and only a few unique topologies.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct DiversityMetrics {
+    /// The mean compatibility distance between two networks, as used for the speciation.
+    pub mean_distance: f32,
+    /// The Shannon entropy in bits of the distribution of the topologies, it is **0.0** if all
+    /// networks share their topology and `log2(n)` if all `n` networks have different ones.
+    pub genotypic_entropy: f32,
+    /// The number of different topologies, networks have the same topology if they have the same
+    /// enabled edges regardless of their weights.
+    pub unique_topologies: usize,
+}
+
+impl GenerationStats {
+    /// Computes the statistics of the given networks, the sizes of their species and their
+    /// diversity. The direction decides which fitness is the best one.
+    pub(crate) fn compute(
+        generation: usize,
+        networks: &[NeuralNetwork],
+        species_sizes: Vec<usize>,
+        diversity: DiversityMetrics,
+        direction: Direction,
+    ) -> Self {
+        let count = networks.len().max(1) as f32;
+        let mut fitness: Vec<f32> = networks.iter().map(|nn| nn.fitness()).collect();
+        fitness.sort_by(|a, b| a.total_cmp(b));
+        let average_fitness = fitness.iter().sum::<f32>() / count;
+        let median_fitness = match fitness.len() {
+            0 => f32::MIN,
+            n if n % 2 == 0 => (fitness[n / 2 - 1] + fitness[n / 2]) / 2.0,
+            n => fitness[n / 2],
+        };
+        let variance = fitness
+            .iter()
+            .map(|f| (f - average_fitness) * (f - average_fitness))
+            .sum::<f32>()
+            / count;
+        let nodes: usize = networks.iter().map(|nn| nn.nodes.len()).sum();
+        let edges: usize = networks
+            .iter()
+            .map(|nn| nn.edges.iter().filter(|edge| edge.enabled).count())
+            .sum();
+        GenerationStats {
+            generation,
+            best_fitness: fitness
+                .iter()
+                .copied()
+                .reduce(|best, f| {
+                    if direction.is_better(f, best) {
+                        f
+                    } else {
+                        best
+                    }
+                })
+                .unwrap_or(f32::MIN),
+            average_fitness,
+            median_fitness,
+            fitness_std_dev: variance.sqrt(),
+            species_count: species_sizes.len(),
+            species_sizes,
+            average_nodes: nodes as f32 / count,
+            average_edges: edges as f32 / count,
+            diversity,
+        }
+    }
+}
+
+/// The result of `Solver::train`.
+#[derive(Debug)]
+pub struct TrainReport {
+    /// The statistics of every evaluated generation in order.
+    pub history: Vec<GenerationStats>,
+    /// The reason why the training has been stopped.
+    pub stop_reason: StopReason,
+    /// The best network which has been found during the training.
+    pub champion: NeuralNetwork,
+    /// The generation and the id of the champion, e.g. to look up its ancestry in the lineage of
+    /// the solver, see `Lineage::ancestry_of`.
+    pub champion_id: GenomeId,
+    /// The wall-clock time the training took.
+    pub elapsed: Duration,
+    /// The error if the checkpoint of the options could not be saved.
+    pub checkpoint_error: Option<Error>,
+}
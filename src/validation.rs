@@ -0,0 +1,377 @@
+use crate::error::Error;
+use crate::float::Float;
+use crate::neuralnetwork::{NeuralNetwork, NodeType};
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::fmt;
+
+/// A structural problem of a genome found by `NeuralNetwork::validate`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GenomeProblem {
+    /// Multiple nodes have the given id.
+    DuplicateNode(usize),
+    /// The given number of input nodes (including the bias node) is not stored at the front
+    /// of the node list.
+    MisplacedInputs(usize),
+    /// The number of output nodes does not match the size of the network.
+    OutputCount {
+        /// The number of outputs according to the size of the network.
+        expected: usize,
+        /// The number of output nodes.
+        found: usize,
+    },
+    /// The edge with the given innovation number references a node which does not exist.
+    MissingNode {
+        /// The innovation number of the edge.
+        innovation: usize,
+        /// The id of the source node of the edge.
+        from: usize,
+        /// The id of the destination node of the edge.
+        to: usize,
+    },
+    /// The edge with the given innovation number leads into an input node.
+    ConnectionIntoInput(usize),
+    /// There are multiple edges between the given nodes.
+    DuplicateConnection {
+        /// The id of the source node of the edges.
+        from: usize,
+        /// The id of the destination node of the edges.
+        to: usize,
+    },
+    /// The edges are not sorted by their innovation number.
+    UnsortedEdges,
+    /// The edge with the given innovation number closes a cycle, but it is not a recurrent edge.
+    Cycle(usize),
+    /// The output node with the given id cannot be reached from any input over enabled edges, so
+    /// its value does not depend on the inputs.
+    DisconnectedOutput(usize),
+    /// The input scaling does not have statistics for exactly the inputs of the network, see
+    /// `NeuralNetwork::set_input_scaling`.
+    InputScalingLength {
+        /// The number of inputs of the network.
+        expected: usize,
+        /// The number of inputs the scaling has statistics for.
+        found: usize,
+    },
+}
+
+impl GenomeProblem {
+    /// Returns whether the network cannot be computed with this problem, these problems are
+    /// rejected when loading a network.
+    fn prevents_computation(&self) -> bool {
+        matches!(
+            self,
+            GenomeProblem::DuplicateNode(_)
+                | GenomeProblem::MisplacedInputs(_)
+                | GenomeProblem::OutputCount { .. }
+                | GenomeProblem::MissingNode { .. }
+        )
+    }
+}
+
+impl fmt::Display for GenomeProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GenomeProblem::DuplicateNode(id) => write!(f, "duplicate node id {}", id),
+            GenomeProblem::MisplacedInputs(inputs) => write!(
+                f,
+                "expected {} input nodes at the front of the node list",
+                inputs
+            ),
+            GenomeProblem::OutputCount { expected, found } => {
+                write!(f, "expected {} output nodes but found {}", expected, found)
+            }
+            GenomeProblem::MissingNode {
+                innovation,
+                from,
+                to,
+            } => write!(
+                f,
+                "edge {} connects missing nodes {} -> {}",
+                innovation, from, to
+            ),
+            GenomeProblem::ConnectionIntoInput(innovation) => {
+                write!(f, "edge {} leads into an input node", innovation)
+            }
+            GenomeProblem::DuplicateConnection { from, to } => {
+                write!(f, "multiple edges connect the nodes {} -> {}", from, to)
+            }
+            GenomeProblem::UnsortedEdges => write!(f, "the edges are not sorted by innovation"),
+            GenomeProblem::Cycle(innovation) => {
+                write!(
+                    f,
+                    "edge {} forms a cycle without being recurrent",
+                    innovation
+                )
+            }
+            GenomeProblem::DisconnectedOutput(id) => {
+                write!(f, "output node {} cannot be reached from the inputs", id)
+            }
+            GenomeProblem::InputScalingLength { expected, found } => write!(
+                f,
+                "the input scaling has statistics for {} inputs but the network has {}",
+                found, expected
+            ),
+        }
+    }
+}
+
+impl<F: Float> NeuralNetwork<F> {
+    /// Returns every structural problem of the genome, an empty list means the network is valid.
+    ///
+    /// Networks created by this crate never have problems, except for disconnected outputs,
+    /// which are normal for networks starting with `InitialTopology::Unconnected`. Genomes created
+    /// by other tools, edited by hand or stored by older versions should be checked before they
+    /// are trained, see `repair` to fix them.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::{GenomeProblem, NeuralNetwork};
+    /// let mut nn = NeuralNetwork::with_size(2, 1);
+    /// assert!(nn.validate().is_empty());
+    /// nn.edit().set_enabled(0, 3, false).unwrap();
+    /// nn.edit().set_enabled(1, 3, false).unwrap();
+    /// nn.edit().set_enabled(2, 3, false).unwrap();
+    /// assert_eq!(nn.validate(), vec![GenomeProblem::DisconnectedOutput(3)]);
+    /// ```
+    pub fn validate(&self) -> Vec<GenomeProblem> {
+        let mut problems: Vec<GenomeProblem> = Vec::new();
+        let mut types: FxHashMap<usize, NodeType> = FxHashMap::default();
+        for node in self.nodes.iter() {
+            if types.insert(node.id, node.node_type).is_some() {
+                problems.push(GenomeProblem::DuplicateNode(node.id));
+            }
+        }
+        // the first node is the bias node, or the first input node in older genomes
+        let inputs = self.input_node_count() + 1;
+        if self.nodes.len() < inputs
+            || !self.nodes[0].node_type.is_input_or_bias()
+            || self.nodes[1..inputs]
+                .iter()
+                .any(|node| node.node_type != NodeType::Input)
+            || self.nodes[inputs..]
+                .iter()
+                .any(|node| node.node_type.is_input_or_bias())
+        {
+            problems.push(GenomeProblem::MisplacedInputs(inputs));
+        }
+        let outputs: Vec<usize> = self
+            .nodes
+            .iter()
+            .filter(|node| node.node_type == NodeType::Output)
+            .map(|node| node.id)
+            .collect();
+        if outputs.len() != self.size.1 {
+            problems.push(GenomeProblem::OutputCount {
+                expected: self.size.1,
+                found: outputs.len(),
+            });
+        }
+        let mut connections: FxHashSet<(usize, usize)> = FxHashSet::default();
+        for edge in self.edges.iter() {
+            if !types.contains_key(&edge.from) || !types.contains_key(&edge.to) {
+                problems.push(GenomeProblem::MissingNode {
+                    innovation: edge.innovation,
+                    from: edge.from,
+                    to: edge.to,
+                });
+            } else if types[&edge.to].is_input_or_bias() {
+                problems.push(GenomeProblem::ConnectionIntoInput(edge.innovation));
+            }
+            if !connections.insert((edge.from, edge.to)) {
+                problems.push(GenomeProblem::DuplicateConnection {
+                    from: edge.from,
+                    to: edge.to,
+                });
+            }
+        }
+        if self
+            .edges
+            .windows(2)
+            .any(|pair| pair[0].innovation > pair[1].innovation)
+        {
+            problems.push(GenomeProblem::UnsortedEdges);
+        }
+        for innovation in self.cycle_edges() {
+            problems.push(GenomeProblem::Cycle(innovation));
+        }
+        let inputs: Vec<usize> = self
+            .nodes
+            .iter()
+            .filter(|node| node.node_type.is_input_or_bias())
+            .map(|node| node.id)
+            .collect();
+        let reachable = self.reachable(&inputs, false);
+        for id in outputs {
+            if !reachable.contains(&id) {
+                problems.push(GenomeProblem::DisconnectedOutput(id));
+            }
+        }
+        if let Some(found) = self.input_scaling.inputs() {
+            if found != self.size.0 {
+                problems.push(GenomeProblem::InputScalingLength {
+                    expected: self.size.0,
+                    found,
+                });
+            }
+        }
+        problems
+    }
+
+    /// Fixes the problems of the genome which can be fixed and returns the remaining ones, see
+    /// `validate`.
+    ///
+    /// Duplicate nodes and edges are removed, keeping the first one, the bias node and the input
+    /// nodes are moved to the front, edges referencing missing nodes or leading into inputs are
+    /// removed and the edges are sorted. The first input node of a genome stored by an earlier
+    /// version becomes the bias node. Edges closing a cycle become recurrent edges in a recurrent
+    /// network and are removed otherwise. A wrong number of outputs, disconnected outputs and an
+    /// input scaling of the wrong length cannot be fixed.
+    ///
+    /// # Example:
+    /// ```
+    /// use neaters::NeuralNetwork;
+    /// let json = r#"{"nodes":[{"id":1,"node_type":"Input"},{"id":0,"node_type":"Input"},
+    ///     {"id":2,"node_type":"Output"},{"id":2,"node_type":"Output"}],
+    ///     "edges":[{"from":1,"to":2,"weight":0.5,"enabled":true,"innovation":1},
+    ///     {"from":0,"to":2,"weight":1.0,"enabled":true,"innovation":0},
+    ///     {"from":2,"to":7,"weight":1.0,"enabled":true,"innovation":2}],
+    ///     "id":0,"size":[1,1],"fitness":0.0}"#;
+    /// # #[cfg(feature = "serde_json")]
+    /// # {
+    /// let mut nn: NeuralNetwork = serde_json::from_str(json).unwrap();
+    /// // the duplicated output node, which also counts as a second output, the edge to the missing
+    /// // node 7 and the unsorted edges
+    /// assert_eq!(nn.validate().len(), 4);
+    /// assert!(nn.repair().is_empty());
+    /// assert_eq!(nn.compute([0.5]).len(), 1);
+    /// # }
+    /// ```
+    pub fn repair(&mut self) -> Vec<GenomeProblem> {
+        let mut ids: FxHashSet<usize> = FxHashSet::default();
+        self.nodes.retain(|node| ids.insert(node.id));
+        // a stable sort keeps the order of the inputs and of the other nodes
+        self.nodes.sort_by_key(|node| match node.node_type {
+            NodeType::Bias => 0,
+            NodeType::Input => 1,
+            NodeType::Hidden | NodeType::Output => 2,
+        });
+        self.mark_bias_node();
+        let inputs: FxHashSet<usize> = self
+            .nodes
+            .iter()
+            .filter(|node| node.node_type.is_input_or_bias())
+            .map(|node| node.id)
+            .collect();
+        let mut connections: FxHashSet<(usize, usize)> = FxHashSet::default();
+        self.edges.retain(|edge| {
+            ids.contains(&edge.from)
+                && ids.contains(&edge.to)
+                && !inputs.contains(&edge.to)
+                && connections.insert((edge.from, edge.to))
+        });
+        self.edges.sort_by_key(|edge| edge.innovation);
+        let cycles: FxHashSet<usize> = self.cycle_edges().into_iter().collect();
+        if self.recurrent {
+            for edge in self.edges.iter_mut() {
+                if cycles.contains(&edge.innovation) {
+                    edge.recurrent = true;
+                }
+            }
+        } else {
+            self.edges.retain(|edge| !cycles.contains(&edge.innovation));
+        }
+        self.genome_changed();
+        self.validate()
+    }
+
+    /// Returns the ids of all nodes which can be reached from the given nodes over enabled edges,
+    /// including the given nodes themselves. If `backwards` is set, the edges are followed in the
+    /// opposite direction.
+    pub(crate) fn reachable(&self, start: &[usize], backwards: bool) -> FxHashSet<usize> {
+        let mut visited: FxHashSet<usize> = start.iter().copied().collect();
+        let mut stack: Vec<usize> = start.to_vec();
+        while let Some(node) = stack.pop() {
+            for edge in self.edges.iter().filter(|edge| edge.enabled) {
+                let (from, to) = if backwards {
+                    (edge.to, edge.from)
+                } else {
+                    (edge.from, edge.to)
+                };
+                if from == node && visited.insert(to) {
+                    stack.push(to);
+                }
+            }
+        }
+        visited
+    }
+
+    /// Marks the first node as the bias node if the genome has been stored by an earlier version
+    /// of this crate, where the bias node has been the first input node.
+    pub(crate) fn mark_bias_node(&mut self) {
+        let inputs = self.input_node_count() + 1;
+        if self.nodes.len() >= inputs
+            && self.nodes[..inputs]
+                .iter()
+                .all(|node| node.node_type == NodeType::Input)
+        {
+            self.nodes[0].node_type = NodeType::Bias;
+            self.genome_changed();
+        }
+    }
+
+    /// Checks the invariants the phenotype relies on: unique node ids, the input nodes being
+    /// stored at the front of the node list, the right number of outputs and every edge
+    /// connecting two existing nodes.
+    pub(crate) fn check_structure(&self) -> Result<(), Error> {
+        match self
+            .validate()
+            .into_iter()
+            .find(GenomeProblem::prevents_computation)
+        {
+            Some(problem) => Err(Error::InvalidGenome(problem.to_string())),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the innovation numbers of the non-recurrent edges which close a cycle, disabled
+    /// edges are taken into account as well like in `mutate_add_connection`. Removing these
+    /// edges makes the network acyclic.
+    fn cycle_edges(&self) -> Vec<usize> {
+        let mut outgoing: FxHashMap<usize, Vec<(usize, usize)>> = FxHashMap::default();
+        for edge in self.edges.iter().filter(|edge| !edge.recurrent) {
+            outgoing
+                .entry(edge.from)
+                .or_default()
+                .push((edge.to, edge.innovation));
+        }
+        // depth first search, an edge to a node which is still on the stack closes a cycle
+        let mut on_stack: FxHashSet<usize> = FxHashSet::default();
+        let mut done: FxHashSet<usize> = FxHashSet::default();
+        let mut cycles: Vec<usize> = Vec::new();
+        for start in self.nodes.iter().map(|node| node.id) {
+            if done.contains(&start) {
+                continue;
+            }
+            let mut stack: Vec<(usize, usize)> = vec![(start, 0)];
+            on_stack.insert(start);
+            while let Some((node, next)) = stack.last_mut() {
+                let edges = outgoing.get(node).map_or(&[][..], |edges| &edges[..]);
+                if let Some(&(to, innovation)) = edges.get(*next) {
+                    *next += 1;
+                    if on_stack.contains(&to) {
+                        cycles.push(innovation);
+                    } else if !done.contains(&to) {
+                        on_stack.insert(to);
+                        stack.push((to, 0));
+                    }
+                } else {
+                    on_stack.remove(node);
+                    done.insert(*node);
+                    stack.pop();
+                }
+            }
+        }
+        cycles.sort_unstable();
+        cycles
+    }
+}
@@ -0,0 +1,133 @@
+//! JavaScript bindings for networks and solvers, available with the `wasm` feature.
+//!
+//! Build the crate for the `wasm32-unknown-unknown` target without the default features, e.g. with
+//! `wasm-pack build --no-default-features --features wasm`. Networks and solvers are exchanged
+//! with JavaScript as bytes in the same format as `as_byte_representation`.
+use crate::neuralnetwork::NeuralNetwork;
+use crate::solver::Solver;
+use wasm_bindgen::prelude::*;
+
+/// A neural network which can be used from JavaScript.
+#[wasm_bindgen]
+#[derive(Debug)]
+pub struct WasmNetwork {
+    nn: NeuralNetwork,
+}
+
+#[wasm_bindgen]
+impl WasmNetwork {
+    /// Decodes a network from the bytes returned by `toBytes` or `NeuralNetwork::save_as`.
+    #[wasm_bindgen(js_name = fromBytes)]
+    pub fn from_bytes(bytes: &[u8]) -> Result<WasmNetwork, JsError> {
+        let nn = NeuralNetwork::load_from_reader(bytes)?;
+        Ok(WasmNetwork { nn })
+    }
+
+    /// Encodes the network as bytes, which can be stored and decoded again with `fromBytes`.
+    #[wasm_bindgen(js_name = toBytes)]
+    pub fn to_bytes(&self) -> Result<Vec<u8>, JsError> {
+        Ok(self.nn.as_byte_representation()?)
+    }
+
+    /// Computes the outputs of the network for the given inputs.
+    pub fn compute(&mut self, inputs: &[f32]) -> Vec<f32> {
+        self.nn.compute(inputs)
+    }
+
+    /// The fitness of the network.
+    #[wasm_bindgen(getter)]
+    pub fn fitness(&self) -> f32 {
+        self.nn.fitness
+    }
+}
+
+impl From<NeuralNetwork> for WasmNetwork {
+    fn from(nn: NeuralNetwork) -> Self {
+        WasmNetwork { nn }
+    }
+}
+
+/// A solver which can be trained from JavaScript, the networks of the current generation are
+/// addressed by their index.
+///
+/// # Example:
+/// ```js
+/// const solver = new WasmSolver(2, 1, 100);
+/// for (let generation = 0; generation < 50; generation++) {
+///     for (let i = 0; i < solver.networkCount; i++) {
+///         const output = solver.compute(i, new Float32Array([0.5, 1.0]));
+///         solver.setFitness(i, 1.0 - Math.abs(output[0] - 0.75));
+///     }
+///     solver.newGeneration();
+/// }
+/// const best = solver.best();
+/// ```
+#[wasm_bindgen]
+#[derive(Debug)]
+pub struct WasmSolver {
+    solver: Solver,
+}
+
+#[wasm_bindgen]
+impl WasmSolver {
+    /// Constructor for a solver with the given number of inputs, outputs and networks per
+    /// generation, using the default configuration.
+    #[wasm_bindgen(constructor)]
+    pub fn new(inputs: usize, outputs: usize, generation_size: usize) -> WasmSolver {
+        WasmSolver {
+            solver: Solver::with_size(inputs, outputs, generation_size),
+        }
+    }
+
+    /// Decodes a solver from the bytes returned by `toBytes` or `Solver::save_as`.
+    #[wasm_bindgen(js_name = fromBytes)]
+    pub fn from_bytes(bytes: &[u8]) -> Result<WasmSolver, JsError> {
+        let solver = Solver::load_from_reader(bytes)?;
+        Ok(WasmSolver { solver })
+    }
+
+    /// Encodes the solver as bytes, so the training can be continued later with `fromBytes`.
+    #[wasm_bindgen(js_name = toBytes)]
+    pub fn to_bytes(&self) -> Result<Vec<u8>, JsError> {
+        Ok(self.solver.as_byte_representation()?)
+    }
+
+    /// The number of networks in the current generation.
+    #[wasm_bindgen(getter, js_name = networkCount)]
+    pub fn network_count(&mut self) -> usize {
+        self.solver.neural_nets().len()
+    }
+
+    /// Computes the outputs of the network with the given index for the given inputs.
+    pub fn compute(&mut self, index: usize, inputs: &[f32]) -> Result<Vec<f32>, JsError> {
+        Ok(self.network(index)?.compute(inputs))
+    }
+
+    /// Sets the fitness of the network with the given index.
+    #[wasm_bindgen(js_name = setFitness)]
+    pub fn set_fitness(&mut self, index: usize, fitness: f32) -> Result<(), JsError> {
+        self.network(index)?.set_fitness(fitness);
+        Ok(())
+    }
+
+    /// Creates the next generation, the fitness of every network should have been set.
+    #[wasm_bindgen(js_name = newGeneration)]
+    pub fn new_generation(&mut self) {
+        self.solver.new_generation();
+    }
+
+    /// Returns a copy of the best network of the current generation.
+    pub fn best(&mut self) -> WasmNetwork {
+        self.solver.best_network().into()
+    }
+}
+
+impl WasmSolver {
+    /// Returns the network with the given index or an error if there is no such network.
+    fn network(&mut self, index: usize) -> Result<&mut NeuralNetwork, JsError> {
+        self.solver
+            .neural_nets()
+            .nth(index)
+            .ok_or_else(|| JsError::new(&format!("there is no network with the index {}", index)))
+    }
+}
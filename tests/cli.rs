@@ -0,0 +1,83 @@
+//! Runs the subcommands of the `neaters-cli` binary on saved networks.
+use neaters::NeuralNetwork;
+use std::process::{Command, Output};
+
+fn cli(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_neaters-cli"))
+        .args(args)
+        .output()
+        .expect("the binary can be run")
+}
+
+fn stdout(output: &Output) -> String {
+    assert!(output.status.success(), "{:?}", output);
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+#[test]
+fn inspect_dot_and_convert() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("network.nn");
+    let path = path.to_str().unwrap();
+    let nn = NeuralNetwork::with_size(3, 2);
+    nn.save_as(path).unwrap();
+
+    let summary = stdout(&cli(&["inspect", path]));
+    assert!(summary.contains("nodes: 3 input, 1 bias, 0 hidden, 2 output\n"));
+    assert!(summary.contains("edges: 8 enabled (0 recurrent), 0 disabled\n"));
+    assert!(summary.contains("valid: yes\n"));
+    assert_eq!(stdout(&cli(&["dot", path])), nn.to_dot());
+
+    let json = dir.path().join("network.json");
+    let json = json.to_str().unwrap();
+    stdout(&cli(&["convert", path, "--to", "json", "--output", json]));
+    assert_eq!(
+        NeuralNetwork::from_json(&std::fs::read_to_string(json).unwrap()).unwrap(),
+        nn
+    );
+    let binary = dir.path().join("converted.nn");
+    let binary = binary.to_str().unwrap();
+    stdout(&cli(&[
+        "convert", json, "--to", "binary", "--output", binary,
+    ]));
+    assert_eq!(NeuralNetwork::load_from(binary).unwrap(), nn);
+
+    assert!(!cli(&["inspect", "missing.nn"]).status.success());
+    assert!(!cli(&["convert", path, "--to", "yaml"]).status.success());
+    assert!(!cli(&["unknown"]).status.success());
+}
+
+#[test]
+fn train_with_config() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = dir.path().join("config.toml");
+    std::fs::write(&config, "generation_size = 30\nseed = 1\n").unwrap();
+    let champion = dir.path().join("champion.nn");
+    let log = dir.path().join("log.csv");
+    let output = cli(&[
+        "train",
+        "--problem",
+        "cartpole",
+        "--config",
+        config.to_str().unwrap(),
+        "--generations",
+        "3",
+        "--output",
+        champion.to_str().unwrap(),
+        "--log",
+        log.to_str().unwrap(),
+    ]);
+    assert!(stdout(&output).contains("stopped after"));
+    assert_eq!(NeuralNetwork::load_from(&champion).unwrap().size(), (4, 1));
+    assert!(std::fs::read_to_string(log).unwrap().lines().count() > 1);
+
+    std::fs::write(&config, "generation_size = \"many\"\n").unwrap();
+    let output = cli(&[
+        "train",
+        "--problem",
+        "xor",
+        "--config",
+        config.to_str().unwrap(),
+    ]);
+    assert!(!output.status.success());
+}
@@ -0,0 +1,150 @@
+//! Loads networks and solvers from fixture files written by earlier versions of this crate or
+//! containing fields of future versions, so stored files stay loadable across versions.
+//!
+//! The fixtures in `tests/fixtures` must never be regenerated: a failing test means that files
+//! written by users can no longer be loaded, which needs a migration in the decoding instead.
+use neaters::{Activation, Error, NeuralNetwork, Solver, TrainOptions};
+
+/// A network with a hidden node written by the first release of this crate, whose files have no
+/// header and only contain the bincode of the network.
+const NETWORK_V0: &[u8] = include_bytes!("fixtures/network-v0.bin");
+/// A network with hidden nodes stored with format version 1, where the payload is bincode.
+const NETWORK_V1: &[u8] = include_bytes!("fixtures/network-v1.bin");
+/// The same network stored with format version 2, where the payload is MessagePack.
+const NETWORK_V2: &[u8] = include_bytes!("fixtures/network-v2.bin");
+/// The same network stored with format version 3, whose header contains a checksum.
+const NETWORK_V3: &[u8] = include_bytes!("fixtures/network-v3.bin");
+/// The same network with additional fields of the network, its nodes and its edges, which this
+/// version does not know.
+const NETWORK_FUTURE_V2: &[u8] = include_bytes!("fixtures/network-future-v2.bin");
+/// A solver after three generations stored with format version 2.
+const SOLVER_V2: &[u8] = include_bytes!("fixtures/solver-v2.bin");
+
+/// Checks that the network is the one stored in the fixtures.
+fn assert_fixture_network(nn: &NeuralNetwork) {
+    assert_eq!(nn.size(), (2, 1));
+    // the first input node of earlier versions becomes the bias node
+    assert_eq!(nn.bias_node(), Some(nn.nodes()[0].id));
+    assert_eq!(nn.hidden_node_count(), 2);
+    assert_eq!(nn.edges().len(), 8);
+    assert_eq!(nn.fitness(), 204.9076);
+    let activations: Vec<Activation> = nn.nodes().iter().map(|node| node.activation).collect();
+    assert_eq!(
+        activations,
+        [
+            Activation::Sigmoid,
+            Activation::Sigmoid,
+            Activation::Sigmoid,
+            Activation::Identity,
+            Activation::Square,
+            Activation::Identity,
+        ]
+    );
+    let output = nn.clone().compute([0.5, -0.25])[0];
+    assert!((output + 83.06707).abs() < 1e-3, "output is {}", output);
+}
+
+#[test]
+fn loads_every_format_version() {
+    let v1: NeuralNetwork = NeuralNetwork::from_bytes(NETWORK_V1).unwrap();
+    assert_fixture_network(&v1);
+    let v2 = NeuralNetwork::from_bytes(NETWORK_V2).unwrap();
+    assert_eq!(v2, v1);
+    let v3 = NeuralNetwork::from_bytes(NETWORK_V3).unwrap();
+    assert_eq!(v3, v1);
+}
+
+#[test]
+fn loads_files_without_header() {
+    let nn: NeuralNetwork = NeuralNetwork::from_bytes(NETWORK_V0).unwrap();
+    assert_eq!(nn.size(), (2, 1));
+    assert_eq!(nn.id(), 7);
+    assert_eq!(nn.bias_node(), Some(0));
+    assert_eq!(nn.hidden_node_count(), 1);
+    assert_eq!(nn.enabled_edge_count(), 4);
+    assert_eq!(nn.fitness(), 12.5);
+    assert!(nn.validate().is_empty());
+    // the output computed by the first release
+    let output = nn.clone().compute([0.5, -0.25])[0];
+    assert!((output - 0.55107915).abs() < 1e-6, "output is {}", output);
+    // the migrated network is stored with the current format
+    let bytes = nn.as_byte_representation().unwrap();
+    assert_eq!(NeuralNetwork::from_bytes(&bytes).unwrap(), nn);
+    assert!(NeuralNetwork::<f64>::from_bytes(NETWORK_V0).is_err());
+}
+
+#[test]
+fn ignores_unknown_fields() {
+    let future: NeuralNetwork = NeuralNetwork::from_bytes(NETWORK_FUTURE_V2).unwrap();
+    assert_eq!(future, NeuralNetwork::from_bytes(NETWORK_V1).unwrap());
+}
+
+#[test]
+fn round_trips_the_current_format() {
+    let nn: NeuralNetwork = NeuralNetwork::from_bytes(NETWORK_V1).unwrap();
+    let bytes = nn.as_byte_representation().unwrap();
+    assert_eq!(NeuralNetwork::from_bytes(&bytes).unwrap(), nn);
+}
+
+#[test]
+fn continues_training_a_stored_solver() {
+    let mut solver = Solver::load_from_reader(SOLVER_V2).unwrap();
+    assert_eq!(solver.history().len(), 3);
+    assert_eq!(solver.networks().len(), 10);
+    let options = TrainOptions {
+        max_generations: Some(2),
+        ..TrainOptions::default()
+    };
+    let report = solver.train(
+        &|nn: &mut NeuralNetwork| nn.compute([1.0, 0.0])[0],
+        &options,
+    );
+    assert_eq!(report.history.len(), 2);
+
+    let bytes = solver.as_byte_representation().unwrap();
+    assert!(Solver::load_from_reader(&bytes[..]).is_ok());
+}
+
+#[test]
+fn rejects_truncated_payloads() {
+    let truncated = &NETWORK_V3[..NETWORK_V3.len() - 8];
+    assert!(matches!(
+        NeuralNetwork::<f32>::from_bytes(truncated),
+        Err(Error::Corrupted)
+    ));
+    // older versions have no checksum, so only incomplete values are detected
+    let truncated = &NETWORK_V2[..NETWORK_V2.len() / 2];
+    assert!(matches!(
+        NeuralNetwork::<f32>::from_bytes(truncated),
+        Err(Error::Serialization(_))
+    ));
+}
+
+#[cfg(feature = "serde_json")]
+mod json {
+    use super::*;
+
+    #[test]
+    fn loads_json() {
+        let nn = NeuralNetwork::from_json(include_str!("fixtures/network.json")).unwrap();
+        assert_eq!(nn, NeuralNetwork::from_bytes(NETWORK_V1).unwrap());
+        let future = NeuralNetwork::from_json(include_str!("fixtures/network-future.json"));
+        assert_eq!(future.unwrap(), nn);
+    }
+
+    #[test]
+    fn defaults_missing_fields() {
+        let nn = NeuralNetwork::from_json(include_str!("fixtures/network-minimal.json")).unwrap();
+        assert_eq!(nn.size(), (1, 1));
+        assert_eq!(nn.id(), 0);
+        assert_eq!(nn.fitness(), f32::MIN);
+        assert!(nn
+            .nodes()
+            .iter()
+            .all(|node| node.activation == Activation::default()));
+        assert!(nn.edges()[0].enabled);
+        assert!(!nn.edges()[1].enabled);
+        assert!(!nn.edges()[1].recurrent);
+        assert!(!nn.recurrent());
+    }
+}
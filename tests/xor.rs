@@ -0,0 +1,35 @@
+//! Trains networks on the exclusive or, the canonical correctness test of the NEAT
+//! implementation: it can only be solved by evolving a hidden node.
+use neaters::problems::XorProblem;
+use neaters::{Activation, Solver, StopReason, TrainOptions};
+
+/// The number of generations a solver has to solve the problem in. Networks using `Tanh` have
+/// needed less than 80 generations when measured, so this leaves plenty of room.
+const MAX_GENERATIONS: usize = 300;
+
+/// The number of independent solvers which all have to solve the problem.
+const RUNS: usize = 5;
+
+#[test]
+fn solves_xor_reliably() {
+    for _ in 0..RUNS {
+        solve_xor();
+    }
+}
+
+fn solve_xor() {
+    let mut solver = Solver::with_size(2, 1, 150);
+    for nn in solver.neural_nets() {
+        *nn = nn.clone().with_activation(Activation::Tanh);
+    }
+    let options = TrainOptions {
+        max_generations: Some(MAX_GENERATIONS),
+        target_fitness: Some(XorProblem::TARGET_FITNESS),
+        ..TrainOptions::default()
+    };
+    let mut report = solver.train(&XorProblem, &options);
+    assert_eq!(report.stop_reason, StopReason::TargetFitness);
+    assert!(report.history.len() <= MAX_GENERATIONS);
+    assert!(XorProblem::solves(&mut report.champion));
+    assert!(report.champion.hidden_node_count() > 0);
+}